@@ -58,6 +58,22 @@ blueprint! {
             }
         }
 
+        /// Creates a bucket ref backed by the vault of this account, locking the resource until
+        /// the bucket ref is dropped.
+        pub fn create_proof(&self, amount: Decimal, resource_address: Address) -> BucketRef {
+            if !Context::transaction_signers().contains(&self.key) {
+                panic!("Not authorized! Make sure you sign transaction with the correct keys.",)
+            }
+
+            let vault = self.vaults.get(&resource_address);
+            match vault {
+                Some(vault) => vault.create_bucket_ref(amount),
+                None => {
+                    panic!("Insufficient balance");
+                }
+            }
+        }
+
         /// Withdraws resource from this account.
         pub fn withdraw_with_auth(
             &mut self,
@@ -103,6 +119,35 @@ blueprint! {
             }
         }
 
+        /// Withdraws the NFTs whose ids fall within `[start, end)` from this account.
+        ///
+        /// Ids in the range that this account doesn't hold are skipped, so the bucket returned
+        /// is empty if this account holds none of them.
+        pub fn withdraw_nfts_range(
+            &mut self,
+            start: u128,
+            end: u128,
+            resource_address: Address,
+        ) -> Bucket {
+            if !Context::transaction_signers().contains(&self.key) {
+                panic!("Not authorized! Make sure you sign transaction with the correct keys.",)
+            }
+
+            let vault = self.vaults.get(&resource_address);
+            match vault {
+                Some(vault) => {
+                    let bucket = Bucket::new(resource_address);
+                    for id in vault.get_nft_ids() {
+                        if id >= start && id < end {
+                            bucket.put(vault.take_nft(id));
+                        }
+                    }
+                    bucket
+                }
+                None => Bucket::new(resource_address),
+            }
+        }
+
         /// Withdraws NFTs from this account.
         pub fn withdraw_nfts_with_auth(
             &mut self,
@@ -0,0 +1,24 @@
+use scrypto::prelude::*;
+
+blueprint! {
+    struct PriceFeed {
+        prices: LazyMap<Address, Decimal>,
+    }
+
+    impl PriceFeed {
+        pub fn new() -> Component {
+            Self {
+                prices: LazyMap::new(),
+            }
+            .instantiate()
+        }
+
+        pub fn set_price(&self, resource_address: Address, price: Decimal) {
+            self.prices.insert(resource_address, price);
+        }
+
+        pub fn get_price(&self, resource_address: Address) -> Option<Decimal> {
+            self.prices.get(&resource_address)
+        }
+    }
+}
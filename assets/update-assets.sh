@@ -8,6 +8,7 @@ cd "$(dirname "$0")"
 echo "Building packages..."
 (cd account; cargo build --target wasm32-unknown-unknown --release)
 (cd system; cargo build --target wasm32-unknown-unknown --release)
+(cd price-feed; cargo build --target wasm32-unknown-unknown --release)
 
 echo "Publishing artifacts..."
 wasm-opt \
@@ -20,5 +21,10 @@ wasm-opt \
   --strip-debug --strip-dwarf --strip-producers \
   -o ./system.wasm \
   ./system/target/wasm32-unknown-unknown/release/out.wasm
+wasm-opt \
+  -Os -g \
+  --strip-debug --strip-dwarf --strip-producers \
+  -o ./price-feed.wasm \
+  ./price-feed/target/wasm32-unknown-unknown/release/out.wasm
 
 echo "Done!"
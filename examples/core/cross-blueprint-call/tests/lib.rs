@@ -2,6 +2,9 @@ use radix_engine::ledger::*;
 use radix_engine::transaction::*;
 use scrypto::prelude::*;
 
+/// The package the `Airdrop` blueprint is imported from.
+const AIRDROP_PACKAGE: &str = "01bda8686d6c2fa45dce04fac71a09b54efbc8028c23aac74bc00e";
+
 #[test]
 fn test_proxy_1() {
     // Set up environment.
@@ -13,7 +16,7 @@ fn test_proxy_1() {
 
     // Airdrop blueprint.
     executor.overwrite_package(
-        Address::from_str("01bda8686d6c2fa45dce04fac71a09b54efbc8028c23aac74bc00e").unwrap(),
+        Address::from_str(AIRDROP_PACKAGE).unwrap(),
         include_code!("cross_blueprint_call"),
     );
 
@@ -22,7 +25,7 @@ fn test_proxy_1() {
         .call_function(package, "Proxy1", "new", vec![], None)
         .build(vec![key])
         .unwrap();
-    let receipt1 = executor.run(transaction1, true).unwrap();
+    let receipt1 = executor.sign_and_run(transaction1, true).unwrap();
     println!("{:?}\n", receipt1);
     assert!(receipt1.success);
 
@@ -34,7 +37,7 @@ fn test_proxy_1() {
         .deposit_all_buckets(account)
         .build(vec![key])
         .unwrap();
-    let receipt2 = executor.run(transaction2, true).unwrap();
+    let receipt2 = executor.sign_and_run(transaction2, true).unwrap();
     println!("{:?}\n", receipt2);
     assert!(receipt2.success);
 }
@@ -50,7 +53,7 @@ fn test_proxy_2() {
 
     // Airdrop blueprint.
     executor.overwrite_package(
-        Address::from_str("01bda8686d6c2fa45dce04fac71a09b54efbc8028c23aac74bc00e").unwrap(),
+        Address::from_str(AIRDROP_PACKAGE).unwrap(),
         include_code!("cross_blueprint_call"),
     );
 
@@ -59,7 +62,7 @@ fn test_proxy_2() {
         .call_function(package, "Proxy2", "new", vec![], None)
         .build(vec![key])
         .unwrap();
-    let receipt1 = executor.run(transaction1, true).unwrap();
+    let receipt1 = executor.sign_and_run(transaction1, true).unwrap();
     println!("{:?}\n", receipt1);
     assert!(receipt1.success);
 
@@ -71,7 +74,7 @@ fn test_proxy_2() {
         .deposit_all_buckets(account)
         .build(vec![key])
         .unwrap();
-    let receipt2 = executor.run(transaction2, true).unwrap();
+    let receipt2 = executor.sign_and_run(transaction2, true).unwrap();
     println!("{:?}\n", receipt2);
     assert!(receipt2.success);
 }
@@ -18,7 +18,7 @@ fn test_create_additional_admin() {
         .deposit_all_buckets(account)
         .build(vec![key])
         .unwrap();
-    let receipt1 = executor.run(transaction1, false).unwrap();
+    let receipt1 = executor.sign_and_run(transaction1, false).unwrap();
     println!("{:?}\n", receipt1);
     assert!(receipt1.success);
 
@@ -36,7 +36,7 @@ fn test_create_additional_admin() {
         .deposit_all_buckets(account)
         .build(vec![key])
         .unwrap();
-    let receipt2 = executor.run(transaction2, false).unwrap();
+    let receipt2 = executor.sign_and_run(transaction2, false).unwrap();
     println!("{:?}\n", receipt2);
     assert!(receipt2.success);
 }
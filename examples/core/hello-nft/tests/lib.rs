@@ -18,7 +18,7 @@ fn test_hello() {
         .deposit_all_buckets(account)
         .build(vec![key])
         .unwrap();
-    let receipt1 = executor.run(transaction1, true).unwrap();
+    let receipt1 = executor.sign_and_run(transaction1, true).unwrap();
     println!("{:?}\n", receipt1);
     assert!(receipt1.success);
 
@@ -38,7 +38,7 @@ fn test_hello() {
         .deposit_all_buckets(account)
         .build(vec![key])
         .unwrap();
-    let receipt2 = executor.run(transaction2, true).unwrap();
+    let receipt2 = executor.sign_and_run(transaction2, true).unwrap();
     println!("{:?}\n", receipt2);
     assert!(receipt2.success);
 }
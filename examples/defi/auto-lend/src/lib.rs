@@ -244,9 +244,13 @@ impl User {
         self.deposit_last_update = Context::current_epoch();
 
         // Calculate the aggregated interest of previous deposits & the new deposit
-        self.deposit_interest_rate = (self.deposit_balance * self.deposit_interest_rate
-            + amount * interest_rate)
-            / (self.deposit_balance + amount);
+        self.deposit_interest_rate = if self.deposit_balance.is_zero() {
+            interest_rate
+        } else {
+            (self.deposit_balance * self.deposit_interest_rate + amount * interest_rate)
+                .checked_div(self.deposit_balance + amount)
+                .expect("Failed to calculate the deposit interest rate")
+        };
 
         // Increase principle balance by the amount.
         self.deposit_balance += amount;
@@ -267,9 +271,13 @@ impl User {
         self.borrow_last_update = Context::current_epoch();
 
         // Calculate the aggregated interest of previous borrows & the new borrow
-        self.borrow_interest_rate = (self.borrow_balance * self.borrow_interest_rate
-            + amount * interest_rate)
-            / (self.borrow_balance + amount);
+        self.borrow_interest_rate = if self.borrow_balance.is_zero() {
+            interest_rate
+        } else {
+            (self.borrow_balance * self.borrow_interest_rate + amount * interest_rate)
+                .checked_div(self.borrow_balance + amount)
+                .expect("Failed to calculate the borrow interest rate")
+        };
 
         // Increase principle balance by the amount.
         self.borrow_balance += amount;
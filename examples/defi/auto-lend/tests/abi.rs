@@ -0,0 +1,22 @@
+use scrypto::abi;
+use scrypto::buffer::*;
+use scrypto::prelude::*;
+
+use auto_lend::AutoLend_abi;
+
+#[test]
+fn test_abi_json() {
+    let ptr = AutoLend_abi();
+    let (functions, methods): (Vec<abi::Function>, Vec<abi::Method>) =
+        unsafe { scrypto_consume(ptr, |slice| scrypto_decode(slice).unwrap()) };
+    let blueprint = abi::Blueprint {
+        package: "01ca59a8d6ea4f7efa1765cef702d14e47570c079aedd44992dd09".to_owned(),
+        name: "AutoLend".to_owned(),
+        functions,
+        methods,
+    };
+
+    let json = blueprint.to_json();
+    assert_eq!(json, include_str!("abi.json"));
+    assert_eq!(abi::Blueprint::from_json(&json).unwrap(), blueprint);
+}
@@ -12,23 +12,12 @@ struct TestEnv<'a, L: Ledger> {
     lending_pool: Address,
 }
 
-fn set_up_test_env<'a, L: Ledger>(ledger: &'a mut L) -> TestEnv<'a, L> {
+fn set_up_test_env<'a, L: Ledger>(ledger: &'a mut L, genesis: &GenesisAccount) -> TestEnv<'a, L> {
     let mut executor = TransactionExecutor::new(ledger, 0, 0);
-    let key = executor.new_public_key();
-    let account = executor.new_account(key);
+    let (key, account) = (genesis.key, genesis.address);
     let package = executor.publish_package(include_code!("auto_lend"));
 
-    let receipt = executor
-        .run(
-            TransactionBuilder::new(&executor)
-                .new_token_fixed(HashMap::new(), 1_000_000.into())
-                .deposit_all_buckets(account)
-                .build(vec![key])
-                .unwrap(),
-            false,
-        )
-        .unwrap();
-    let usd = receipt.resource_def(0).unwrap();
+    let usd = executor.fund_account(account, 1_000_000.into(), HashMap::new());
 
     let receipt = executor
         .run(
@@ -46,6 +35,7 @@ fn set_up_test_env<'a, L: Ledger>(ledger: &'a mut L) -> TestEnv<'a, L> {
             false,
         )
         .unwrap();
+    assert!(receipt.success);
     let lending_pool = receipt.component(0).unwrap();
 
     TestEnv {
@@ -74,7 +64,7 @@ fn create_user<'a, L: Ledger>(env: &mut TestEnv<'a, L>) -> Address {
 }
 
 fn get_user_state<'a, L: Ledger>(env: &mut TestEnv<'a, L>, user_id: Address) -> User {
-    let mut receipt = env
+    let receipt = env
         .executor
         .run(
             TransactionBuilder::new(&env.executor)
@@ -91,14 +81,13 @@ fn get_user_state<'a, L: Ledger>(env: &mut TestEnv<'a, L>, user_id: Address) ->
         )
         .unwrap();
     assert!(receipt.success);
-    let encoded = receipt.results.swap_remove(0).unwrap().unwrap().encoded;
-    scrypto_decode(&encoded).unwrap()
+    receipt.return_value(0).unwrap()
 }
 
 #[test]
 fn test_deposit_and_redeem() {
-    let mut ledger = InMemoryLedger::with_bootstrap();
-    let mut env = set_up_test_env(&mut ledger);
+    let (mut ledger, accounts) = InMemoryLedger::with_genesis_accounts(1, 1_000_000.into());
+    let mut env = set_up_test_env(&mut ledger, &accounts[0]);
 
     let user_id = create_user(&mut env);
 
@@ -217,8 +206,8 @@ fn test_deposit_and_redeem() {
 
 #[test]
 fn test_borrow_and_repay() {
-    let mut ledger = InMemoryLedger::with_bootstrap();
-    let mut env = set_up_test_env(&mut ledger);
+    let (mut ledger, accounts) = InMemoryLedger::with_genesis_accounts(1, 1_000_000.into());
+    let mut env = set_up_test_env(&mut ledger, &accounts[0]);
 
     let user_id = create_user(&mut env);
 
@@ -402,3 +391,239 @@ fn test_borrow_and_repay() {
         }
     );
 }
+
+#[test]
+fn test_deposit_after_full_redemption() {
+    let (mut ledger, accounts) = InMemoryLedger::with_genesis_accounts(1, 1_000_000.into());
+    let mut env = set_up_test_env(&mut ledger, &accounts[0]);
+
+    let user_id = create_user(&mut env);
+
+    // Another user provides the liquidity for the interest paid on redemption
+    let other_user_id = create_user(&mut env);
+    let receipt = env
+        .executor
+        .run(
+            TransactionBuilder::new(&env.executor)
+                .call_method(
+                    env.lending_pool,
+                    "deposit",
+                    vec![
+                        format!("{},{}", 1, other_user_id),
+                        format!("{},{}", 1000, env.usd),
+                    ],
+                    Some(env.account),
+                )
+                .deposit_all_buckets(env.account)
+                .build(vec![env.key])
+                .unwrap(),
+            false,
+        )
+        .unwrap();
+    println!("{:?}", receipt);
+    assert!(receipt.success);
+
+    // Deposit 100 USD and redeem all of it
+    let receipt = env
+        .executor
+        .run(
+            TransactionBuilder::new(&env.executor)
+                .call_method(
+                    env.lending_pool,
+                    "deposit",
+                    vec![format!("{},{}", 1, user_id), format!("{},{}", 100, env.usd)],
+                    Some(env.account),
+                )
+                .deposit_all_buckets(env.account)
+                .build(vec![env.key])
+                .unwrap(),
+            false,
+        )
+        .unwrap();
+    println!("{:?}", receipt);
+    assert!(receipt.success);
+    let receipt = env
+        .executor
+        .run(
+            TransactionBuilder::new(&env.executor)
+                .call_method(
+                    env.lending_pool,
+                    "redeem",
+                    vec![format!("{},{}", 1, user_id), "100".to_owned()],
+                    Some(env.account),
+                )
+                .deposit_all_buckets(env.account)
+                .build(vec![env.key])
+                .unwrap(),
+            false,
+        )
+        .unwrap();
+    println!("{:?}", receipt);
+    assert!(receipt.success);
+
+    // Change the pool rate, then deposit into the now empty balance
+    let receipt = env
+        .executor
+        .run(
+            TransactionBuilder::new(&env.executor)
+                .call_method(
+                    env.lending_pool,
+                    "set_deposit_interest_rate",
+                    vec!["0.03".to_string()],
+                    Some(env.account),
+                )
+                .call_method(
+                    env.lending_pool,
+                    "deposit",
+                    vec![format!("{},{}", 1, user_id), format!("{},{}", 7, env.usd)],
+                    Some(env.account),
+                )
+                .deposit_all_buckets(env.account)
+                .build(vec![env.key])
+                .unwrap(),
+            false,
+        )
+        .unwrap();
+    println!("{:?}", receipt);
+    assert!(receipt.success);
+    let user_state = get_user_state(&mut env, user_id);
+    assert_eq!(
+        user_state,
+        User {
+            deposit_balance: "7".parse().unwrap(),
+            deposit_interest_rate: "0.03".parse().unwrap(),
+            deposit_last_update: 0,
+            borrow_balance: "0".parse().unwrap(),
+            borrow_interest_rate: "0".parse().unwrap(),
+            borrow_last_update: 0
+        }
+    );
+}
+
+#[test]
+fn test_list_methods() {
+    let (mut ledger, accounts) = InMemoryLedger::with_genesis_accounts(1, 1_000_000.into());
+    let env = set_up_test_env(&mut ledger, &accounts[0]);
+
+    let builder = TransactionBuilder::new(&env.executor);
+    let methods = builder.list_methods(env.lending_pool);
+    assert!(methods.contains(&"deposit".to_owned()));
+    assert!(methods.contains(&"redeem".to_owned()));
+    assert!(builder.component_has_method(env.lending_pool, "deposit"));
+    assert!(!builder.component_has_method(env.lending_pool, "withdraw"));
+}
+
+#[test]
+fn test_new_pool_via_existing_pool() {
+    let (mut ledger, accounts) = InMemoryLedger::with_genesis_accounts(1, 1_000_000.into());
+    let mut env = set_up_test_env(&mut ledger, &accounts[0]);
+
+    // The package and blueprint are resolved from the lending pool component
+    let transaction = TransactionBuilder::new(&env.executor)
+        .call_blueprint_function_of(
+            env.lending_pool,
+            "new",
+            vec![env.usd.to_string(), "USD".to_owned()],
+            Some(env.account),
+        )
+        .deposit_all_buckets(env.account)
+        .build(vec![env.key])
+        .unwrap();
+    assert!(transaction.instructions().iter().any(|i| matches!(
+        i,
+        Instruction::CallFunction {
+            blueprint_name,
+            function,
+            ..
+        } if blueprint_name == "AutoLend" && function == "new"
+    )));
+    let receipt = env.executor.run(transaction, false).unwrap();
+    println!("{:?}", receipt);
+    assert!(receipt.success);
+    let lending_pool = receipt.component(0).unwrap();
+    assert_ne!(lending_pool, env.lending_pool);
+
+    let result = TransactionBuilder::new(&env.executor)
+        .call_blueprint_function_of(
+            Address::Component([7u8; 26]),
+            "new",
+            vec![env.usd.to_string(), "USD".to_owned()],
+            Some(env.account),
+        )
+        .build(vec![env.key]);
+    assert!(matches!(
+        result,
+        Err(BuildTransactionError::FailedToResolveBlueprint(_))
+    ));
+}
+
+#[test]
+fn test_decode_user_with_abi_schema() {
+    let (mut ledger, accounts) = InMemoryLedger::with_genesis_accounts(1, 1_000_000.into());
+    let mut env = set_up_test_env(&mut ledger, &accounts[0]);
+
+    let user_id = create_user(&mut env);
+    let receipt = env
+        .executor
+        .run(
+            TransactionBuilder::new(&env.executor)
+                .call_method(
+                    env.lending_pool,
+                    "deposit",
+                    vec![format!("{},{}", 1, user_id), format!("{},{}", 100, env.usd)],
+                    Some(env.account),
+                )
+                .deposit_all_buckets(env.account)
+                .build(vec![env.key])
+                .unwrap(),
+            false,
+        )
+        .unwrap();
+    assert!(receipt.success);
+
+    let receipt = env
+        .executor
+        .run(
+            TransactionBuilder::new(&env.executor)
+                .call_method(
+                    env.lending_pool,
+                    "get_user",
+                    vec![user_id.to_string()],
+                    Some(env.account),
+                )
+                .deposit_all_buckets(env.account)
+                .build(vec![env.key])
+                .unwrap(),
+            false,
+        )
+        .unwrap();
+    assert!(receipt.success);
+    let state = match &receipt.results[0] {
+        Ok(Some(value)) => value.encoded.clone(),
+        _ => panic!("Expected a return value"),
+    };
+
+    // The schema comes from the ABI, not from the `User` type
+    let abi = env
+        .executor
+        .export_abi_component(env.lending_pool, false)
+        .unwrap();
+    let schema = &abi
+        .methods
+        .iter()
+        .find(|m| m.name == "get_user")
+        .unwrap()
+        .output;
+
+    let value = sbor::decode_with_schema(&state, schema).unwrap();
+    let fields = match value {
+        sbor::any::LabeledValue::Struct {
+            name,
+            fields: sbor::any::LabeledFields::Named(fields),
+        } if name == "User" => fields,
+        _ => panic!("Unexpected value: {:?}", value),
+    };
+    let names: Vec<&str> = fields.iter().map(|(name, _)| name.as_str()).collect();
+    assert!(names.contains(&"deposit_balance"));
+    assert!(names.contains(&"borrow_balance"));
+}
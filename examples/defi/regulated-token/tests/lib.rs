@@ -5,10 +5,9 @@ use scrypto::prelude::*;
 #[test]
 fn test_hello() {
     // Set up environment.
-    let mut ledger = InMemoryLedger::with_bootstrap();
+    let (mut ledger, accounts) = InMemoryLedger::with_genesis_accounts(1, 1_000_000.into());
+    let (key, account) = (accounts[0].key, accounts[0].address);
     let mut executor = TransactionExecutor::new(&mut ledger, 0, 0);
-    let key = executor.new_public_key();
-    let account = executor.new_account(key);
     let package = executor.publish_package(include_code!("regulated_token"));
 
     // Test the `new` function.
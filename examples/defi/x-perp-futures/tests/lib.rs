@@ -13,23 +13,12 @@ struct TestEnv<'a, L: Ledger> {
     clearing_house: Address,
 }
 
-fn set_up_test_env<'a, L: Ledger>(ledger: &'a mut L) -> TestEnv<'a, L> {
+fn set_up_test_env<'a, L: Ledger>(ledger: &'a mut L, genesis: &GenesisAccount) -> TestEnv<'a, L> {
     let mut executor = TransactionExecutor::new(ledger, 0, 0);
-    let key = executor.new_public_key();
-    let account = executor.new_account(key);
+    let (key, account) = (genesis.key, genesis.address);
     let package = executor.publish_package(include_code!("x_perp_futures"));
 
-    let receipt = executor
-        .run(
-            TransactionBuilder::new(&executor)
-                .new_token_fixed(HashMap::new(), 1_000_000.into())
-                .deposit_all_buckets(account)
-                .build(vec![key])
-                .unwrap(),
-            false,
-        )
-        .unwrap();
-    let usd = receipt.resource_def(0).unwrap();
+    let usd = executor.fund_account(account, 1_000_000.into(), HashMap::new());
 
     let receipt = executor
         .run(
@@ -47,6 +36,7 @@ fn set_up_test_env<'a, L: Ledger>(ledger: &'a mut L) -> TestEnv<'a, L> {
             false,
         )
         .unwrap();
+    assert!(receipt.success);
     let clearing_house = receipt.component(0).unwrap();
 
     TestEnv {
@@ -75,7 +65,7 @@ fn create_user<'a, L: Ledger>(env: &mut TestEnv<'a, L>) -> Address {
 }
 
 fn get_position<'a, L: Ledger>(env: &mut TestEnv<'a, L>, user_id: Address, nth: usize) -> Position {
-    let mut receipt = env
+    let receipt = env
         .executor
         .run(
             TransactionBuilder::new(&env.executor)
@@ -92,14 +82,13 @@ fn get_position<'a, L: Ledger>(env: &mut TestEnv<'a, L>, user_id: Address, nth:
         )
         .unwrap();
     assert!(receipt.success);
-    let encoded = receipt.results.swap_remove(0).unwrap().unwrap().encoded;
-    scrypto_decode(&encoded).unwrap()
+    receipt.return_value(0).unwrap()
 }
 
 #[test]
 fn test_long() {
-    let mut ledger = InMemoryLedger::with_bootstrap();
-    let mut env = set_up_test_env(&mut ledger);
+    let (mut ledger, accounts) = InMemoryLedger::with_genesis_accounts(1, 1_000_000.into());
+    let mut env = set_up_test_env(&mut ledger, &accounts[0]);
 
     let user1 = create_user(&mut env);
     let user2 = create_user(&mut env);
@@ -194,8 +183,8 @@ fn test_long() {
 
 #[test]
 fn test_short() {
-    let mut ledger = InMemoryLedger::with_bootstrap();
-    let mut env = set_up_test_env(&mut ledger);
+    let (mut ledger, accounts) = InMemoryLedger::with_genesis_accounts(1, 1_000_000.into());
+    let mut env = set_up_test_env(&mut ledger, &accounts[0]);
 
     let user1 = create_user(&mut env);
     let user2 = create_user(&mut env);
@@ -16,7 +16,7 @@ fn test_hello() {
         .call_function(package, "HelloNft", "new", vec![], None)
         .build(vec![key])
         .unwrap();
-    let receipt1 = executor.run(transaction1, false).unwrap();
+    let receipt1 = executor.sign_and_run(transaction1, false).unwrap();
     println!("{:?}\n", receipt1);
     assert!(receipt1.success);
 
@@ -32,7 +32,7 @@ fn test_hello() {
         .deposit_all_buckets(account)
         .build(vec![key])
         .unwrap();
-    let receipt2 = executor.run(transaction2, false).unwrap();
+    let receipt2 = executor.sign_and_run(transaction2, false).unwrap();
     println!("{:?}\n", receipt2);
     assert!(receipt2.success);
 
@@ -48,7 +48,7 @@ fn test_hello() {
         .deposit_all_buckets(account)
         .build(vec![key])
         .unwrap();
-    let receipt3 = executor.run(transaction3, false).unwrap();
+    let receipt3 = executor.sign_and_run(transaction3, false).unwrap();
     println!("{:?}\n", receipt3);
     assert!(receipt3.success);
 }
@@ -65,9 +65,9 @@ blueprint! {
                         prediction: Team::Home,
                     };
                     ticket_bucket.put(
-                        my_admin.authorize(
-                            |auth| ticket_bucket.resource_def().mint_nft(manual_id, ticket, auth)
-                        )
+                        my_admin.authorize(|| {
+                            ticket_bucket.resource_def().mint_nft(manual_id, ticket, BucketRef::presented())
+                        })
                     );
                     manual_id += 1;
                 }
@@ -82,9 +82,9 @@ blueprint! {
                     prediction: Team::Home,
                 };
                 ticket_bucket.put(
-                    my_admin.authorize(
-                        |auth| ticket_bucket.resource_def().mint_nft(manual_id, ticket, auth)
-                    )
+                    my_admin.authorize(|| {
+                        ticket_bucket.resource_def().mint_nft(manual_id, ticket, BucketRef::presented())
+                    })
                 );
             }
 
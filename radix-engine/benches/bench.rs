@@ -13,7 +13,7 @@ fn bench_transfer(b: &mut Bencher) {
     let account1 = executor.new_account(key1);
     let key2 = executor.new_public_key();
     let account2 = executor.new_account(key2);
-    let transaction = TransactionBuilder::new(&executor)
+    let mut transaction = TransactionBuilder::new(&executor)
         .withdraw_from_account(
             &ResourceAmount::Fungible {
                 amount: 1.into(),
@@ -25,6 +25,7 @@ fn bench_transfer(b: &mut Bencher) {
         .deposit_all_buckets(account2)
         .build(vec![key1])
         .unwrap();
+    executor.sign(&mut transaction);
 
     b.iter(|| {
         let receipt = executor.run(transaction.clone(), false).unwrap();
@@ -2,6 +2,7 @@ use scrypto::types::*;
 use scrypto::utils::*;
 
 /// An ID allocator defines how identities are generated.
+#[derive(Clone)]
 pub struct IdAllocator {
     count: u32,
 }
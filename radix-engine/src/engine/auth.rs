@@ -0,0 +1,32 @@
+use sbor::*;
+use scrypto::rust::string::String;
+use scrypto::rust::vec::Vec;
+use scrypto::types::*;
+
+/// Represents an authorization an operation required but was not given, as recorded
+/// when previewing auth.
+#[derive(Debug, Clone, PartialEq, Eq, TypeId, Encode, Decode)]
+pub enum AuthRequirement {
+    /// A resource permission, required by an engine operation.
+    Resource {
+        /// The index of the instruction which made the operation.
+        instruction_index: usize,
+        resource_address: Address,
+        permission: u16,
+        /// The badge granting the permission, if there is one.
+        required_badge: Option<Address>,
+    },
+
+    /// A badge required by a blueprint method guarded with `#[auth]`.
+    Method {
+        /// The index of the instruction which led to the call.
+        instruction_index: usize,
+        component_address: Address,
+        method: String,
+        /// The badges, any of which authorizes the call.
+        allowed_badges: Vec<Address>,
+    },
+
+    /// A signature from a signer declared by the transaction.
+    Signature { signer: Address },
+}
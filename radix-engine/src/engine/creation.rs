@@ -0,0 +1,16 @@
+use sbor::*;
+use scrypto::kernel::*;
+use scrypto::types::*;
+
+use crate::model::*;
+
+/// Represents a resource definition created by an instruction.
+#[derive(Debug, Clone, PartialEq, Eq, TypeId, Encode, Decode)]
+pub struct ResourceCreation {
+    /// The index of the instruction which created the resource.
+    pub instruction_index: usize,
+    pub resource_address: Address,
+    pub resource_type: ResourceType,
+    /// The supply created along with the resource, if any.
+    pub initial_supply: Option<Supply>,
+}
@@ -5,6 +5,9 @@ use wasmi::*;
 pub const KERNEL_INDEX: usize = 0;
 /// Kernel entrance function name.
 pub const KERNEL_NAME: &str = "kernel";
+/// Kernel operation injected by the engine into loops, for checking the execution time limit
+/// and metering cost budgets.
+pub const CHECK_EXECUTION_TIME: u32 = 0xffff_fff0;
 
 /// An `env` module resolver defines how symbols in `env` are resolved.
 pub struct EnvModuleResolver;
@@ -1,4 +1,5 @@
 use sbor::*;
+use scrypto::rust::collections::BTreeSet;
 use scrypto::rust::fmt;
 use scrypto::types::*;
 use wasmi::*;
@@ -65,6 +66,15 @@ pub enum RuntimeError {
     /// Component does not exist.
     ComponentNotFound(Address),
 
+    /// The deposit hook can only be invoked by the engine.
+    DepositHookNotCallable(Address),
+
+    /// The deposit hook of a component received resources while it was running.
+    DepositHookReentered(Address),
+
+    /// Component can't be deleted, as its vaults are not empty.
+    ComponentNotEmpty(Address),
+
     /// Resource definition does not exist.
     ResourceDefNotFound(Address),
 
@@ -80,6 +90,9 @@ pub enum RuntimeError {
     /// Vault does not exist.
     VaultNotFound(Vid),
 
+    /// The component does not own a vault of the resource.
+    ComponentVaultNotFound(Address, Address),
+
     /// Bucket does not exist.
     BucketNotFound(Bid),
 
@@ -98,6 +111,12 @@ pub enum RuntimeError {
     /// The referenced bucket contains no resource.
     EmptyBucketRef,
 
+    /// No bucket is presented, outside of `Bucket::authorize`.
+    NoPresentedBucketRef,
+
+    /// A clone of a presented bucket ref is still held once the bucket is no longer presented.
+    PresentedBucketRefLeaked(Rid),
+
     /// Bucket access error.
     BucketError(BucketError),
 
@@ -136,6 +155,71 @@ pub enum RuntimeError {
 
     /// Resource check failure.
     ResourceCheckFailure,
+
+    /// The transaction context holds less than the required amount of a resource.
+    InsufficientWorktopBalance(Address, Decimal),
+
+    /// The transaction context holds resources, when it's asserted to be empty.
+    WorktopNotEmpty(Address),
+
+    /// The transaction context lacks some of the NFTs it's asserted to hold.
+    WorktopAssertionFailed {
+        resource_address: Address,
+        missing_ids: BTreeSet<u128>,
+    },
+
+    /// The total size of logs emitted by the transaction exceeds the limit.
+    LogLimitExceeded(usize),
+
+    /// The transaction context holds more distinct resources than the limit.
+    WorktopResourceLimitExceeded(usize),
+
+    /// More random bytes are requested at once than the limit.
+    RandomBytesLimitExceeded(usize),
+
+    /// The transaction ran for longer than the execution time limit.
+    Timeout,
+
+    /// A call ran out of its cost budget.
+    CostBudgetExceeded,
+
+    /// The actor is not authorized; the badge granting the permission is named if there is one.
+    NotAuthorized {
+        required_badge: Option<Address>,
+        permission: u16,
+    },
+}
+
+impl RuntimeError {
+    /// Returns the error that originally caused this one, looking through errors raised by the
+    /// engine while a nested wasm invocation was running.
+    pub fn root_cause(&self) -> &RuntimeError {
+        match self {
+            Self::InvokeError(e) => match e
+                .as_host_error()
+                .and_then(|h| h.downcast_ref::<RuntimeError>())
+            {
+                Some(cause) => cause.root_cause(),
+                None => self,
+            },
+            _ => self,
+        }
+    }
+}
+
+impl From<ResourceDefError> for RuntimeError {
+    fn from(error: ResourceDefError) -> Self {
+        match error {
+            ResourceDefError::NotAuthorized {
+                required_badge,
+                permission,
+            } => Self::NotAuthorized {
+                required_badge,
+                permission,
+            },
+            e => Self::ResourceDefError(e),
+        }
+    }
 }
 
 impl fmt::Display for RuntimeError {
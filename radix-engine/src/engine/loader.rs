@@ -1,3 +1,6 @@
+use parity_wasm::elements;
+use scrypto::rust::string::ToString;
+use scrypto::rust::vec;
 use wasmi::*;
 
 use crate::engine::*;
@@ -7,6 +10,41 @@ pub fn parse_module(code: &[u8]) -> Result<Module, RuntimeError> {
     Module::from_buffer(code).map_err(RuntimeError::InvalidModule)
 }
 
+/// Parses a WASM module, injecting a `CHECK_EXECUTION_TIME` kernel call at the start of every
+/// loop iteration, so that a running blueprint can be aborted or metered.
+pub fn parse_module_with_time_checks(code: &[u8]) -> Result<Module, RuntimeError> {
+    let mut module = elements::deserialize_buffer::<elements::Module>(code)
+        .map_err(|e| RuntimeError::InvalidModule(Error::Validation(e.to_string())))?;
+
+    let kernel = module.import_section().and_then(|s| {
+        s.entries()
+            .iter()
+            .filter(|e| matches!(e.external(), elements::External::Function(_)))
+            .position(|e| e.module() == "env" && e.field() == KERNEL_NAME)
+    });
+    if let (Some(kernel), Some(code_section)) = (kernel, module.code_section_mut()) {
+        for body in code_section.bodies_mut() {
+            let instructions = body.code_mut().elements_mut();
+            *instructions = instructions
+                .drain(..)
+                .flat_map(|inst| match inst {
+                    elements::Instruction::Loop(_) => vec![
+                        inst,
+                        elements::Instruction::I32Const(CHECK_EXECUTION_TIME as i32),
+                        elements::Instruction::I32Const(0),
+                        elements::Instruction::I32Const(0),
+                        elements::Instruction::Call(kernel as u32),
+                        elements::Instruction::Drop,
+                    ],
+                    _ => vec![inst],
+                })
+                .collect();
+        }
+    }
+
+    Module::from_parity_wasm_module(module).map_err(RuntimeError::InvalidModule)
+}
+
 /// Instantiates a WASM module.
 pub fn instantiate_module(module: &Module) -> Result<(ModuleRef, MemoryRef), RuntimeError> {
     // Instantiate
@@ -1,13 +1,23 @@
 mod allocator;
+mod auth;
+mod creation;
 mod env;
 mod error;
 mod loader;
+mod movement;
 mod process;
+mod tracer;
 mod track;
 
 pub use allocator::IdAllocator;
-pub use env::{EnvModuleResolver, KERNEL_INDEX, KERNEL_NAME};
+pub use auth::AuthRequirement;
+pub use creation::ResourceCreation;
+pub use env::{EnvModuleResolver, CHECK_EXECUTION_TIME, KERNEL_INDEX, KERNEL_NAME};
 pub use error::RuntimeError;
-pub use loader::{instantiate_module, parse_module, validate_module};
+pub use loader::{
+    instantiate_module, parse_module, parse_module_with_time_checks, validate_module,
+};
+pub use movement::{pair_movements, Movement, MovementKind};
 pub use process::{Invocation, Process};
-pub use track::Track;
+pub use tracer::{TraceEvent, Tracer};
+pub use track::{Track, TrackCheckpoint, MAX_RANDOM_BYTES};
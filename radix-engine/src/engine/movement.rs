@@ -0,0 +1,96 @@
+use sbor::*;
+use scrypto::rust::vec;
+use scrypto::rust::vec::Vec;
+use scrypto::types::*;
+
+/// Represents the kind of a resource movement.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, TypeId, Encode, Decode)]
+pub enum MovementKind {
+    /// New supply is created.
+    Minted,
+
+    /// Supply is destroyed.
+    Burned,
+
+    /// Resource is taken from transaction context into a temporary bucket.
+    TakenFromContext,
+
+    /// Resource is taken out of a vault.
+    Withdrawn,
+
+    /// Resource is put into a vault.
+    Deposited,
+}
+
+/// Represents a resource movement made by an instruction.
+#[derive(Debug, Clone, PartialEq, Eq, TypeId, Encode, Decode)]
+pub struct Movement {
+    /// The index of the instruction which made the movement.
+    pub instruction_index: usize,
+    pub kind: MovementKind,
+    pub resource: Address,
+    pub amount: Decimal,
+    /// The component the resource left, if any.
+    pub from: Option<Address>,
+    /// The component the resource entered, if any.
+    pub to: Option<Address>,
+}
+
+/// Pairs each withdrawal with the deposits of the same resource that follow it, filling in
+/// the destination of the withdrawal and the source of the deposit.
+///
+/// A withdrawal split into several deposits, or a deposit drawn from several withdrawals, is
+/// recorded as one movement per pair. Resource which is withdrawn but never deposited, e.g.
+/// burned, is left without a destination.
+pub fn pair_movements(movements: &[Movement]) -> Vec<Movement> {
+    let mut parts: Vec<Vec<Movement>> = vec![Vec::new(); movements.len()];
+    // (index, amount not yet deposited) of each withdrawal without a destination
+    let mut pending: Vec<(usize, Decimal)> = Vec::new();
+
+    for (i, m) in movements.iter().enumerate() {
+        match (m.kind, m.from, m.to) {
+            (MovementKind::Withdrawn, Some(_), None) => pending.push((i, m.amount)),
+            (MovementKind::Deposited, None, Some(_)) => {
+                let mut left = m.amount;
+                for (w, remaining) in pending.iter_mut() {
+                    if left.is_zero() {
+                        break;
+                    }
+                    let withdrawal = &movements[*w];
+                    if withdrawal.resource != m.resource || remaining.is_zero() {
+                        continue;
+                    }
+                    let amount = if *remaining < left { *remaining } else { left };
+                    *remaining -= amount;
+                    left -= amount;
+                    parts[*w].push(Movement {
+                        amount,
+                        to: m.to,
+                        ..withdrawal.clone()
+                    });
+                    parts[i].push(Movement {
+                        amount,
+                        from: withdrawal.from,
+                        ..m.clone()
+                    });
+                }
+                pending.retain(|(_, remaining)| !remaining.is_zero());
+                if !left.is_zero() {
+                    parts[i].push(Movement {
+                        amount: left,
+                        ..m.clone()
+                    });
+                }
+            }
+            _ => parts[i].push(m.clone()),
+        }
+    }
+    for (w, remaining) in pending {
+        parts[w].push(Movement {
+            amount: remaining,
+            ..movements[w].clone()
+        });
+    }
+
+    parts.into_iter().flatten().collect()
+}
@@ -2,13 +2,18 @@ use colored::*;
 use sbor::any::*;
 use sbor::rust::boxed::Box;
 use sbor::*;
+use scrypto::abi;
+use scrypto::args;
 use scrypto::buffer::*;
+use scrypto::core::DEPOSIT_HOOK;
 use scrypto::kernel::*;
+use scrypto::resource::ResourceAmount;
 use scrypto::rust::borrow::ToOwned;
 use scrypto::rust::collections::*;
 use scrypto::rust::convert::TryFrom;
 use scrypto::rust::fmt;
 use scrypto::rust::format;
+use scrypto::rust::mem;
 use scrypto::rust::rc::Rc;
 use scrypto::rust::string::String;
 use scrypto::rust::vec;
@@ -20,6 +25,16 @@ use crate::engine::*;
 use crate::ledger::*;
 use crate::model::*;
 
+const ACCOUNT_BLUEPRINT_NAME: &str = "Account";
+
+/// The state of an account component, as defined by the account blueprint.
+#[derive(TypeId, Decode)]
+struct AccountState {
+    key: Address,
+    #[allow(dead_code)]
+    vaults: Mid,
+}
+
 macro_rules! re_trace {
     ($proc:expr, $($args: expr),+) => {
         if $proc.trace {
@@ -60,12 +75,15 @@ pub struct Process<'r, 'l, L: Ledger> {
     buckets: HashMap<Bid, Bucket>,
     bucket_refs: HashMap<Rid, BucketRef>,
     locked_buckets: HashMap<Bid, BucketRef>,
+    presented_bucket_refs: Vec<(BucketRef, Vec<Rid>)>,
     moving_buckets: HashMap<Bid, Bucket>,
     moving_bucket_refs: HashMap<Rid, BucketRef>,
     temp_buckets: HashMap<Bid, Bucket>,
     temp_bucket_refs: HashMap<Rid, BucketRef>,
     reserved_bids: HashSet<Bid>,
     reserved_rids: HashSet<Rid>,
+    deposits: Vec<ResourceAmount>,
+    child_deposits: Vec<ResourceAmount>,
     vm: Option<Interpreter>,
 }
 
@@ -80,6 +98,7 @@ pub struct Interpreter {
 #[derive(Debug, Clone)]
 pub struct Invocation {
     package_address: Address,
+    component_address: Option<Address>,
     export_name: String,
     function: String,
     args: Vec<Vec<u8>>,
@@ -95,12 +114,15 @@ impl<'r, 'l, L: Ledger> Process<'r, 'l, L> {
             buckets: HashMap::new(),
             bucket_refs: HashMap::new(),
             locked_buckets: HashMap::new(),
+            presented_bucket_refs: Vec::new(),
             moving_buckets: HashMap::new(),
             moving_bucket_refs: HashMap::new(),
             temp_buckets: HashMap::new(),
             temp_bucket_refs: HashMap::new(),
             reserved_bids: HashSet::new(),
             reserved_rids: HashSet::new(),
+            deposits: Vec::new(),
+            child_deposits: Vec::new(),
             vm: None,
         }
     }
@@ -198,11 +220,189 @@ impl<'r, 'l, L: Ledger> Process<'r, 'l, L> {
             return Err(RuntimeError::BucketNotReserved);
         }
         let bucket = self.withdraw_resource(amount, resource_address)?;
+        self.track.add_movement(
+            MovementKind::TakenFromContext,
+            resource_address,
+            bucket.amount(),
+            None,
+            None,
+        );
         self.temp_buckets.insert(bid, bucket);
 
         Ok(())
     }
 
+    /// Takes resource from this context to a temporary bucket, failing without taking
+    /// anything if this context holds less than the exact amount.
+    pub fn take_exact_from_context(
+        &mut self,
+        amount: Decimal,
+        resource_address: Address,
+        bid: Bid,
+    ) -> Result<(), RuntimeError> {
+        let available = self
+            .buckets
+            .values()
+            .filter(|b| b.resource_address() == resource_address)
+            .fold(Decimal::zero(), |sum, b| sum + b.amount());
+        if available < amount {
+            return Err(RuntimeError::InsufficientWorktopBalance(
+                resource_address,
+                amount,
+            ));
+        }
+
+        self.take_from_context(amount, resource_address, bid)
+    }
+
+    /// Checks that transaction context holds no more distinct resources than the limit.
+    fn check_worktop_resources(&self) -> Result<(), RuntimeError> {
+        if let Some(limit) = self.track.max_worktop_resources() {
+            if self.depth == 0 {
+                let resources: HashSet<Address> = self
+                    .buckets
+                    .values()
+                    .filter(|b| !b.amount().is_zero())
+                    .map(Bucket::resource_address)
+                    .collect();
+                if resources.len() > limit {
+                    return Err(RuntimeError::WorktopResourceLimitExceeded(limit));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Returns the total amount of each resource held by this context.
+    fn worktop_amounts(&self) -> HashMap<Address, Decimal> {
+        let mut amounts = HashMap::<Address, Decimal>::new();
+        for bucket in self.buckets.values() {
+            let amount = amounts
+                .entry(bucket.resource_address())
+                .or_insert_with(Decimal::zero);
+            *amount += bucket.amount();
+        }
+        amounts
+    }
+
+    /// Checks that this context holds no resource.
+    pub fn assert_worktop_empty(&self) -> Result<(), RuntimeError> {
+        match self.buckets.values().find(|b| !b.amount().is_zero()) {
+            Some(bucket) => Err(RuntimeError::WorktopNotEmpty(bucket.resource_address())),
+            None => Ok(()),
+        }
+    }
+
+    /// Checks that this context holds all the given NFTs.
+    pub fn assert_worktop_contains_nfts(
+        &self,
+        ids: &BTreeSet<u128>,
+        resource_address: Address,
+    ) -> Result<(), RuntimeError> {
+        let held: BTreeSet<u128> = self
+            .buckets
+            .values()
+            .filter(|b| b.resource_address() == resource_address)
+            .filter_map(|b| b.get_nft_ids().ok())
+            .flatten()
+            .collect();
+        let missing_ids: BTreeSet<u128> = ids.difference(&held).copied().collect();
+        if missing_ids.is_empty() {
+            Ok(())
+        } else {
+            Err(RuntimeError::WorktopAssertionFailed {
+                resource_address,
+                missing_ids,
+            })
+        }
+    }
+
+    /// Deposits all resources left in this context into an account, and checks that nothing
+    /// is left behind.
+    ///
+    /// Returns the amount deposited of each resource, ordered by resource address.
+    pub fn deposit_worktop_remainder(
+        &mut self,
+        account: Address,
+    ) -> Result<Vec<(Address, Decimal)>, RuntimeError> {
+        let mut remainder: Vec<(Address, Decimal)> = Vec::new();
+        for bucket in self.buckets.values() {
+            let amount = bucket.amount();
+            if amount.is_negative() {
+                return Err(RuntimeError::BucketError(BucketError::InvalidAmount(
+                    amount,
+                )));
+            }
+            if amount.is_zero() {
+                continue;
+            }
+            match remainder
+                .iter_mut()
+                .find(|(address, _)| *address == bucket.resource_address())
+            {
+                Some((_, total)) => *total += amount,
+                None => remainder.push((bucket.resource_address(), amount)),
+            }
+        }
+        remainder.sort_by_key(|(address, _)| address.to_vec());
+
+        let buckets = self.list_buckets();
+        if !buckets.is_empty() {
+            self.call_method(account, "deposit_batch", args!(buckets))?;
+        }
+        self.assert_worktop_empty()?;
+
+        Ok(remainder)
+    }
+
+    /// Deposits all resources in this context into an account, or into the fallback account
+    /// if the deposit fails.
+    ///
+    /// A failed deposit is rolled back, and the buckets are returned to this context before
+    /// being redirected. Failing to find the account, or reaching a limit of the transaction,
+    /// isn't a failed deposit and is returned as is.
+    pub fn deposit_all_buckets_or(
+        &mut self,
+        account: Address,
+        fallback: Address,
+    ) -> Result<(), RuntimeError> {
+        let buckets = self.list_buckets();
+        if buckets.is_empty() {
+            return Ok(());
+        }
+
+        let checkpoint = self.track.checkpoint();
+        let backup = self.buckets.clone();
+        match self.call_method(account, "deposit_batch", args!(buckets.clone())) {
+            Ok(_) => self.track.release(checkpoint),
+            Err(e)
+                if matches!(
+                    e,
+                    RuntimeError::ComponentNotFound(_)
+                        | RuntimeError::PackageNotFound(_)
+                        | RuntimeError::InvalidComponentAddress(_)
+                ) || matches!(
+                    e.root_cause(),
+                    RuntimeError::Timeout
+                        | RuntimeError::CostBudgetExceeded
+                        | RuntimeError::LogLimitExceeded(_)
+                ) =>
+            {
+                // the account can't be found, or a limit of the transaction is reached
+                self.track.release(checkpoint);
+                return Err(e);
+            }
+            Err(e) => {
+                re_warn!(self, "Deposit into {} failed: {:?}", account, e);
+                self.track.rollback(checkpoint);
+                self.buckets = backup;
+                self.moving_buckets.clear();
+                self.call_method(fallback, "deposit_batch", args!(buckets))?;
+            }
+        }
+        Ok(())
+    }
+
     /// Borrows resource from this context to a temporary bucket ref.
     ///
     /// A bucket will be created to support the reference.
@@ -233,6 +433,179 @@ impl<'r, 'l, L: Ledger> Process<'r, 'l, L> {
         Ok(())
     }
 
+    /// Creates a bucket ref backed by the vault of an account, through its `create_proof` method.
+    ///
+    /// The resource stays in the account, locked until the bucket ref is dropped.
+    pub fn borrow_from_account(
+        &mut self,
+        account: Address,
+        amount: Decimal,
+        resource_address: Address,
+        rid: Rid,
+    ) -> Result<(), RuntimeError> {
+        if !self.reserved_rids.remove(&rid) {
+            return Err(RuntimeError::BucketRefNotReserved);
+        }
+        let rtn = self.call_method(account, "create_proof", args!(amount, resource_address))?;
+        let proof: Rid = scrypto_decode(&rtn).map_err(RuntimeError::InvalidData)?;
+        let bucket_ref = self
+            .bucket_refs
+            .remove(&proof)
+            .ok_or(RuntimeError::BucketRefNotFound(proof))?;
+        self.temp_bucket_refs.insert(rid, bucket_ref);
+
+        Ok(())
+    }
+
+    /// Moves resource from a vault of one component directly into a vault of another.
+    ///
+    /// The owner of the source component must be a signer, and the badge must grant transfer
+    /// if the resource is restricted. The deposit hook of the destination component is invoked.
+    pub fn transfer_between_vaults(
+        &mut self,
+        from_component: Address,
+        to_component: Address,
+        resource_address: Address,
+        amount: Decimal,
+        auth: Rid,
+    ) -> Result<(), RuntimeError> {
+        re_debug!(
+            self,
+            "Transferring between vaults: from = {:?}, to = {:?}, resource_def = {:?}, amount = {:?}",
+            from_component,
+            to_component,
+            resource_address,
+            amount
+        );
+        Self::expect_component_address(from_component)?;
+        Self::expect_component_address(to_component)?;
+        Self::expect_resource_address(resource_address)?;
+
+        // authenticate with the badge, then return it to this context
+        let bucket_ref = self
+            .temp_bucket_refs
+            .remove(&auth)
+            .or_else(|| self.bucket_refs.remove(&auth))
+            .ok_or(RuntimeError::BucketRefNotFound(auth))?;
+        if bucket_ref.bucket().amount().is_zero() {
+            return Err(RuntimeError::EmptyBucketRef);
+        }
+        let mut badges = HashSet::new();
+        badges.insert(bucket_ref.bucket().resource_address());
+        self.bucket_refs.insert(auth, bucket_ref);
+        self.handle_drop_bucket_ref(DropBucketRefInput { rid: auth })?;
+
+        self.check_withdraw_auth(from_component)?;
+        self.check_auth(
+            resource_address,
+            Actor::Badges(badges),
+            ResourceDef::check_take_from_vault_auth,
+        )?;
+
+        let from_vid = self.find_vault_of(from_component, resource_address)?;
+        let to_vid = self.find_vault_of(to_component, resource_address)?;
+        let bucket = self
+            .track
+            .get_vault_mut(from_vid)
+            .ok_or(RuntimeError::VaultNotFound(from_vid))?
+            .take(amount, Actor::SuperUser)
+            .map_err(RuntimeError::VaultError)?;
+        self.track.add_movement(
+            MovementKind::Withdrawn,
+            resource_address,
+            amount,
+            Some(from_component),
+            Some(to_component),
+        );
+        self.track.add_movement(
+            MovementKind::Deposited,
+            resource_address,
+            amount,
+            Some(from_component),
+            Some(to_component),
+        );
+        let deposit = bucket.resource_amount();
+        self.track
+            .get_vault_mut(to_vid)
+            .ok_or(RuntimeError::VaultNotFound(to_vid))?
+            .put(bucket, Actor::SuperUser)
+            .map_err(RuntimeError::VaultError)?;
+
+        self.run_deposit_hooks(to_component, vec![deposit])
+    }
+
+    /// Checks that the transaction signers may withdraw from a component, as the account
+    /// blueprint does for its key. Any other component must be owned by a signer.
+    fn check_withdraw_auth(&mut self, component_address: Address) -> Result<(), RuntimeError> {
+        let signers = self.track.transaction_signers();
+        let component = self
+            .track
+            .get_component(component_address)
+            .ok_or(RuntimeError::ComponentNotFound(component_address))?;
+
+        if component.package_address() == ACCOUNT_PACKAGE
+            && component.blueprint_name() == ACCOUNT_BLUEPRINT_NAME
+        {
+            let state = component
+                .state(Actor::SuperUser)
+                .map_err(RuntimeError::ComponentError)?;
+            let account: AccountState = scrypto_decode(state).map_err(RuntimeError::InvalidData)?;
+            return if signers.contains(&account.key) {
+                Ok(())
+            } else {
+                Err(RuntimeError::ComponentError(
+                    ComponentError::UnauthorizedAccess,
+                ))
+            };
+        }
+
+        component
+            .check_owner(&signers)
+            .map_err(RuntimeError::ComponentError)
+    }
+
+    /// Deletes a component, which must be owned by a transaction signer and hold no resource.
+    ///
+    /// The vaults and lazy maps of the component are deleted along with it.
+    pub fn delete_component(&mut self, component_address: Address) -> Result<(), RuntimeError> {
+        re_debug!(
+            self,
+            "Deleting component: address = {:?}",
+            component_address
+        );
+        Self::expect_component_address(component_address)?;
+
+        let signers = self.track.transaction_signers();
+        self.track
+            .get_component(component_address)
+            .ok_or(RuntimeError::ComponentNotFound(component_address))?
+            .check_owner(&signers)
+            .map_err(RuntimeError::ComponentError)?;
+
+        let (vids, mids) = self.find_ids(component_address)?;
+        for vid in &vids {
+            let vid = *vid;
+            let amount = self
+                .track
+                .get_vault(vid)
+                .ok_or(RuntimeError::VaultNotFound(vid))?
+                .amount(Actor::SuperUser)
+                .map_err(RuntimeError::VaultError)?;
+            if !amount.is_zero() {
+                return Err(RuntimeError::ComponentNotEmpty(component_address));
+            }
+        }
+
+        for vid in vids {
+            self.track.delete_vault(vid);
+        }
+        for mid in mids {
+            self.track.delete_lazy_map(mid);
+        }
+        self.track.delete_component(component_address);
+        Ok(())
+    }
+
     /// Puts buckets and bucket refs into this process.
     pub fn put_resources(
         &mut self,
@@ -255,6 +628,15 @@ impl<'r, 'l, L: Ledger> Process<'r, 'l, L> {
         self.buckets.keys().copied().collect()
     }
 
+    /// Returns the ids of all buckets of the given resource.
+    pub fn list_buckets_of(&self, resource_address: Address) -> Vec<Bid> {
+        self.buckets
+            .iter()
+            .filter(|(_, v)| v.resource_address() == resource_address)
+            .map(|(k, _)| *k)
+            .collect()
+    }
+
     /// Returns all bucket ids.
     pub fn drop_bucket_refs(&mut self) {
         let rids: Vec<Rid> = self.bucket_refs.keys().copied().collect();
@@ -329,6 +711,7 @@ impl<'r, 'l, L: Ledger> Process<'r, 'l, L> {
     ) -> Result<Invocation, RuntimeError> {
         Ok(Invocation {
             package_address,
+            component_address: None,
             export_name: format!("{}_main", blueprint_name),
             function: function.to_owned(),
             args,
@@ -351,12 +734,14 @@ impl<'r, 'l, L: Ledger> Process<'r, 'l, L> {
         let mut self_args = vec![scrypto_encode(&component_address)];
         self_args.extend(args);
 
-        self.prepare_call_function(
+        let mut invocation = self.prepare_call_function(
             component.package_address(),
             component.blueprint_name(),
             method,
             self_args,
-        )
+        )?;
+        invocation.component_address = Some(component_address);
+        Ok(invocation)
     }
 
     /// Prepares an ABI call.
@@ -367,6 +752,7 @@ impl<'r, 'l, L: Ledger> Process<'r, 'l, L> {
     ) -> Result<Invocation, RuntimeError> {
         Ok(Invocation {
             package_address: package_address,
+            component_address: None,
             export_name: format!("{}_abi", blueprint_name),
             function: String::new(),
             args: Vec::new(),
@@ -380,18 +766,48 @@ impl<'r, 'l, L: Ledger> Process<'r, 'l, L> {
             self.process_data(arg, Self::move_buckets, Self::move_bucket_refs)?;
         }
         let (buckets_out, bucket_refs_out) = self.take_moving_resources();
+        let is_method = invocation.component_address.is_some();
+        if self.depth == 0 {
+            self.track.set_worktop_amounts(self.worktop_amounts());
+        }
+        self.track.trace(TraceEvent::CallStarted {
+            depth: self.depth + 1,
+            package_address: invocation.package_address,
+            export_name: invocation.export_name.clone(),
+            function: invocation.function.clone(),
+        });
         let mut process = Process::new(self.depth + 1, self.trace, self.track);
         process.put_resources(buckets_out, bucket_refs_out);
 
         // run the function
-        let result = process.run(invocation)?;
-        process.check_resource()?;
+        let result = process
+            .run(invocation)
+            .and_then(|rtn| process.check_resource().map(|_| rtn));
+        process.track.trace(TraceEvent::CallEnded {
+            depth: process.depth,
+            success: result.is_ok(),
+        });
+        let result = result?;
 
         // move resource
         let (buckets_in, bucket_refs_in) = process.take_moving_resources();
+        // only method calls run deposit hooks, for the deposits made into their component
+        self.child_deposits = if is_method {
+            mem::take(&mut process.deposits)
+        } else {
+            Vec::new()
+        };
         self.put_resources(buckets_in, bucket_refs_in);
+        self.check_worktop_resources()?;
+        self.unlock_buckets();
+        self.track.unlock_vault_amounts()?;
 
-        // scan locked buckets for some might have been unlocked by child processes
+        Ok(result)
+    }
+
+    /// Unlocks buckets which are no longer referenced, for some might have been unlocked by
+    /// child processes.
+    fn unlock_buckets(&mut self) {
         let bids: Vec<Bid> = self
             .locked_buckets
             .values()
@@ -404,8 +820,6 @@ impl<'r, 'l, L: Ledger> Process<'r, 'l, L> {
             let bucket = Rc::try_unwrap(bucket_rc).unwrap();
             self.buckets.insert(bid, bucket.into());
         }
-
-        Ok(result)
     }
 
     /// Calls a function.
@@ -425,19 +839,103 @@ impl<'r, 'l, L: Ledger> Process<'r, 'l, L> {
     }
 
     /// Calls a method.
+    ///
+    /// The deposit hook is reserved for the engine, and calling it is rejected.
     pub fn call_method(
         &mut self,
         component_address: Address,
         method: &str,
         args: Vec<Vec<u8>>,
+    ) -> Result<Vec<u8>, RuntimeError> {
+        if method == DEPOSIT_HOOK {
+            return Err(RuntimeError::DepositHookNotCallable(component_address));
+        }
+        self.invoke_method(component_address, method, args)
+    }
+
+    /// Calls a method, and then the deposit hook if the component has received resources.
+    fn invoke_method(
+        &mut self,
+        component_address: Address,
+        method: &str,
+        args: Vec<Vec<u8>>,
     ) -> Result<Vec<u8>, RuntimeError> {
         re_debug!(self, "Call method started");
         let invocation = self.prepare_call_method(component_address, method, args)?;
-        let result = self.call(invocation);
+        let result = self.call(invocation)?;
+
+        // invoke the deposit hook, if the component has received resources
+        let deposits = mem::take(&mut self.child_deposits);
+        if method != DEPOSIT_HOOK {
+            self.run_deposit_hooks(component_address, deposits)?;
+        }
         re_debug!(self, "Call method ended");
+        Ok(result)
+    }
+
+    /// Invokes the deposit hook of a component once for each deposit, if its blueprint has one.
+    ///
+    /// A hook whose component receives resources again while it's running, e.g. by calling a
+    /// method of its own component, fails the call instead of invoking itself without end.
+    fn run_deposit_hooks(
+        &mut self,
+        component_address: Address,
+        deposits: Vec<ResourceAmount>,
+    ) -> Result<(), RuntimeError> {
+        if deposits.is_empty() || !self.has_deposit_hook(component_address)? {
+            return Ok(());
+        }
+        if !self.track.enter_deposit_hook(component_address) {
+            return Err(RuntimeError::DepositHookReentered(component_address));
+        }
+
+        let result = deposits.into_iter().try_for_each(|deposit| {
+            re_debug!(
+                self,
+                "Invoking deposit hook: component = {:?}, deposit = {:?}",
+                component_address,
+                deposit
+            );
+            self.invoke_method(
+                component_address,
+                DEPOSIT_HOOK,
+                vec![scrypto_encode(&deposit)],
+            )
+            .map(|_| ())
+        });
+        self.track.exit_deposit_hook(component_address);
         result
     }
 
+    /// Checks if the blueprint of a component marks a method as the deposit hook in its ABI.
+    ///
+    /// The answer is kept per blueprint, and carried over to later transactions by the executor,
+    /// so that the ABI is only exported once.
+    fn has_deposit_hook(&mut self, component_address: Address) -> Result<bool, RuntimeError> {
+        let component = self
+            .track
+            .get_component(component_address)
+            .ok_or(RuntimeError::ComponentNotFound(component_address))?
+            .clone();
+        let key = (
+            component.package_address(),
+            component.blueprint_name().to_owned(),
+        );
+
+        if let Some(result) = self.track.get_deposit_hook(&key) {
+            return Ok(result);
+        }
+        let invocation =
+            self.prepare_call_abi(component.package_address(), component.blueprint_name())?;
+        let abi: (Vec<abi::Function>, Vec<abi::Method>) =
+            Process::new(self.depth + 1, self.trace, self.track)
+                .run(invocation)
+                .and_then(|rtn| scrypto_decode(&rtn).map_err(RuntimeError::InvalidData))?;
+        let result = abi.1.iter().any(|m| m.hook == Some(abi::Hook::Deposit));
+        self.track.put_deposit_hook(key, result);
+        Ok(result)
+    }
+
     /// Calls the ABI generator of a blueprint.
     pub fn call_abi(
         &mut self,
@@ -468,6 +966,10 @@ impl<'r, 'l, L: Ledger> Process<'r, 'l, L> {
             re_warn!(self, "Dangling bucket ref: {:?}, {:?}", rid, bucket_ref);
             success = false;
         }
+        for (bucket_ref, _) in &self.presented_bucket_refs {
+            re_warn!(self, "Dangling presented bucket ref: {:?}", bucket_ref);
+            success = false;
+        }
         for (bid, bucket) in &self.temp_buckets {
             re_warn!(self, "Dangling temp bucket: {:?}, {:?}", bid, bucket);
             success = false;
@@ -490,6 +992,16 @@ impl<'r, 'l, L: Ledger> Process<'r, 'l, L> {
         }
     }
 
+    /// Sets the index of the instruction being executed, for attributing resource movements.
+    pub fn set_instruction_index(&mut self, index: usize) {
+        self.track.set_instruction_index(index);
+    }
+
+    /// Emits a trace event.
+    pub fn trace(&self, event: TraceEvent) {
+        self.track.trace(event);
+    }
+
     /// Logs a message to the console.
     #[allow(unused_variables)]
     pub fn log(&self, level: LogLevel, msg: String) {
@@ -513,6 +1025,13 @@ impl<'r, 'l, L: Ledger> Process<'r, 'l, L> {
             .map(|vm| vm.invocation.package_address)
     }
 
+    /// Returns the component whose method is being run, if any.
+    fn component_address(&self) -> Option<Address> {
+        self.vm
+            .as_ref()
+            .and_then(|vm| vm.invocation.component_address)
+    }
+
     /// Return the function name
     fn function(&self) -> Result<String, RuntimeError> {
         self.vm
@@ -610,7 +1129,7 @@ impl<'r, 'l, L: Ledger> Process<'r, 'l, L> {
                 Ok(Value::HashMap(ty_k, ty_v, self.visit_map(values, bf, rf)?))
             }
             // custom types
-            Value::Custom(ty, data) => self.visit_custom(ty, data, bf, rf),
+            Value::Custom { ty, name, bytes } => self.visit_custom(ty, name, bytes, bf, rf),
         }
     }
 
@@ -656,6 +1175,7 @@ impl<'r, 'l, L: Ledger> Process<'r, 'l, L> {
     fn visit_custom(
         &mut self,
         ty: u8,
+        name: Option<String>,
         data: Vec<u8>,
         bf: fn(&mut Self, Bid) -> Result<Bid, RuntimeError>,
         rf: fn(&mut Self, Rid) -> Result<Rid, RuntimeError>,
@@ -668,7 +1188,11 @@ impl<'r, 'l, L: Ledger> Process<'r, 'l, L> {
                         RuntimeError::InvalidData(DecodeError::InvalidCustomData(ty))
                     })?,
                 )?;
-                Ok(Value::Custom(ty, bid.to_vec()))
+                Ok(Value::Custom {
+                    ty,
+                    name,
+                    bytes: bid.to_vec(),
+                })
             }
             SCRYPTO_TYPE_RID => {
                 let rid = rf(
@@ -677,14 +1201,25 @@ impl<'r, 'l, L: Ledger> Process<'r, 'l, L> {
                         RuntimeError::InvalidData(DecodeError::InvalidCustomData(ty))
                     })?,
                 )?;
-                Ok(Value::Custom(ty, rid.to_vec()))
+                Ok(Value::Custom {
+                    ty,
+                    name,
+                    bytes: rid.to_vec(),
+                })
             }
             SCRYPTO_TYPE_DECIMAL
             | SCRYPTO_TYPE_BIG_DECIMAL
             | SCRYPTO_TYPE_ADDRESS
             | SCRYPTO_TYPE_H256
             | SCRYPTO_TYPE_MID
-            | SCRYPTO_TYPE_VID => Ok(Value::Custom(ty, data)),
+            | SCRYPTO_TYPE_VID
+            | SCRYPTO_TYPE_U256
+            | SCRYPTO_TYPE_I256
+            | SCRYPTO_TYPE_FIXED_POINT => Ok(Value::Custom {
+                ty,
+                name,
+                bytes: data,
+            }),
             _ => Err(RuntimeError::InvalidData(DecodeError::InvalidCustomData(
                 ty,
             ))),
@@ -699,6 +1234,12 @@ impl<'r, 'l, L: Ledger> Process<'r, 'l, L> {
             .or_else(|| self.temp_buckets.remove(&bid))
             .ok_or(RuntimeError::BucketNotFound(bid))?;
         re_debug!(self, "Moving bucket: {:?}, {:?}", bid, bucket);
+        self.track.trace(TraceEvent::BucketMoved {
+            depth: self.depth,
+            bid,
+            resource_address: bucket.resource_address(),
+            amount: bucket.amount(),
+        });
         self.moving_buckets.insert(bid, bucket);
         Ok(bid)
     }
@@ -711,6 +1252,12 @@ impl<'r, 'l, L: Ledger> Process<'r, 'l, L> {
             .or_else(|| self.temp_bucket_refs.remove(&rid))
             .ok_or(RuntimeError::BucketRefNotFound(rid))?;
         re_debug!(self, "Moving bucket ref: {:?}, {:?}", rid, bucket_ref);
+        self.track.trace(TraceEvent::BucketRefMoved {
+            depth: self.depth,
+            rid,
+            resource_address: bucket_ref.bucket().resource_address(),
+            amount: bucket_ref.bucket().amount(),
+        });
         self.moving_bucket_refs.insert(rid, bucket_ref);
         Ok(rid)
     }
@@ -926,14 +1473,69 @@ impl<'r, 'l, L: Ledger> Process<'r, 'l, L> {
             input.args
         );
 
-        let invocation =
-            self.prepare_call_method(input.component_address, input.method.as_str(), input.args)?;
-        let result = self.call(invocation);
+        let result = self.call_method(input.component_address, input.method.as_str(), input.args);
 
         re_debug!(self, "CALL finished");
         Ok(CallMethodOutput { rtn: result? })
     }
 
+    fn handle_call_method_with_budget(
+        &mut self,
+        input: CallMethodWithBudgetInput,
+    ) -> Result<CallMethodWithBudgetOutput, RuntimeError> {
+        Self::expect_component_address(input.component_address)?;
+
+        re_debug!(
+            self,
+            "CALL started: component = {:?}, method = {:?}, args = {:?}, max_cost = {:?}",
+            input.component_address,
+            input.method,
+            input.args,
+            input.max_cost
+        );
+
+        let checkpoint = self.track.checkpoint();
+        let buckets = self.buckets.clone();
+        let bucket_refs = self.bucket_refs.clone();
+        self.track.push_cost_budget(input.max_cost);
+        let result = self.call_method(input.component_address, input.method.as_str(), input.args);
+        let remaining = self.track.pop_cost_budget();
+
+        let rtn = match result {
+            Ok(rtn) => {
+                self.track.release(checkpoint);
+                // the backup held references to locked buckets
+                drop(bucket_refs);
+                self.unlock_buckets();
+                Ok(rtn)
+            }
+            Err(e) => {
+                let error = if remaining == 0 {
+                    CallError::BudgetExceeded
+                } else if matches!(
+                    e.root_cause(),
+                    RuntimeError::Timeout | RuntimeError::CostBudgetExceeded
+                ) {
+                    // the limit of the transaction or an outer budget is reached
+                    self.track.release(checkpoint);
+                    return Err(e);
+                } else {
+                    CallError::Failed
+                };
+                re_warn!(self, "Call to {} failed: {:?}", input.component_address, e);
+                self.track.rollback(checkpoint);
+                self.buckets = buckets;
+                self.bucket_refs = bucket_refs;
+                self.moving_buckets.clear();
+                self.moving_bucket_refs.clear();
+                Err(error)
+            }
+        };
+
+        re_debug!(self, "CALL finished");
+        Ok(CallMethodWithBudgetOutput { rtn })
+    }
+
     fn handle_create_component(
         &mut self,
         input: CreateComponentInput,
@@ -1020,6 +1622,25 @@ impl<'r, 'l, L: Ledger> Process<'r, 'l, L> {
         Ok(PutComponentStateOutput {})
     }
 
+    fn handle_set_component_owner(
+        &mut self,
+        input: SetComponentOwnerInput,
+    ) -> Result<SetComponentOwnerOutput, RuntimeError> {
+        Self::expect_component_address(input.component_address)?;
+        let actor = self.authenticate()?;
+
+        let component = self
+            .track
+            .get_component_mut(input.component_address)
+            .ok_or(RuntimeError::ComponentNotFound(input.component_address))?;
+
+        component
+            .set_owner(input.owner, actor)
+            .map_err(RuntimeError::ComponentError)?;
+
+        Ok(SetComponentOwnerOutput {})
+    }
+
     fn handle_create_lazy_map(
         &mut self,
         _input: CreateLazyMapInput,
@@ -1126,28 +1747,58 @@ impl<'r, 'l, L: Ledger> Process<'r, 'l, L> {
             return Err(RuntimeError::ResourceDefAlreadyExists(resource_address));
         }
         re_debug!(self, "New resource definition: {:?}", resource_address);
-        let definition = ResourceDef::new(
-            input.resource_type,
-            input.metadata,
-            input.flags,
-            input.mutable_flags,
-            input.authorities,
-            &input.initial_supply,
-        )
-        .map_err(RuntimeError::ResourceDefError)?;
+        let (definition, bucket) = match (input.resource_type, &input.initial_supply) {
+            // fixed supply, i.e. no flags nor authorities to ever change it
+            (ResourceType::Fungible { divisibility }, Some(NewSupply::Fungible { amount }))
+                if input.flags == 0 && input.mutable_flags == 0 && input.authorities.is_empty() =>
+            {
+                let (definition, bucket) =
+                    ResourceDef::new_fixed(resource_address, divisibility, input.metadata, *amount)
+                        .map_err(RuntimeError::from)?;
+                (definition, Some(bucket))
+            }
+            _ => {
+                let definition = ResourceDef::new(
+                    input.resource_type,
+                    input.metadata,
+                    input.flags,
+                    input.mutable_flags,
+                    input.authorities,
+                    &input.initial_supply,
+                )
+                .map_err(RuntimeError::from)?;
+
+                // allocate supply
+                let bucket = match input.initial_supply {
+                    Some(initial_supply) => {
+                        let supply = self.allocate_resource(resource_address, initial_supply)?;
+                        Some(Bucket::new(resource_address, input.resource_type, supply))
+                    }
+                    None => None,
+                };
+                (definition, bucket)
+            }
+        };
         self.track.put_resource_def(resource_address, definition);
 
-        // allocate supply
-        let bucket = if let Some(initial_supply) = input.initial_supply {
-            let supply = self.allocate_resource(resource_address, initial_supply)?;
-
-            let bucket = Bucket::new(resource_address, input.resource_type, supply);
+        let supply = bucket.as_ref().map(Bucket::supply);
+        if let Some(bucket) = &bucket {
+            let to = self.component_address();
+            self.track.add_movement(
+                MovementKind::Minted,
+                resource_address,
+                bucket.amount(),
+                None,
+                to,
+            );
+        }
+        let bucket = bucket.map(|bucket| {
             let bid = self.track.new_bid();
             self.buckets.insert(bid, bucket);
-            Some(bid)
-        } else {
-            None
-        };
+            bid
+        });
+        self.track
+            .add_resource_creation(resource_address, input.resource_type, supply);
 
         Ok(CreateResourceOutput {
             resource_address,
@@ -1209,14 +1860,19 @@ impl<'r, 'l, L: Ledger> Process<'r, 'l, L> {
     ) -> Result<UpdateResourceFlagsOutput, RuntimeError> {
         Self::expect_resource_address(input.resource_address)?;
         let actor = self.authenticate_with_badge(Some(input.auth))?;
+        self.check_auth(
+            input.resource_address,
+            actor,
+            ResourceDef::check_manage_flags_auth,
+        )?;
 
         let resource_def = self
             .track
             .get_resource_def_mut(input.resource_address)
             .ok_or(RuntimeError::ResourceDefNotFound(input.resource_address))?;
         resource_def
-            .update_flags(input.new_flags, actor)
-            .map_err(RuntimeError::ResourceDefError)?;
+            .update_flags(input.new_flags, Actor::SuperUser)
+            .map_err(RuntimeError::from)?;
 
         Ok(UpdateResourceFlagsOutput {})
     }
@@ -1243,14 +1899,19 @@ impl<'r, 'l, L: Ledger> Process<'r, 'l, L> {
     ) -> Result<UpdateResourceMutableFlagsOutput, RuntimeError> {
         Self::expect_resource_address(input.resource_address)?;
         let actor = self.authenticate_with_badge(Some(input.auth))?;
+        self.check_auth(
+            input.resource_address,
+            actor,
+            ResourceDef::check_manage_flags_auth,
+        )?;
 
         let resource_def = self
             .track
             .get_resource_def_mut(input.resource_address)
             .ok_or(RuntimeError::ResourceDefNotFound(input.resource_address))?;
         resource_def
-            .update_mutable_flags(input.new_mutable_flags, actor)
-            .map_err(RuntimeError::ResourceDefError)?;
+            .update_mutable_flags(input.new_mutable_flags, Actor::SuperUser)
+            .map_err(RuntimeError::from)?;
 
         Ok(UpdateResourceMutableFlagsOutput {})
     }
@@ -1277,6 +1938,7 @@ impl<'r, 'l, L: Ledger> Process<'r, 'l, L> {
     ) -> Result<MintResourceOutput, RuntimeError> {
         Self::expect_resource_address(input.resource_address)?;
         let actor = self.authenticate_with_badge(Some(input.auth))?;
+        self.check_auth(input.resource_address, actor, ResourceDef::check_mint_auth)?;
 
         // allocate resource
         let supply = self.allocate_resource(input.resource_address, input.new_supply)?;
@@ -1287,11 +1949,19 @@ impl<'r, 'l, L: Ledger> Process<'r, 'l, L> {
             .get_resource_def_mut(input.resource_address)
             .ok_or(RuntimeError::ResourceDefNotFound(input.resource_address))?;
         resource_def
-            .mint(&supply, actor)
-            .map_err(RuntimeError::ResourceDefError)?;
+            .mint(&supply, Actor::SuperUser)
+            .map_err(RuntimeError::from)?;
 
         // wrap resource into a bucket
         let bucket = Bucket::new(input.resource_address, resource_def.resource_type(), supply);
+        let to = self.component_address();
+        self.track.add_movement(
+            MovementKind::Minted,
+            input.resource_address,
+            bucket.amount(),
+            None,
+            to,
+        );
         let bid = self.track.new_bid();
         self.buckets.insert(bid, bucket);
 
@@ -1308,6 +1978,11 @@ impl<'r, 'l, L: Ledger> Process<'r, 'l, L> {
             .buckets
             .remove(&input.bid)
             .ok_or(RuntimeError::BucketNotFound(input.bid))?;
+        self.check_auth(
+            bucket.resource_address(),
+            actor,
+            ResourceDef::check_burn_auth,
+        )?;
 
         let resource_def = self
             .track
@@ -1315,8 +1990,16 @@ impl<'r, 'l, L: Ledger> Process<'r, 'l, L> {
             .ok_or(RuntimeError::ResourceDefNotFound(bucket.resource_address()))?;
 
         resource_def
-            .burn(bucket.supply(), actor)
-            .map_err(RuntimeError::ResourceDefError)?;
+            .burn(bucket.supply(), Actor::SuperUser)
+            .map_err(RuntimeError::from)?;
+        let from = self.component_address();
+        self.track.add_movement(
+            MovementKind::Burned,
+            bucket.resource_address(),
+            bucket.amount(),
+            from,
+            None,
+        );
         Ok(BurnResourceOutput {})
     }
 
@@ -1327,23 +2010,24 @@ impl<'r, 'l, L: Ledger> Process<'r, 'l, L> {
         let actor = self.authenticate_with_badge(Some(input.auth))?;
 
         // obtain authorization from resource definition
-        let resource_def = self
-            .track
-            .get_resource_def(input.resource_address)
-            .ok_or(RuntimeError::ResourceDefNotFound(input.resource_address))?;
-        resource_def
-            .check_update_nft_mutable_data_auth(actor)
-            .map_err(RuntimeError::ResourceDefError)?;
+        self.check_auth(
+            input.resource_address,
+            actor,
+            ResourceDef::check_update_nft_mutable_data_auth,
+        )?;
         // update state
         let mutable_data = self.process_data(
             &input.new_mutable_data,
             Self::reject_buckets,
             Self::reject_bucket_refs,
         )?;
-        self.track
+        let nft = self
+            .track
             .get_nft_mut(input.resource_address, input.id)
-            .ok_or(RuntimeError::NftNotFound(input.resource_address, input.id))?
-            .set_mutable_data(mutable_data)
+            .ok_or(RuntimeError::NftNotFound(input.resource_address, input.id))?;
+        nft.check_immutable_data(&input.immutable_data)
+            .map_err(RuntimeError::NftError)?;
+        nft.set_mutable_data(mutable_data)
             .map_err(RuntimeError::NftError)?;
 
         Ok(UpdateNftMutableDataOutput {})
@@ -1369,18 +2053,211 @@ impl<'r, 'l, L: Ledger> Process<'r, 'l, L> {
         input: UpdateResourceMetadataInput,
     ) -> Result<UpdateResourceMetadataOutput, RuntimeError> {
         let actor = self.authenticate_with_badge(Some(input.auth))?;
+        self.check_auth(
+            input.resource_address,
+            actor,
+            ResourceDef::check_update_metadata_auth,
+        )?;
 
         let resource_def = self
             .track
             .get_resource_def_mut(input.resource_address)
             .ok_or(RuntimeError::ResourceDefNotFound(input.resource_address))?;
         resource_def
-            .update_metadata(input.new_metadata, actor)
-            .map_err(RuntimeError::ResourceDefError)?;
+            .update_metadata(input.new_metadata, Actor::SuperUser)
+            .map_err(RuntimeError::from)?;
 
         Ok(UpdateResourceMetadataOutput {})
     }
 
+    fn handle_recall_resource(
+        &mut self,
+        input: RecallResourceInput,
+    ) -> Result<RecallResourceOutput, RuntimeError> {
+        Self::expect_resource_address(input.resource_address)?;
+        Self::expect_component_address(input.component_address)?;
+        let actor = self.authenticate_with_badge(Some(input.auth))?;
+
+        self.check_auth(
+            input.resource_address,
+            actor,
+            ResourceDef::check_recall_auth,
+        )?;
+
+        let vid = self.find_vault_of(input.component_address, input.resource_address)?;
+        re_debug!(self, "Recalling from vault: {:?}", vid);
+
+        let new_bucket = self
+            .track
+            .get_vault_mut(vid)
+            .ok_or(RuntimeError::VaultNotFound(vid))?
+            .take(input.amount, Actor::SuperUser)
+            .map_err(RuntimeError::VaultError)?;
+
+        self.track.add_movement(
+            MovementKind::Withdrawn,
+            new_bucket.resource_address(),
+            new_bucket.amount(),
+            Some(input.component_address),
+            None,
+        );
+        let bid = self.track.new_bid();
+        self.buckets.insert(bid, new_bucket);
+
+        Ok(RecallResourceOutput { bid })
+    }
+
+    /// Finds the vault of the given resource owned by a component, bypassing component ownership.
+    fn find_vault_of(
+        &mut self,
+        component_address: Address,
+        resource_address: Address,
+    ) -> Result<Vid, RuntimeError> {
+        self.find_vaults(component_address)?
+            .into_iter()
+            .find(|vid| {
+                self.track
+                    .get_vault(*vid)
+                    .and_then(|v| v.resource_address(Actor::SuperUser).ok())
+                    == Some(resource_address)
+            })
+            .ok_or(RuntimeError::ComponentVaultNotFound(
+                component_address,
+                resource_address,
+            ))
+    }
+
+    /// Returns the ids of the NFTs held by a component, in its vault of the given resource.
+    pub fn get_nft_ids_of(
+        &mut self,
+        component_address: Address,
+        resource_address: Address,
+    ) -> Result<Vec<u128>, RuntimeError> {
+        let vid = self.find_vault_of(component_address, resource_address)?;
+        self.track
+            .get_vault(vid)
+            .ok_or(RuntimeError::VaultNotFound(vid))?
+            .get_nft_ids(Actor::SuperUser)
+            .map_err(RuntimeError::VaultError)
+    }
+
+    /// Finds all vaults owned by a component, including those within its lazy maps.
+    fn find_vaults(&mut self, component_address: Address) -> Result<Vec<Vid>, RuntimeError> {
+        self.find_ids(component_address).map(|(vids, _)| vids)
+    }
+
+    /// Finds all vaults and lazy maps owned by a component, including those nested within
+    /// its lazy maps.
+    fn find_ids(
+        &mut self,
+        component_address: Address,
+    ) -> Result<(Vec<Vid>, Vec<Mid>), RuntimeError> {
+        let state = self
+            .track
+            .get_component(component_address)
+            .ok_or(RuntimeError::ComponentNotFound(component_address))?
+            .state(Actor::SuperUser)
+            .map_err(RuntimeError::ComponentError)?
+            .to_vec();
+
+        Self::find_ids_in(&state, |mid| {
+            self.track.get_lazy_map(mid).map(|lazy_map| {
+                lazy_map
+                    .map()
+                    .iter()
+                    .flat_map(|(k, v)| [k.clone(), v.clone()])
+                    .collect()
+            })
+        })
+    }
+
+    /// Finds all vaults and lazy maps referenced by a component state, including those nested
+    /// within its lazy maps, whose keys and values are read with `lazy_map_entries`.
+    pub fn find_ids_in<F: FnMut(Mid) -> Option<Vec<Vec<u8>>>>(
+        state: &[u8],
+        mut lazy_map_entries: F,
+    ) -> Result<(Vec<Vid>, Vec<Mid>), RuntimeError> {
+        let mut vids = Vec::new();
+        let mut mids = Vec::new();
+        Self::collect_ids(
+            &decode_any(state).map_err(RuntimeError::InvalidData)?,
+            &mut vids,
+            &mut mids,
+        );
+
+        let mut visited = HashSet::new();
+        let mut found = Vec::new();
+        while let Some(mid) = mids.pop() {
+            if !visited.insert(mid) {
+                continue;
+            }
+            found.push(mid);
+            let entries = lazy_map_entries(mid).ok_or(RuntimeError::LazyMapNotFound(mid))?;
+            for data in entries {
+                Self::collect_ids(
+                    &decode_any(&data).map_err(RuntimeError::InvalidData)?,
+                    &mut vids,
+                    &mut mids,
+                );
+            }
+        }
+
+        Ok((vids, found))
+    }
+
+    /// Collects the vault and lazy map ids within a value.
+    pub fn collect_ids(v: &Value, vids: &mut Vec<Vid>, mids: &mut Vec<Mid>) {
+        match v {
+            Value::Struct(fields) | Value::Enum(_, fields) => match fields {
+                Fields::Named(values) | Fields::Unnamed(values) => {
+                    values.iter().for_each(|e| Self::collect_ids(e, vids, mids))
+                }
+                Fields::Unit => {}
+            },
+            Value::Option(x) => {
+                if let Some(value) = x.as_ref() {
+                    Self::collect_ids(value, vids, mids);
+                }
+            }
+            Value::Box(value) => Self::collect_ids(value, vids, mids),
+            Value::Result(x) => match x.as_ref() {
+                Ok(value) | Err(value) => Self::collect_ids(value, vids, mids),
+            },
+            Value::Array(_, values)
+            | Value::Tuple(values)
+            | Value::Vec(_, values)
+            | Value::TreeSet(_, values)
+            | Value::HashSet(_, values) => {
+                values.iter().for_each(|e| Self::collect_ids(e, vids, mids))
+            }
+            Value::TreeMap(_, _, values) | Value::HashMap(_, _, values) => {
+                for (k, v) in values {
+                    Self::collect_ids(k, vids, mids);
+                    Self::collect_ids(v, vids, mids);
+                }
+            }
+            Value::Custom {
+                ty: SCRYPTO_TYPE_VID,
+                bytes,
+                ..
+            } => {
+                if let Ok(vid) = Vid::try_from(bytes.as_slice()) {
+                    vids.push(vid);
+                }
+            }
+            Value::Custom {
+                ty: SCRYPTO_TYPE_MID,
+                bytes,
+                ..
+            } => {
+                if let Ok(mid) = Mid::try_from(bytes.as_slice()) {
+                    mids.push(mid);
+                }
+            }
+            _ => {}
+        }
+    }
+
     fn handle_create_vault(
         &mut self,
         input: CreateEmptyVaultInput,
@@ -1421,6 +2298,15 @@ impl<'r, 'l, L: Ledger> Process<'r, 'l, L> {
             .buckets
             .remove(&input.bid)
             .ok_or(RuntimeError::BucketNotFound(input.bid))?;
+        self.deposits.push(other.resource_amount());
+        let to = self.component_address();
+        self.track.add_movement(
+            MovementKind::Deposited,
+            other.resource_address(),
+            other.amount(),
+            None,
+            to,
+        );
 
         self.track
             .get_vault_mut(input.vid)
@@ -1438,13 +2324,48 @@ impl<'r, 'l, L: Ledger> Process<'r, 'l, L> {
             .ok_or(RuntimeError::VaultNotFound(vid))?
             .resource_address(actor.clone())
             .map_err(RuntimeError::VaultError)?;
+        self.check_auth(
+            resource_address,
+            actor,
+            ResourceDef::check_take_from_vault_auth,
+        )
+    }
+
+    /// Checks that an actor holds a permission on a resource.
+    ///
+    /// When previewing auth, a missing permission is recorded instead. Operations checked here
+    /// proceed as a super user, so that the permission is not checked twice.
+    fn check_auth(
+        &mut self,
+        resource_address: Address,
+        actor: Actor,
+        check: fn(&ResourceDef, Actor) -> Result<(), ResourceDefError>,
+    ) -> Result<(), RuntimeError> {
         let resource_def = self
             .track
             .get_resource_def(resource_address)
             .ok_or(RuntimeError::ResourceDefNotFound(resource_address))?;
-        resource_def
-            .check_take_from_vault_auth(actor)
-            .map_err(RuntimeError::ResourceDefError)
+        match check(resource_def, actor) {
+            Ok(()) => Ok(()),
+            Err(ResourceDefError::NotAuthorized {
+                required_badge,
+                permission,
+            }) if self.track.is_auth_preview() => {
+                re_debug!(
+                    self,
+                    "Missing permission: {:?}, {}",
+                    resource_address,
+                    permission
+                );
+                self.track.add_resource_auth_requirement(
+                    resource_address,
+                    permission,
+                    required_badge,
+                );
+                Ok(())
+            }
+            Err(e) => Err(e.into()),
+        }
     }
 
     fn handle_take_from_vault(
@@ -1461,6 +2382,14 @@ impl<'r, 'l, L: Ledger> Process<'r, 'l, L> {
             .take(input.amount, actor)
             .map_err(RuntimeError::VaultError)?;
 
+        let from = self.component_address();
+        self.track.add_movement(
+            MovementKind::Withdrawn,
+            new_bucket.resource_address(),
+            new_bucket.amount(),
+            from,
+            None,
+        );
         let bid = self.track.new_bid();
         self.buckets.insert(bid, new_bucket);
 
@@ -1481,6 +2410,14 @@ impl<'r, 'l, L: Ledger> Process<'r, 'l, L> {
             .take_nft(input.id, actor)
             .map_err(RuntimeError::VaultError)?;
 
+        let from = self.component_address();
+        self.track.add_movement(
+            MovementKind::Withdrawn,
+            new_bucket.resource_address(),
+            new_bucket.amount(),
+            from,
+            None,
+        );
         let bid = self.track.new_bid();
         self.buckets.insert(bid, new_bucket);
 
@@ -1693,28 +2630,56 @@ impl<'r, 'l, L: Ledger> Process<'r, 'l, L> {
         Ok(CreateBucketRefOutput { rid })
     }
 
+    fn handle_create_bucket_ref_from_vault(
+        &mut self,
+        input: CreateBucketRefFromVaultInput,
+    ) -> Result<CreateBucketRefFromVaultOutput, RuntimeError> {
+        let actor = self.authenticate()?;
+
+        // the amount is held by the track until the last bucket ref is dropped, so that it
+        // can't be withdrawn meanwhile
+        let bucket = self
+            .track
+            .lock_vault_amount(input.vid, input.amount, actor)?;
+        let rid = self.track.new_rid();
+        re_debug!(
+            self,
+            "Borrowing from vault: vid = {:?}, rid = {:?}",
+            input.vid,
+            rid
+        );
+        self.bucket_refs.insert(rid, bucket);
+
+        Ok(CreateBucketRefFromVaultOutput { rid })
+    }
+
     fn handle_drop_bucket_ref(
         &mut self,
         input: DropBucketRefInput,
     ) -> Result<DropBucketRefOutput, RuntimeError> {
         let rid = input.rid;
 
-        let (count, bid) = {
-            let bucket_ref = self
-                .bucket_refs
-                .remove(&rid)
-                .ok_or(RuntimeError::BucketRefNotFound(rid))?;
-            re_debug!(self, "Returning {:?}: {:?}", rid, bucket_ref);
-            (Rc::strong_count(&bucket_ref) - 1, bucket_ref.bucket_id())
-        };
+        let bucket_ref = self
+            .bucket_refs
+            .remove(&rid)
+            .ok_or(RuntimeError::BucketRefNotFound(rid))?;
+        re_debug!(self, "Returning {:?}: {:?}", rid, bucket_ref);
+        self.release_bucket_ref(bucket_ref)?;
+
+        Ok(DropBucketRefOutput {})
+    }
+
+    /// Drops a bucket ref, unlocking the bucket once its last ref is gone.
+    fn release_bucket_ref(&mut self, bucket_ref: BucketRef) -> Result<(), RuntimeError> {
+        let (count, bid) = (Rc::strong_count(&bucket_ref) - 1, bucket_ref.bucket_id());
+        drop(bucket_ref);
 
         if count == 1 {
             if let Some(b) = self.locked_buckets.remove(&bid) {
                 self.buckets.insert(bid, Rc::try_unwrap(b).unwrap().into());
             }
         }
-
-        Ok(DropBucketRefOutput {})
+        self.track.unlock_vault_amounts()
     }
 
     fn handle_get_bucket_ref_amount(
@@ -1762,6 +2727,35 @@ impl<'r, 'l, L: Ledger> Process<'r, 'l, L> {
         })
     }
 
+    fn handle_get_nft_data_in_bucket_ref(
+        &mut self,
+        input: GetNftDataInBucketRefInput,
+    ) -> Result<GetNftDataInBucketRefOutput, RuntimeError> {
+        let bucket = self
+            .bucket_refs
+            .get(&input.rid)
+            .ok_or(RuntimeError::BucketRefNotFound(input.rid))?
+            .bucket();
+        let resource_address = bucket.resource_address();
+        if !bucket
+            .get_nft_ids()
+            .map_err(RuntimeError::BucketError)?
+            .contains(&input.id)
+        {
+            return Err(RuntimeError::BucketError(BucketError::NftNotFound));
+        }
+
+        let nft = self
+            .track
+            .get_nft(resource_address, input.id)
+            .ok_or(RuntimeError::NftNotFound(resource_address, input.id))?;
+
+        Ok(GetNftDataInBucketRefOutput {
+            immutable_data: nft.immutable_data(),
+            mutable_data: nft.mutable_data(),
+        })
+    }
+
     fn handle_clone_bucket_ref(
         &mut self,
         input: CloneBucketRefInput,
@@ -1784,8 +2778,95 @@ impl<'r, 'l, L: Ledger> Process<'r, 'l, L> {
         Ok(CloneBucketRefOutput { rid: new_rid })
     }
 
+    fn handle_present_bucket(
+        &mut self,
+        input: PresentBucketInput,
+    ) -> Result<PresentBucketOutput, RuntimeError> {
+        // the presented bucket ref is held by the process, out of reach of blueprint code
+        let rid = self
+            .handle_create_bucket_ref(CreateBucketRefInput { bid: input.bid })?
+            .rid;
+        let bucket_ref = self
+            .bucket_refs
+            .remove(&rid)
+            .ok_or(RuntimeError::BucketRefNotFound(rid))?;
+        re_debug!(self, "Presenting: bid = {:?}", input.bid);
+        self.presented_bucket_refs.push((bucket_ref, Vec::new()));
+
+        Ok(PresentBucketOutput {})
+    }
+
+    fn handle_clone_presented_bucket_ref(
+        &mut self,
+        _input: ClonePresentedBucketRefInput,
+    ) -> Result<ClonePresentedBucketRefOutput, RuntimeError> {
+        let new_rid = self.track.new_rid();
+        let (bucket_ref, clones) = self
+            .presented_bucket_refs
+            .last_mut()
+            .ok_or(RuntimeError::NoPresentedBucketRef)?;
+        clones.push(new_rid);
+        let bucket_ref = bucket_ref.clone();
+        re_debug!(self, "Cloning presented: new rid = {:?}", new_rid);
+
+        self.bucket_refs.insert(new_rid, bucket_ref);
+        Ok(ClonePresentedBucketRefOutput { rid: new_rid })
+    }
+
+    fn handle_drop_presented_bucket_ref(
+        &mut self,
+        _input: DropPresentedBucketRefInput,
+    ) -> Result<DropPresentedBucketRefOutput, RuntimeError> {
+        let (bucket_ref, clones) = self
+            .presented_bucket_refs
+            .pop()
+            .ok_or(RuntimeError::NoPresentedBucketRef)?;
+        if let Some(rid) = clones
+            .into_iter()
+            .find(|rid| self.bucket_refs.contains_key(rid))
+        {
+            return Err(RuntimeError::PresentedBucketRefLeaked(rid));
+        }
+        re_debug!(self, "Dropping presented: {:?}", bucket_ref);
+        self.release_bucket_ref(bucket_ref)?;
+
+        Ok(DropPresentedBucketRefOutput {})
+    }
+
+    fn handle_check_method_auth(
+        &mut self,
+        input: CheckMethodAuthInput,
+    ) -> Result<CheckMethodAuthOutput, RuntimeError> {
+        let bucket = self
+            .bucket_refs
+            .get(&input.rid)
+            .ok_or(RuntimeError::BucketRefNotFound(input.rid))?
+            .bucket();
+        let authorized =
+            !bucket.amount().is_zero() && input.allowed_badges.contains(&bucket.resource_address());
+
+        if !authorized && self.track.is_auth_preview() {
+            let component_address = self
+                .component_address()
+                .ok_or(RuntimeError::InterpreterNotStarted)?;
+            let method = self.function()?;
+            re_debug!(
+                self,
+                "Missing badge: {:?}, {}, {:?}",
+                component_address,
+                method,
+                input.allowed_badges
+            );
+            self.track
+                .add_method_auth_requirement(component_address, method, input.allowed_badges);
+            return Ok(CheckMethodAuthOutput { authorized: true });
+        }
+
+        Ok(CheckMethodAuthOutput { authorized })
+    }
+
     fn handle_emit_log(&mut self, input: EmitLogInput) -> Result<EmitLogOutput, RuntimeError> {
-        self.track.add_log(input.level, input.message);
+        self.track.add_log(input.level, input.message)?;
 
         Ok(EmitLogOutput {})
     }
@@ -1836,6 +2917,37 @@ impl<'r, 'l, L: Ledger> Process<'r, 'l, L> {
         })
     }
 
+    fn handle_get_current_time(
+        &mut self,
+        _input: GetCurrentTimeInput,
+    ) -> Result<GetCurrentTimeOutput, RuntimeError> {
+        Ok(GetCurrentTimeOutput {
+            current_time: self.track.current_time(),
+        })
+    }
+
+    fn handle_get_worktop_amount(
+        &mut self,
+        input: GetWorktopAmountInput,
+    ) -> Result<GetWorktopAmountOutput, RuntimeError> {
+        Ok(GetWorktopAmountOutput {
+            amount: self.track.worktop_amount(input.resource_address),
+        })
+    }
+
+    fn handle_generate_random_bytes(
+        &mut self,
+        input: GenerateRandomBytesInput,
+    ) -> Result<GenerateRandomBytesOutput, RuntimeError> {
+        let len = input.len as usize;
+        if len > MAX_RANDOM_BYTES {
+            return Err(RuntimeError::RandomBytesLimitExceeded(len));
+        }
+        Ok(GenerateRandomBytesOutput {
+            bytes: self.track.random_bytes(len),
+        })
+    }
+
     fn handle_generate_uuid(
         &mut self,
         _input: GenerateUuidInput,
@@ -1859,15 +2971,27 @@ impl<'r, 'l, L: Ledger> Externals for Process<'r, 'l, L> {
         match index {
             KERNEL_INDEX => {
                 let operation: u32 = args.nth_checked(0)?;
+                self.track.consume_cost(1).map_err(Trap::from)?;
                 match operation {
+                    CHECK_EXECUTION_TIME => {
+                        if self.track.is_timed_out() {
+                            Err(RuntimeError::Timeout.into())
+                        } else {
+                            Ok(Some(RuntimeValue::I32(0)))
+                        }
+                    }
                     PUBLISH_PACKAGE => self.handle(args, Self::handle_publish),
                     CALL_FUNCTION => self.handle(args, Self::handle_call_function),
                     CALL_METHOD => self.handle(args, Self::handle_call_method),
+                    CALL_METHOD_WITH_BUDGET => {
+                        self.handle(args, Self::handle_call_method_with_budget)
+                    }
 
                     CREATE_COMPONENT => self.handle(args, Self::handle_create_component),
                     GET_COMPONENT_INFO => self.handle(args, Self::handle_get_component_info),
                     GET_COMPONENT_STATE => self.handle(args, Self::handle_get_component_state),
                     PUT_COMPONENT_STATE => self.handle(args, Self::handle_put_component_state),
+                    SET_COMPONENT_OWNER => self.handle(args, Self::handle_set_component_owner),
 
                     CREATE_LAZY_MAP => self.handle(args, Self::handle_create_lazy_map),
                     GET_LAZY_MAP_ENTRY => self.handle(args, Self::handle_get_lazy_map_entry),
@@ -1896,6 +3020,7 @@ impl<'r, 'l, L: Ledger> Externals for Process<'r, 'l, L> {
                     UPDATE_RESOURCE_METADATA => {
                         self.handle(args, Self::handle_update_resource_metadata)
                     }
+                    RECALL_RESOURCE => self.handle(args, Self::handle_recall_resource),
 
                     CREATE_EMPTY_VAULT => self.handle(args, Self::handle_create_vault),
                     PUT_INTO_VAULT => self.handle(args, Self::handle_put_into_vault),
@@ -1906,6 +3031,9 @@ impl<'r, 'l, L: Ledger> Externals for Process<'r, 'l, L> {
                     }
                     TAKE_NFT_FROM_VAULT => self.handle(args, Self::handle_take_nft_from_vault),
                     GET_NFT_IDS_IN_VAULT => self.handle(args, Self::handle_get_nft_ids_in_vault),
+                    CREATE_BUCKET_REF_FROM_VAULT => {
+                        self.handle(args, Self::handle_create_bucket_ref_from_vault)
+                    }
 
                     CREATE_EMPTY_BUCKET => self.handle(args, Self::handle_create_bucket),
                     PUT_INTO_BUCKET => self.handle(args, Self::handle_put_into_bucket),
@@ -1927,16 +3055,30 @@ impl<'r, 'l, L: Ledger> Externals for Process<'r, 'l, L> {
                         self.handle(args, Self::handle_get_nft_ids_in_bucket_ref)
                     }
                     CLONE_BUCKET_REF => self.handle(args, Self::handle_clone_bucket_ref),
+                    CHECK_METHOD_AUTH => self.handle(args, Self::handle_check_method_auth),
+                    GET_NFT_DATA_IN_BUCKET_REF => {
+                        self.handle(args, Self::handle_get_nft_data_in_bucket_ref)
+                    }
+                    PRESENT_BUCKET => self.handle(args, Self::handle_present_bucket),
+                    CLONE_PRESENTED_BUCKET_REF => {
+                        self.handle(args, Self::handle_clone_presented_bucket_ref)
+                    }
+                    DROP_PRESENTED_BUCKET_REF => {
+                        self.handle(args, Self::handle_drop_presented_bucket_ref)
+                    }
 
                     EMIT_LOG => self.handle(args, Self::handle_emit_log),
                     GET_PACKAGE_ADDRESS => self.handle(args, Self::handle_get_package_address),
                     GET_CALL_DATA => self.handle(args, Self::handle_get_call_data),
                     GET_TRANSACTION_HASH => self.handle(args, Self::handle_get_transaction_hash),
                     GET_CURRENT_EPOCH => self.handle(args, Self::handle_get_current_epoch),
+                    GET_CURRENT_TIME => self.handle(args, Self::handle_get_current_time),
+                    GET_WORKTOP_AMOUNT => self.handle(args, Self::handle_get_worktop_amount),
                     GET_TRANSACTION_SIGNERS => {
                         self.handle(args, Self::handle_get_transaction_signers)
                     }
                     GENERATE_UUID => self.handle(args, Self::handle_generate_uuid),
+                    GENERATE_RANDOM_BYTES => self.handle(args, Self::handle_generate_random_bytes),
 
                     _ => Err(RuntimeError::InvalidRequestCode(operation).into()),
                 }
@@ -0,0 +1,43 @@
+use scrypto::rust::rc::Rc;
+use scrypto::rust::string::String;
+use scrypto::types::*;
+
+/// A callback which receives execution events.
+pub type Tracer = Rc<dyn Fn(&TraceEvent)>;
+
+/// Represents an event emitted during transaction execution.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TraceEvent {
+    /// An instruction is about to be executed.
+    InstructionStarted { index: usize },
+
+    /// An instruction has been executed.
+    InstructionEnded { index: usize, success: bool },
+
+    /// A function or method call is entered.
+    CallStarted {
+        depth: usize,
+        package_address: Address,
+        export_name: String,
+        function: String,
+    },
+
+    /// A function or method call is exited.
+    CallEnded { depth: usize, success: bool },
+
+    /// A bucket is moved across a call boundary.
+    BucketMoved {
+        depth: usize,
+        bid: Bid,
+        resource_address: Address,
+        amount: Decimal,
+    },
+
+    /// A bucket ref is moved across a call boundary.
+    BucketRefMoved {
+        depth: usize,
+        rid: Rid,
+        resource_address: Address,
+        amount: Decimal,
+    },
+}
@@ -1,29 +1,52 @@
+use core::hash::Hash;
 use lru::LruCache;
+use sbor::rust::boxed::Box;
 use scrypto::kernel::*;
 use scrypto::rust::collections::*;
+use scrypto::rust::rc::Rc;
 use scrypto::rust::string::String;
 use scrypto::rust::vec::Vec;
 use scrypto::types::*;
+use scrypto::utils::*;
 use wasmi::*;
 
 use crate::engine::*;
 use crate::ledger::*;
 use crate::model::*;
 
+/// The maximum number of random bytes a blueprint can request at once.
+pub const MAX_RANDOM_BYTES: usize = 1024;
+
 /// An abstraction of transaction execution state.
 ///
 /// It acts as the facade of ledger state and keeps track of all temporary state updates,
-/// until the `commit()` method is called.
+/// until the `commit()` method is called. A track over a read-only ledger can run a transaction
+/// but not commit it.
 ///
 /// Typically, a track is shared by all the processes created within a transaction.
 ///
 pub struct Track<'l, L: Ledger> {
-    ledger: &'l mut L,
+    ledger: LedgerRef<'l, L>,
     current_epoch: u64,
+    current_time: u64,
     transaction_hash: H256,
     transaction_signers: Vec<Address>,
     id_alloc: IdAllocator,
     logs: Vec<(LogLevel, String)>,
+    log_size: usize,
+    max_log_size: Option<usize>,
+    logs_truncated: bool,
+    max_worktop_resources: Option<usize>,
+    timeout: Option<Box<dyn Fn() -> bool>>,
+    cost_budgets: Vec<u64>,
+    worktop_amounts: HashMap<Address, Decimal>,
+    instruction_index: usize,
+    random_seed: H256,
+    random_blocks: u64,
+    movements: Vec<Movement>,
+    resources_created: Vec<ResourceCreation>,
+    auth_preview: bool,
+    auth_requirements: Vec<AuthRequirement>,
     packages: HashMap<Address, Package>,
     components: HashMap<Address, Component>,
     resource_defs: HashMap<Address, ResourceDef>,
@@ -32,12 +55,114 @@ pub struct Track<'l, L: Ledger> {
     nfts: HashMap<(Address, u128), Nft>,
     updated_packages: HashSet<Address>,
     updated_components: HashSet<Address>,
+    deleted_components: HashSet<Address>,
     updated_lazy_maps: HashSet<Mid>,
+    deleted_lazy_maps: HashSet<Mid>,
     updated_resource_defs: HashSet<Address>,
     updated_vaults: HashSet<Vid>,
+    deleted_vaults: HashSet<Vid>,
     updated_nfts: HashSet<(Address, u128)>,
     new_entities: Vec<Address>,
-    code_cache: LruCache<Address, Module>, // TODO: move to ledger level
+    code_cache: LruCache<(Address, bool), Module>, // TODO: move to ledger level
+    tracer: Option<Tracer>,
+    deposit_hooks: HashMap<(Address, String), bool>,
+    running_deposit_hooks: HashSet<Address>,
+    vault_proofs: Vec<(Vid, BucketRef)>,
+    journals: Vec<TrackJournal>,
+}
+
+/// The state updates of a track at some point, which can be restored by `Track::rollback()`.
+///
+/// Substates are not copied; instead, the track saves each substate the first time it is
+/// updated after the checkpoint, so the cost is proportional to the substates touched.
+pub struct TrackCheckpoint {
+    depth: usize,
+    worktop_amounts: HashMap<Address, Decimal>,
+    movements: usize,
+    resources_created: usize,
+    auth_requirements: usize,
+    new_entities: usize,
+    vault_proofs: Vec<Bid>,
+    random_blocks: u64,
+}
+
+/// The substates as they were before being first updated since a checkpoint.
+#[derive(Default)]
+struct TrackJournal {
+    packages: HashMap<Address, SavedSubstate<Package>>,
+    components: HashMap<Address, SavedSubstate<Component>>,
+    resource_defs: HashMap<Address, SavedSubstate<ResourceDef>>,
+    lazy_maps: HashMap<Mid, SavedSubstate<LazyMap>>,
+    vaults: HashMap<Vid, SavedSubstate<Vault>>,
+    nfts: HashMap<(Address, u128), SavedSubstate<Nft>>,
+}
+
+impl TrackJournal {
+    /// Adds the saved substates of a nested journal, keeping the older ones.
+    fn merge(&mut self, nested: TrackJournal) {
+        merge_substates(&mut self.packages, nested.packages);
+        merge_substates(&mut self.components, nested.components);
+        merge_substates(&mut self.resource_defs, nested.resource_defs);
+        merge_substates(&mut self.lazy_maps, nested.lazy_maps);
+        merge_substates(&mut self.vaults, nested.vaults);
+        merge_substates(&mut self.nfts, nested.nfts);
+    }
+}
+
+/// A substate in track, i.e. its loaded value and whether it's updated or deleted.
+struct SavedSubstate<V> {
+    value: Option<V>,
+    updated: bool,
+    deleted: bool,
+}
+
+fn save_substate<K: Copy + Eq + Hash, V: Clone>(
+    saved: &mut HashMap<K, SavedSubstate<V>>,
+    key: K,
+    values: &HashMap<K, V>,
+    updated: &HashSet<K>,
+    deleted: Option<&HashSet<K>>,
+) {
+    saved.entry(key).or_insert_with(|| SavedSubstate {
+        value: values.get(&key).cloned(),
+        updated: updated.contains(&key),
+        deleted: deleted.is_some_and(|d| d.contains(&key)),
+    });
+}
+
+fn restore_substates<K: Copy + Eq + Hash, V>(
+    saved: HashMap<K, SavedSubstate<V>>,
+    values: &mut HashMap<K, V>,
+    updated: &mut HashSet<K>,
+    mut deleted: Option<&mut HashSet<K>>,
+) {
+    for (key, substate) in saved {
+        match substate.value {
+            Some(value) => values.insert(key, value),
+            None => values.remove(&key),
+        };
+        if substate.updated {
+            updated.insert(key);
+        } else {
+            updated.remove(&key);
+        }
+        if let Some(deleted) = deleted.as_mut() {
+            if substate.deleted {
+                deleted.insert(key);
+            } else {
+                deleted.remove(&key);
+            }
+        }
+    }
+}
+
+fn merge_substates<K: Eq + Hash, V>(
+    saved: &mut HashMap<K, SavedSubstate<V>>,
+    nested: HashMap<K, SavedSubstate<V>>,
+) {
+    for (key, substate) in nested {
+        saved.entry(key).or_insert(substate);
+    }
 }
 
 impl<'l, L: Ledger> Track<'l, L> {
@@ -46,14 +171,58 @@ impl<'l, L: Ledger> Track<'l, L> {
         current_epoch: u64,
         transaction_hash: H256,
         transaction_signers: Vec<Address>,
+    ) -> Self {
+        Self::with_ledger_ref(
+            LedgerRef::Mutable(ledger),
+            current_epoch,
+            transaction_hash,
+            transaction_signers,
+        )
+    }
+
+    /// Creates a track which only reads the ledger, and so can't be committed.
+    pub fn new_read_only(
+        ledger: &'l L,
+        current_epoch: u64,
+        transaction_hash: H256,
+        transaction_signers: Vec<Address>,
+    ) -> Self {
+        Self::with_ledger_ref(
+            LedgerRef::ReadOnly(ledger),
+            current_epoch,
+            transaction_hash,
+            transaction_signers,
+        )
+    }
+
+    fn with_ledger_ref(
+        ledger: LedgerRef<'l, L>,
+        current_epoch: u64,
+        transaction_hash: H256,
+        transaction_signers: Vec<Address>,
     ) -> Self {
         Self {
             ledger,
             current_epoch,
+            current_time: 0,
             transaction_hash,
             transaction_signers,
             id_alloc: IdAllocator::new(),
             logs: Vec::new(),
+            log_size: 0,
+            max_log_size: None,
+            logs_truncated: false,
+            max_worktop_resources: None,
+            timeout: None,
+            cost_budgets: Vec::new(),
+            worktop_amounts: HashMap::new(),
+            instruction_index: 0,
+            random_seed: transaction_hash,
+            random_blocks: 0,
+            movements: Vec::new(),
+            resources_created: Vec::new(),
+            auth_preview: false,
+            auth_requirements: Vec::new(),
             packages: HashMap::new(),
             components: HashMap::new(),
             resource_defs: HashMap::new(),
@@ -62,12 +231,20 @@ impl<'l, L: Ledger> Track<'l, L> {
             nfts: HashMap::new(),
             updated_packages: HashSet::new(),
             updated_components: HashSet::new(),
+            deleted_components: HashSet::new(),
             updated_lazy_maps: HashSet::new(),
+            deleted_lazy_maps: HashSet::new(),
             updated_resource_defs: HashSet::new(),
             updated_vaults: HashSet::new(),
+            deleted_vaults: HashSet::new(),
             updated_nfts: HashSet::new(),
             new_entities: Vec::new(),
             code_cache: LruCache::new(1024),
+            tracer: None,
+            deposit_hooks: HashMap::new(),
+            running_deposit_hooks: HashSet::new(),
+            vault_proofs: Vec::new(),
+            journals: Vec::new(),
         }
     }
 
@@ -91,31 +268,270 @@ impl<'l, L: Ledger> Track<'l, L> {
         self.current_epoch
     }
 
+    /// Returns the current time.
+    pub fn current_time(&self) -> u64 {
+        self.current_time
+    }
+
+    /// Sets the current time, as seen by this transaction.
+    pub fn set_current_time(&mut self, current_time: u64) {
+        self.current_time = current_time;
+    }
+
     /// Returns the logs collected so far.
     pub fn logs(&self) -> &Vec<(LogLevel, String)> {
         &self.logs
     }
 
+    /// Returns whether any log message has been dropped for exceeding the log limit.
+    pub fn logs_truncated(&self) -> bool {
+        self.logs_truncated
+    }
+
+    /// Sets the maximum total size of log messages, in bytes.
+    pub fn set_max_log_size(&mut self, max_log_size: Option<usize>) {
+        self.max_log_size = max_log_size;
+    }
+
+    /// Returns the maximum number of distinct resources in transaction context.
+    pub fn max_worktop_resources(&self) -> Option<usize> {
+        self.max_worktop_resources
+    }
+
+    /// Sets the maximum number of distinct resources in transaction context.
+    pub fn set_max_worktop_resources(&mut self, max_worktop_resources: Option<usize>) {
+        self.max_worktop_resources = max_worktop_resources;
+    }
+
+    /// Sets a check which tells whether the execution time limit has been reached.
+    ///
+    /// With a check in place, loops in blueprint code are instrumented to call it.
+    pub fn set_timeout(&mut self, timeout: Option<Box<dyn Fn() -> bool>>) {
+        self.timeout = timeout;
+    }
+
+    /// Starts a cost budget, nested in the budgets already in effect.
+    ///
+    /// While a budget is in effect, loops in blueprint code are instrumented, and every loop
+    /// iteration and kernel call costs one unit.
+    pub fn push_cost_budget(&mut self, max_cost: u64) {
+        self.cost_budgets.push(max_cost);
+    }
+
+    /// Ends the innermost cost budget, and returns the cost left in it.
+    pub fn pop_cost_budget(&mut self) -> u64 {
+        self.cost_budgets.pop().expect("No cost budget in effect")
+    }
+
+    /// Consumes cost units from every budget in effect.
+    pub fn consume_cost(&mut self, cost: u64) -> Result<(), RuntimeError> {
+        let mut exceeded = false;
+        for remaining in &mut self.cost_budgets {
+            if *remaining < cost {
+                *remaining = 0;
+                exceeded = true;
+            } else {
+                *remaining -= cost;
+            }
+        }
+        if exceeded {
+            Err(RuntimeError::CostBudgetExceeded)
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Returns whether the execution time limit has been reached.
+    pub fn is_timed_out(&self) -> bool {
+        self.timeout.as_ref().is_some_and(|f| f())
+    }
+
+    /// Returns the amount of a resource staged in transaction context, as of the latest call.
+    pub fn worktop_amount(&self, resource_address: Address) -> Decimal {
+        self.worktop_amounts
+            .get(&resource_address)
+            .cloned()
+            .unwrap_or_else(Decimal::zero)
+    }
+
+    /// Records the resources staged in transaction context.
+    pub fn set_worktop_amounts(&mut self, worktop_amounts: HashMap<Address, Decimal>) {
+        self.worktop_amounts = worktop_amounts;
+    }
+
     /// Returns new entities created so far.
     pub fn new_entities(&self) -> &[Address] {
         &self.new_entities
     }
 
     /// Adds a log message.
-    pub fn add_log(&mut self, level: LogLevel, message: String) {
+    ///
+    /// Messages that would push the total log size over the limit are dropped.
+    pub fn add_log(&mut self, level: LogLevel, message: String) -> Result<(), RuntimeError> {
+        let log_size = self.log_size + message.len();
+        if let Some(limit) = self.max_log_size {
+            if log_size > limit {
+                self.logs_truncated = true;
+                return Err(RuntimeError::LogLimitExceeded(limit));
+            }
+        }
+        self.log_size = log_size;
         self.logs.push((level, message));
+        Ok(())
+    }
+
+    /// Sets the index of the instruction being executed.
+    pub fn set_instruction_index(&mut self, index: usize) {
+        self.instruction_index = index;
+    }
+
+    /// Returns the resource definitions created so far.
+    pub fn resources_created(&self) -> &Vec<ResourceCreation> {
+        &self.resources_created
+    }
+
+    /// Records a resource definition created by the current instruction.
+    pub fn add_resource_creation(
+        &mut self,
+        resource_address: Address,
+        resource_type: ResourceType,
+        initial_supply: Option<Supply>,
+    ) {
+        self.resources_created.push(ResourceCreation {
+            instruction_index: self.instruction_index,
+            resource_address,
+            resource_type,
+            initial_supply,
+        });
+    }
+
+    /// Returns whether missing resource permissions are recorded instead of enforced.
+    pub fn is_auth_preview(&self) -> bool {
+        self.auth_preview
+    }
+
+    /// Sets whether missing resource permissions are recorded instead of enforced.
+    pub fn set_auth_preview(&mut self, auth_preview: bool) {
+        self.auth_preview = auth_preview;
+    }
+
+    /// Returns the missing resource permissions recorded so far.
+    pub fn auth_requirements(&self) -> &Vec<AuthRequirement> {
+        &self.auth_requirements
+    }
+
+    /// Records a resource permission missing for the current instruction.
+    pub fn add_resource_auth_requirement(
+        &mut self,
+        resource_address: Address,
+        permission: u16,
+        required_badge: Option<Address>,
+    ) {
+        self.auth_requirements.push(AuthRequirement::Resource {
+            instruction_index: self.instruction_index,
+            resource_address,
+            permission,
+            required_badge,
+        });
+    }
+
+    /// Records a badge missing for a method call made by the current instruction.
+    pub fn add_method_auth_requirement(
+        &mut self,
+        component_address: Address,
+        method: String,
+        allowed_badges: Vec<Address>,
+    ) {
+        self.auth_requirements.push(AuthRequirement::Method {
+            instruction_index: self.instruction_index,
+            component_address,
+            method,
+            allowed_badges,
+        });
+    }
+
+    /// Returns the resource movements made so far.
+    pub fn movements(&self) -> &Vec<Movement> {
+        &self.movements
+    }
+
+    /// Records a resource movement made by the current instruction.
+    pub fn add_movement(
+        &mut self,
+        kind: MovementKind,
+        resource: Address,
+        amount: Decimal,
+        from: Option<Address>,
+        to: Option<Address>,
+    ) {
+        self.movements.push(Movement {
+            instruction_index: self.instruction_index,
+            kind,
+            resource,
+            amount,
+            from,
+            to,
+        });
+    }
+
+    /// Sets the tracer which receives execution events.
+    pub fn set_tracer(&mut self, tracer: Option<Tracer>) {
+        self.tracer = tracer;
+    }
+
+    /// Emits a trace event, if a tracer is set.
+    pub fn trace(&self, event: TraceEvent) {
+        if let Some(tracer) = &self.tracer {
+            tracer(&event);
+        }
+    }
+
+    /// Returns whether a blueprint defines the deposit hook, if known.
+    pub fn get_deposit_hook(&self, blueprint: &(Address, String)) -> Option<bool> {
+        self.deposit_hooks.get(blueprint).copied()
+    }
+
+    /// Records whether a blueprint defines the deposit hook.
+    pub fn put_deposit_hook(&mut self, blueprint: (Address, String), defined: bool) {
+        self.deposit_hooks.insert(blueprint, defined);
+    }
+
+    /// Returns whether blueprints define the deposit hook, as found so far.
+    pub fn deposit_hooks(&self) -> &HashMap<(Address, String), bool> {
+        &self.deposit_hooks
+    }
+
+    /// Sets whether blueprints define the deposit hook, e.g. as found by earlier transactions,
+    /// so that their ABI isn't exported again.
+    pub fn set_deposit_hooks(&mut self, deposit_hooks: HashMap<(Address, String), bool>) {
+        self.deposit_hooks = deposit_hooks;
+    }
+
+    /// Marks the deposit hook of a component as running, returning false if it already is.
+    pub fn enter_deposit_hook(&mut self, component_address: Address) -> bool {
+        self.running_deposit_hooks.insert(component_address)
+    }
+
+    /// Marks the deposit hook of a component as no longer running.
+    pub fn exit_deposit_hook(&mut self, component_address: Address) {
+        self.running_deposit_hooks.remove(&component_address);
     }
 
     /// Loads a module.
     pub fn load_module(&mut self, address: Address) -> Option<(ModuleRef, MemoryRef)> {
         match self.get_package(address).map(Clone::clone) {
             Some(p) => {
-                if let Some(m) = self.code_cache.get(&address) {
+                let metered = self.timeout.is_some() || !self.cost_budgets.is_empty();
+                if let Some(m) = self.code_cache.get(&(address, metered)) {
                     Some(instantiate_module(m).unwrap())
                 } else {
-                    let module = parse_module(p.code()).unwrap();
+                    let module = if metered {
+                        parse_module_with_time_checks(p.code()).unwrap()
+                    } else {
+                        parse_module(p.code()).unwrap()
+                    };
                     let inst = instantiate_module(&module).unwrap();
-                    self.code_cache.put(address, module);
+                    self.code_cache.put((address, metered), module);
                     Some(inst)
                 }
             }
@@ -129,7 +545,7 @@ impl<'l, L: Ledger> Track<'l, L> {
             return self.packages.get(&address);
         }
 
-        if let Some(package) = self.ledger.get_package(address) {
+        if let Some(package) = self.ledger.get().get_package(address) {
             self.packages.insert(address, package);
             self.packages.get(&address)
         } else {
@@ -140,13 +556,14 @@ impl<'l, L: Ledger> Track<'l, L> {
     /// Returns a mutable reference to a package, if exists.
     #[allow(dead_code)]
     pub fn get_package_mut(&mut self, address: Address) -> Option<&mut Package> {
+        self.save_package(address);
         self.updated_packages.insert(address);
 
         if self.packages.contains_key(&address) {
             return self.packages.get_mut(&address);
         }
 
-        if let Some(package) = self.ledger.get_package(address) {
+        if let Some(package) = self.ledger.get().get_package(address) {
             self.packages.insert(address, package);
             self.packages.get_mut(&address)
         } else {
@@ -156,6 +573,7 @@ impl<'l, L: Ledger> Track<'l, L> {
 
     /// Inserts a new package.
     pub fn put_package(&mut self, address: Address, package: Package) {
+        self.save_package(address);
         self.updated_packages.insert(address);
 
         self.packages.insert(address, package);
@@ -163,11 +581,14 @@ impl<'l, L: Ledger> Track<'l, L> {
 
     /// Returns an immutable reference to a component, if exists.
     pub fn get_component(&mut self, address: Address) -> Option<&Component> {
+        if self.deleted_components.contains(&address) {
+            return None;
+        }
         if self.components.contains_key(&address) {
             return self.components.get(&address);
         }
 
-        if let Some(component) = self.ledger.get_component(address) {
+        if let Some(component) = self.ledger.get().get_component(address) {
             self.components.insert(address, component);
             self.components.get(&address)
         } else {
@@ -176,13 +597,17 @@ impl<'l, L: Ledger> Track<'l, L> {
     }
     /// Returns a mutable reference to a component, if exists.
     pub fn get_component_mut(&mut self, address: Address) -> Option<&mut Component> {
+        self.save_component(address);
+        if self.deleted_components.contains(&address) {
+            return None;
+        }
         self.updated_components.insert(address);
 
         if self.components.contains_key(&address) {
             return self.components.get_mut(&address);
         }
 
-        if let Some(component) = self.ledger.get_component(address) {
+        if let Some(component) = self.ledger.get().get_component(address) {
             self.components.insert(address, component);
             self.components.get_mut(&address)
         } else {
@@ -192,18 +617,47 @@ impl<'l, L: Ledger> Track<'l, L> {
 
     /// Inserts a new component.
     pub fn put_component(&mut self, address: Address, component: Component) {
+        self.save_component(address);
         self.updated_components.insert(address);
+        self.deleted_components.remove(&address);
 
         self.components.insert(address, component);
     }
 
+    /// Removes a component.
+    pub fn delete_component(&mut self, address: Address) {
+        self.save_component(address);
+        self.updated_components.remove(&address);
+        self.deleted_components.insert(address);
+
+        self.components.remove(&address);
+    }
+
+    /// Deletes a lazy map.
+    pub fn delete_lazy_map(&mut self, mid: Mid) {
+        self.save_lazy_map(mid);
+        self.updated_lazy_maps.remove(&mid);
+        self.deleted_lazy_maps.insert(mid);
+
+        self.lazy_maps.remove(&mid);
+    }
+
+    /// Deletes a vault.
+    pub fn delete_vault(&mut self, vid: Vid) {
+        self.save_vault(vid);
+        self.updated_vaults.remove(&vid);
+        self.deleted_vaults.insert(vid);
+
+        self.vaults.remove(&vid);
+    }
+
     /// Returns an immutable reference to a nft, if exists.
     pub fn get_nft(&mut self, resource_address: Address, id: u128) -> Option<&Nft> {
         if self.nfts.contains_key(&(resource_address, id)) {
             return self.nfts.get(&(resource_address, id));
         }
 
-        if let Some(nft) = self.ledger.get_nft(resource_address, id) {
+        if let Some(nft) = self.ledger.get().get_nft(resource_address, id) {
             self.nfts.insert((resource_address, id), nft);
             self.nfts.get(&(resource_address, id))
         } else {
@@ -213,13 +667,14 @@ impl<'l, L: Ledger> Track<'l, L> {
 
     /// Returns a mutable reference to a nft, if exists.
     pub fn get_nft_mut(&mut self, resource_address: Address, id: u128) -> Option<&mut Nft> {
+        self.save_nft(resource_address, id);
         self.updated_nfts.insert((resource_address, id));
 
         if self.nfts.contains_key(&(resource_address, id)) {
             return self.nfts.get_mut(&(resource_address, id));
         }
 
-        if let Some(nft) = self.ledger.get_nft(resource_address, id) {
+        if let Some(nft) = self.ledger.get().get_nft(resource_address, id) {
             self.nfts.insert((resource_address, id), nft);
             self.nfts.get_mut(&(resource_address, id))
         } else {
@@ -229,6 +684,7 @@ impl<'l, L: Ledger> Track<'l, L> {
 
     /// Inserts a new nft.
     pub fn put_nft(&mut self, resource_address: Address, id: u128, nft: Nft) {
+        self.save_nft(resource_address, id);
         self.updated_nfts.insert((resource_address, id));
 
         self.nfts.insert((resource_address, id), nft);
@@ -236,11 +692,14 @@ impl<'l, L: Ledger> Track<'l, L> {
 
     /// Returns an immutable reference to a lazy map, if exists.
     pub fn get_lazy_map(&mut self, mid: Mid) -> Option<&LazyMap> {
+        if self.deleted_lazy_maps.contains(&mid) {
+            return None;
+        }
         if self.lazy_maps.contains_key(&mid) {
             return self.lazy_maps.get(&mid);
         }
 
-        if let Some(lazy_map) = self.ledger.get_lazy_map(mid) {
+        if let Some(lazy_map) = self.ledger.get().get_lazy_map(mid) {
             self.lazy_maps.insert(mid, lazy_map);
             self.lazy_maps.get(&mid)
         } else {
@@ -250,13 +709,17 @@ impl<'l, L: Ledger> Track<'l, L> {
 
     /// Returns a mutable reference to a lazy map, if exists.
     pub fn get_lazy_map_mut(&mut self, mid: Mid) -> Option<&mut LazyMap> {
+        self.save_lazy_map(mid);
+        if self.deleted_lazy_maps.contains(&mid) {
+            return None;
+        }
         self.updated_lazy_maps.insert(mid);
 
         if self.lazy_maps.contains_key(&mid) {
             return self.lazy_maps.get_mut(&mid);
         }
 
-        if let Some(lazy_map) = self.ledger.get_lazy_map(mid) {
+        if let Some(lazy_map) = self.ledger.get().get_lazy_map(mid) {
             self.lazy_maps.insert(mid, lazy_map);
             self.lazy_maps.get_mut(&mid)
         } else {
@@ -266,7 +729,9 @@ impl<'l, L: Ledger> Track<'l, L> {
 
     /// Inserts a new lazy map.
     pub fn put_lazy_map(&mut self, mid: Mid, lazy_map: LazyMap) {
+        self.save_lazy_map(mid);
         self.updated_lazy_maps.insert(mid);
+        self.deleted_lazy_maps.remove(&mid);
 
         self.lazy_maps.insert(mid, lazy_map);
     }
@@ -277,7 +742,7 @@ impl<'l, L: Ledger> Track<'l, L> {
             return self.resource_defs.get(&address);
         }
 
-        if let Some(resource_def) = self.ledger.get_resource_def(address) {
+        if let Some(resource_def) = self.ledger.get().get_resource_def(address) {
             self.resource_defs.insert(address, resource_def);
             self.resource_defs.get(&address)
         } else {
@@ -288,13 +753,14 @@ impl<'l, L: Ledger> Track<'l, L> {
     /// Returns a mutable reference to a resource definition, if exists.
     #[allow(dead_code)]
     pub fn get_resource_def_mut(&mut self, address: Address) -> Option<&mut ResourceDef> {
+        self.save_resource_def(address);
         self.updated_resource_defs.insert(address);
 
         if self.resource_defs.contains_key(&address) {
             return self.resource_defs.get_mut(&address);
         }
 
-        if let Some(resource_def) = self.ledger.get_resource_def(address) {
+        if let Some(resource_def) = self.ledger.get().get_resource_def(address) {
             self.resource_defs.insert(address, resource_def);
             self.resource_defs.get_mut(&address)
         } else {
@@ -304,6 +770,7 @@ impl<'l, L: Ledger> Track<'l, L> {
 
     /// Inserts a new resource definition.
     pub fn put_resource_def(&mut self, address: Address, resource_def: ResourceDef) {
+        self.save_resource_def(address);
         self.updated_resource_defs.insert(address);
 
         self.resource_defs.insert(address, resource_def);
@@ -312,11 +779,14 @@ impl<'l, L: Ledger> Track<'l, L> {
     /// Returns an immutable reference to a vault, if exists.
     #[allow(dead_code)]
     pub fn get_vault(&mut self, vid: Vid) -> Option<&Vault> {
+        if self.deleted_vaults.contains(&vid) {
+            return None;
+        }
         if self.vaults.contains_key(&vid) {
             return self.vaults.get(&vid);
         }
 
-        if let Some(vault) = self.ledger.get_vault(vid) {
+        if let Some(vault) = self.ledger.get().get_vault(vid) {
             self.vaults.insert(vid, vault);
             self.vaults.get(&vid)
         } else {
@@ -326,13 +796,17 @@ impl<'l, L: Ledger> Track<'l, L> {
 
     /// Returns a mutable reference to a vault, if exists.
     pub fn get_vault_mut(&mut self, vid: Vid) -> Option<&mut Vault> {
+        self.save_vault(vid);
+        if self.deleted_vaults.contains(&vid) {
+            return None;
+        }
         self.updated_vaults.insert(vid);
 
         if self.vaults.contains_key(&vid) {
             return self.vaults.get_mut(&vid);
         }
 
-        if let Some(vault) = self.ledger.get_vault(vid) {
+        if let Some(vault) = self.ledger.get().get_vault(vid) {
             self.vaults.insert(vid, vault);
             self.vaults.get_mut(&vid)
         } else {
@@ -342,7 +816,9 @@ impl<'l, L: Ledger> Track<'l, L> {
 
     /// Inserts a new vault.
     pub fn put_vault(&mut self, vid: Vid, vault: Vault) {
+        self.save_vault(vid);
         self.updated_vaults.insert(vid);
+        self.deleted_vaults.remove(&vid);
 
         self.vaults.insert(vid, vault);
     }
@@ -373,6 +849,218 @@ impl<'l, L: Ledger> Track<'l, L> {
         self.id_alloc.new_uuid(self.transaction_hash())
     }
 
+    /// Sets the seed of random bytes, which is the transaction hash by default.
+    pub fn set_random_seed(&mut self, random_seed: H256) {
+        self.random_seed = random_seed;
+    }
+
+    /// Generates deterministic random bytes, by hashing the random seed, the current
+    /// instruction index and a counter of the blocks generated so far.
+    pub fn random_bytes(&mut self, len: usize) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(len);
+        while bytes.len() < len {
+            let mut seed = self.random_seed.to_vec();
+            seed.extend((self.instruction_index as u64).to_le_bytes());
+            seed.extend(self.random_blocks.to_le_bytes());
+            self.random_blocks += 1;
+            let block = sha256(seed);
+            let n = (len - bytes.len()).min(block.0.len());
+            bytes.extend(&block.0[..n]);
+        }
+        bytes
+    }
+
+    /// Records the current state updates, so that they can be restored later.
+    ///
+    /// The checkpoint must be passed to either `rollback()` or `release()`.
+    pub fn checkpoint(&mut self) -> TrackCheckpoint {
+        self.journals.push(TrackJournal::default());
+        TrackCheckpoint {
+            depth: self.journals.len() - 1,
+            worktop_amounts: self.worktop_amounts.clone(),
+            movements: self.movements.len(),
+            resources_created: self.resources_created.len(),
+            auth_requirements: self.auth_requirements.len(),
+            new_entities: self.new_entities.len(),
+            vault_proofs: self
+                .vault_proofs
+                .iter()
+                .map(|(_, b)| b.bucket_id())
+                .collect(),
+            random_blocks: self.random_blocks,
+        }
+    }
+
+    /// Discards all state updates made since the checkpoint was taken.
+    ///
+    /// Vault amounts locked since then are dropped with the vaults they were taken from.
+    pub fn rollback(&mut self, checkpoint: TrackCheckpoint) {
+        while self.journals.len() > checkpoint.depth {
+            let journal = self.journals.pop().unwrap();
+            restore_substates(
+                journal.packages,
+                &mut self.packages,
+                &mut self.updated_packages,
+                None,
+            );
+            restore_substates(
+                journal.components,
+                &mut self.components,
+                &mut self.updated_components,
+                Some(&mut self.deleted_components),
+            );
+            restore_substates(
+                journal.resource_defs,
+                &mut self.resource_defs,
+                &mut self.updated_resource_defs,
+                None,
+            );
+            restore_substates(
+                journal.lazy_maps,
+                &mut self.lazy_maps,
+                &mut self.updated_lazy_maps,
+                Some(&mut self.deleted_lazy_maps),
+            );
+            restore_substates(
+                journal.vaults,
+                &mut self.vaults,
+                &mut self.updated_vaults,
+                Some(&mut self.deleted_vaults),
+            );
+            restore_substates(journal.nfts, &mut self.nfts, &mut self.updated_nfts, None);
+        }
+
+        self.worktop_amounts = checkpoint.worktop_amounts;
+        self.movements.truncate(checkpoint.movements);
+        self.resources_created
+            .truncate(checkpoint.resources_created);
+        self.auth_requirements
+            .truncate(checkpoint.auth_requirements);
+        self.new_entities.truncate(checkpoint.new_entities);
+        self.vault_proofs
+            .retain(|(_, b)| checkpoint.vault_proofs.contains(&b.bucket_id()));
+        self.random_blocks = checkpoint.random_blocks;
+    }
+
+    /// Keeps all state updates made since the checkpoint was taken.
+    pub fn release(&mut self, checkpoint: TrackCheckpoint) {
+        while self.journals.len() > checkpoint.depth {
+            let journal = self.journals.pop().unwrap();
+            if let Some(parent) = self.journals.last_mut() {
+                parent.merge(journal);
+            }
+        }
+    }
+
+    fn save_package(&mut self, address: Address) {
+        if let Some(journal) = self.journals.last_mut() {
+            save_substate(
+                &mut journal.packages,
+                address,
+                &self.packages,
+                &self.updated_packages,
+                None,
+            );
+        }
+    }
+
+    fn save_component(&mut self, address: Address) {
+        if let Some(journal) = self.journals.last_mut() {
+            save_substate(
+                &mut journal.components,
+                address,
+                &self.components,
+                &self.updated_components,
+                Some(&self.deleted_components),
+            );
+        }
+    }
+
+    fn save_resource_def(&mut self, address: Address) {
+        if let Some(journal) = self.journals.last_mut() {
+            save_substate(
+                &mut journal.resource_defs,
+                address,
+                &self.resource_defs,
+                &self.updated_resource_defs,
+                None,
+            );
+        }
+    }
+
+    fn save_lazy_map(&mut self, mid: Mid) {
+        if let Some(journal) = self.journals.last_mut() {
+            save_substate(
+                &mut journal.lazy_maps,
+                mid,
+                &self.lazy_maps,
+                &self.updated_lazy_maps,
+                Some(&self.deleted_lazy_maps),
+            );
+        }
+    }
+
+    fn save_vault(&mut self, vid: Vid) {
+        if let Some(journal) = self.journals.last_mut() {
+            save_substate(
+                &mut journal.vaults,
+                vid,
+                &self.vaults,
+                &self.updated_vaults,
+                Some(&self.deleted_vaults),
+            );
+        }
+    }
+
+    fn save_nft(&mut self, resource_address: Address, id: u128) {
+        if let Some(journal) = self.journals.last_mut() {
+            save_substate(
+                &mut journal.nfts,
+                (resource_address, id),
+                &self.nfts,
+                &self.updated_nfts,
+                None,
+            );
+        }
+    }
+
+    /// Locks some amount of a vault for backing a bucket ref, by taking it out of the vault
+    /// until the last reference to the returned bucket is dropped.
+    pub fn lock_vault_amount(
+        &mut self,
+        vid: Vid,
+        amount: Decimal,
+        actor: Actor,
+    ) -> Result<BucketRef, RuntimeError> {
+        let bucket = self
+            .get_vault_mut(vid)
+            .ok_or(RuntimeError::VaultNotFound(vid))?
+            .take(amount, actor)
+            .map_err(RuntimeError::VaultError)?;
+        let bid = self.new_bid();
+        let locked = BucketRef::new(LockedBucket::new(bid, bucket));
+        self.vault_proofs.push((vid, locked.clone()));
+        Ok(locked)
+    }
+
+    /// Puts the resource locked for bucket refs back into their vaults, once no bucket ref
+    /// is left.
+    pub fn unlock_vault_amounts(&mut self) -> Result<(), RuntimeError> {
+        let (released, locked): (Vec<_>, Vec<_>) = self
+            .vault_proofs
+            .drain(..)
+            .partition(|(_, b)| Rc::strong_count(b) == 1);
+        self.vault_proofs = locked;
+        for (vid, bucket) in released {
+            let bucket: Bucket = Rc::try_unwrap(bucket).unwrap().into();
+            self.get_vault_mut(vid)
+                .ok_or(RuntimeError::VaultNotFound(vid))?
+                .put(bucket, Actor::SuperUser)
+                .map_err(RuntimeError::VaultError)?;
+        }
+        Ok(())
+    }
+
     /// Creates a new bucket ID.
     pub fn new_bid(&mut self) -> Bid {
         self.id_alloc.new_bid()
@@ -394,34 +1082,47 @@ impl<'l, L: Ledger> Track<'l, L> {
     }
 
     /// Commits changes to the underlying ledger.
+    ///
+    /// Panics if the track was created over a read-only ledger.
     pub fn commit(&mut self) {
+        let ledger = self
+            .ledger
+            .get_mut()
+            .expect("Can't commit a track over a read-only ledger");
         for address in self.updated_packages.clone() {
-            self.ledger
-                .put_package(address, self.packages.get(&address).unwrap().clone());
+            ledger.put_package(address, self.packages.get(&address).unwrap().clone());
         }
 
         for address in self.updated_components.clone() {
-            self.ledger
-                .put_component(address, self.components.get(&address).unwrap().clone());
+            ledger.put_component(address, self.components.get(&address).unwrap().clone());
+        }
+
+        for address in self.deleted_components.clone() {
+            ledger.delete_component(address);
         }
 
         for address in self.updated_resource_defs.clone() {
-            self.ledger
-                .put_resource_def(address, self.resource_defs.get(&address).unwrap().clone());
+            ledger.put_resource_def(address, self.resource_defs.get(&address).unwrap().clone());
         }
 
         for mid in self.updated_lazy_maps.clone() {
-            self.ledger
-                .put_lazy_map(mid, self.lazy_maps.get(&mid).unwrap().clone());
+            ledger.put_lazy_map(mid, self.lazy_maps.get(&mid).unwrap().clone());
+        }
+
+        for mid in self.deleted_lazy_maps.clone() {
+            ledger.delete_lazy_map(mid);
         }
 
         for vid in self.updated_vaults.clone() {
-            self.ledger
-                .put_vault(vid, self.vaults.get(&vid).unwrap().clone());
+            ledger.put_vault(vid, self.vaults.get(&vid).unwrap().clone());
+        }
+
+        for vid in self.deleted_vaults.clone() {
+            ledger.delete_vault(vid);
         }
 
         for (resource_def, id) in self.updated_nfts.clone() {
-            self.ledger.put_nft(
+            ledger.put_nft(
                 resource_def,
                 id,
                 self.nfts.get(&(resource_def, id)).unwrap().clone(),
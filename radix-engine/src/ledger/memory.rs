@@ -1,8 +1,17 @@
+use sbor::rust::hash::Hash;
+use sbor::*;
+use scrypto::buffer::*;
 use scrypto::rust::collections::HashMap;
+use scrypto::rust::vec::Vec;
 use scrypto::types::*;
 
 use crate::ledger::*;
 use crate::model::*;
+use crate::transaction::*;
+
+/// The first nonce of genesis transactions, far above the nonces of an executor started from
+/// zero, so that the entities they create never clash.
+const GENESIS_NONCE: u64 = u64::MAX / 2;
 
 /// An in-memory ledger stores all substates in host memory.
 #[derive(Debug, Clone)]
@@ -32,6 +41,181 @@ impl InMemoryLedger {
         ledger.bootstrap();
         ledger
     }
+
+    /// Creates a bootstrapped ledger with `n` accounts, each holding `starting_balance` XRD.
+    ///
+    /// Each `GenesisAccount` holds its private key, so it can sign for its account.
+    pub fn with_genesis_accounts(
+        n: usize,
+        starting_balance: Decimal,
+    ) -> (Self, Vec<GenesisAccount>) {
+        let mut ledger = Self::with_bootstrap();
+        let accounts = TransactionExecutor::new(&mut ledger, 0, GENESIS_NONCE)
+            .new_genesis_accounts(n, starting_balance);
+        (ledger, accounts)
+    }
+
+    /// Takes a snapshot of all substates.
+    pub fn snapshot(&self) -> LedgerSnapshot {
+        LedgerSnapshot {
+            ledger: self.clone(),
+        }
+    }
+
+    /// Restores all substates from a snapshot.
+    pub fn restore(&mut self, snapshot: &LedgerSnapshot) {
+        *self = snapshot.ledger.clone();
+    }
+
+    /// Returns the substates that have been added, removed or changed since the given snapshot.
+    pub fn diff(&self, earlier: &LedgerSnapshot) -> StateDiff {
+        let earlier = &earlier.ledger;
+        let mut diff = StateDiff::default();
+        diff_substates(
+            &self.packages,
+            &earlier.packages,
+            |address| SubstateId::Package(*address),
+            &mut diff,
+        );
+        diff_substates(
+            &self.components,
+            &earlier.components,
+            |address| SubstateId::Component(*address),
+            &mut diff,
+        );
+        diff_substates(
+            &self.lazy_maps,
+            &earlier.lazy_maps,
+            |mid| SubstateId::LazyMap(*mid),
+            &mut diff,
+        );
+        diff_substates(
+            &self.resource_defs,
+            &earlier.resource_defs,
+            |address| SubstateId::ResourceDef(*address),
+            &mut diff,
+        );
+        diff_substates(
+            &self.vaults,
+            &earlier.vaults,
+            |vid| SubstateId::Vault(*vid),
+            &mut diff,
+        );
+        diff_substates(
+            &self.nfts,
+            &earlier.nfts,
+            |(resource_address, id)| SubstateId::Nft(*resource_address, *id),
+            &mut diff,
+        );
+        diff
+    }
+}
+
+impl InMemoryLedger {
+    /// Checks that the total supply of each resource equals the amount held across all vaults.
+    ///
+    /// Resources can't be left outside vaults once a transaction completes, so any mismatch
+    /// means resources have been created or destroyed other than by minting or burning.
+    pub fn check_conservation(&self) -> Result<(), ConservationError> {
+        let mut held = HashMap::<Address, Decimal>::new();
+        for vault in self.vaults.values() {
+            let resource_address = vault.resource_address(Actor::SuperUser).unwrap();
+            let amount = vault.amount(Actor::SuperUser).unwrap();
+            *held.entry(resource_address).or_insert_with(Decimal::zero) += amount;
+        }
+
+        for resource_address in held.keys() {
+            if !self.resource_defs.contains_key(resource_address) {
+                return Err(ConservationError::ResourceDefNotFound(*resource_address));
+            }
+        }
+
+        for (resource_address, resource_def) in &self.resource_defs {
+            let total_supply = resource_def.total_supply();
+            let held = held
+                .get(resource_address)
+                .cloned()
+                .unwrap_or_else(Decimal::zero);
+            if total_supply != held {
+                return Err(ConservationError::SupplyMismatch {
+                    resource_address: *resource_address,
+                    total_supply,
+                    held,
+                });
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Represents an error when resources on ledger don't add up to their total supply.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ConservationError {
+    /// The total supply of a resource differs from the amount held in vaults.
+    SupplyMismatch {
+        resource_address: Address,
+        total_supply: Decimal,
+        held: Decimal,
+    },
+
+    /// A vault holds a resource which has no definition.
+    ResourceDefNotFound(Address),
+}
+
+/// A point-in-time copy of an in-memory ledger.
+#[derive(Debug, Clone)]
+pub struct LedgerSnapshot {
+    ledger: InMemoryLedger,
+}
+
+/// Identifies a substate on ledger.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, TypeId, Encode, Decode)]
+pub enum SubstateId {
+    Package(Address),
+    Component(Address),
+    LazyMap(Mid),
+    ResourceDef(Address),
+    Vault(Vid),
+    Nft(Address, u128),
+}
+
+/// The substate changes between two ledger states.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct StateDiff {
+    pub added: Vec<SubstateId>,
+    pub removed: Vec<SubstateId>,
+    pub changed: Vec<SubstateId>,
+}
+
+impl StateDiff {
+    /// Returns whether no substate has been touched.
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.changed.is_empty()
+    }
+}
+
+fn diff_substates<K: Eq + Hash, V: PartialEq, F: Fn(&K) -> SubstateId>(
+    current: &HashMap<K, V>,
+    earlier: &HashMap<K, V>,
+    id: F,
+    diff: &mut StateDiff,
+) {
+    for (key, value) in current {
+        match earlier.get(key) {
+            Some(old) => {
+                if old != value {
+                    diff.changed.push(id(key));
+                }
+            }
+            None => diff.added.push(id(key)),
+        }
+    }
+    for key in earlier.keys() {
+        if !current.contains_key(key) {
+            diff.removed.push(id(key));
+        }
+    }
 }
 
 impl Default for InMemoryLedger {
@@ -65,6 +249,10 @@ impl Ledger for InMemoryLedger {
         self.components.insert(address, component);
     }
 
+    fn delete_component(&mut self, address: Address) {
+        self.components.remove(&address);
+    }
+
     fn get_lazy_map(&self, mid: Mid) -> Option<LazyMap> {
         self.lazy_maps.get(&mid).map(Clone::clone)
     }
@@ -73,6 +261,10 @@ impl Ledger for InMemoryLedger {
         self.lazy_maps.insert(mid, lazy_map);
     }
 
+    fn delete_lazy_map(&mut self, mid: Mid) {
+        self.lazy_maps.remove(&mid);
+    }
+
     fn get_vault(&self, vid: Vid) -> Option<Vault> {
         self.vaults.get(&vid).map(Clone::clone)
     }
@@ -81,6 +273,10 @@ impl Ledger for InMemoryLedger {
         self.vaults.insert(vid, vault);
     }
 
+    fn delete_vault(&mut self, vid: Vid) {
+        self.vaults.remove(&vid);
+    }
+
     fn get_nft(&self, resource_address: Address, id: u128) -> Option<Nft> {
         self.nfts.get(&(resource_address, id)).cloned()
     }
@@ -88,4 +284,45 @@ impl Ledger for InMemoryLedger {
     fn put_nft(&mut self, resource_address: Address, id: u128, nft: Nft) {
         self.nfts.insert((resource_address, id), nft);
     }
+
+    fn state_root(&self) -> H256 {
+        let mut substates = Vec::new();
+        collect_substates(
+            &self.packages,
+            |address| SubstateId::Package(*address),
+            &mut substates,
+        );
+        collect_substates(
+            &self.components,
+            |address| SubstateId::Component(*address),
+            &mut substates,
+        );
+        collect_substates(
+            &self.lazy_maps,
+            |mid| SubstateId::LazyMap(*mid),
+            &mut substates,
+        );
+        collect_substates(
+            &self.resource_defs,
+            |address| SubstateId::ResourceDef(*address),
+            &mut substates,
+        );
+        collect_substates(&self.vaults, |vid| SubstateId::Vault(*vid), &mut substates);
+        collect_substates(
+            &self.nfts,
+            |(resource_address, id)| SubstateId::Nft(*resource_address, *id),
+            &mut substates,
+        );
+        compute_state_root(substates)
+    }
+}
+
+fn collect_substates<K, V: Encode, F: Fn(&K) -> SubstateId>(
+    map: &HashMap<K, V>,
+    id: F,
+    substates: &mut Vec<(SubstateId, Vec<u8>)>,
+) {
+    for (key, value) in map {
+        substates.push((id(key), scrypto_encode(value)));
+    }
 }
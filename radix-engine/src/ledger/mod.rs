@@ -1,5 +1,10 @@
 mod memory;
+#[cfg(not(feature = "alloc"))]
+mod shared;
 mod traits;
 
-pub use memory::InMemoryLedger;
-pub use traits::Ledger;
+pub use memory::{ConservationError, InMemoryLedger, LedgerSnapshot, StateDiff, SubstateId};
+#[cfg(not(feature = "alloc"))]
+pub use shared::{SharedLedger, SharedLedgerReader};
+pub(crate) use traits::LedgerRef;
+pub use traits::{compute_state_root, Ledger};
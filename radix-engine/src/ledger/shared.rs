@@ -0,0 +1,114 @@
+use std::sync::{Arc, RwLock, RwLockReadGuard, RwLockWriteGuard};
+
+use scrypto::types::*;
+
+use crate::ledger::*;
+use crate::model::*;
+
+/// An in-memory ledger which can be shared across threads.
+///
+/// Clones are handles to the same substates. Readers can query concurrently through the handle
+/// returned by `read`, while a writer applies transactions through the exclusive guard returned
+/// by `write`, so that readers never observe a partially committed transaction.
+#[derive(Debug, Clone)]
+pub struct SharedLedger {
+    inner: Arc<RwLock<InMemoryLedger>>,
+}
+
+impl SharedLedger {
+    pub fn new(ledger: InMemoryLedger) -> Self {
+        Self {
+            inner: Arc::new(RwLock::new(ledger)),
+        }
+    }
+
+    pub fn with_bootstrap() -> Self {
+        Self::new(InMemoryLedger::with_bootstrap())
+    }
+
+    /// Locks the ledger for reading, giving a consistent view over multiple queries.
+    pub fn read(&self) -> SharedLedgerReader<'_> {
+        SharedLedgerReader {
+            guard: self.inner.read().expect("Ledger lock poisoned"),
+        }
+    }
+
+    /// Locks the ledger for writing, e.g. to run transactions on it.
+    pub fn write(&self) -> RwLockWriteGuard<'_, InMemoryLedger> {
+        self.inner.write().expect("Ledger lock poisoned")
+    }
+}
+
+/// A read handle to a shared ledger, holding its read lock until dropped.
+///
+/// It implements the read methods of `Ledger`, e.g. for `TransactionExecutor::new_read_only`.
+/// The write methods panic.
+pub struct SharedLedgerReader<'a> {
+    guard: RwLockReadGuard<'a, InMemoryLedger>,
+}
+
+impl<'a> Ledger for SharedLedgerReader<'a> {
+    fn get_resource_def(&self, address: Address) -> Option<ResourceDef> {
+        self.guard.get_resource_def(address)
+    }
+
+    fn put_resource_def(&mut self, _address: Address, _resource_def: ResourceDef) {
+        panic!("Ledger is read-only")
+    }
+
+    fn get_package(&self, address: Address) -> Option<Package> {
+        self.guard.get_package(address)
+    }
+
+    fn put_package(&mut self, _address: Address, _package: Package) {
+        panic!("Ledger is read-only")
+    }
+
+    fn get_component(&self, address: Address) -> Option<Component> {
+        self.guard.get_component(address)
+    }
+
+    fn put_component(&mut self, _address: Address, _component: Component) {
+        panic!("Ledger is read-only")
+    }
+
+    fn delete_component(&mut self, _address: Address) {
+        panic!("Ledger is read-only")
+    }
+
+    fn get_lazy_map(&self, mid: Mid) -> Option<LazyMap> {
+        self.guard.get_lazy_map(mid)
+    }
+
+    fn put_lazy_map(&mut self, _mid: Mid, _lazy_map: LazyMap) {
+        panic!("Ledger is read-only")
+    }
+
+    fn delete_lazy_map(&mut self, _mid: Mid) {
+        panic!("Ledger is read-only")
+    }
+
+    fn get_vault(&self, vid: Vid) -> Option<Vault> {
+        self.guard.get_vault(vid)
+    }
+
+    fn put_vault(&mut self, _vid: Vid, _vault: Vault) {
+        panic!("Ledger is read-only")
+    }
+
+    fn delete_vault(&mut self, _vid: Vid) {
+        panic!("Ledger is read-only")
+    }
+
+    fn get_nft(&self, resource_address: Address, id: u128) -> Option<Nft> {
+        self.guard.get_nft(resource_address, id)
+    }
+
+    fn put_nft(&mut self, _resource_address: Address, _id: u128, _nft: Nft) {
+        panic!("Ledger is read-only")
+    }
+
+    fn state_root(&self) -> H256 {
+        self.guard.state_root()
+    }
+}
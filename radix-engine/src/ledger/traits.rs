@@ -1,10 +1,15 @@
+use sbor::any::*;
+use sbor::rust::boxed::Box;
 use sbor::*;
 use scrypto::buffer::*;
 use scrypto::kernel::*;
 use scrypto::rust::borrow::ToOwned;
 use scrypto::rust::collections::*;
+use scrypto::rust::vec::Vec;
 use scrypto::types::*;
+use scrypto::utils::*;
 
+use crate::ledger::SubstateId;
 use crate::model::*;
 
 const XRD_SYMBOL: &str = "XRD";
@@ -35,18 +40,27 @@ pub trait Ledger {
 
     fn put_component(&mut self, address: Address, component: Component);
 
+    fn delete_component(&mut self, address: Address);
+
     fn get_lazy_map(&self, mid: Mid) -> Option<LazyMap>;
 
     fn put_lazy_map(&mut self, mid: Mid, lazy_map: LazyMap);
 
+    fn delete_lazy_map(&mut self, mid: Mid);
+
     fn get_vault(&self, vid: Vid) -> Option<Vault>;
 
     fn put_vault(&mut self, vid: Vid, vault: Vault);
 
+    fn delete_vault(&mut self, vid: Vid);
+
     fn get_nft(&self, resource_address: Address, id: u128) -> Option<Nft>;
 
     fn put_nft(&mut self, resource_address: Address, id: u128, nft: Nft);
 
+    /// Returns a deterministic digest of all substates.
+    fn state_root(&self) -> H256;
+
     fn bootstrap(&mut self) {
         if self.get_package(SYSTEM_PACKAGE).is_none() {
             // System package
@@ -107,3 +121,115 @@ pub trait Ledger {
         }
     }
 }
+
+/// A ledger which is either borrowed mutably or for reading only.
+pub(crate) enum LedgerRef<'l, L: Ledger> {
+    Mutable(&'l mut L),
+    ReadOnly(&'l L),
+}
+
+impl<'l, L: Ledger> LedgerRef<'l, L> {
+    pub(crate) fn get(&self) -> &L {
+        match self {
+            Self::Mutable(ledger) => ledger,
+            Self::ReadOnly(ledger) => ledger,
+        }
+    }
+
+    /// Returns the ledger for writing, or `None` if it's borrowed for reading only.
+    pub(crate) fn get_mut(&mut self) -> Option<&mut L> {
+        match self {
+            Self::Mutable(ledger) => Some(ledger),
+            Self::ReadOnly(_) => None,
+        }
+    }
+}
+
+/// Computes the Merkle root of the given `(id, encoded substate)` pairs, after sorting them by
+/// encoded id.
+///
+/// Every ledger passes the same ids, so that the same state gives the same root. Substates are
+/// canonicalized first, as hash maps and sets are encoded in iteration order.
+pub fn compute_state_root(substates: Vec<(SubstateId, Vec<u8>)>) -> H256 {
+    let mut substates: Vec<(Vec<u8>, Vec<u8>)> = substates
+        .into_iter()
+        .map(|(id, value)| (scrypto_encode(&id), value))
+        .collect();
+    substates.sort();
+
+    let mut hashes: Vec<H256> = substates
+        .iter()
+        .map(|(key, value)| {
+            let value = match decode_any(value) {
+                Ok(v) => encode_value(&canonicalize(v)),
+                Err(_) => value.clone(),
+            };
+            sha256([key.as_slice(), value.as_slice()].concat())
+        })
+        .collect();
+    if hashes.is_empty() {
+        return H256([0u8; 32]);
+    }
+    while hashes.len() > 1 {
+        hashes = hashes
+            .chunks(2)
+            .map(|pair| sha256(pair.iter().flat_map(|h| h.0).collect::<Vec<u8>>()))
+            .collect();
+    }
+    hashes[0]
+}
+
+fn encode_value(value: &Value) -> Vec<u8> {
+    let mut encoder = Encoder::with_type(Vec::new());
+    encode_any(None, value, &mut encoder);
+    encoder.into()
+}
+
+/// Sorts the entries of hash maps and sets by their encoding, recursively.
+fn canonicalize(v: Value) -> Value {
+    let canonicalize_fields = |fields: Fields| match fields {
+        Fields::Named(values) => Fields::Named(values.into_iter().map(canonicalize).collect()),
+        Fields::Unnamed(values) => Fields::Unnamed(values.into_iter().map(canonicalize).collect()),
+        Fields::Unit => Fields::Unit,
+    };
+    match v {
+        Value::Struct(fields) => Value::Struct(canonicalize_fields(fields)),
+        Value::Enum(index, fields) => Value::Enum(index, canonicalize_fields(fields)),
+        Value::Option(x) => Value::Option(Box::new(x.map(canonicalize))),
+        Value::Box(x) => Value::Box(Box::new(canonicalize(*x))),
+        Value::Result(x) => Value::Result(Box::new(match *x {
+            Ok(v) => Ok(canonicalize(v)),
+            Err(v) => Err(canonicalize(v)),
+        })),
+        Value::Array(ty, values) => {
+            Value::Array(ty, values.into_iter().map(canonicalize).collect())
+        }
+        Value::Tuple(values) => Value::Tuple(values.into_iter().map(canonicalize).collect()),
+        Value::Vec(ty, values) => Value::Vec(ty, values.into_iter().map(canonicalize).collect()),
+        Value::TreeSet(ty, values) => {
+            Value::TreeSet(ty, values.into_iter().map(canonicalize).collect())
+        }
+        Value::TreeMap(ty_k, ty_v, values) => Value::TreeMap(
+            ty_k,
+            ty_v,
+            values
+                .into_iter()
+                .map(|(k, v)| (canonicalize(k), canonicalize(v)))
+                .collect(),
+        ),
+        Value::HashSet(ty, values) => {
+            let mut values: Vec<Value> = values.into_iter().map(canonicalize).collect();
+            values.sort_by_cached_key(encode_value);
+            Value::HashSet(ty, values)
+        }
+        Value::HashMap(ty_k, ty_v, values) => {
+            let mut values: Vec<(Value, Value)> = values
+                .into_iter()
+                .map(|(k, v)| (canonicalize(k), canonicalize(v)))
+                .collect();
+            values.sort_by_cached_key(|(k, _)| encode_value(k));
+            Value::HashMap(ty_k, ty_v, values)
+        }
+        _ => v,
+    }
+}
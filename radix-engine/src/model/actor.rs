@@ -10,6 +10,8 @@ pub enum Actor {
     Package(Address),
 
     PackageWithBadges(Address, HashSet<Address>),
+
+    Badges(HashSet<Address>),
 }
 
 impl Actor {
@@ -21,6 +23,7 @@ impl Actor {
             Self::PackageWithBadges(pkg, badges) => {
                 *pkg == authority || badges.contains(&authority)
             }
+            Self::Badges(badges) => badges.contains(&authority),
         }
     }
 
@@ -47,6 +50,14 @@ impl Actor {
                 }
                 false
             }
+            Self::Badges(badges) => {
+                for badge in badges {
+                    if let Some(v) = authorities.get(badge) {
+                        return v & permission == permission;
+                    }
+                }
+                false
+            }
         }
     }
 }
@@ -1,5 +1,6 @@
 use sbor::*;
 use scrypto::kernel::*;
+use scrypto::resource::ResourceAmount;
 use scrypto::rust::collections::BTreeSet;
 use scrypto::rust::rc::Rc;
 use scrypto::rust::string::ToString;
@@ -12,12 +13,13 @@ pub enum BucketError {
     ResourceNotMatching,
     InsufficientBalance,
     InvalidAmount(Decimal),
+    FractionalNonFungible(Decimal),
     UnsupportedOperation,
     NftNotFound,
 }
 
 /// Represents the supply of resource.
-#[derive(Debug, Clone, TypeId, Encode, Decode)]
+#[derive(Debug, Clone, PartialEq, Eq, TypeId, Encode, Decode)]
 pub enum Supply {
     Fungible { amount: Decimal },
 
@@ -25,7 +27,7 @@ pub enum Supply {
 }
 
 /// A transient resource container.
-#[derive(Debug, Clone, TypeId, Encode, Decode)]
+#[derive(Debug, Clone, PartialEq, Eq, TypeId, Encode, Decode)]
 pub struct Bucket {
     resource_address: Address,
     resource_type: ResourceType,
@@ -33,7 +35,7 @@ pub struct Bucket {
 }
 
 /// A bucket becomes locked after a borrow operation.
-#[derive(Debug, Clone, TypeId, Encode, Decode)]
+#[derive(Debug, Clone, PartialEq, Eq, TypeId, Encode, Decode)]
 pub struct LockedBucket {
     bucket_id: Bid,
     bucket: Bucket,
@@ -80,7 +82,7 @@ impl Bucket {
     }
 
     pub fn take(&mut self, quantity: Decimal) -> Result<Self, BucketError> {
-        Self::check_amount(quantity, self.resource_type.divisibility())?;
+        Self::check_amount(quantity, self.resource_type)?;
 
         if self.amount() < quantity {
             Err(BucketError::InsufficientBalance)
@@ -153,9 +155,28 @@ impl Bucket {
         self.resource_address
     }
 
-    fn check_amount(amount: Decimal, divisibility: u8) -> Result<(), BucketError> {
+    /// Returns the amount of resource in this bucket, with the ids of NFTs.
+    pub fn resource_amount(&self) -> ResourceAmount {
+        match &self.supply {
+            Supply::Fungible { amount } => ResourceAmount::Fungible {
+                amount: *amount,
+                resource_address: self.resource_address,
+            },
+            Supply::NonFungible { ids } => ResourceAmount::NonFungible {
+                ids: ids.clone(),
+                resource_address: self.resource_address,
+            },
+        }
+    }
+
+    fn check_amount(amount: Decimal, resource_type: ResourceType) -> Result<(), BucketError> {
+        let divisibility = resource_type.divisibility();
+
         if !amount.is_negative() && amount.0 % 10i128.pow((18 - divisibility).into()) != 0.into() {
-            Err(BucketError::InvalidAmount(amount))
+            match resource_type {
+                ResourceType::NonFungible => Err(BucketError::FractionalNonFungible(amount)),
+                ResourceType::Fungible { .. } => Err(BucketError::InvalidAmount(amount)),
+            }
         } else {
             Ok(())
         }
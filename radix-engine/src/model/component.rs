@@ -12,12 +12,13 @@ pub enum ComponentError {
 }
 
 /// A component is an instance of blueprint.
-#[derive(Debug, Clone, TypeId, Encode, Decode)]
+#[derive(Debug, Clone, PartialEq, Eq, TypeId, Encode, Decode)]
 pub struct Component {
     package_address: Address,
     blueprint_name: String,
     state: Vec<u8>,
     authority: Address,
+    owner: Option<Address>,
 }
 
 impl Component {
@@ -27,6 +28,29 @@ impl Component {
             blueprint_name,
             state,
             authority: package_address,
+            owner: None,
+        }
+    }
+
+    /// Returns the owner, who is allowed to delete this component.
+    pub fn owner(&self) -> Option<Address> {
+        self.owner
+    }
+
+    pub fn set_owner(&mut self, owner: Address, actor: Actor) -> Result<(), ComponentError> {
+        if actor.check(self.authority) {
+            self.owner = Some(owner);
+            Ok(())
+        } else {
+            Err(ComponentError::UnauthorizedAccess)
+        }
+    }
+
+    /// Checks that the owner of this component is one of the signers.
+    pub fn check_owner(&self, signers: &[Address]) -> Result<(), ComponentError> {
+        match self.owner {
+            Some(owner) if signers.contains(&owner) => Ok(()),
+            _ => Err(ComponentError::UnauthorizedAccess),
         }
     }
 
@@ -55,3 +79,36 @@ impl Component {
         }
     }
 }
+
+/// A component as stored before owners were introduced.
+#[derive(Debug, Clone, PartialEq, Eq, TypeId, Encode, Decode)]
+pub struct ComponentV1 {
+    pub package_address: Address,
+    pub blueprint_name: String,
+    pub state: Vec<u8>,
+    pub authority: Address,
+}
+
+impl ComponentV1 {
+    /// Converts into the latest layout, without an owner.
+    pub fn into_latest(self) -> Component {
+        Component {
+            package_address: self.package_address,
+            blueprint_name: self.blueprint_name,
+            state: self.state,
+            authority: self.authority,
+            owner: None,
+        }
+    }
+}
+
+impl Component {
+    /// Decodes a stored component of any layout.
+    pub fn decode_stored(bytes: &[u8]) -> Result<Self, DecodeError> {
+        decode_with_type::<Self>(bytes).or_else(|e| {
+            decode_with_type::<ComponentV1>(bytes)
+                .map(ComponentV1::into_latest)
+                .map_err(|_| e)
+        })
+    }
+}
@@ -12,7 +12,7 @@ pub enum LazyMapError {
 }
 
 /// A key-value map where keys and values are lazily loaded on-demand.
-#[derive(Debug, Clone, TypeId, Encode, Decode)]
+#[derive(Debug, Clone, PartialEq, Eq, TypeId, Encode, Decode)]
 pub struct LazyMap {
     map: HashMap<Vec<u8>, Vec<u8>>,
     authority: Address,
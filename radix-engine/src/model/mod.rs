@@ -9,7 +9,7 @@ mod vault;
 
 pub use actor::Actor;
 pub use bucket::{Bucket, BucketError, BucketRef, LockedBucket, Supply};
-pub use component::{Component, ComponentError};
+pub use component::{Component, ComponentError, ComponentV1};
 pub use lazy_map::{LazyMap, LazyMapError};
 pub use nft::{Nft, NftError};
 pub use package::Package;
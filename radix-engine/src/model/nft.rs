@@ -5,10 +5,11 @@ use scrypto::rust::vec::Vec;
 #[derive(Debug, Clone)]
 pub enum NftError {
     UnauthorizedAccess,
+    ImmutableDataChanged,
 }
 
 /// An nft is a peirece of data that is uniquely identified within a resource.
-#[derive(Debug, Clone, TypeId, Encode, Decode)]
+#[derive(Debug, Clone, PartialEq, Eq, TypeId, Encode, Decode)]
 pub struct Nft {
     immutable_data: Vec<u8>,
     mutable_data: Vec<u8>,
@@ -30,6 +31,15 @@ impl Nft {
         self.mutable_data.clone()
     }
 
+    /// Checks that the given immutable data matches the stored one.
+    pub fn check_immutable_data(&self, immutable_data: &[u8]) -> Result<(), NftError> {
+        if self.immutable_data == immutable_data {
+            Ok(())
+        } else {
+            Err(NftError::ImmutableDataChanged)
+        }
+    }
+
     pub fn set_mutable_data(&mut self, new_mutable_data: Vec<u8>) -> Result<(), NftError> {
         self.mutable_data = new_mutable_data;
         Ok(())
@@ -2,7 +2,7 @@ use sbor::*;
 use scrypto::rust::vec::Vec;
 
 /// A collection of blueprints, compiled and published as a single unit.
-#[derive(Debug, Clone, TypeId, Encode, Decode)]
+#[derive(Debug, Clone, PartialEq, Eq, TypeId, Encode, Decode)]
 pub struct Package {
     code: Vec<u8>,
 }
@@ -6,17 +6,21 @@ use scrypto::rust::collections::HashMap;
 use scrypto::rust::string::String;
 use scrypto::types::*;
 
-use crate::model::{Actor, Supply};
+use crate::model::{Actor, Bucket, Supply};
 
 /// Represents an error when accessing a bucket.
 #[derive(Debug, Clone)]
 pub enum ResourceDefError {
-    UnauthorizedAccess,
+    NotAuthorized {
+        required_badge: Option<Address>,
+        permission: u16,
+    },
     TypeAndSupplyNotMatching,
     UnsupportedOperation,
     OperationNotAllowed,
     InvalidDivisibility,
     InvalidAmount(Decimal),
+    FractionalNonFungible(Decimal),
     InvalidFlagUpdate {
         flags: u16,
         mutable_flags: u16,
@@ -26,7 +30,7 @@ pub enum ResourceDefError {
 }
 
 /// The definition of a resource.
-#[derive(Debug, Clone, TypeId, Encode, Decode)]
+#[derive(Debug, Clone, PartialEq, Eq, TypeId, Encode, Decode)]
 pub struct ResourceDef {
     resource_type: ResourceType,
     metadata: HashMap<String, String>,
@@ -73,6 +77,28 @@ impl ResourceDef {
         Ok(resource_def)
     }
 
+    /// Creates a fungible resource with fixed supply, along with a bucket of the entire supply.
+    ///
+    /// The supply is rejected if it's not a multiple of the granularity implied by divisibility.
+    pub fn new_fixed(
+        resource_address: Address,
+        divisibility: u8,
+        metadata: HashMap<String, String>,
+        amount: Decimal,
+    ) -> Result<(Self, Bucket), ResourceDefError> {
+        let resource_type = ResourceType::Fungible { divisibility };
+        let resource_def = Self::new(
+            resource_type,
+            metadata,
+            0,
+            0,
+            HashMap::new(),
+            &Some(NewSupply::Fungible { amount }),
+        )?;
+        let bucket = Bucket::new(resource_address, resource_type, Supply::Fungible { amount });
+        Ok((resource_def, bucket))
+    }
+
     pub fn resource_type(&self) -> ResourceType {
         self.resource_type
     }
@@ -207,19 +233,13 @@ impl ResourceDef {
         if !self.is_flag_on(RESTRICTED_TRANSFER) {
             Ok(())
         } else {
-            actor
-                .check_permission(self.authorities(), MAY_TRANSFER)
-                .then(|| ())
-                .ok_or(ResourceDefError::UnauthorizedAccess)
+            self.check_permission(actor, MAY_TRANSFER)
         }
     }
 
     pub fn check_mint_auth(&self, actor: Actor) -> Result<(), ResourceDefError> {
         if self.is_flag_on(MINTABLE) {
-            actor
-                .check_permission(self.authorities(), MAY_MINT)
-                .then(|| ())
-                .ok_or(ResourceDefError::UnauthorizedAccess)
+            self.check_permission(actor, MAY_MINT)
         } else {
             Err(ResourceDefError::OperationNotAllowed)
         }
@@ -229,10 +249,15 @@ impl ResourceDef {
         if self.is_flag_on(FREELY_BURNABLE) {
             Ok(())
         } else if self.is_flag_on(BURNABLE) {
-            actor
-                .check_permission(self.authorities(), MAY_BURN)
-                .then(|| ())
-                .ok_or(ResourceDefError::UnauthorizedAccess)
+            self.check_permission(actor, MAY_BURN)
+        } else {
+            Err(ResourceDefError::OperationNotAllowed)
+        }
+    }
+
+    pub fn check_recall_auth(&self, actor: Actor) -> Result<(), ResourceDefError> {
+        if self.is_flag_on(RECALLABLE) {
+            self.check_permission(actor, MAY_RECALL)
         } else {
             Err(ResourceDefError::OperationNotAllowed)
         }
@@ -240,10 +265,7 @@ impl ResourceDef {
 
     pub fn check_update_nft_mutable_data_auth(&self, actor: Actor) -> Result<(), ResourceDefError> {
         if self.is_flag_on(INDIVIDUAL_METADATA_MUTABLE) {
-            actor
-                .check_permission(self.authorities(), MAY_CHANGE_INDIVIDUAL_METADATA)
-                .then(|| ())
-                .ok_or(ResourceDefError::UnauthorizedAccess)
+            self.check_permission(actor, MAY_CHANGE_INDIVIDUAL_METADATA)
         } else {
             Err(ResourceDefError::OperationNotAllowed)
         }
@@ -251,27 +273,47 @@ impl ResourceDef {
 
     pub fn check_update_metadata_auth(&self, actor: Actor) -> Result<(), ResourceDefError> {
         if self.is_flag_on(SHARED_METADATA_MUTABLE) {
-            actor
-                .check_permission(self.authorities(), MAY_CHANGE_SHARED_METADATA)
-                .then(|| ())
-                .ok_or(ResourceDefError::UnauthorizedAccess)
+            self.check_permission(actor, MAY_CHANGE_SHARED_METADATA)
         } else {
             Err(ResourceDefError::OperationNotAllowed)
         }
     }
 
     pub fn check_manage_flags_auth(&self, actor: Actor) -> Result<(), ResourceDefError> {
-        actor
-            .check_permission(self.authorities(), MAY_MANAGE_RESOURCE_FLAGS)
-            .then(|| ())
-            .ok_or(ResourceDefError::UnauthorizedAccess)
+        self.check_permission(actor, MAY_MANAGE_RESOURCE_FLAGS)
+    }
+
+    /// Checks if the actor holds the given permission, reporting the badge that would grant it.
+    fn check_permission(&self, actor: Actor, permission: u16) -> Result<(), ResourceDefError> {
+        if actor.check_permission(self.authorities(), permission) {
+            Ok(())
+        } else {
+            Err(ResourceDefError::NotAuthorized {
+                required_badge: self.required_badge(permission),
+                permission,
+            })
+        }
+    }
+
+    /// Returns the authority granted with the given permission, if any.
+    ///
+    /// When multiple authorities qualify, the one with the smallest address is returned.
+    pub fn required_badge(&self, permission: u16) -> Option<Address> {
+        self.authorities
+            .iter()
+            .filter(|(_, v)| *v & permission == permission)
+            .map(|(k, _)| *k)
+            .min_by_key(Address::to_vec)
     }
 
     pub fn check_amount(&self, amount: Decimal) -> Result<(), ResourceDefError> {
         let divisibility = self.resource_type.divisibility();
 
         if !amount.is_negative() && amount.0 % 10i128.pow((18 - divisibility).into()) != 0.into() {
-            Err(ResourceDefError::InvalidAmount(amount))
+            match self.resource_type {
+                ResourceType::NonFungible => Err(ResourceDefError::FractionalNonFungible(amount)),
+                ResourceType::Fungible { .. } => Err(ResourceDefError::InvalidAmount(amount)),
+            }
         } else {
             Ok(())
         }
@@ -12,7 +12,7 @@ pub enum VaultError {
 }
 
 /// A persistent resource container on ledger state.
-#[derive(Debug, Clone, TypeId, Encode, Decode)]
+#[derive(Debug, Clone, PartialEq, Eq, TypeId, Encode, Decode)]
 pub struct Vault {
     bucket: Bucket,
     authority: Address,
@@ -1,5 +1,7 @@
 use scrypto::abi;
 use scrypto::buffer::*;
+use scrypto::kernel::*;
+use scrypto::resource::resource_flags::*;
 use scrypto::rust::borrow::ToOwned;
 use scrypto::rust::string::String;
 use scrypto::rust::string::ToString;
@@ -11,6 +13,33 @@ use crate::engine::*;
 use crate::ledger::*;
 use crate::model::*;
 
+/// The capabilities of a resource, as described by its flags.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ResourceBehavior {
+    pub mintable: bool,
+    pub burnable: bool,
+    pub freely_burnable: bool,
+    pub recallable: bool,
+    pub restricted_transfer: bool,
+    pub shared_metadata_mutable: bool,
+    pub individual_metadata_mutable: bool,
+}
+
+impl ResourceBehavior {
+    pub fn from_flags(flags: u16) -> Self {
+        Self {
+            mintable: flags & MINTABLE == MINTABLE,
+            burnable: flags & (BURNABLE | FREELY_BURNABLE) != 0,
+            freely_burnable: flags & FREELY_BURNABLE == FREELY_BURNABLE,
+            recallable: flags & RECALLABLE == RECALLABLE,
+            restricted_transfer: flags & RESTRICTED_TRANSFER == RESTRICTED_TRANSFER,
+            shared_metadata_mutable: flags & SHARED_METADATA_MUTABLE == SHARED_METADATA_MUTABLE,
+            individual_metadata_mutable: flags & INDIVIDUAL_METADATA_MUTABLE
+                == INDIVIDUAL_METADATA_MUTABLE,
+        }
+    }
+}
+
 /// An interface for exporting the ABI of a blueprint.
 pub trait AbiProvider {
     /// Exports the ABI of a blueprint.
@@ -27,6 +56,23 @@ pub trait AbiProvider {
         component_address: Address,
         trace: bool,
     ) -> Result<abi::Blueprint, RuntimeError>;
+
+    /// Exports the behavior of a resource, as described by its flags.
+    ///
+    /// Providers without resource definitions treat every resource as unknown.
+    fn export_resource_behavior(
+        &self,
+        resource_address: Address,
+    ) -> Result<ResourceBehavior, RuntimeError> {
+        Err(RuntimeError::ResourceDefNotFound(resource_address))
+    }
+
+    /// Returns the type of a resource, if it's known.
+    ///
+    /// Used to validate resource arguments; resources of unknown type are not checked.
+    fn resource_type(&self, _resource_address: Address) -> Option<ResourceType> {
+        None
+    }
 }
 
 /// Provides ABIs for blueprints either installed during bootstrap or added manually.
@@ -104,4 +150,20 @@ impl AbiProvider for BasicAbiProvider {
             trace,
         )
     }
+
+    fn export_resource_behavior(
+        &self,
+        resource_address: Address,
+    ) -> Result<ResourceBehavior, RuntimeError> {
+        self.ledger
+            .get_resource_def(resource_address)
+            .map(|r| ResourceBehavior::from_flags(r.flags()))
+            .ok_or(RuntimeError::ResourceDefNotFound(resource_address))
+    }
+
+    fn resource_type(&self, resource_address: Address) -> Option<ResourceType> {
+        self.ledger
+            .get_resource_def(resource_address)
+            .map(|r| r.resource_type())
+    }
 }
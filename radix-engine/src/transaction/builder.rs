@@ -1,4 +1,6 @@
+use core::ops::Range;
 use sbor::describe::*;
+use sbor::type_id::*;
 use sbor::*;
 use scrypto::abi;
 use scrypto::buffer::*;
@@ -8,101 +10,19 @@ use scrypto::resource::resource_permissions::*;
 use scrypto::rust::borrow::ToOwned;
 use scrypto::rust::collections::*;
 use scrypto::rust::fmt;
+use scrypto::rust::format;
 use scrypto::rust::str::FromStr;
 use scrypto::rust::string::String;
+use scrypto::rust::string::ToString;
 use scrypto::rust::vec;
 use scrypto::rust::vec::Vec;
 use scrypto::types::*;
+use scrypto::utils::*;
 
 use crate::engine::*;
 use crate::transaction::*;
 
-/// Represents some amount of resource.
-pub enum ResourceAmount {
-    Fungible {
-        amount: Decimal,
-        resource_address: Address,
-    },
-    NonFungible {
-        ids: BTreeSet<u128>,
-        resource_address: Address,
-    },
-}
-
-/// Represents an error when parsing `ResourceAmount` from string.
-#[derive(Debug, Clone)]
-pub enum ParseResourceAmountError {
-    InvalidAmount,
-    InvalidNftId,
-    InvalidResourceAddress,
-    MissingResourceAddress,
-}
-
-impl FromStr for ResourceAmount {
-    type Err = ParseResourceAmountError;
-
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let tokens: Vec<&str> = s.trim().split(',').collect();
-
-        if tokens.len() >= 2 {
-            let resource_address = tokens
-                .last()
-                .unwrap()
-                .parse::<Address>()
-                .map_err(|_| ParseResourceAmountError::InvalidResourceAddress)?;
-            if tokens[0].starts_with('#') {
-                let mut ids = BTreeSet::<u128>::new();
-                for id in &tokens[..tokens.len() - 1] {
-                    if id.starts_with('#') {
-                        ids.insert(
-                            id[1..]
-                                .parse()
-                                .map_err(|_| ParseResourceAmountError::InvalidNftId)?,
-                        );
-                    } else {
-                        return Err(ParseResourceAmountError::InvalidNftId);
-                    }
-                }
-                Ok(ResourceAmount::NonFungible {
-                    ids,
-                    resource_address,
-                })
-            } else {
-                if tokens.len() == 2 {
-                    Ok(ResourceAmount::Fungible {
-                        amount: tokens[0]
-                            .parse()
-                            .map_err(|_| ParseResourceAmountError::InvalidAmount)?,
-                        resource_address,
-                    })
-                } else {
-                    Err(ParseResourceAmountError::InvalidAmount)
-                }
-            }
-        } else {
-            Err(ParseResourceAmountError::MissingResourceAddress)
-        }
-    }
-}
-
-impl ResourceAmount {
-    pub fn amount(&self) -> Decimal {
-        match self {
-            ResourceAmount::Fungible { amount, .. } => *amount,
-            ResourceAmount::NonFungible { ids, .. } => ids.len().into(),
-        }
-    }
-    pub fn resource_address(&self) -> Address {
-        match self {
-            ResourceAmount::Fungible {
-                resource_address, ..
-            }
-            | ResourceAmount::NonFungible {
-                resource_address, ..
-            } => *resource_address,
-        }
-    }
-}
+pub use scrypto::resource::{MergeError, ParseResourceAmountError, ResourceAmount};
 
 /// Utility for building transaction.
 pub struct TransactionBuilder<'a, A: AbiProvider> {
@@ -113,6 +33,8 @@ pub struct TransactionBuilder<'a, A: AbiProvider> {
     reservations: Vec<Instruction>,
     /// Instructions generated.
     instructions: Vec<Instruction>,
+    /// Blobs attached.
+    blobs: Vec<Vec<u8>>,
     /// Collected Errors
     errors: Vec<BuildTransactionError>,
 }
@@ -125,10 +47,30 @@ impl<'a, A: AbiProvider> TransactionBuilder<'a, A> {
             allocator: IdAllocator::new(),
             reservations: Vec::new(),
             instructions: Vec::new(),
+            blobs: Vec::new(),
             errors: Vec::new(),
         }
     }
 
+    /// Clears all accumulated state, so the builder can be reused for a new transaction.
+    pub fn reset(&mut self) -> &mut Self {
+        self.allocator = IdAllocator::new();
+        self.reservations.clear();
+        self.instructions.clear();
+        self.blobs.clear();
+        self.errors.clear();
+        self
+    }
+
+    /// Attaches a blob to the transaction, returning the hash by which instructions refer to it.
+    pub fn add_blob(&mut self, blob: &[u8]) -> H256 {
+        let hash = sha256(blob);
+        if !self.blobs.iter().any(|b| sha256(b) == hash) {
+            self.blobs.push(blob.to_vec());
+        }
+        hash
+    }
+
     /// Adds a raw instruction.
     pub fn add_instruction(&mut self, inst: Instruction) -> &mut Self {
         self.instructions.push(inst);
@@ -169,6 +111,21 @@ impl<'a, A: AbiProvider> TransactionBuilder<'a, A> {
         })
     }
 
+    /// Creates a bucket by withdrawing the exact amount of resource from context, failing
+    /// the transaction if the context holds less.
+    pub fn take_exact_from_context(
+        &mut self,
+        amount: Decimal,
+        resource_address: Address,
+        to: Bid,
+    ) -> &mut Self {
+        self.add_instruction(Instruction::TakeExactFromContext {
+            amount,
+            resource_address,
+            to,
+        })
+    }
+
     /// Creates a bucket ref by borrowing resource from context.
     pub fn borrow_from_context(
         &mut self,
@@ -198,27 +155,77 @@ impl<'a, A: AbiProvider> TransactionBuilder<'a, A> {
         args: Vec<String>,
         account: Option<Address>,
     ) -> &mut Self {
-        let result = self
+        if let Err(e) =
+            self.try_call_function(package_address, blueprint_name, function, args, account)
+        {
+            self.errors.push(e);
+        }
+
+        self
+    }
+
+    /// Calls a function, like `call_function`, but returns any error immediately instead of
+    /// deferring it to `build`.
+    pub fn try_call_function(
+        &mut self,
+        package_address: Address,
+        blueprint_name: &str,
+        function: &str,
+        args: Vec<String>,
+        account: Option<Address>,
+    ) -> Result<&mut Self, BuildTransactionError> {
+        let abi = self
             .abi_provider
             .export_abi(package_address, blueprint_name, false)
-            .map_err(|_| {
-                BuildTransactionError::FailedToExportFunctionAbi(
-                    package_address,
-                    blueprint_name.to_owned(),
-                    function.to_owned(),
-                )
+            .map_err(|e| Self::abi_export_error(package_address, e))?;
+        let f = Self::find_function_abi(&abi, function)?;
+        let args = self
+            .prepare_args(&f.inputs, args, account)
+            .map_err(BuildTransactionError::FailedToBuildArgs)?;
+
+        Ok(self.add_instruction(Instruction::CallFunction {
+            package_address,
+            blueprint_name: blueprint_name.to_owned(),
+            function: function.to_owned(),
+            args,
+        }))
+    }
+
+    /// Calls a function of the blueprint, from which the given component is instantiated.
+    ///
+    /// The package and blueprint are resolved from the ledger; arguments are prepared
+    /// the same way as `call_function`.
+    pub fn call_blueprint_function_of(
+        &mut self,
+        component_address: Address,
+        function: &str,
+        args: Vec<String>,
+        account: Option<Address>,
+    ) -> &mut Self {
+        let result = self
+            .abi_provider
+            .export_abi_component(component_address, false)
+            .ok()
+            .and_then(|abi| {
+                Address::from_str(&abi.package)
+                    .ok()
+                    .map(|package_address| (package_address, abi))
             })
-            .and_then(|abi| Self::find_function_abi(&abi, function))
-            .and_then(|f| {
+            .ok_or(BuildTransactionError::FailedToResolveBlueprint(
+                component_address,
+            ))
+            .and_then(|(package_address, abi)| {
+                let f = Self::find_function_abi(&abi, function)?;
                 self.prepare_args(&f.inputs, args, account)
-                    .map_err(|e| BuildTransactionError::FailedToBuildArgs(e))
+                    .map(|args| (package_address, abi.name, args))
+                    .map_err(BuildTransactionError::FailedToBuildArgs)
             });
 
         match result {
-            Ok(args) => {
+            Ok((package_address, blueprint_name, args)) => {
                 self.add_instruction(Instruction::CallFunction {
                     package_address,
-                    blueprint_name: blueprint_name.to_owned(),
+                    blueprint_name,
                     function: function.to_owned(),
                     args,
                 });
@@ -243,30 +250,119 @@ impl<'a, A: AbiProvider> TransactionBuilder<'a, A> {
         args: Vec<String>,
         account: Option<Address>,
     ) -> &mut Self {
-        let result = self
+        if let Err(e) = self.try_call_method(component_address, method, args, account) {
+            self.errors.push(e);
+        }
+
+        self
+    }
+
+    /// Calls a method, like `call_method`, but returns any error immediately instead of
+    /// deferring it to `build`.
+    pub fn try_call_method(
+        &mut self,
+        component_address: Address,
+        method: &str,
+        args: Vec<String>,
+        account: Option<Address>,
+    ) -> Result<&mut Self, BuildTransactionError> {
+        let abi = self
             .abi_provider
             .export_abi_component(component_address, false)
-            .map_err(|_| {
-                BuildTransactionError::FailedToExportMethodAbi(component_address, method.to_owned())
-            })
-            .and_then(|abi| Self::find_method_abi(&abi, method))
-            .and_then(|m| {
-                self.prepare_args(&m.inputs, args, account)
-                    .map_err(|e| BuildTransactionError::FailedToBuildArgs(e))
-            });
+            .map_err(|e| Self::abi_export_error(component_address, e))?;
+        let m = Self::find_method_abi(&abi, method, args.len())?;
+        let args = self
+            .prepare_args(&m.inputs, args, account)
+            .map_err(BuildTransactionError::FailedToBuildArgs)?;
 
-        match result {
-            Ok(args) => {
-                self.add_instruction(Instruction::CallMethod {
-                    component_address,
-                    method: method.to_owned(),
-                    args,
-                });
-            }
-            Err(e) => self.errors.push(e),
-        }
+        Ok(self.add_instruction(Instruction::CallMethod {
+            component_address,
+            method: method.to_owned(),
+            args,
+        }))
+    }
 
-        self
+    /// Checks that the arguments of a function call parse against its ABI, without emitting
+    /// any instruction.
+    ///
+    /// Unlike building, the errors of all arguments are reported, not only the first one.
+    pub fn validate_function_args(
+        &self,
+        package_address: Address,
+        blueprint_name: &str,
+        function: &str,
+        args: &[String],
+    ) -> Result<(), BuildTransactionError> {
+        let abi = self
+            .abi_provider
+            .export_abi(package_address, blueprint_name, false)
+            .map_err(|e| Self::abi_export_error(package_address, e))?;
+        let f = Self::find_function_abi(&abi, function)?;
+        self.validate_args(&f.inputs, args)
+    }
+
+    /// Checks that the arguments of a method call parse against its ABI, without emitting
+    /// any instruction.
+    ///
+    /// Unlike building, the errors of all arguments are reported, not only the first one.
+    pub fn validate_method_args(
+        &self,
+        component_address: Address,
+        method: &str,
+        args: &[String],
+    ) -> Result<(), BuildTransactionError> {
+        let abi = self
+            .abi_provider
+            .export_abi_component(component_address, false)
+            .map_err(|e| Self::abi_export_error(component_address, e))?;
+        let m = Self::find_method_abi(&abi, method, args.len())?;
+        self.validate_args(&m.inputs, args)
+    }
+
+    /// Calls a method with all resources in transaction context, passed as a `Vec<Bucket>`.
+    pub fn call_method_with_all_resources(
+        &mut self,
+        component_address: Address,
+        method: &str,
+    ) -> &mut Self {
+        self.add_instruction(Instruction::CallMethodWithAllResources {
+            component_address,
+            method: method.to_owned(),
+        })
+    }
+
+    /// Moves resource directly between the vaults of two components, which requires the
+    /// signature of the source component owner.
+    pub fn transfer_between_vaults(
+        &mut self,
+        from_component: Address,
+        to_component: Address,
+        resource_address: Address,
+        amount: Decimal,
+        auth: Rid,
+    ) -> &mut Self {
+        self.add_instruction(Instruction::TransferBetweenVaults {
+            from_component,
+            to_component,
+            resource_address,
+            amount,
+            auth,
+        })
+    }
+
+    /// Returns whether the blueprint of a component defines the given method.
+    pub fn component_has_method(&self, component_address: Address, method: &str) -> bool {
+        self.list_methods(component_address)
+            .iter()
+            .any(|m| m == method)
+    }
+
+    /// Returns the names of all methods of a component, or nothing if its ABI can't be exported.
+    pub fn list_methods(&self, component_address: Address) -> Vec<String> {
+        self.abi_provider
+            .export_abi_component(component_address, false)
+            .map(|abi| abi.methods.into_iter().map(|m| m.name).collect())
+            .unwrap_or_default()
     }
 
     /// Drops all bucket refs.
@@ -274,13 +370,84 @@ impl<'a, A: AbiProvider> TransactionBuilder<'a, A> {
         self.add_instruction(Instruction::DropAllBucketRefs)
     }
 
+    /// Deposits a bucket into an account.
+    pub fn deposit(&mut self, bid: Bid, account: Address) -> &mut Self {
+        self.add_instruction(Instruction::Deposit { bid, account })
+    }
+
+    /// Deposits each bucket into its designated account.
+    pub fn deposit_batch(&mut self, routes: Vec<(Bid, Address)>) -> &mut Self {
+        for (bid, account) in routes {
+            self.deposit(bid, account);
+        }
+        self
+    }
+
     /// Deposits everything into an account.
     pub fn deposit_all_buckets(&mut self, account: Address) -> &mut Self {
         self.add_instruction(Instruction::DepositAllBuckets { account })
     }
 
-    /// Builds a transaction.
-    pub fn build(&mut self, signers: Vec<Address>) -> Result<Transaction, BuildTransactionError> {
+    /// Deposits everything into an account, or into the fallback account if the deposit
+    /// fails.
+    pub fn deposit_all_buckets_or(&mut self, account: Address, fallback: Address) -> &mut Self {
+        self.add_instruction(Instruction::DepositAllBucketsOr { account, fallback })
+    }
+
+    /// Deposits everything of the given resource into an account.
+    pub fn deposit_all_of(&mut self, resource_address: Address, account: Address) -> &mut Self {
+        self.add_instruction(Instruction::DepositAllOf {
+            resource_address,
+            account,
+        })
+    }
+
+    /// Deposits everything left into an account, such as the change of a swap, and fails if
+    /// anything is left behind.
+    ///
+    /// Unlike `deposit_all_buckets`, the instruction returns what was deposited.
+    pub fn deposit_worktop_remainder(&mut self, account: Address) -> &mut Self {
+        self.add_instruction(Instruction::DepositWorktopRemainder { account })
+    }
+
+    /// Makes the transaction valid only from the given epoch onwards.
+    ///
+    /// The epoch is part of the signed instructions, and is checked before execution starts.
+    pub fn not_before(&mut self, epoch: u64) -> &mut Self {
+        self.add_instruction(Instruction::NotBefore { epoch })
+    }
+
+    /// Labels the instructions that follow, for readability of receipts.
+    pub fn label(&mut self, text: &str) -> &mut Self {
+        self.add_instruction(Instruction::Comment {
+            text: text.to_owned(),
+        })
+    }
+
+    /// Deletes a component, which must be owned by a signer and hold no resource.
+    pub fn delete_component(&mut self, component_address: Address) -> &mut Self {
+        self.add_instruction(Instruction::DeleteComponent { component_address })
+    }
+
+    /// Asserts that transaction context holds no resource.
+    pub fn assert_worktop_empty(&mut self) -> &mut Self {
+        self.add_instruction(Instruction::AssertWorktopEmpty)
+    }
+
+    /// Asserts that transaction context holds the given NFTs.
+    pub fn assert_worktop_contains_nfts(
+        &mut self,
+        ids: BTreeSet<u128>,
+        resource_address: Address,
+    ) -> &mut Self {
+        self.add_instruction(Instruction::AssertWorktopContainsNfts {
+            ids,
+            resource_address,
+        })
+    }
+
+    /// Returns the manifest of the instructions added so far.
+    pub fn manifest(&self) -> Result<TransactionManifest, BuildTransactionError> {
         if !self.errors.is_empty() {
             return Err(self.errors[0].clone());
         }
@@ -288,9 +455,30 @@ impl<'a, A: AbiProvider> TransactionBuilder<'a, A> {
         let mut v = Vec::new();
         v.extend(self.reservations.clone());
         v.extend(self.instructions.clone());
-        v.push(Instruction::End { signers });
 
-        Ok(Transaction { instructions: v })
+        Ok(TransactionManifest {
+            instructions: v,
+            blobs: self.blobs.clone(),
+        })
+    }
+
+    /// Builds a transaction.
+    pub fn build(&mut self, signers: Vec<Address>) -> Result<Transaction, BuildTransactionError> {
+        self.manifest()
+            .map(|manifest| Transaction::from_manifest(manifest, signers))
+    }
+
+    /// Builds a transaction and signs it with the given signers.
+    pub fn build_signed(
+        &mut self,
+        signers: &[&dyn Signer],
+    ) -> Result<Transaction, BuildTransactionError> {
+        let mut transaction = self.build(signers.iter().map(|s| s.public_key()).collect())?;
+
+        let hash = transaction.hash();
+        transaction.signatures = signers.iter().map(|s| s.sign(hash.as_ref())).collect();
+
+        Ok(transaction)
     }
 
     //===============================
@@ -307,12 +495,37 @@ impl<'a, A: AbiProvider> TransactionBuilder<'a, A> {
         })
     }
 
+    /// Publishes a package, with code from a previously added blob.
+    pub fn publish_package_blob(&mut self, code_hash: H256) -> &mut Self {
+        self.add_instruction(Instruction::PublishPackageFromBlob { code_hash })
+    }
+
     fn single_authority(badge: Address, permission: u16) -> HashMap<Address, u16> {
         let mut map = HashMap::new();
         map.insert(badge, permission);
         map
     }
 
+    /// Encodes resource metadata with its entries sorted by key, so that the same metadata
+    /// always produces the same transaction.
+    ///
+    /// The value is still encoded as a `HashMap`, which is what `new_resource` expects.
+    fn metadata_value(metadata: HashMap<String, String>) -> SmartValue {
+        let sorted: BTreeMap<String, String> = metadata.into_iter().collect();
+        let mut encoder = Encoder::with_type(Vec::new());
+        encoder.write_type(TYPE_HASH_MAP);
+        encoder.write_type(String::type_id());
+        encoder.write_type(String::type_id());
+        encoder.write_len(sorted.len());
+        for (key, value) in &sorted {
+            key.encode_value(&mut encoder);
+            value.encode_value(&mut encoder);
+        }
+        SmartValue {
+            encoded: encoder.into(),
+        }
+    }
+
     /// Creates a token resource with mutable supply.
     pub fn new_token_mutable(
         &mut self,
@@ -325,7 +538,7 @@ impl<'a, A: AbiProvider> TransactionBuilder<'a, A> {
             function: "new_resource".to_owned(),
             args: vec![
                 SmartValue::from(ResourceType::Fungible { divisibility: 18 }),
-                SmartValue::from(metadata),
+                Self::metadata_value(metadata),
                 SmartValue::from(MINTABLE | BURNABLE),
                 SmartValue::from(0u16),
                 SmartValue::from(Self::single_authority(
@@ -349,7 +562,7 @@ impl<'a, A: AbiProvider> TransactionBuilder<'a, A> {
             function: "new_resource".to_owned(),
             args: vec![
                 SmartValue::from(ResourceType::Fungible { divisibility: 18 }),
-                SmartValue::from(metadata),
+                Self::metadata_value(metadata),
                 SmartValue::from(0u16),
                 SmartValue::from(0u16),
                 SmartValue::from(HashMap::<Address, u16>::new()),
@@ -372,7 +585,7 @@ impl<'a, A: AbiProvider> TransactionBuilder<'a, A> {
             function: "new_resource".to_owned(),
             args: vec![
                 SmartValue::from(ResourceType::Fungible { divisibility: 0 }),
-                SmartValue::from(metadata),
+                Self::metadata_value(metadata),
                 SmartValue::from(MINTABLE | BURNABLE),
                 SmartValue::from(0u16),
                 SmartValue::from(Self::single_authority(
@@ -396,7 +609,7 @@ impl<'a, A: AbiProvider> TransactionBuilder<'a, A> {
             function: "new_resource".to_owned(),
             args: vec![
                 SmartValue::from(ResourceType::Fungible { divisibility: 0 }),
-                SmartValue::from(metadata),
+                Self::metadata_value(metadata),
                 SmartValue::from(0u16),
                 SmartValue::from(0u16),
                 SmartValue::from(HashMap::<Address, u16>::new()),
@@ -429,6 +642,36 @@ impl<'a, A: AbiProvider> TransactionBuilder<'a, A> {
         })
     }
 
+    /// Puts resource into transaction context, so that buckets for function or method
+    /// arguments can be taken from there without an account.
+    ///
+    /// The resource is minted if a mint badge is given, in which case the badge must be in
+    /// transaction context. Otherwise, only XRD can be staged, from the system faucet.
+    pub fn stage_to_worktop(
+        &mut self,
+        amount: Decimal,
+        resource_address: Address,
+        mint_badge_address: Option<Address>,
+    ) -> &mut Self {
+        match mint_badge_address {
+            Some(mint_badge_address) => self.mint(amount, resource_address, mint_badge_address),
+            None if resource_address == RADIX_TOKEN => {
+                self.add_instruction(Instruction::CallMethod {
+                    component_address: SYSTEM_COMPONENT,
+                    method: "free_xrd".to_owned(),
+                    args: vec![SmartValue::from(amount)],
+                })
+            }
+            None => {
+                self.errors
+                    .push(BuildTransactionError::MintBadgeNotProvided(
+                        resource_address,
+                    ));
+                self
+            }
+        }
+    }
+
     /// Creates an account.
     pub fn new_account(&mut self, key: Address) -> &mut Self {
         self.add_instruction(Instruction::CallFunction {
@@ -439,6 +682,27 @@ impl<'a, A: AbiProvider> TransactionBuilder<'a, A> {
         })
     }
 
+    /// Creates a liquidity pool seeded with two tokens from an account, by calling the
+    /// blueprint's `new` function with one bucket of each, and deposits whatever is returned,
+    /// e.g. LP tokens, back into the account.
+    pub fn create_liquidity_pool(
+        &mut self,
+        package_address: Address,
+        blueprint_name: &str,
+        account: Address,
+        token_a: ResourceAmount,
+        token_b: ResourceAmount,
+    ) -> &mut Self {
+        self.call_function(
+            package_address,
+            blueprint_name,
+            "new",
+            vec![token_a.to_string(), token_b.to_string()],
+            Some(account),
+        )
+        .deposit_all_buckets(account)
+    }
+
     /// Creates an account with resource taken from context.
     ///
     /// Note: need to make sure the context contains the required resource.
@@ -491,6 +755,76 @@ impl<'a, A: AbiProvider> TransactionBuilder<'a, A> {
         }
     }
 
+    /// Withdraws a number of whole units of a resource from an account, e.g. badges.
+    ///
+    /// The resource must be fungible with zero divisibility.
+    pub fn withdraw_units_from_account(
+        &mut self,
+        count: u64,
+        resource_address: Address,
+        account: Address,
+    ) -> &mut Self {
+        match self.abi_provider.resource_type(resource_address) {
+            Some(ResourceType::Fungible { divisibility: 0 }) => self.withdraw_from_account(
+                &ResourceAmount::Fungible {
+                    amount: count.into(),
+                    resource_address,
+                },
+                account,
+            ),
+            Some(_) => {
+                self.errors
+                    .push(BuildTransactionError::NotWholeUnitResource(
+                        resource_address,
+                    ));
+                self
+            }
+            None => {
+                self.errors
+                    .push(BuildTransactionError::UnknownResourceType(resource_address));
+                self
+            }
+        }
+    }
+
+    /// Creates a bucket ref backed by the vault of an account, without withdrawing the resource.
+    ///
+    /// The resource is locked in the account until the bucket ref is dropped.
+    pub fn create_proof_from_account(
+        &mut self,
+        amount: Decimal,
+        resource_address: Address,
+        account: Address,
+        rid: Rid,
+    ) -> &mut Self {
+        self.add_instruction(Instruction::BorrowFromAccount {
+            account,
+            amount,
+            resource_address,
+            to: rid,
+        })
+    }
+
+    /// Withdraws the NFTs with ids in the given range from an account.
+    ///
+    /// The account picks the matching ids from its vault, so ids that it doesn't hold are skipped.
+    pub fn withdraw_nfts_range_from_account(
+        &mut self,
+        ids: Range<u128>,
+        resource_address: Address,
+        account: Address,
+    ) -> &mut Self {
+        self.add_instruction(Instruction::CallMethod {
+            component_address: account,
+            method: "withdraw_nfts_range".to_owned(),
+            args: vec![
+                SmartValue::from(ids.start),
+                SmartValue::from(ids.end),
+                SmartValue::from(resource_address),
+            ],
+        })
+    }
+
     //===============================
     // private methods below
     //===============================
@@ -509,48 +843,129 @@ impl<'a, A: AbiProvider> TransactionBuilder<'a, A> {
     fn find_method_abi(
         abi: &abi::Blueprint,
         method: &str,
+        num_args: usize,
     ) -> Result<abi::Method, BuildTransactionError> {
-        abi.methods
+        let candidates: Vec<&abi::Method> =
+            abi.methods.iter().filter(|m| m.name == method).collect();
+
+        match candidates.len() {
+            0 => Err(BuildTransactionError::MethodNotFound(method.to_owned())),
+            1 => Ok(candidates[0].clone()),
+            _ => {
+                // Overloaded; resolve by the number of arguments provided.
+                let matched: Vec<&abi::Method> = candidates
+                    .into_iter()
+                    .filter(|m| m.inputs.len() == num_args)
+                    .collect();
+                match matched.len() {
+                    0 => Err(BuildTransactionError::NoMatchingOverload(
+                        method.to_owned(),
+                        num_args,
+                    )),
+                    1 => Ok(matched[0].clone()),
+                    _ => Err(BuildTransactionError::AmbiguousMethod(
+                        method.to_owned(),
+                        num_args,
+                    )),
+                }
+            }
+        }
+    }
+
+    /// Classifies a failure to export the ABI of a package or component.
+    fn abi_export_error(address: Address, error: RuntimeError) -> BuildTransactionError {
+        match error {
+            RuntimeError::ComponentNotFound(component_address) => {
+                BuildTransactionError::ComponentNotFound(component_address)
+            }
+            RuntimeError::InvalidData(e) => {
+                BuildTransactionError::AbiParseFailed(address, format!("{:?}", e))
+            }
+            e => BuildTransactionError::AbiReadFailed(address, e.to_string()),
+        }
+    }
+
+    /// Checks the arguments against the given types, on a scratch builder so that no
+    /// instruction is emitted, and collects the errors of all arguments.
+    fn validate_args(&self, types: &[Type], args: &[String]) -> Result<(), BuildTransactionError> {
+        let mut scratch = TransactionBuilder::new(self.abi_provider);
+        let errors: Vec<(usize, BuildArgsError)> = types
             .iter()
-            .find(|m| m.name == method)
-            .map(Clone::clone)
-            .ok_or_else(|| BuildTransactionError::MethodNotFound(method.to_owned()))
+            .enumerate()
+            .filter_map(|(i, t)| {
+                scratch
+                    .prepare_arg(i, t, args.get(i), None)
+                    .err()
+                    .map(|e| (i, e))
+            })
+            .collect();
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(BuildTransactionError::InvalidArgs(errors))
+        }
     }
 
+    /// Prepares the arguments of a call.
+    ///
+    /// Bucket and bucket ref arguments emit instructions as they are prepared, so these are
+    /// rolled back if any later argument fails, leaving the builder as it was.
     fn prepare_args(
         &mut self,
         types: &[Type],
         args: Vec<String>,
         account: Option<Address>,
     ) -> Result<Vec<SmartValue>, BuildArgsError> {
-        let mut encoded = Vec::new();
-
-        for (i, t) in types.iter().enumerate() {
-            let arg = args
-                .get(i)
-                .ok_or_else(|| BuildArgsError::MissingArgument(i, t.clone()))?;
-            let res = match t {
-                Type::Bool => self.prepare_basic_ty::<bool>(i, t, arg),
-                Type::I8 => self.prepare_basic_ty::<i8>(i, t, arg),
-                Type::I16 => self.prepare_basic_ty::<i16>(i, t, arg),
-                Type::I32 => self.prepare_basic_ty::<i32>(i, t, arg),
-                Type::I64 => self.prepare_basic_ty::<i64>(i, t, arg),
-                Type::I128 => self.prepare_basic_ty::<i128>(i, t, arg),
-                Type::U8 => self.prepare_basic_ty::<u8>(i, t, arg),
-                Type::U16 => self.prepare_basic_ty::<u16>(i, t, arg),
-                Type::U32 => self.prepare_basic_ty::<u32>(i, t, arg),
-                Type::U64 => self.prepare_basic_ty::<u64>(i, t, arg),
-                Type::U128 => self.prepare_basic_ty::<u128>(i, t, arg),
-                Type::String => self.prepare_basic_ty::<String>(i, t, arg),
-                Type::Custom { name, .. } => self.prepare_custom_ty(i, t, arg, name, account),
-                _ => Err(BuildArgsError::UnsupportedType(i, t.clone())),
-            };
-            encoded.push(res?);
+        let allocator = self.allocator.clone();
+        let reservations = self.reservations.len();
+        let instructions = self.instructions.len();
+        let errors = self.errors.len();
+
+        let result: Result<Vec<_>, _> = types
+            .iter()
+            .enumerate()
+            .map(|(i, t)| self.prepare_arg(i, t, args.get(i), account))
+            .collect();
+        if result.is_err() {
+            self.allocator = allocator;
+            self.reservations.truncate(reservations);
+            self.instructions.truncate(instructions);
+            self.errors.truncate(errors);
         }
+        result
+    }
 
-        Ok(encoded)
+    fn prepare_arg(
+        &mut self,
+        i: usize,
+        t: &Type,
+        arg: Option<&String>,
+        account: Option<Address>,
+    ) -> Result<SmartValue, BuildArgsError> {
+        let arg = arg.ok_or_else(|| BuildArgsError::MissingArgument(i, t.clone()))?;
+        match t {
+            Type::Bool => self.prepare_basic_ty::<bool>(i, t, arg),
+            Type::I8 => self.prepare_basic_ty::<i8>(i, t, arg),
+            Type::I16 => self.prepare_basic_ty::<i16>(i, t, arg),
+            Type::I32 => self.prepare_basic_ty::<i32>(i, t, arg),
+            Type::I64 => self.prepare_basic_ty::<i64>(i, t, arg),
+            Type::I128 => self.prepare_basic_ty::<i128>(i, t, arg),
+            Type::U8 => self.prepare_basic_ty::<u8>(i, t, arg),
+            Type::U16 => self.prepare_basic_ty::<u16>(i, t, arg),
+            Type::U32 => self.prepare_basic_ty::<u32>(i, t, arg),
+            Type::U64 => self.prepare_basic_ty::<u64>(i, t, arg),
+            Type::U128 => self.prepare_basic_ty::<u128>(i, t, arg),
+            Type::String => self.prepare_basic_ty::<String>(i, t, arg),
+            Type::Struct { fields, .. } => self.prepare_struct(i, t, arg, fields, account),
+            Type::Enum { variants, .. } => self.prepare_enum(i, t, arg, variants, account),
+            Type::Custom { name, .. } => self.prepare_custom_ty(i, t, arg, name, account),
+            _ => Err(BuildArgsError::UnsupportedType(i, t.clone())),
+        }
     }
 
+    /// Parses an argument from string, for both basic types and custom types that
+    /// implement `FromStr`, so that they fail in the same way.
     fn prepare_basic_ty<T>(
         &mut self,
         i: usize,
@@ -576,32 +991,14 @@ impl<'a, A: AbiProvider> TransactionBuilder<'a, A> {
         account: Option<Address>,
     ) -> Result<SmartValue, BuildArgsError> {
         match name {
-            SCRYPTO_NAME_DECIMAL => {
-                let value = arg
-                    .parse::<Decimal>()
-                    .map_err(|_| BuildArgsError::FailedToParse(i, ty.clone(), arg.to_owned()))?;
-                Ok(SmartValue::from(value))
-            }
-            SCRYPTO_NAME_BIG_DECIMAL => {
-                let value = arg
-                    .parse::<BigDecimal>()
-                    .map_err(|_| BuildArgsError::FailedToParse(i, ty.clone(), arg.to_owned()))?;
-                Ok(SmartValue::from(value))
-            }
-            SCRYPTO_NAME_ADDRESS => {
-                let value = arg
-                    .parse::<Address>()
-                    .map_err(|_| BuildArgsError::FailedToParse(i, ty.clone(), arg.to_owned()))?;
-                Ok(SmartValue::from(value))
-            }
-            SCRYPTO_NAME_H256 => {
-                let value = arg
-                    .parse::<H256>()
-                    .map_err(|_| BuildArgsError::FailedToParse(i, ty.clone(), arg.to_owned()))?;
-                Ok(SmartValue::from(value))
-            }
+            SCRYPTO_NAME_DECIMAL => self.prepare_basic_ty::<Decimal>(i, ty, arg),
+            SCRYPTO_NAME_BIG_DECIMAL => self.prepare_basic_ty::<BigDecimal>(i, ty, arg),
+            SCRYPTO_NAME_ADDRESS => self.prepare_basic_ty::<Address>(i, ty, arg),
+            SCRYPTO_NAME_H256 => self.prepare_basic_ty::<H256>(i, ty, arg),
+            SCRYPTO_NAME_U256 => self.prepare_basic_ty::<U256>(i, ty, arg),
+            SCRYPTO_NAME_I256 => self.prepare_basic_ty::<I256>(i, ty, arg),
             SCRYPTO_NAME_BID | SCRYPTO_NAME_BUCKET => {
-                let resource_spec = parse_resource_spec(i, ty, arg)?;
+                let resource_spec = self.parse_resource_spec(i, ty, arg)?;
 
                 if let Some(account) = account {
                     self.withdraw_from_account(&resource_spec, account);
@@ -618,7 +1015,7 @@ impl<'a, A: AbiProvider> TransactionBuilder<'a, A> {
                 Ok(SmartValue::from(created_bid.unwrap()))
             }
             SCRYPTO_NAME_RID | SCRYPTO_NAME_BUCKET_REF => {
-                let resource_spec = parse_resource_spec(i, ty, arg)?;
+                let resource_spec = self.parse_resource_spec(i, ty, arg)?;
                 if let Some(account) = account {
                     self.withdraw_from_account(&resource_spec, account);
                 }
@@ -636,9 +1033,335 @@ impl<'a, A: AbiProvider> TransactionBuilder<'a, A> {
             _ => Err(BuildArgsError::UnsupportedType(i, ty.clone())),
         }
     }
+
+    /// Parses a struct argument, written as `{field1=value1,field2=value2}` for named fields,
+    /// `{value1,value2}` for unnamed fields and `{}` for a unit struct.
+    ///
+    /// A bucket or bucket ref field is written like a standalone argument, e.g.
+    /// `{payment=100,resource_address}`.
+    fn prepare_struct(
+        &mut self,
+        i: usize,
+        ty: &Type,
+        arg: &str,
+        fields: &Fields,
+        account: Option<Address>,
+    ) -> Result<SmartValue, BuildArgsError> {
+        let parse_error = || BuildArgsError::FailedToParse(i, ty.clone(), arg.to_owned());
+        let values = arg
+            .strip_prefix('{')
+            .and_then(|s| s.strip_suffix('}'))
+            .map(split_fields)
+            .ok_or_else(parse_error)?;
+
+        let (fields_type, ordered): (u8, Vec<(&Type, String)>) = match fields {
+            Fields::Named { named } => {
+                let types: BTreeMap<&str, &Type> =
+                    named.iter().map(|(name, t)| (name.as_str(), t)).collect();
+                let mut by_name: BTreeMap<&str, String> = BTreeMap::new();
+                let mut last: Option<&str> = None;
+                for value in &values {
+                    match value.split_once('=') {
+                        Some((name, value)) => {
+                            let name = name.trim();
+                            if by_name.insert(name, value.trim().to_owned()).is_some() {
+                                return Err(parse_error());
+                            }
+                            last = Some(name);
+                        }
+                        None => {
+                            // the rest of a bucket or bucket ref field, e.g. `payment=100,address`
+                            let name = last
+                                .filter(|name| types.get(name).is_some_and(|t| is_resource_type(t)))
+                                .ok_or_else(parse_error)?;
+                            let joined = by_name.get_mut(name).unwrap();
+                            joined.push(',');
+                            joined.push_str(value.trim());
+                        }
+                    }
+                }
+                if by_name.len() != named.len() {
+                    return Err(parse_error());
+                }
+                let ordered = named
+                    .iter()
+                    .map(|(name, t)| by_name.remove(name.as_str()).map(|v| (t, v)))
+                    .collect::<Option<Vec<_>>>()
+                    .ok_or_else(parse_error)?;
+                (TYPE_FIELDS_NAMED, ordered)
+            }
+            Fields::Unnamed { unnamed } => {
+                let types: Vec<&Type> = unnamed.iter().collect();
+                let values = join_resource_fields(values, &types).ok_or_else(parse_error)?;
+                let ordered = unnamed
+                    .iter()
+                    .zip(values.into_iter().map(|v| v.trim().to_owned()))
+                    .collect();
+                (TYPE_FIELDS_UNNAMED, ordered)
+            }
+            Fields::Unit => {
+                if !values.is_empty() {
+                    return Err(parse_error());
+                }
+                (TYPE_FIELDS_UNIT, Vec::new())
+            }
+        };
+
+        let mut encoder = Encoder::with_type(Vec::new());
+        encoder.write_type(TYPE_STRUCT);
+        encoder.write_type(fields_type);
+        if fields_type != TYPE_FIELDS_UNIT {
+            encoder.write_len(ordered.len());
+            for (t, value) in ordered {
+                let value = self.prepare_arg(i, t, Some(&value), account)?;
+                encoder.write_slice(&value.encoded);
+            }
+        }
+        Ok(SmartValue {
+            encoded: encoder.into(),
+        })
+    }
+
+    /// Parses an enum argument, written as `Variant` or `Variant(field1,field2)` with the
+    /// fields given in declaration order.
+    ///
+    /// A bucket or bucket ref field is written like a standalone argument, e.g.
+    /// `Paid(100,resource_address)`.
+    fn prepare_enum(
+        &mut self,
+        i: usize,
+        ty: &Type,
+        arg: &str,
+        variants: &[Variant],
+        account: Option<Address>,
+    ) -> Result<SmartValue, BuildArgsError> {
+        let parse_error = || BuildArgsError::FailedToParse(i, ty.clone(), arg.to_owned());
+        let (name, fields) = match arg.strip_suffix(')').and_then(|s| s.split_once('(')) {
+            Some((name, fields)) => (name, split_fields(fields)),
+            None => (arg, Vec::new()),
+        };
+        let (index, variant) = variants
+            .iter()
+            .enumerate()
+            .find(|(_, v)| v.name == name)
+            .ok_or_else(parse_error)?;
+        let (fields_type, types): (u8, Vec<&Type>) = match &variant.fields {
+            Fields::Named { named } => (TYPE_FIELDS_NAMED, named.iter().map(|(_, t)| t).collect()),
+            Fields::Unnamed { unnamed } => (TYPE_FIELDS_UNNAMED, unnamed.iter().collect()),
+            Fields::Unit => (TYPE_FIELDS_UNIT, Vec::new()),
+        };
+        let fields = join_resource_fields(fields, &types).ok_or_else(parse_error)?;
+
+        let mut encoder = Encoder::with_type(Vec::new());
+        encoder.write_type(TYPE_ENUM);
+        encoder.write_u8(index as u8);
+        encoder.write_type(fields_type);
+        if fields_type != TYPE_FIELDS_UNIT {
+            encoder.write_len(types.len());
+            for (t, field) in types.into_iter().zip(fields.iter()) {
+                let value = self.prepare_arg(i, t, Some(field), account)?;
+                encoder.write_slice(&value.encoded);
+            }
+        }
+        Ok(SmartValue {
+            encoded: encoder.into(),
+        })
+    }
+
+    /// Parses a resource amount, checking it against the resource type when it's known.
+    fn parse_resource_spec(
+        &self,
+        i: usize,
+        ty: &Type,
+        arg: &str,
+    ) -> Result<ResourceAmount, BuildArgsError> {
+        let resource_spec = ResourceAmount::from_str(arg)
+            .map_err(|_| BuildArgsError::FailedToParse(i, ty.clone(), arg.to_owned()))?;
+
+        if let Some(resource_type) = self
+            .abi_provider
+            .resource_type(resource_spec.resource_address())
+        {
+            if resource_type.is_fungible()
+                && matches!(resource_spec, ResourceAmount::NonFungible { .. })
+            {
+                return Err(BuildArgsError::ResourceTypeMismatch(i, arg.to_owned()));
+            }
+            if resource_spec.check_resource_type(resource_type).is_err() {
+                return Err(BuildArgsError::FractionalNonFungible(i, arg.to_owned()));
+            }
+        }
+        Ok(resource_spec)
+    }
+}
+
+/// Returns whether a type is a bucket or a bucket ref, whose argument spans several fields.
+fn is_resource_type(t: &Type) -> bool {
+    match t {
+        Type::Custom { name, .. } => matches!(
+            name.as_str(),
+            SCRYPTO_NAME_BID | SCRYPTO_NAME_BUCKET | SCRYPTO_NAME_RID | SCRYPTO_NAME_BUCKET_REF
+        ),
+        _ => false,
+    }
+}
+
+/// Matches the split fields of an enum or struct argument with their types, joining the parts
+/// of each bucket or bucket ref field up to and including its resource address.
+fn join_resource_fields(fields: Vec<String>, types: &[&Type]) -> Option<Vec<String>> {
+    let mut parts = fields.into_iter();
+    let mut result = Vec::new();
+    for t in types {
+        if is_resource_type(t) {
+            let mut joined = Vec::new();
+            loop {
+                let part = parts.next()?;
+                let is_address = part.trim().parse::<Address>().is_ok();
+                joined.push(part);
+                if is_address {
+                    break;
+                }
+            }
+            result.push(joined.join(","));
+        } else {
+            result.push(parts.next()?);
+        }
+    }
+    match parts.next() {
+        Some(_) => None,
+        None => Some(result),
+    }
 }
 
-fn parse_resource_spec(i: usize, ty: &Type, arg: &str) -> Result<ResourceAmount, BuildArgsError> {
-    ResourceAmount::from_str(arg)
-        .map_err(|_| BuildArgsError::FailedToParse(i, ty.clone(), arg.to_owned()))
+/// Splits the fields of an enum or struct argument by the commas outside of any nested value.
+fn split_fields(fields: &str) -> Vec<String> {
+    if fields.is_empty() {
+        return Vec::new();
+    }
+    let mut result = Vec::new();
+    let mut depth = 0;
+    let mut start = 0;
+    for (pos, c) in fields.char_indices() {
+        match c {
+            '(' | '{' => depth += 1,
+            ')' | '}' => depth -= 1,
+            ',' if depth == 0 => {
+                result.push(fields[start..pos].to_owned());
+                start = pos + 1;
+            }
+            _ => {}
+        }
+    }
+    result.push(fields[start..].to_owned());
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_resource_amount_merge() {
+        let fungible = |amount: i32, resource_address| ResourceAmount::Fungible {
+            amount: amount.into(),
+            resource_address,
+        };
+        let non_fungible = |ids: &[u128], resource_address| ResourceAmount::NonFungible {
+            ids: ids.iter().cloned().collect(),
+            resource_address,
+        };
+
+        assert_eq!(
+            fungible(1, RADIX_TOKEN).try_merge(&fungible(2, RADIX_TOKEN)),
+            Ok(fungible(3, RADIX_TOKEN))
+        );
+        assert_eq!(
+            non_fungible(&[1, 2], RADIX_TOKEN).try_merge(&non_fungible(&[2, 3], RADIX_TOKEN)),
+            Ok(non_fungible(&[1, 2, 3], RADIX_TOKEN))
+        );
+        assert_eq!(
+            fungible(1, RADIX_TOKEN).try_merge(&fungible(1, Address::ResourceDef([1u8; 26]))),
+            Err(MergeError::ResourceAddressMismatch)
+        );
+        assert_eq!(
+            fungible(1, RADIX_TOKEN).try_merge(&non_fungible(&[1], RADIX_TOKEN)),
+            Err(MergeError::ResourceTypeMismatch)
+        );
+    }
+
+    #[test]
+    fn test_resource_amount_arithmetic() {
+        let withdrawal = ResourceAmount::Fungible {
+            amount: 40.into(),
+            resource_address: RADIX_TOKEN,
+        };
+
+        // Scaling a withdrawal, e.g. for a 2.5x position
+        let scaled = withdrawal.clone() * Decimal::from_str("2.5").unwrap();
+        assert_eq!(scaled.amount(), 100.into());
+        assert_eq!(scaled.resource_address(), RADIX_TOKEN);
+        assert_eq!((withdrawal.clone() + scaled).amount(), 140.into());
+        assert_eq!((withdrawal.clone() * 3).amount(), 120.into());
+
+        let nfts = ResourceAmount::NonFungible {
+            ids: BTreeSet::from([1, 2]),
+            resource_address: RADIX_TOKEN,
+        };
+        assert_eq!(
+            nfts.try_scale(2.into()),
+            Err(MergeError::ResourceTypeMismatch)
+        );
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_resource_amount_add_mismatched_resources() {
+        let _ = ResourceAmount::Fungible {
+            amount: 40.into(),
+            resource_address: RADIX_TOKEN,
+        } + ResourceAmount::Fungible {
+            amount: 1.into(),
+            resource_address: Address::ResourceDef([1u8; 26]),
+        };
+    }
+
+    #[test]
+    fn test_resource_amount_display_roundtrip() {
+        let mut seed = 0x2545f4914f6cdd1du64;
+        let mut next = move || {
+            seed ^= seed << 13;
+            seed ^= seed >> 7;
+            seed ^= seed << 17;
+            seed
+        };
+
+        for _ in 0..1000 {
+            let mut address = [0u8; 26];
+            address[..8].copy_from_slice(&next().to_le_bytes());
+            let resource_address = Address::ResourceDef(address);
+
+            let amount = if next() % 2 == 0 {
+                ResourceAmount::Fungible {
+                    amount: Decimal::from_smallest_units((next() as i128) << 32 | next() as i128),
+                    resource_address,
+                }
+            } else {
+                ResourceAmount::NonFungible {
+                    ids: (0..1 + next() % 5)
+                        .map(|_| (next() as u128) << 64 | next() as u128)
+                        .collect(),
+                    resource_address,
+                }
+            };
+
+            let rendered = amount.to_string();
+            assert_eq!(ResourceAmount::from_str(&rendered).unwrap(), amount);
+        }
+
+        let amount = ResourceAmount::NonFungible {
+            ids: BTreeSet::from([2, 1]),
+            resource_address: RADIX_TOKEN,
+        };
+        assert_eq!(amount.to_string(), format!("#1,#2,{}", RADIX_TOKEN));
+    }
 }
@@ -1,7 +1,12 @@
 use sbor::describe::Type;
+use sbor::DecodeError;
+use scrypto::rust::fmt;
 use scrypto::rust::string::String;
+use scrypto::rust::vec::Vec;
 use scrypto::types::*;
 
+use crate::utils::*;
+
 /// Represents an error when parsing arguments.
 #[derive(Debug, Clone)]
 pub enum BuildArgsError {
@@ -13,6 +18,47 @@ pub enum BuildArgsError {
 
     /// Failure when parsing an argument.
     FailedToParse(usize, Type, String),
+
+    /// The resource amount doesn't apply to the type of the resource, e.g. NFT ids for a token.
+    ResourceTypeMismatch(usize, String),
+
+    /// The resource amount asks for a fraction of an NFT.
+    FractionalNonFungible(usize, String),
+}
+
+impl fmt::Display for BuildArgsError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            BuildArgsError::MissingArgument(i, ty) => {
+                write!(f, "Missing argument #{} of type {}", i, render_type(ty))
+            }
+            BuildArgsError::UnsupportedType(i, ty) => {
+                write!(f, "Unsupported type {} of argument #{}", render_type(ty), i)
+            }
+            BuildArgsError::FailedToParse(i, ty, arg) => write!(
+                f,
+                "Failed to parse argument #{} as {}: {}",
+                i,
+                render_type(ty),
+                arg
+            ),
+            BuildArgsError::ResourceTypeMismatch(i, arg) => write!(
+                f,
+                "Argument #{} doesn't match the resource type: {}",
+                i, arg
+            ),
+            BuildArgsError::FractionalNonFungible(i, arg) => {
+                write!(f, "Argument #{} asks for a fraction of an NFT: {}", i, arg)
+            }
+        }
+    }
+}
+
+/// Represents an error when a value does not match the expected type.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TypeMismatch {
+    pub expected: Type,
+    pub actual: Type,
 }
 
 /// Represents an error when building a transaction.
@@ -24,15 +70,156 @@ pub enum BuildTransactionError {
     /// The given component method does not exist.
     MethodNotFound(String),
 
+    /// The given component method is overloaded, and more than one overload takes the number of arguments provided.
+    AmbiguousMethod(String, usize),
+
+    /// The given component method is overloaded, and no overload takes the number of arguments provided.
+    NoMatchingOverload(String, usize),
+
     /// The provided arguments do not match ABI.
     FailedToBuildArgs(BuildArgsError),
 
-    /// Failed to export the ABI of a function.
-    FailedToExportFunctionAbi(Address, String, String),
+    /// The component whose method is called does not exist.
+    ComponentNotFound(Address),
 
-    /// Failed to export the ABI of a method.
-    FailedToExportMethodAbi(Address, String),
+    /// The ABI of a package or component could not be read, with the cause, e.g. the package
+    /// does not exist or its ABI export fails to run.
+    AbiReadFailed(Address, String),
+
+    /// The ABI exported by a package or component could not be parsed, with the cause.
+    AbiParseFailed(Address, String),
 
     /// Account is required but not provided.
     AccountNotProvided,
+
+    /// A mint badge is required to stage the given resource, but not provided.
+    MintBadgeNotProvided(Address),
+
+    /// The resource is counted in units, but is not fungible with zero divisibility.
+    NotWholeUnitResource(Address),
+
+    /// The type of the resource is unknown, so amounts of it can't be validated.
+    UnknownResourceType(Address),
+
+    /// Failed to resolve the package and blueprint of a component.
+    FailedToResolveBlueprint(Address),
+
+    /// Some of the provided arguments do not match ABI, keyed by argument index.
+    InvalidArgs(Vec<(usize, BuildArgsError)>),
+}
+
+impl fmt::Display for BuildTransactionError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            BuildTransactionError::FunctionNotFound(function) => {
+                write!(f, "Function not found: {}", function)
+            }
+            BuildTransactionError::MethodNotFound(method) => {
+                write!(f, "Method not found: {}", method)
+            }
+            BuildTransactionError::AmbiguousMethod(method, num_args) => write!(
+                f,
+                "Ambiguous method: {} with {} argument(s)",
+                method, num_args
+            ),
+            BuildTransactionError::NoMatchingOverload(method, num_args) => write!(
+                f,
+                "No overload of method {} takes {} argument(s)",
+                method, num_args
+            ),
+            BuildTransactionError::FailedToBuildArgs(e) => write!(f, "{}", e),
+            BuildTransactionError::ComponentNotFound(component_address) => {
+                write!(f, "Component not found: {}", component_address)
+            }
+            BuildTransactionError::AbiReadFailed(address, cause) => {
+                write!(f, "Failed to read ABI of {}: {}", address, cause)
+            }
+            BuildTransactionError::AbiParseFailed(address, cause) => {
+                write!(f, "Failed to parse ABI of {}: {}", address, cause)
+            }
+            BuildTransactionError::AccountNotProvided => write!(f, "Account not provided"),
+            BuildTransactionError::MintBadgeNotProvided(resource_address) => {
+                write!(
+                    f,
+                    "Mint badge not provided for resource: {}",
+                    resource_address
+                )
+            }
+            BuildTransactionError::NotWholeUnitResource(resource_address) => write!(
+                f,
+                "Resource can't be counted in whole units: {}",
+                resource_address
+            ),
+            BuildTransactionError::UnknownResourceType(resource_address) => {
+                write!(f, "Unknown resource type: {}", resource_address)
+            }
+            BuildTransactionError::InvalidArgs(errors) => {
+                for (i, (_, e)) in errors.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, "; ")?;
+                    }
+                    write!(f, "{}", e)?;
+                }
+                Ok(())
+            }
+            BuildTransactionError::FailedToResolveBlueprint(component_address) => write!(
+                f,
+                "Failed to resolve blueprint of component: {}",
+                component_address
+            ),
+        }
+    }
+}
+
+/// Represents an error when validating a transaction.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TransactionValidationError {
+    /// The transaction contains more instructions than allowed.
+    TooManyInstructions { count: usize, limit: usize },
+
+    /// The signature does not match the transaction payload.
+    InvalidSignature(Address),
+
+    /// A signer of the transaction has not signed it.
+    MissingSignature(Address),
+
+    /// The referenced blob is not attached to the transaction.
+    BlobNotFound(H256),
+
+    /// The last instruction is not `End`.
+    MissingEndInstruction,
+
+    /// An `End` instruction appears before the last instruction, at the given index.
+    UnexpectedEndInstruction(usize),
+
+    /// A bucket or bucket ref is declared after the first non-declaring instruction, at the given index.
+    ReservationAfterUse(usize),
+
+    /// The instruction at the given index references an undeclared bucket.
+    UndeclaredBucket(usize, Bid),
+
+    /// The instruction at the given index references an undeclared bucket ref.
+    UndeclaredBucketRef(usize, Rid),
+
+    /// The instruction at the given index has an argument that can't be decoded.
+    InvalidArgument(usize),
+
+    /// The transaction is submitted before the epoch it's valid from.
+    NotYetValid { not_before: u64, current: u64 },
+}
+
+/// Represents an error when reading the return value of an instruction from a receipt.
+#[derive(Debug, Clone)]
+pub enum ReturnError {
+    /// There is no instruction at the given index.
+    IndexOutOfRange(usize),
+
+    /// The instruction at the given index failed.
+    InstructionFailed(usize),
+
+    /// The instruction at the given index returned nothing.
+    NoReturnValue(usize),
+
+    /// The return value can't be decoded into the requested type.
+    DecodeError(DecodeError),
 }
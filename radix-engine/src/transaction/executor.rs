@@ -1,5 +1,12 @@
+use core::time::Duration;
+use sbor::rust::boxed::Box;
+use sbor::*;
 use scrypto::abi;
 use scrypto::args;
+use scrypto::kernel::*;
+use scrypto::rust::collections::*;
+use scrypto::rust::rc::Rc;
+use scrypto::rust::string::String;
 use scrypto::rust::string::ToString;
 use scrypto::rust::vec;
 use scrypto::rust::vec::Vec;
@@ -10,18 +17,69 @@ use crate::engine::*;
 use crate::ledger::*;
 use crate::model::*;
 use crate::transaction::*;
+use crate::utils::*;
+
+/// The default maximum number of distinct resources in transaction context.
+pub const DEFAULT_MAX_WORKTOP_RESOURCES: usize = 256;
 
 /// An executor that runs transactions.
 pub struct TransactionExecutor<'l, L: Ledger> {
-    ledger: &'l mut L,
+    ledger: LedgerRef<'l, L>,
     current_epoch: u64,
     nonce: u64,
+    max_instructions: Option<usize>,
+    max_log_size: Option<usize>,
+    max_worktop_resources: Option<usize>,
+    max_execution_time: Option<Duration>,
+    tracer: Option<Tracer>,
+    clock: Option<Box<dyn Fn() -> u64>>,
+    recorded: Option<Vec<AppliedTransaction>>,
+    require_signatures: bool,
+    keys: HashMap<Address, [u8; 32]>,
+    deposit_hooks: HashMap<(Address, String), bool>,
 }
 
 /// Represents an error when executing the transaction.
 #[derive(Debug)]
 pub enum TransactionExecutionError {
     MissingEndInstruction,
+    ValidationError(TransactionValidationError),
+    ReplayDiverged(u64),
+    ReadOnlyLedger,
+    /// The transaction failed while previewing auth; the receipt tells why.
+    PreviewFailed(Box<Receipt>),
+}
+
+/// A transaction applied to the ledger, with the execution context required to reproduce it.
+#[derive(Debug, Clone, PartialEq, Eq, TypeId, Encode, Decode)]
+pub struct AppliedTransaction {
+    pub epoch: u64,
+    pub time: u64,
+    pub nonce: u64,
+    pub transaction: Transaction,
+    /// The state root of the ledger once the transaction was applied.
+    pub state_root: H256,
+}
+
+/// An account created at genesis, funded with XRD.
+///
+/// It holds the private key of the account, so it can sign transactions run by any executor.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GenesisAccount {
+    pub key: Address,
+    pub secret: [u8; 32],
+    pub address: Address,
+    pub balance: Decimal,
+}
+
+impl Signer for GenesisAccount {
+    fn public_key(&self) -> Address {
+        self.key
+    }
+
+    fn sign(&self, payload: &[u8]) -> Signature {
+        ed25519_sign(&self.secret, payload)
+    }
 }
 
 impl<'l, L: Ledger> AbiProvider for TransactionExecutor<'l, L> {
@@ -33,6 +91,7 @@ impl<'l, L: Ledger> AbiProvider for TransactionExecutor<'l, L> {
     ) -> Result<abi::Blueprint, RuntimeError> {
         let p = self
             .ledger
+            .get()
             .get_package(package_address)
             .ok_or(RuntimeError::PackageNotFound(package_address))?;
 
@@ -48,30 +107,123 @@ impl<'l, L: Ledger> AbiProvider for TransactionExecutor<'l, L> {
     ) -> Result<abi::Blueprint, RuntimeError> {
         let c = self
             .ledger
+            .get()
             .get_component(component_address)
             .ok_or(RuntimeError::ComponentNotFound(component_address))?;
         let p = self
             .ledger
+            .get()
             .get_package(c.package_address())
             .ok_or(RuntimeError::PackageNotFound(c.package_address()))?;
         BasicAbiProvider::new()
             .with_package(c.package_address(), p.code().to_vec())
             .export_abi(c.package_address(), c.blueprint_name(), trace)
     }
+
+    fn export_resource_behavior(
+        &self,
+        resource_address: Address,
+    ) -> Result<ResourceBehavior, RuntimeError> {
+        self.ledger
+            .get()
+            .get_resource_def(resource_address)
+            .map(|r| ResourceBehavior::from_flags(r.flags()))
+            .ok_or(RuntimeError::ResourceDefNotFound(resource_address))
+    }
+
+    fn resource_type(&self, resource_address: Address) -> Option<ResourceType> {
+        self.ledger
+            .get()
+            .get_resource_def(resource_address)
+            .map(|r| r.resource_type())
+    }
 }
 
 impl<'l, L: Ledger> TransactionExecutor<'l, L> {
     pub fn new(ledger: &'l mut L, current_epoch: u64, nonce: u64) -> Self {
+        Self::with_ledger_ref(LedgerRef::Mutable(ledger), current_epoch, nonce)
+    }
+
+    /// Creates an executor which only borrows the ledger for reading.
+    ///
+    /// It supports queries, e.g. `balance_of`, and `preview_auth`, but fails to run any
+    /// transaction.
+    pub fn new_read_only(ledger: &'l L, current_epoch: u64, nonce: u64) -> Self {
+        Self::with_ledger_ref(LedgerRef::ReadOnly(ledger), current_epoch, nonce)
+    }
+
+    fn with_ledger_ref(ledger: LedgerRef<'l, L>, current_epoch: u64, nonce: u64) -> Self {
         Self {
             ledger,
             current_epoch,
             nonce,
+            max_instructions: None,
+            max_log_size: None,
+            max_worktop_resources: Some(DEFAULT_MAX_WORKTOP_RESOURCES),
+            max_execution_time: None,
+            tracer: None,
+            clock: None,
+            recorded: None,
+            require_signatures: false,
+            keys: HashMap::new(),
+            deposit_hooks: HashMap::new(),
         }
     }
 
+    /// Re-applies recorded transactions to a ledger, e.g. a freshly bootstrapped one.
+    ///
+    /// Fails if a transaction doesn't succeed or leaves a different state root than recorded.
+    pub fn replay(
+        ledger: &'l mut L,
+        transactions: &[AppliedTransaction],
+    ) -> Result<(), TransactionExecutionError> {
+        let mut executor = Self::new(ledger, 0, 0);
+        for applied in transactions {
+            executor.current_epoch = applied.epoch;
+            let time = applied.time;
+            executor.clock = Some(Box::new(move || time));
+            executor.nonce = applied.nonce;
+            let receipt = executor.run(applied.transaction.clone(), false)?;
+            if !receipt.success || executor.ledger().state_root() != applied.state_root {
+                return Err(TransactionExecutionError::ReplayDiverged(applied.nonce));
+            }
+        }
+        Ok(())
+    }
+
+    /// Enables recording of transactions applied to the ledger.
+    pub fn with_recording(mut self) -> Self {
+        self.recorded = Some(Vec::new());
+        self
+    }
+
+    /// Returns the transactions applied since recording was enabled.
+    pub fn recorded(&self) -> &[AppliedTransaction] {
+        self.recorded.as_deref().unwrap_or(&[])
+    }
+
+    /// Sets a callback which receives execution events, e.g. for building a step debugger.
+    pub fn with_tracer(mut self, tracer: Box<dyn Fn(&TraceEvent)>) -> Self {
+        self.tracer = Some(Rc::from(tracer));
+        self
+    }
+
+    /// Sets a clock which blueprints read through `Context::current_time()`.
+    ///
+    /// The clock is sampled once at the start of each transaction; without one, the time is `0`.
+    pub fn with_custom_clock(mut self, clock: Box<dyn Fn() -> u64>) -> Self {
+        self.clock = Some(clock);
+        self
+    }
+
     /// Returns the underlying ledger.
     pub fn ledger(&self) -> &L {
-        self.ledger
+        self.ledger.get()
+    }
+
+    /// Returns whether this executor only has read access to the ledger.
+    pub fn is_read_only(&self) -> bool {
+        matches!(self.ledger, LedgerRef::ReadOnly(_))
     }
 
     /// Returns the current epoch.
@@ -94,12 +246,117 @@ impl<'l, L: Ledger> TransactionExecutor<'l, L> {
         self.nonce
     }
 
-    /// Generates a new public key.
+    /// Returns the maximum number of instructions allowed per transaction.
+    pub fn max_instructions(&self) -> Option<usize> {
+        self.max_instructions
+    }
+
+    /// Sets the maximum number of instructions allowed per transaction.
+    pub fn set_max_instructions(&mut self, max_instructions: Option<usize>) {
+        self.max_instructions = max_instructions;
+    }
+
+    /// Returns whether every signer of a transaction must have signed it.
+    pub fn require_signatures(&self) -> bool {
+        self.require_signatures
+    }
+
+    /// Sets whether every signer of a transaction must have signed it, which is off by default.
+    ///
+    /// Even when off, attached signatures are verified, and once any signature is attached
+    /// every signer must have signed.
+    pub fn set_require_signatures(&mut self, require_signatures: bool) {
+        self.require_signatures = require_signatures;
+    }
+
+    /// Returns the maximum total size of log messages allowed per transaction, in bytes.
+    pub fn max_log_size(&self) -> Option<usize> {
+        self.max_log_size
+    }
+
+    /// Sets the maximum total size of log messages allowed per transaction, in bytes.
+    ///
+    /// A transaction that logs beyond the limit fails with `RuntimeError::LogLimitExceeded`.
+    pub fn set_max_log_size(&mut self, max_log_size: Option<usize>) {
+        self.max_log_size = max_log_size;
+    }
+
+    /// Returns the maximum number of distinct resources transaction context may hold.
+    pub fn max_worktop_resources(&self) -> Option<usize> {
+        self.max_worktop_resources
+    }
+
+    /// Sets the maximum number of distinct resources transaction context may hold.
+    ///
+    /// A transaction that exceeds the limit fails with
+    /// `RuntimeError::WorktopResourceLimitExceeded`.
+    pub fn set_max_worktop_resources(&mut self, max_worktop_resources: Option<usize>) {
+        self.max_worktop_resources = max_worktop_resources;
+    }
+
+    /// Returns the maximum wall-clock time a transaction may run for.
+    pub fn max_execution_time(&self) -> Option<Duration> {
+        self.max_execution_time
+    }
+
+    /// Sets the maximum wall-clock time a transaction may run for, e.g. to keep a blueprint
+    /// stuck in a loop from hanging a test suite.
+    ///
+    /// A transaction that runs for longer fails with `RuntimeError::Timeout`. The limit is
+    /// checked on every loop iteration in blueprint code, and is ignored without `std`.
+    pub fn set_max_execution_time(&mut self, max_execution_time: Option<Duration>) {
+        self.max_execution_time = max_execution_time;
+    }
+
+    /// Generates a new key pair and returns its public key.
+    ///
+    /// The executor keeps the private key, so that `sign` and `sign_and_run` can sign for it.
+    /// The private key comes from the OS random number generator. Without `std`, it's derived
+    /// from the nonce instead, which anyone can recompute, so such keys are only fit for tests.
     pub fn new_public_key(&mut self) -> Address {
-        let mut raw = [0u8; 33];
-        raw[1..].copy_from_slice(sha256(self.nonce.to_string()).as_ref());
+        let secret = self.new_secret_key();
         self.nonce += 1;
-        Address::PublicKey(raw)
+        let key = ed25519_public_key(&secret);
+        self.keys.insert(key, secret);
+        key
+    }
+
+    #[cfg(not(feature = "alloc"))]
+    fn new_secret_key(&self) -> [u8; 32] {
+        let mut secret = [0u8; 32];
+        getrandom::getrandom(&mut secret).expect("Failed to generate a private key");
+        secret
+    }
+
+    #[cfg(feature = "alloc")]
+    fn new_secret_key(&self) -> [u8; 32] {
+        let mut secret = [0u8; 32];
+        secret.copy_from_slice(sha256(self.nonce.to_string()).as_ref());
+        secret
+    }
+
+    /// Signs a transaction for each of its signers whose key was generated by this executor
+    /// and who hasn't signed it yet, e.g. to run it on another executor.
+    pub fn sign(&self, transaction: &mut Transaction) {
+        let signers = match transaction.instructions().last() {
+            Some(Instruction::End { signers }) => signers.clone(),
+            _ => return,
+        };
+        let hash = transaction.hash();
+        for signer in signers {
+            if transaction
+                .signatures
+                .iter()
+                .any(|sig| sig.signer == signer)
+            {
+                continue;
+            }
+            if let Some(secret) = self.keys.get(&signer) {
+                transaction
+                    .signatures
+                    .push(ed25519_sign(secret, hash.as_ref()));
+            }
+        }
     }
 
     /// Creates an account with 1,000,000 XRD in balance.
@@ -124,6 +381,188 @@ impl<'l, L: Ledger> TransactionExecutor<'l, L> {
         .unwrap()
     }
 
+    /// Creates `n` accounts, each holding `starting_balance` XRD.
+    ///
+    /// Each account takes two nonces, one for its key and one for its funding transaction.
+    /// The executor keeps the private keys, so `sign_and_run` signs for the accounts, and
+    /// each `GenesisAccount` is a `Signer` for transactions run elsewhere.
+    pub fn new_genesis_accounts(
+        &mut self,
+        n: usize,
+        starting_balance: Decimal,
+    ) -> Vec<GenesisAccount> {
+        (0..n)
+            .map(|_| {
+                let key = self.new_public_key();
+                let receipt = self
+                    .run(
+                        TransactionBuilder::new(self)
+                            .call_method(
+                                SYSTEM_COMPONENT,
+                                "free_xrd",
+                                vec![starting_balance.to_string()],
+                                None,
+                            )
+                            .new_account_with_resource(key, starting_balance, RADIX_TOKEN)
+                            .build(Vec::new())
+                            .unwrap(),
+                        false,
+                    )
+                    .unwrap();
+                if !receipt.success {
+                    #[cfg(not(feature = "alloc"))]
+                    println!("{:?}", receipt);
+                    panic!("Failed to create genesis account. See receipt above.");
+                }
+                GenesisAccount {
+                    key,
+                    secret: self.keys[&key],
+                    address: receipt.component(0).unwrap(),
+                    balance: starting_balance,
+                }
+            })
+            .collect()
+    }
+
+    /// Creates a fixed-supply token and deposits the whole supply into an account.
+    ///
+    /// Returns the address of the new resource definition.
+    pub fn fund_account(
+        &mut self,
+        account: Address,
+        amount: Decimal,
+        metadata: HashMap<String, String>,
+    ) -> Address {
+        let receipt = self
+            .run(
+                TransactionBuilder::new(self)
+                    .new_token_fixed(metadata, amount)
+                    .deposit_all_buckets(account)
+                    .build(Vec::new())
+                    .unwrap(),
+                false,
+            )
+            .unwrap();
+
+        if !receipt.success {
+            #[cfg(not(feature = "alloc"))]
+            println!("{:?}", receipt);
+            panic!("Failed to fund account. See receipt above.");
+        } else {
+            receipt.resource_def(0).unwrap()
+        }
+    }
+
+    /// Creates a fixed-supply badge and deposits the whole supply into an account.
+    ///
+    /// Returns the address of the new resource definition.
+    pub fn create_badge(&mut self, account: Address, supply: Decimal) -> Address {
+        let receipt = self
+            .run(
+                TransactionBuilder::new(self)
+                    .new_badge_fixed(HashMap::new(), supply)
+                    .deposit_all_buckets(account)
+                    .build(Vec::new())
+                    .unwrap(),
+                false,
+            )
+            .unwrap();
+
+        if !receipt.success {
+            #[cfg(not(feature = "alloc"))]
+            println!("{:?}", receipt);
+            panic!("Failed to create badge. See receipt above.");
+        } else {
+            receipt.resource_def(0).unwrap()
+        }
+    }
+
+    /// Returns whether a component is an account.
+    pub fn is_account(&self, component_address: Address) -> bool {
+        self.ledger
+            .get()
+            .get_component(component_address)
+            .map(|c| c.package_address() == ACCOUNT_PACKAGE && c.blueprint_name() == "Account")
+            .unwrap_or(false)
+    }
+
+    /// Returns the amount of a resource held by a component, e.g. an account, in its vault of
+    /// the given resource.
+    pub fn balance_of(
+        &self,
+        component_address: Address,
+        resource_address: Address,
+    ) -> Result<Decimal, RuntimeError> {
+        self.find_vault_of(component_address, resource_address)?
+            .amount(Actor::SuperUser)
+            .map_err(RuntimeError::VaultError)
+    }
+
+    /// Returns the ids of the NFTs held by a component, e.g. an account, in its vault of the
+    /// given resource.
+    pub fn nft_ids_of(
+        &self,
+        component_address: Address,
+        resource_address: Address,
+    ) -> Result<Vec<u128>, RuntimeError> {
+        self.find_vault_of(component_address, resource_address)?
+            .get_nft_ids(Actor::SuperUser)
+            .map_err(RuntimeError::VaultError)
+    }
+
+    /// Returns the spot price of `resource_a` in `resource_b`, as the ratio of a component's
+    /// reserves of the two, e.g. those of an AMM pool.
+    ///
+    /// Returns `None` if either reserve is missing or empty.
+    pub fn pool_price(
+        &self,
+        component_address: Address,
+        resource_a: Address,
+        resource_b: Address,
+    ) -> Option<Decimal> {
+        let reserve_a = self.balance_of(component_address, resource_a).ok()?;
+        let reserve_b = self.balance_of(component_address, resource_b).ok()?;
+        if reserve_a.is_zero() || reserve_b.is_zero() {
+            None
+        } else {
+            Some(reserve_b / reserve_a)
+        }
+    }
+
+    /// Finds the vault of the given resource owned by a component, including those within
+    /// its lazy maps, by reading the ledger.
+    fn find_vault_of(
+        &self,
+        component_address: Address,
+        resource_address: Address,
+    ) -> Result<Vault, RuntimeError> {
+        let ledger = self.ledger.get();
+        let component = ledger
+            .get_component(component_address)
+            .ok_or(RuntimeError::ComponentNotFound(component_address))?;
+        let state = component
+            .state(Actor::SuperUser)
+            .map_err(RuntimeError::ComponentError)?;
+
+        let (vids, _) = Process::<L>::find_ids_in(state, |mid| {
+            ledger.get_lazy_map(mid).map(|lazy_map| {
+                lazy_map
+                    .map()
+                    .iter()
+                    .flat_map(|(k, v)| [k.clone(), v.clone()])
+                    .collect()
+            })
+        })?;
+
+        vids.into_iter()
+            .filter_map(|vid| ledger.get_vault(vid))
+            .find(|vault| vault.resource_address(Actor::SuperUser).ok() == Some(resource_address))
+            .ok_or(RuntimeError::ComponentVaultNotFound(
+                component_address,
+                resource_address,
+            ))
+    }
+
     /// Publishes a package.
     pub fn publish_package(&mut self, code: &[u8]) -> Address {
         let receipt = self
@@ -145,130 +584,468 @@ impl<'l, L: Ledger> TransactionExecutor<'l, L> {
         }
     }
 
+    /// Publishes the price-feed package and instantiates a `MockOracle`.
+    pub fn new_mock_oracle(&mut self) -> MockOracle {
+        MockOracle::new(self)
+    }
+
     /// Publishes a package to a specified address.
     pub fn overwrite_package(&mut self, address: Address, code: &[u8]) {
+        self.deposit_hooks
+            .retain(|(package, _), _| *package != address);
         self.ledger
+            .get_mut()
+            .expect("Ledger is read-only")
             .put_package(address, Package::new(code.to_vec()));
     }
 
+    /// Validates a transaction against the executor configuration.
+    pub fn validate(&self, transaction: &Transaction) -> Result<(), TransactionValidationError> {
+        self.validate_with(
+            transaction,
+            self.require_signatures || !transaction.signatures.is_empty(),
+        )
+    }
+
+    /// Validates a transaction, requiring a signature from every declared signer only if
+    /// `require_signers` is set. Attached signatures are always verified.
+    fn validate_with(
+        &self,
+        transaction: &Transaction,
+        require_signers: bool,
+    ) -> Result<(), TransactionValidationError> {
+        transaction.validate()?;
+
+        if let Some(limit) = self.max_instructions {
+            let count = transaction.len();
+            if count > limit {
+                return Err(TransactionValidationError::TooManyInstructions { count, limit });
+            }
+        }
+
+        for inst in transaction.instructions() {
+            match inst {
+                Instruction::PublishPackageFromBlob { code_hash }
+                    if transaction.blob(*code_hash).is_none() =>
+                {
+                    return Err(TransactionValidationError::BlobNotFound(*code_hash));
+                }
+                Instruction::NotBefore { epoch } if self.current_epoch < *epoch => {
+                    return Err(TransactionValidationError::NotYetValid {
+                        not_before: *epoch,
+                        current: self.current_epoch,
+                    });
+                }
+                _ => {}
+            }
+        }
+
+        let hash = transaction.hash();
+        for signature in &transaction.signatures {
+            if !signature.verify(hash.as_ref()) {
+                return Err(TransactionValidationError::InvalidSignature(
+                    signature.signer,
+                ));
+            }
+        }
+
+        if require_signers {
+            if let Some(Instruction::End { signers }) = transaction.instructions().last() {
+                if let Some(signer) = signers
+                    .iter()
+                    .find(|s| !transaction.signatures.iter().any(|sig| sig.signer == **s))
+                {
+                    return Err(TransactionValidationError::MissingSignature(*signer));
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Executes a transaction as if it was not signed by anyone, by stripping the signers
+    /// and signatures.
+    ///
+    /// Useful for checking that auth-gated calls actually fail without the signer.
+    pub fn run_unsigned(
+        &mut self,
+        transaction: Transaction,
+        trace: bool,
+    ) -> Result<Receipt, TransactionExecutionError> {
+        let mut instructions = transaction.instructions().to_vec();
+        if let Some(Instruction::End { signers }) = instructions.last_mut() {
+            signers.clear();
+        }
+
+        self.run(
+            Transaction::from_instructions(instructions, transaction.blobs),
+            trace,
+        )
+    }
+
+    /// Signs a transaction for the signers whose key was generated by this executor, and
+    /// executes it.
+    pub fn sign_and_run(
+        &mut self,
+        mut transaction: Transaction,
+        trace: bool,
+    ) -> Result<Receipt, TransactionExecutionError> {
+        self.sign(&mut transaction);
+        self.run(transaction, trace)
+    }
+
     /// Executes a transaction.
     pub fn run(
         &mut self,
         transaction: Transaction,
         trace: bool,
     ) -> Result<Receipt, TransactionExecutionError> {
-        #[cfg(not(feature = "alloc"))]
-        let now = std::time::Instant::now();
+        self.execute(transaction, trace, |_| true)
+            .map(|(receipt, _)| receipt)
+    }
 
-        let signers = if let Some(Instruction::End { signers }) = transaction.instructions.last() {
-            // TODO: check all signer addresses are public key; eventually should be computed from signature.
-            signers.clone()
-        } else {
-            return Err(TransactionExecutionError::MissingEndInstruction);
-        };
+    /// Executes a transaction, keeping its state updates only if the transaction succeeds
+    /// and the predicate accepts the receipt.
+    ///
+    /// Returns the receipt together with whether the state updates were committed. When not
+    /// committed, the ledger, nonce and recording are left as if the transaction had never
+    /// been run.
+    pub fn run_and_commit_if<F: FnOnce(&Receipt) -> bool>(
+        &mut self,
+        transaction: Transaction,
+        trace: bool,
+        predicate: F,
+    ) -> Result<(Receipt, bool), TransactionExecutionError> {
+        self.execute(transaction, trace, predicate)
+    }
 
+    /// Runs a transaction without committing it, with missing authorizations recorded
+    /// instead of enforced, and returns them in the order they were hit.
+    ///
+    /// Declared signers without an attached signature are reported first, and the transaction
+    /// then runs as if they had signed. Both the resource permissions checked by the engine and
+    /// the badges of blueprint methods guarded with `#[auth]` are reported. The preview only
+    /// reads the ledger, so it also works on a read-only executor.
+    ///
+    /// Fails with `PreviewFailed` if the transaction doesn't succeed even so.
+    pub fn preview_auth(
+        &self,
+        transaction: Transaction,
+    ) -> Result<Vec<AuthRequirement>, TransactionExecutionError> {
+        self.validate_with(&transaction, false)
+            .map_err(TransactionExecutionError::ValidationError)?;
+
+        let signers = end_signers(&transaction)?;
+        let mut requirements: Vec<AuthRequirement> = signers
+            .iter()
+            .filter(|s| !transaction.signatures.iter().any(|sig| sig.signer == **s))
+            .map(|s| AuthRequirement::Signature { signer: *s })
+            .collect();
+
+        let settings = self.track_settings();
+        let mut track = Track::new_read_only(
+            self.ledger.get(),
+            self.current_epoch,
+            sha256(self.nonce.to_string()),
+            signers,
+        );
+        track.set_auth_preview(true);
+        track.set_deposit_hooks(self.deposit_hooks.clone());
+        let receipt = run_on_track(&mut track, &settings, transaction, false);
+        if !receipt.success {
+            return Err(TransactionExecutionError::PreviewFailed(Box::new(receipt)));
+        }
+
+        requirements.extend(receipt.auth_requirements);
+        Ok(requirements)
+    }
+
+    /// Returns the executor configuration to apply to the track of a transaction.
+    fn track_settings(&self) -> TrackSettings {
+        TrackSettings {
+            tracer: self.tracer.clone(),
+            max_log_size: self.max_log_size,
+            max_worktop_resources: self.max_worktop_resources,
+            max_execution_time: self.max_execution_time,
+            current_time: self.clock.as_ref().map(|clock| clock()).unwrap_or(0),
+        }
+    }
+
+    /// Executes a transaction on a track, which is committed to the ledger only if the
+    /// transaction succeeds and `commit_if` accepts the receipt.
+    fn execute<F: FnOnce(&Receipt) -> bool>(
+        &mut self,
+        transaction: Transaction,
+        trace: bool,
+        commit_if: F,
+    ) -> Result<(Receipt, bool), TransactionExecutionError> {
+        self.validate(&transaction)
+            .map_err(TransactionExecutionError::ValidationError)?;
+
+        let signers = end_signers(&transaction)?;
+        let settings = self.track_settings();
         let mut track = Track::new(
-            self.ledger,
+            self.ledger
+                .get_mut()
+                .ok_or(TransactionExecutionError::ReadOnlyLedger)?,
             self.current_epoch,
             sha256(self.nonce.to_string()),
             signers,
         );
-        let mut proc = track.start_process(trace);
-
-        let mut results = vec![];
-        let mut success = true;
-        for inst in &transaction.instructions {
-            let res = match inst {
-                Instruction::DeclareTempBucket => {
-                    proc.declare_bucket();
-                    Ok(None)
-                }
-                Instruction::DeclareTempBucketRef => {
-                    proc.declare_bucket_ref();
-                    Ok(None)
-                }
-                Instruction::TakeFromContext {
-                    amount,
-                    resource_address,
-                    to,
-                } => proc
-                    .take_from_context(*amount, *resource_address, *to)
-                    .map(|_| None),
-                Instruction::BorrowFromContext {
-                    amount,
-                    resource_address,
-                    to,
-                } => proc
-                    .borrow_from_context(*amount, *resource_address, *to)
-                    .map(|_| None),
-                Instruction::CallFunction {
-                    package_address,
-                    blueprint_name,
-                    function,
-                    args,
-                } => proc
-                    .call_function(
-                        *package_address,
-                        blueprint_name.as_str(),
-                        function.as_str(),
-                        args.iter().map(|v| v.encoded.clone()).collect(),
-                    )
-                    .map(|rtn| Some(SmartValue { encoded: rtn })),
-                Instruction::CallMethod {
-                    component_address,
-                    method,
-                    args,
-                } => proc
-                    .call_method(
-                        *component_address,
-                        method.as_str(),
-                        args.iter().map(|v| v.encoded.clone()).collect(),
-                    )
-                    .map(|rtn| Some(SmartValue { encoded: rtn })),
+        track.set_deposit_hooks(self.deposit_hooks.clone());
+        let receipt = run_on_track(&mut track, &settings, transaction, trace);
+
+        // commit state updates
+        let nonce = self.nonce;
+        let committed = receipt.success && commit_if(&receipt);
+        if committed {
+            track.commit();
+            self.deposit_hooks = track.deposit_hooks().clone();
+            self.nonce += 1;
+        }
+
+        if committed && self.recorded.is_some() {
+            let state_root = self.ledger.get().state_root();
+            if let Some(recorded) = &mut self.recorded {
+                recorded.push(AppliedTransaction {
+                    epoch: self.current_epoch,
+                    time: settings.current_time,
+                    nonce,
+                    transaction: receipt.transaction.clone(),
+                    state_root,
+                });
+            }
+        }
+        Ok((receipt, committed))
+    }
+}
+
+/// The executor configuration applied to the track of a transaction.
+struct TrackSettings {
+    tracer: Option<Tracer>,
+    max_log_size: Option<usize>,
+    max_worktop_resources: Option<usize>,
+    max_execution_time: Option<Duration>,
+    current_time: u64,
+}
 
-                Instruction::DropAllBucketRefs => {
-                    proc.drop_bucket_refs();
+/// Returns the signers declared by the `End` instruction of a transaction.
+fn end_signers(transaction: &Transaction) -> Result<Vec<Address>, TransactionExecutionError> {
+    if let Some(Instruction::End { signers }) = transaction.instructions().last() {
+        // TODO: check all signer addresses are public key; eventually should be computed from signature.
+        Ok(signers.clone())
+    } else {
+        Err(TransactionExecutionError::MissingEndInstruction)
+    }
+}
+
+/// Runs the instructions of a transaction on a track, without committing it.
+fn run_on_track<L: Ledger>(
+    track: &mut Track<L>,
+    settings: &TrackSettings,
+    transaction: Transaction,
+    trace: bool,
+) -> Receipt {
+    #[cfg(not(feature = "alloc"))]
+    let now = std::time::Instant::now();
+
+    track.set_tracer(settings.tracer.clone());
+    track.set_max_log_size(settings.max_log_size);
+    track.set_max_worktop_resources(settings.max_worktop_resources);
+    #[cfg(not(feature = "alloc"))]
+    if let Some(limit) = settings.max_execution_time {
+        track.set_timeout(Some(Box::new(move || now.elapsed() > limit)));
+    }
+    track.set_current_time(settings.current_time);
+    track.set_random_seed(transaction.hash());
+    let mut proc = track.start_process(trace);
+
+    let mut results = vec![];
+    let mut success = true;
+    for (index, inst) in transaction.instructions().iter().enumerate() {
+        proc.set_instruction_index(index);
+        proc.trace(TraceEvent::InstructionStarted { index });
+        let res = match inst {
+            Instruction::DeclareTempBucket => {
+                proc.declare_bucket();
+                Ok(None)
+            }
+            Instruction::DeclareTempBucketRef => {
+                proc.declare_bucket_ref();
+                Ok(None)
+            }
+            Instruction::TakeFromContext {
+                amount,
+                resource_address,
+                to,
+            } => proc
+                .take_from_context(*amount, *resource_address, *to)
+                .map(|_| None),
+            Instruction::TakeExactFromContext {
+                amount,
+                resource_address,
+                to,
+            } => proc
+                .take_exact_from_context(*amount, *resource_address, *to)
+                .map(|_| None),
+            Instruction::BorrowFromContext {
+                amount,
+                resource_address,
+                to,
+            } => proc
+                .borrow_from_context(*amount, *resource_address, *to)
+                .map(|_| None),
+            Instruction::BorrowFromAccount {
+                account,
+                amount,
+                resource_address,
+                to,
+            } => proc
+                .borrow_from_account(*account, *amount, *resource_address, *to)
+                .map(|_| None),
+            Instruction::CallFunction {
+                package_address,
+                blueprint_name,
+                function,
+                args,
+            } => proc
+                .call_function(
+                    *package_address,
+                    blueprint_name.as_str(),
+                    function.as_str(),
+                    args.iter().map(|v| v.encoded.clone()).collect(),
+                )
+                .map(|rtn| Some(SmartValue { encoded: rtn })),
+            Instruction::CallMethod {
+                component_address,
+                method,
+                args,
+            } => proc
+                .call_method(
+                    *component_address,
+                    method.as_str(),
+                    args.iter().map(|v| v.encoded.clone()).collect(),
+                )
+                .map(|rtn| Some(SmartValue { encoded: rtn })),
+            Instruction::CallMethodWithAllResources {
+                component_address,
+                method,
+            } => {
+                let buckets = proc.list_buckets();
+                proc.call_method(*component_address, method.as_str(), args!(buckets))
+                    .map(|rtn| Some(SmartValue { encoded: rtn }))
+            }
+            Instruction::TransferBetweenVaults {
+                from_component,
+                to_component,
+                resource_address,
+                amount,
+                auth,
+            } => proc
+                .transfer_between_vaults(
+                    *from_component,
+                    *to_component,
+                    *resource_address,
+                    *amount,
+                    *auth,
+                )
+                .map(|_| None),
+            Instruction::PublishPackageFromBlob { code_hash } => proc
+                .call_function(
+                    SYSTEM_PACKAGE,
+                    "System",
+                    "publish_package",
+                    args!(transaction.blob(*code_hash).unwrap().clone()),
+                )
+                .map(|rtn| Some(SmartValue { encoded: rtn })),
+            Instruction::DropAllBucketRefs => {
+                proc.drop_bucket_refs();
+                Ok(None)
+            }
+            Instruction::Deposit { bid, account } => proc
+                .call_method(*account, "deposit", args!(*bid))
+                .map(|rtn| Some(SmartValue { encoded: rtn })),
+            Instruction::DepositAllBuckets { account } => {
+                let buckets = proc.list_buckets();
+                if !buckets.is_empty() {
+                    proc.call_method(*account, "deposit_batch", args!(buckets))
+                        .map(|rtn| Some(SmartValue { encoded: rtn }))
+                } else {
                     Ok(None)
                 }
-                Instruction::DepositAllBuckets { account } => {
-                    let buckets = proc.list_buckets();
-                    if !buckets.is_empty() {
-                        proc.call_method(*account, "deposit_batch", args!(buckets))
-                            .map(|rtn| Some(SmartValue { encoded: rtn }))
-                    } else {
-                        Ok(None)
-                    }
+            }
+            Instruction::DepositAllBucketsOr { account, fallback } => proc
+                .deposit_all_buckets_or(*account, *fallback)
+                .map(|_| None),
+            Instruction::DepositAllOf {
+                resource_address,
+                account,
+            } => {
+                let buckets = proc.list_buckets_of(*resource_address);
+                if !buckets.is_empty() {
+                    proc.call_method(*account, "deposit_batch", args!(buckets))
+                        .map(|rtn| Some(SmartValue { encoded: rtn }))
+                } else {
+                    Ok(None)
                 }
-                Instruction::End { .. } => proc.check_resource().map(|_| None),
-            };
-            success &= res.is_ok();
-            results.push(res);
-            if !success {
-                break;
             }
+            Instruction::DepositWorktopRemainder { account } => proc
+                .deposit_worktop_remainder(*account)
+                .map(|remainder| Some(SmartValue::from(remainder))),
+            Instruction::DeleteComponent { component_address } => {
+                proc.delete_component(*component_address).map(|_| None)
+            }
+            Instruction::AssertWorktopEmpty => proc.assert_worktop_empty().map(|_| None),
+            Instruction::AssertWorktopContainsNfts {
+                ids,
+                resource_address,
+            } => proc
+                .assert_worktop_contains_nfts(ids, *resource_address)
+                .map(|_| None),
+            Instruction::NotBefore { .. } | Instruction::Comment { .. } => Ok(None),
+            Instruction::End { .. } => proc.check_resource().map(|_| None),
+        };
+        proc.trace(TraceEvent::InstructionEnded {
+            index,
+            success: res.is_ok(),
+        });
+        success &= res.is_ok();
+        results.push(res);
+        if !success {
+            break;
         }
+    }
 
-        // commit state updates
-        if success {
-            track.commit();
-            self.nonce += 1;
-        }
-        #[cfg(feature = "alloc")]
-        let execution_time = None;
-        #[cfg(not(feature = "alloc"))]
-        let execution_time = Some(now.elapsed().as_millis());
+    #[cfg(feature = "alloc")]
+    let execution_time = None;
+    #[cfg(not(feature = "alloc"))]
+    let execution_time = Some(now.elapsed().as_millis());
 
-        Ok(Receipt {
-            transaction,
-            success,
-            results,
-            logs: track.logs().clone(),
-            new_entities: if success {
-                track.new_entities().to_vec()
-            } else {
-                Vec::new()
-            },
-            execution_time,
-        })
+    Receipt {
+        transaction,
+        success,
+        results,
+        logs: track.logs().clone(),
+        logs_truncated: track.logs_truncated(),
+        resource_movements: pair_movements(track.movements()),
+        new_entities: if success {
+            track.new_entities().to_vec()
+        } else {
+            Vec::new()
+        },
+        execution_time,
+        auth_requirements: track.auth_requirements().clone(),
+        resources_created: if success {
+            track.resources_created().clone()
+        } else {
+            Vec::new()
+        },
+    }
+}
+
+impl<'l> TransactionExecutor<'l, InMemoryLedger> {
+    /// Checks that no resource has been created or destroyed other than by minting or burning.
+    pub fn assert_conservation(&self) -> Result<(), ConservationError> {
+        self.ledger.get().check_conservation()
     }
 }
@@ -0,0 +1,105 @@
+use sbor::*;
+use scrypto::rust::borrow::ToOwned;
+use scrypto::rust::format;
+use scrypto::rust::string::String;
+use scrypto::rust::vec::Vec;
+
+use crate::transaction::*;
+
+/// Describes the expected outcome of a transaction, for asserting on its receipt.
+///
+/// Anything not specified is not checked.
+#[derive(Debug, Clone, Default)]
+pub struct ReceiptExpectation {
+    success: Option<bool>,
+    new_entities: Option<usize>,
+    logs_containing: Vec<String>,
+    return_values: Vec<(usize, SmartValue)>,
+}
+
+impl ReceiptExpectation {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Expects the transaction to succeed, or fail.
+    pub fn success(mut self, success: bool) -> Self {
+        self.success = Some(success);
+        self
+    }
+
+    /// Expects the number of entities created by the transaction.
+    pub fn new_entities(mut self, count: usize) -> Self {
+        self.new_entities = Some(count);
+        self
+    }
+
+    /// Expects a log message containing the given text.
+    pub fn log_containing(mut self, text: &str) -> Self {
+        self.logs_containing.push(text.to_owned());
+        self
+    }
+
+    /// Expects the value returned by the instruction at the given index.
+    pub fn return_value<T: Encode>(mut self, index: usize, value: T) -> Self {
+        self.return_values.push((index, SmartValue::from(value)));
+        self
+    }
+
+    /// Returns the ways the receipt differs from this expectation.
+    pub fn mismatches(&self, receipt: &Receipt) -> Vec<String> {
+        let mut mismatches = Vec::new();
+        if let Some(success) = self.success {
+            if receipt.success != success {
+                mismatches.push(format!(
+                    "Expected success = {}, but got {}",
+                    success, receipt.success
+                ));
+            }
+        }
+        if let Some(count) = self.new_entities {
+            if receipt.new_entities.len() != count {
+                mismatches.push(format!(
+                    "Expected {} new entities, but got {}",
+                    count,
+                    receipt.new_entities.len()
+                ));
+            }
+        }
+        for text in &self.logs_containing {
+            if !receipt
+                .logs
+                .iter()
+                .any(|(_, msg)| msg.contains(text.as_str()))
+            {
+                mismatches.push(format!("Expected a log containing {:?}", text));
+            }
+        }
+        for (index, value) in &self.return_values {
+            match receipt.results.get(*index) {
+                Some(Ok(Some(actual))) if actual == value => {}
+                actual => mismatches.push(format!(
+                    "Expected instruction #{} to return {:?}, but got {:?}",
+                    index, value, actual
+                )),
+            }
+        }
+        mismatches
+    }
+}
+
+impl Receipt {
+    /// Asserts that this receipt meets an expectation.
+    ///
+    /// # Panics
+    /// Panics listing every mismatch, if there is any.
+    pub fn assert_matches(&self, expected: &ReceiptExpectation) {
+        let mismatches = expected.mismatches(self);
+        if !mismatches.is_empty() {
+            panic!(
+                "Receipt doesn't match expectation:\n{}",
+                mismatches.join("\n")
+            );
+        }
+    }
+}
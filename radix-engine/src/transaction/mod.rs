@@ -2,10 +2,23 @@ mod abi;
 mod builder;
 mod error;
 mod executor;
+mod expectation;
 mod model;
+mod signer;
 
-pub use abi::{AbiProvider, BasicAbiProvider};
-pub use builder::{ParseResourceAmountError, ResourceAmount, TransactionBuilder};
-pub use error::{BuildArgsError, BuildTransactionError};
-pub use executor::TransactionExecutor;
-pub use model::{Instruction, Receipt, SmartValue, Transaction};
+pub use abi::{AbiProvider, BasicAbiProvider, ResourceBehavior};
+pub use builder::{MergeError, ParseResourceAmountError, ResourceAmount, TransactionBuilder};
+pub use error::{
+    BuildArgsError, BuildTransactionError, ReturnError, TransactionValidationError, TypeMismatch,
+};
+pub use executor::{
+    AppliedTransaction, GenesisAccount, TransactionExecutionError, TransactionExecutor,
+    DEFAULT_MAX_WORKTOP_RESOURCES,
+};
+pub use expectation::ReceiptExpectation;
+pub use model::{
+    ArchivedReceipt, ArchivedReceiptV1, ArchivedTransactionV1, Instruction, Receipt, SmartValue,
+    Transaction, TransactionManifest, VersionedReceipt,
+};
+pub(crate) use signer::{ed25519_public_key, ed25519_sign};
+pub use signer::{Signature, Signer};
@@ -1,18 +1,25 @@
 use colored::*;
+use sbor::any::*;
+use sbor::describe::Type;
 use sbor::*;
 use scrypto::buffer::*;
 use scrypto::kernel::*;
+use scrypto::rust::collections::*;
+use scrypto::rust::convert::TryFrom;
 use scrypto::rust::fmt;
+use scrypto::rust::format;
 use scrypto::rust::string::String;
 use scrypto::rust::string::ToString;
 use scrypto::rust::vec::Vec;
 use scrypto::types::*;
+use scrypto::utils::*;
 
 use crate::engine::*;
+use crate::transaction::*;
 use crate::utils::*;
 
 /// Represents a universally recognizable value.
-#[derive(Clone, TypeId, Encode, Decode)]
+#[derive(Clone, PartialEq, Eq, TypeId, Encode, Decode)]
 pub struct SmartValue {
     pub encoded: Vec<u8>,
 }
@@ -23,6 +30,19 @@ impl SmartValue {
             encoded: scrypto_encode(&v),
         }
     }
+
+    /// Encodes a value, after checking its schema matches the expected type.
+    pub fn checked_from<T: Encode + Describe>(v: T, expected: &Type) -> Result<Self, TypeMismatch> {
+        let actual = T::describe();
+        if &actual == expected {
+            Ok(Self::from(v))
+        } else {
+            Err(TypeMismatch {
+                expected: expected.clone(),
+                actual,
+            })
+        }
+    }
 }
 
 impl fmt::Debug for SmartValue {
@@ -35,14 +55,264 @@ impl fmt::Debug for SmartValue {
     }
 }
 
+/// An unsigned sequence of instructions, without the terminating `End`.
+///
+/// Instructions can be inspected or rearranged before the manifest is turned into a transaction,
+/// as long as bucket and bucket ref declarations stay in front.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TransactionManifest {
+    pub instructions: Vec<Instruction>,
+    pub blobs: Vec<Vec<u8>>,
+}
+
 /// A transaction consists a sequence of instructions.
-#[derive(Debug, Clone, TypeId, Encode, Decode)]
+#[derive(Debug, Clone, PartialEq, Eq, TypeId, Encode, Decode)]
 pub struct Transaction {
-    pub instructions: Vec<Instruction>,
+    instructions: Vec<Instruction>,
+    pub signatures: Vec<Signature>,
+    pub blobs: Vec<Vec<u8>>,
+}
+
+impl Transaction {
+    /// Creates an unsigned transaction from a manifest.
+    pub fn from_manifest(manifest: TransactionManifest, signers: Vec<Address>) -> Self {
+        let mut instructions = manifest.instructions;
+        instructions.push(Instruction::End { signers });
+
+        Self {
+            instructions,
+            signatures: Vec::new(),
+            blobs: manifest.blobs,
+        }
+    }
+
+    /// Creates an unsigned transaction from raw instructions, which should end with `End`.
+    ///
+    /// Unlike `from_manifest`, the instructions are taken as-is; see `validate`.
+    pub fn from_instructions(instructions: Vec<Instruction>, blobs: Vec<Vec<u8>>) -> Self {
+        Self {
+            instructions,
+            signatures: Vec::new(),
+            blobs,
+        }
+    }
+
+    /// Returns the instructions, including the final `End`.
+    pub fn instructions(&self) -> &[Instruction] {
+        &self.instructions
+    }
+
+    /// Returns the number of instructions, including the final `End`.
+    pub fn len(&self) -> usize {
+        self.instructions.len()
+    }
+
+    /// Returns whether there is no instruction at all.
+    pub fn is_empty(&self) -> bool {
+        self.instructions.is_empty()
+    }
+
+    /// Returns the hash of the instructions, which is the payload being signed.
+    pub fn hash(&self) -> H256 {
+        sha256(scrypto_encode(&self.instructions))
+    }
+
+    /// Returns the attached blob of the given hash.
+    pub fn blob(&self, hash: H256) -> Option<&Vec<u8>> {
+        self.blobs.iter().find(|b| sha256(b) == hash)
+    }
+
+    /// Checks the structure of the instructions, without executing them.
+    ///
+    /// All bucket and bucket ref declarations must come first, every referenced bucket or
+    /// bucket ref must be declared, and `End` must be the last instruction.
+    pub fn validate(&self) -> Result<(), TransactionValidationError> {
+        match self.instructions.last() {
+            Some(Instruction::End { .. }) => {}
+            _ => return Err(TransactionValidationError::MissingEndInstruction),
+        }
+
+        // Declarations are allocated ids in the same order during execution.
+        let mut id_allocator = IdAllocator::new();
+        let mut bids = HashSet::new();
+        let mut rids = HashSet::new();
+        let mut declaring = true;
+        for (i, inst) in self.instructions.iter().enumerate() {
+            match inst {
+                Instruction::DeclareTempBucket | Instruction::DeclareTempBucketRef => {
+                    if !declaring {
+                        return Err(TransactionValidationError::ReservationAfterUse(i));
+                    }
+                    if matches!(inst, Instruction::DeclareTempBucket) {
+                        bids.insert(id_allocator.new_bid());
+                    } else {
+                        rids.insert(id_allocator.new_rid());
+                    }
+                    continue;
+                }
+                Instruction::End { .. } if i != self.instructions.len() - 1 => {
+                    return Err(TransactionValidationError::UnexpectedEndInstruction(i));
+                }
+                _ => declaring = false,
+            }
+
+            let mut used_bids = Vec::new();
+            let mut used_rids = Vec::new();
+            match inst {
+                Instruction::TakeFromContext { to, .. }
+                | Instruction::TakeExactFromContext { to, .. } => used_bids.push(*to),
+                Instruction::BorrowFromContext { to, .. }
+                | Instruction::BorrowFromAccount { to, .. } => used_rids.push(*to),
+                Instruction::TransferBetweenVaults { auth, .. } => used_rids.push(*auth),
+                Instruction::Deposit { bid, .. } => used_bids.push(*bid),
+                Instruction::CallFunction { args, .. } | Instruction::CallMethod { args, .. } => {
+                    for arg in args {
+                        let value = decode_any(&arg.encoded)
+                            .map_err(|_| TransactionValidationError::InvalidArgument(i))?;
+                        collect_ids(&value, &mut used_bids, &mut used_rids)
+                            .map_err(|_| TransactionValidationError::InvalidArgument(i))?;
+                    }
+                }
+                _ => {}
+            }
+            if let Some(bid) = used_bids.into_iter().find(|bid| !bids.contains(bid)) {
+                return Err(TransactionValidationError::UndeclaredBucket(i, bid));
+            }
+            if let Some(rid) = used_rids.into_iter().find(|rid| !rids.contains(rid)) {
+                return Err(TransactionValidationError::UndeclaredBucketRef(i, rid));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Summarizes the resources withdrawn from accounts, by account and resource, in the order
+    /// they first appear.
+    ///
+    /// Only the statically-known `withdraw` and `withdraw_nfts` calls on components accepted by
+    /// `is_account`, e.g. `TransactionExecutor::is_account`, are covered. Resources taken from
+    /// transaction context are not counted again, and whatever a component returns or pulls
+    /// from an account by itself can't be known before execution.
+    pub fn required_withdrawals<F: Fn(Address) -> bool>(
+        &self,
+        is_account: F,
+    ) -> Vec<(Address, ResourceAmount)> {
+        let mut withdrawals: Vec<(Address, ResourceAmount)> = Vec::new();
+        for inst in &self.instructions {
+            let (account, amount) = match inst {
+                Instruction::CallMethod {
+                    component_address,
+                    method,
+                    args,
+                } if args.len() == 2 && is_account(*component_address) => {
+                    let resource_address = match scrypto_decode::<Address>(&args[1].encoded) {
+                        Ok(address) => address,
+                        Err(_) => continue,
+                    };
+                    let amount = match method.as_str() {
+                        "withdraw" => match scrypto_decode::<Decimal>(&args[0].encoded) {
+                            Ok(amount) => ResourceAmount::Fungible {
+                                amount,
+                                resource_address,
+                            },
+                            Err(_) => continue,
+                        },
+                        "withdraw_nfts" => {
+                            match scrypto_decode::<BTreeSet<u128>>(&args[0].encoded) {
+                                Ok(ids) => ResourceAmount::NonFungible {
+                                    ids,
+                                    resource_address,
+                                },
+                                Err(_) => continue,
+                            }
+                        }
+                        _ => continue,
+                    };
+                    (*component_address, amount)
+                }
+                _ => continue,
+            };
+
+            let existing = withdrawals
+                .iter_mut()
+                .find(|(a, r)| *a == account && r.resource_address() == amount.resource_address());
+            match (existing, amount) {
+                (
+                    Some((_, ResourceAmount::Fungible { amount: total, .. })),
+                    ResourceAmount::Fungible { amount, .. },
+                ) => *total += amount,
+                (
+                    Some((_, ResourceAmount::NonFungible { ids: all, .. })),
+                    ResourceAmount::NonFungible { ids, .. },
+                ) => all.extend(ids),
+                (_, amount) => withdrawals.push((account, amount)),
+            }
+        }
+        withdrawals
+    }
+}
+
+/// Collects the bucket and bucket ref ids within a value.
+fn collect_ids(value: &Value, bids: &mut Vec<Bid>, rids: &mut Vec<Rid>) -> Result<(), DecodeError> {
+    match value {
+        Value::Struct(fields) | Value::Enum(_, fields) => match fields {
+            Fields::Named(values) | Fields::Unnamed(values) => {
+                for v in values {
+                    collect_ids(v, bids, rids)?;
+                }
+            }
+            Fields::Unit => {}
+        },
+        Value::Option(v) => {
+            if let Some(v) = v.as_ref() {
+                collect_ids(v, bids, rids)?;
+            }
+        }
+        Value::Box(v) => collect_ids(v, bids, rids)?,
+        Value::Result(v) => match v.as_ref() {
+            Ok(v) | Err(v) => collect_ids(v, bids, rids)?,
+        },
+        Value::Array(_, values)
+        | Value::Tuple(values)
+        | Value::Vec(_, values)
+        | Value::TreeSet(_, values)
+        | Value::HashSet(_, values) => {
+            for v in values {
+                collect_ids(v, bids, rids)?;
+            }
+        }
+        Value::TreeMap(_, _, entries) | Value::HashMap(_, _, entries) => {
+            for (k, v) in entries {
+                collect_ids(k, bids, rids)?;
+                collect_ids(v, bids, rids)?;
+            }
+        }
+        Value::Custom {
+            ty: SCRYPTO_TYPE_BID,
+            bytes,
+            ..
+        } => bids.push(
+            Bid::try_from(bytes.as_slice())
+                .map_err(|_| DecodeError::InvalidCustomData(SCRYPTO_TYPE_BID))?,
+        ),
+        Value::Custom {
+            ty: SCRYPTO_TYPE_RID,
+            bytes,
+            ..
+        } => rids.push(
+            Rid::try_from(bytes.as_slice())
+                .map_err(|_| DecodeError::InvalidCustomData(SCRYPTO_TYPE_RID))?,
+        ),
+        _ => {}
+    }
+    Ok(())
 }
 
 /// Represents an instruction in transaction
-#[derive(Debug, Clone, TypeId, Encode, Decode)]
+///
+/// Instructions are encoded by their position, so new ones must be appended at the end to keep
+/// archived transactions decodable.
+#[derive(Debug, Clone, PartialEq, Eq, TypeId, Encode, Decode)]
 pub enum Instruction {
     /// Declares a temporary bucket for later use.
     DeclareTempBucket,
@@ -93,6 +363,83 @@ pub enum Instruction {
 
     /// Marks the end of transaction with signatures.
     End { signers: Vec<Address> },
+
+    /// Deposits all resources of the given kind from transaction context into the designated account.
+    DepositAllOf {
+        resource_address: Address,
+        account: Address,
+    },
+
+    /// Attaches a human-readable label, which has no effect on execution.
+    Comment { text: String },
+
+    /// Publishes a package, with code from the transaction blob of the given hash.
+    PublishPackageFromBlob { code_hash: H256 },
+
+    /// Calls a component method, passing all resources in transaction context as a vector of buckets.
+    CallMethodWithAllResources {
+        component_address: Address,
+        method: String,
+    },
+
+    /// Moves resource directly between the vaults of two components.
+    ///
+    /// The owner of the source component must sign, and the badge must grant transfer if the
+    /// resource is restricted. The resource never enters transaction context.
+    TransferBetweenVaults {
+        from_component: Address,
+        to_component: Address,
+        resource_address: Address,
+        amount: Decimal,
+        auth: Rid,
+    },
+
+    /// Deposits all resources from transaction context into the designated account, or into
+    /// the fallback account if the deposit fails, e.g. because the account is frozen.
+    DepositAllBucketsOr { account: Address, fallback: Address },
+
+    /// Takes resource from transaction context to a temporary bucket, failing if the context
+    /// holds less than the exact amount.
+    TakeExactFromContext {
+        amount: Decimal,
+        resource_address: Address,
+        to: Bid,
+    },
+
+    /// Asserts that transaction context holds no resource.
+    AssertWorktopEmpty,
+
+    /// Deposits all resources from transaction context into the designated account, checking
+    /// that everything is accounted for.
+    ///
+    /// Returns the amount deposited of each resource, as `Vec<(Address, Decimal)>`.
+    DepositWorktopRemainder { account: Address },
+
+    /// Deletes a component whose owner, as set by its blueprint, is a signer. Its vaults must
+    /// all be empty, and are deleted along with its lazy maps.
+    DeleteComponent { component_address: Address },
+
+    /// Borrows resource from the vault of an account to a temporary bucket ref.
+    ///
+    /// The resource stays in the account.
+    BorrowFromAccount {
+        account: Address,
+        amount: Decimal,
+        resource_address: Address,
+        to: Rid,
+    },
+
+    /// Asserts that transaction context holds the given NFTs.
+    AssertWorktopContainsNfts {
+        ids: BTreeSet<u128>,
+        resource_address: Address,
+    },
+
+    /// Rejects the transaction if it's submitted before the given epoch.
+    NotBefore { epoch: u64 },
+
+    /// Deposits a bucket into the designated account.
+    Deposit { bid: Bid, account: Address },
 }
 
 /// Represents a transaction receipt.
@@ -101,8 +448,12 @@ pub struct Receipt {
     pub success: bool,
     pub results: Vec<Result<Option<SmartValue>, RuntimeError>>,
     pub logs: Vec<(LogLevel, String)>,
+    pub logs_truncated: bool,
+    pub resource_movements: Vec<Movement>,
     pub new_entities: Vec<Address>,
     pub execution_time: Option<u128>,
+    pub auth_requirements: Vec<AuthRequirement>,
+    pub resources_created: Vec<ResourceCreation>,
 }
 
 impl Receipt {
@@ -129,6 +480,139 @@ impl Receipt {
             .map(Clone::clone)
             .nth(nth)
     }
+
+    /// Decodes the return value of the instruction at the given index.
+    pub fn return_value<T: Decode>(&self, index: usize) -> Result<T, ReturnError> {
+        match self.results.get(index) {
+            Some(Ok(Some(value))) => {
+                scrypto_decode(&value.encoded).map_err(ReturnError::DecodeError)
+            }
+            Some(Ok(None)) => Err(ReturnError::NoReturnValue(index)),
+            Some(Err(_)) => Err(ReturnError::InstructionFailed(index)),
+            None => Err(ReturnError::IndexOutOfRange(index)),
+        }
+    }
+
+    /// Returns the root cause of the first failed instruction, if any.
+    pub fn error(&self) -> Option<&RuntimeError> {
+        self.results
+            .iter()
+            .find_map(|r| r.as_ref().err())
+            .map(RuntimeError::root_cause)
+    }
+
+    /// Converts this receipt into a versioned, serializable form.
+    pub fn archive(&self) -> VersionedReceipt {
+        VersionedReceipt::V3(ArchivedReceipt {
+            transaction: self.transaction.clone(),
+            success: self.success,
+            results: self
+                .results
+                .iter()
+                .map(|r| match r {
+                    Ok(v) => Ok(v.clone()),
+                    Err(e) => Err(e.to_string()),
+                })
+                .collect(),
+            logs: self.logs.clone(),
+            logs_truncated: self.logs_truncated,
+            resource_movements: self.resource_movements.clone(),
+            new_entities: self.new_entities.clone(),
+            execution_time: self.execution_time,
+            auth_requirements: self.auth_requirements.clone(),
+            resources_created: self.resources_created.clone(),
+        })
+    }
+}
+
+/// Represents a receipt in serializable form.
+///
+/// Runtime errors are kept in their display form, as they can't be restored.
+#[derive(Debug, Clone, PartialEq, Eq, TypeId, Encode, Decode)]
+pub struct ArchivedReceipt {
+    pub transaction: Transaction,
+    pub success: bool,
+    pub results: Vec<Result<Option<SmartValue>, String>>,
+    pub logs: Vec<(LogLevel, String)>,
+    pub logs_truncated: bool,
+    pub resource_movements: Vec<Movement>,
+    pub new_entities: Vec<Address>,
+    pub execution_time: Option<u128>,
+    pub auth_requirements: Vec<AuthRequirement>,
+    pub resources_created: Vec<ResourceCreation>,
+}
+
+/// Represents a receipt in the second archive format, which has no resource movements,
+/// auth requirements or created resources.
+#[derive(Debug, Clone, PartialEq, Eq, TypeId, Encode, Decode)]
+pub struct ArchivedReceiptV2 {
+    pub transaction: Transaction,
+    pub success: bool,
+    pub results: Vec<Result<Option<SmartValue>, String>>,
+    pub logs: Vec<(LogLevel, String)>,
+    pub new_entities: Vec<Address>,
+    pub execution_time: Option<u128>,
+}
+
+/// Represents a receipt in the first archive format, whose transaction has no signatures
+/// or blobs.
+#[derive(Debug, Clone, PartialEq, Eq, TypeId, Encode, Decode)]
+pub struct ArchivedReceiptV1 {
+    pub transaction: ArchivedTransactionV1,
+    pub success: bool,
+    pub results: Vec<Result<Option<SmartValue>, String>>,
+    pub logs: Vec<(LogLevel, String)>,
+    pub new_entities: Vec<Address>,
+    pub execution_time: Option<u128>,
+}
+
+/// Represents a transaction in the first archive format.
+#[derive(Debug, Clone, PartialEq, Eq, TypeId, Encode, Decode)]
+pub struct ArchivedTransactionV1 {
+    pub instructions: Vec<Instruction>,
+}
+
+/// Represents an archived receipt, tagged with the format version.
+///
+/// New versions should be added as new variants, so older archives remain decodable.
+#[derive(Debug, Clone, PartialEq, Eq, TypeId, Encode, Decode)]
+pub enum VersionedReceipt {
+    V1(ArchivedReceiptV1),
+    V2(ArchivedReceiptV2),
+    V3(ArchivedReceipt),
+}
+
+impl VersionedReceipt {
+    /// Returns the receipt in the latest format.
+    pub fn into_latest(self) -> ArchivedReceipt {
+        match self {
+            VersionedReceipt::V1(receipt) => VersionedReceipt::V2(ArchivedReceiptV2 {
+                transaction: Transaction::from_instructions(
+                    receipt.transaction.instructions,
+                    Vec::new(),
+                ),
+                success: receipt.success,
+                results: receipt.results,
+                logs: receipt.logs,
+                new_entities: receipt.new_entities,
+                execution_time: receipt.execution_time,
+            })
+            .into_latest(),
+            VersionedReceipt::V2(receipt) => ArchivedReceipt {
+                transaction: receipt.transaction,
+                success: receipt.success,
+                results: receipt.results,
+                logs: receipt.logs,
+                logs_truncated: false,
+                resource_movements: Vec::new(),
+                new_entities: receipt.new_entities,
+                execution_time: receipt.execution_time,
+                auth_requirements: Vec::new(),
+                resources_created: Vec::new(),
+            },
+            VersionedReceipt::V3(receipt) => receipt,
+        }
+    }
 }
 
 macro_rules! prefix {
@@ -166,12 +650,20 @@ impl fmt::Debug for Receipt {
 
         write!(f, "\n{}", "Instructions:".bold().green())?;
         for (i, inst) in self.transaction.instructions.iter().enumerate() {
-            write!(
-                f,
-                "\n{} {:?}",
-                prefix!(i, self.transaction.instructions),
-                inst
-            )?;
+            match inst {
+                Instruction::Comment { text } => write!(
+                    f,
+                    "\n{} {}",
+                    prefix!(i, self.transaction.instructions),
+                    format!("# {}", text).italic()
+                )?,
+                _ => write!(
+                    f,
+                    "\n{} {:?}",
+                    prefix!(i, self.transaction.instructions),
+                    inst
+                )?,
+            }
         }
 
         write!(f, "\n{}", "Results:".bold().green())?;
@@ -180,6 +672,9 @@ impl fmt::Debug for Receipt {
         }
 
         write!(f, "\n{} {}", "Logs:".bold().green(), self.logs.len())?;
+        if self.logs_truncated {
+            write!(f, " {}", "(truncated)".yellow())?;
+        }
         for (i, (level, msg)) in self.logs.iter().enumerate() {
             let (l, m) = match level {
                 LogLevel::Error => ("ERROR".red(), msg.red()),
@@ -210,3 +705,115 @@ impl fmt::Debug for Receipt {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use scrypto::rust::vec;
+
+    #[test]
+    fn test_smart_value_checked_from() {
+        let expected = Decimal::describe();
+        assert!(SmartValue::checked_from(Decimal::from(5), &expected).is_ok());
+
+        let err = SmartValue::checked_from(5u32, &expected).unwrap_err();
+        assert_eq!(err.expected, expected);
+        assert_eq!(err.actual, Type::U32);
+    }
+
+    #[test]
+    fn test_instruction_positions() {
+        let address = RADIX_TOKEN;
+        let amount = Decimal::one();
+        let instructions = vec![
+            Instruction::DeclareTempBucket,
+            Instruction::DeclareTempBucketRef,
+            Instruction::TakeFromContext {
+                amount,
+                resource_address: address,
+                to: Bid(0),
+            },
+            Instruction::BorrowFromContext {
+                amount,
+                resource_address: address,
+                to: Rid(0),
+            },
+            Instruction::CallFunction {
+                package_address: address,
+                blueprint_name: String::new(),
+                function: String::new(),
+                args: Vec::new(),
+            },
+            Instruction::CallMethod {
+                component_address: address,
+                method: String::new(),
+                args: Vec::new(),
+            },
+            Instruction::DropAllBucketRefs,
+            Instruction::DepositAllBuckets { account: address },
+            Instruction::End {
+                signers: Vec::new(),
+            },
+            Instruction::DepositAllOf {
+                resource_address: address,
+                account: address,
+            },
+            Instruction::Comment {
+                text: String::new(),
+            },
+            Instruction::PublishPackageFromBlob {
+                code_hash: sha256([]),
+            },
+            Instruction::CallMethodWithAllResources {
+                component_address: address,
+                method: String::new(),
+            },
+            Instruction::TransferBetweenVaults {
+                from_component: address,
+                to_component: address,
+                resource_address: address,
+                amount,
+                auth: Rid(0),
+            },
+            Instruction::DepositAllBucketsOr {
+                account: address,
+                fallback: address,
+            },
+            Instruction::TakeExactFromContext {
+                amount,
+                resource_address: address,
+                to: Bid(0),
+            },
+            Instruction::AssertWorktopEmpty,
+            Instruction::DepositWorktopRemainder { account: address },
+            Instruction::DeleteComponent {
+                component_address: address,
+            },
+            Instruction::BorrowFromAccount {
+                account: address,
+                amount,
+                resource_address: address,
+                to: Rid(0),
+            },
+            Instruction::AssertWorktopContainsNfts {
+                ids: BTreeSet::new(),
+                resource_address: address,
+            },
+            Instruction::NotBefore { epoch: 0 },
+            Instruction::Deposit {
+                bid: Bid(0),
+                account: address,
+            },
+        ];
+
+        // The variant index follows the enum type id.
+        for (i, instruction) in instructions.iter().enumerate() {
+            assert_eq!(
+                scrypto_encode(instruction)[1] as usize,
+                i,
+                "{:?}",
+                instruction
+            );
+        }
+    }
+}
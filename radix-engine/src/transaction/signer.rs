@@ -0,0 +1,56 @@
+use ed25519_dalek::{ExpandedSecretKey, PublicKey, SecretKey, Verifier};
+use sbor::*;
+use scrypto::rust::convert::TryFrom;
+use scrypto::rust::vec::Vec;
+use scrypto::types::*;
+
+/// Represents an Ed25519 signature of a transaction, along with the signer public key.
+#[derive(Debug, Clone, PartialEq, Eq, TypeId, Encode, Decode)]
+pub struct Signature {
+    pub signer: Address,
+    pub bytes: Vec<u8>,
+}
+
+impl Signature {
+    /// Verifies this signature against the given payload.
+    pub fn verify(&self, payload: &[u8]) -> bool {
+        let public_key = match self.signer {
+            Address::PublicKey(raw) => PublicKey::from_bytes(&raw[1..]),
+            _ => return false,
+        };
+        let signature = ed25519_dalek::Signature::try_from(self.bytes.as_slice());
+
+        match (public_key, signature) {
+            (Ok(public_key), Ok(signature)) => public_key.verify(payload, &signature).is_ok(),
+            _ => false,
+        }
+    }
+}
+
+/// An interface for signing transactions, e.g. by a hardware wallet.
+pub trait Signer {
+    /// Returns the public key address of this signer.
+    fn public_key(&self) -> Address;
+
+    /// Signs the given payload.
+    fn sign(&self, payload: &[u8]) -> Signature;
+}
+
+/// Returns the public key address of an Ed25519 private key.
+pub(crate) fn ed25519_public_key(secret: &[u8; 32]) -> Address {
+    let public = PublicKey::from(&SecretKey::from_bytes(secret).unwrap());
+    let mut raw = [0u8; 33];
+    raw[1..].copy_from_slice(public.as_bytes());
+    Address::PublicKey(raw)
+}
+
+/// Signs the given payload with an Ed25519 private key.
+pub(crate) fn ed25519_sign(secret: &[u8; 32], payload: &[u8]) -> Signature {
+    let secret_key = SecretKey::from_bytes(secret).unwrap();
+    let public = PublicKey::from(&secret_key);
+    let signature = ExpandedSecretKey::from(&secret_key).sign(payload, &public);
+    Signature {
+        signer: ed25519_public_key(secret),
+        bytes: signature.to_bytes().to_vec(),
+    }
+}
@@ -1,4 +1,5 @@
 use sbor::any::*;
+use sbor::describe::Type;
 use sbor::*;
 use scrypto::buffer::*;
 use scrypto::rust::borrow::Borrow;
@@ -29,6 +30,57 @@ pub fn format_data_with_ledger<L: Ledger>(
     format_value(&value, ledger, vaults)
 }
 
+/// Renders a type in a compact, Rust-like form, e.g. `Vec<Decimal>`.
+pub fn render_type(ty: &Type) -> String {
+    match ty {
+        Type::Unit => String::from("()"),
+        Type::Bool => String::from("bool"),
+        Type::I8 => String::from("i8"),
+        Type::I16 => String::from("i16"),
+        Type::I32 => String::from("i32"),
+        Type::I64 => String::from("i64"),
+        Type::I128 => String::from("i128"),
+        Type::U8 => String::from("u8"),
+        Type::U16 => String::from("u16"),
+        Type::U32 => String::from("u32"),
+        Type::U64 => String::from("u64"),
+        Type::U128 => String::from("u128"),
+        Type::String => String::from("String"),
+        Type::Option { value } => format!("Option<{}>", render_type(value)),
+        Type::Box { value } => format!("Box<{}>", render_type(value)),
+        Type::Array { element, length } => format!("[{}; {}]", render_type(element), length),
+        Type::Tuple { elements } => format!("({})", render_types(elements)),
+        Type::Struct { name, .. } | Type::Enum { name, .. } => String::from(name.as_str()),
+        Type::Result { okay, error } => {
+            format!("Result<{}, {}>", render_type(okay), render_type(error))
+        }
+        Type::Vec { element } => format!("Vec<{}>", render_type(element)),
+        Type::TreeSet { element } | Type::HashSet { element } => {
+            format!("Set<{}>", render_type(element))
+        }
+        Type::TreeMap { key, value } | Type::HashMap { key, value } => {
+            format!("Map<{}, {}>", render_type(key), render_type(value))
+        }
+        Type::Custom { name, generics } => {
+            // strip the module path, e.g. `scrypto::types::Decimal`
+            let short = name.rsplit("::").next().unwrap_or(name);
+            if generics.is_empty() {
+                String::from(short)
+            } else {
+                format!("{}<{}>", short, render_types(generics))
+            }
+        }
+    }
+}
+
+fn render_types(types: &[Type]) -> String {
+    types
+        .iter()
+        .map(render_type)
+        .collect::<Vec<String>>()
+        .join(", ")
+}
+
 pub fn format_value<L: Ledger>(
     value: &Value,
     ledger: &L,
@@ -86,7 +138,7 @@ pub fn format_value<L: Ledger>(
             format_map(elements.iter(), "HashMap { ", " }", ledger, vaults)
         }
         // custom types
-        Value::Custom(ty, data) => format_custom(*ty, data, ledger, vaults),
+        Value::Custom { ty, bytes, .. } => format_custom(*ty, bytes, ledger, vaults),
     }
 }
 
@@ -170,6 +222,17 @@ pub fn format_custom<L: Ledger>(
             let h256 = H256::try_from(data).map_err(|_| DecodeError::InvalidCustomData(ty))?;
             Ok(format!("{}", h256))
         }
+        SCRYPTO_TYPE_U256 => {
+            let value = U256::try_from(data).map_err(|_| DecodeError::InvalidCustomData(ty))?;
+            Ok(format!("{}", value))
+        }
+        SCRYPTO_TYPE_I256 => {
+            let value = I256::try_from(data).map_err(|_| DecodeError::InvalidCustomData(ty))?;
+            Ok(format!("{}", value))
+        }
+        SCRYPTO_TYPE_FIXED_POINT => {
+            format_fixed_point(data).map_err(|_| DecodeError::InvalidCustomData(ty))
+        }
         SCRYPTO_TYPE_MID => {
             let mid = Mid::try_from(data).map_err(|_| DecodeError::InvalidCustomData(ty))?;
 
@@ -1,5 +1,7 @@
 mod decoder;
 mod formatter;
+mod oracle;
 
 pub use decoder::decode_data;
-pub use formatter::{format_data, format_data_with_ledger};
+pub use formatter::{format_data, format_data_with_ledger, render_type};
+pub use oracle::{MockOracle, PRICE_FEED_BLUEPRINT};
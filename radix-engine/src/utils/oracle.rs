@@ -0,0 +1,97 @@
+use scrypto::rust::string::ToString;
+use scrypto::rust::vec;
+use scrypto::rust::vec::Vec;
+use scrypto::types::*;
+
+use crate::ledger::*;
+use crate::transaction::*;
+
+/// The name of the blueprint published by `MockOracle`.
+pub const PRICE_FEED_BLUEPRINT: &str = "PriceFeed";
+
+/// A price-feed component for tests, backed by the `PriceFeed` blueprint in `assets/price-feed`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MockOracle {
+    package_address: Address,
+    component_address: Address,
+}
+
+impl MockOracle {
+    /// Publishes the price-feed package and instantiates a component.
+    pub fn new<L: Ledger>(executor: &mut TransactionExecutor<L>) -> Self {
+        let package_address =
+            executor.publish_package(include_bytes!("../../../assets/price-feed.wasm"));
+        let receipt = executor
+            .run(
+                TransactionBuilder::new(executor)
+                    .call_function(package_address, PRICE_FEED_BLUEPRINT, "new", vec![], None)
+                    .build(Vec::new())
+                    .unwrap(),
+                false,
+            )
+            .unwrap();
+        assert!(receipt.success, "Failed to instantiate price feed");
+
+        Self {
+            package_address,
+            component_address: receipt.component(0).unwrap(),
+        }
+    }
+
+    /// Returns the address of the price-feed package.
+    pub fn package_address(&self) -> Address {
+        self.package_address
+    }
+
+    /// Returns the address of the price-feed component.
+    pub fn component_address(&self) -> Address {
+        self.component_address
+    }
+
+    /// Sets the price of a resource.
+    pub fn set_price<L: Ledger>(
+        &self,
+        executor: &mut TransactionExecutor<L>,
+        resource_address: Address,
+        price: Decimal,
+    ) {
+        let receipt = executor
+            .run(
+                TransactionBuilder::new(executor)
+                    .call_method(
+                        self.component_address,
+                        "set_price",
+                        vec![resource_address.to_string(), price.to_string()],
+                        None,
+                    )
+                    .build(Vec::new())
+                    .unwrap(),
+                false,
+            )
+            .unwrap();
+        assert!(receipt.success, "Failed to set price");
+    }
+
+    /// Returns the price of a resource, if set.
+    pub fn get_price<L: Ledger>(
+        &self,
+        executor: &mut TransactionExecutor<L>,
+        resource_address: Address,
+    ) -> Option<Decimal> {
+        let receipt = executor
+            .run(
+                TransactionBuilder::new(executor)
+                    .call_method(
+                        self.component_address,
+                        "get_price",
+                        vec![resource_address.to_string()],
+                        None,
+                    )
+                    .build(Vec::new())
+                    .unwrap(),
+                false,
+            )
+            .unwrap();
+        receipt.return_value(0).unwrap()
+    }
+}
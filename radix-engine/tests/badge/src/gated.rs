@@ -0,0 +1,50 @@
+use scrypto::prelude::*;
+
+blueprint! {
+    struct GatedTest {
+        badge: ResourceDef,
+        opened: u32,
+    }
+
+    impl GatedTest {
+        pub fn new(badge: Address) -> Component {
+            Self {
+                badge: badge.into(),
+                opened: 0,
+            }
+            .instantiate()
+        }
+
+        #[auth(badge)]
+        pub fn open(&mut self) -> u32 {
+            self.opened += 1;
+            self.opened
+        }
+
+        pub fn authorize() -> (u32, Bucket) {
+            let badge = ResourceBuilder::new_fungible(DIVISIBILITY_NONE)
+                .metadata("name", "TestBadge")
+                .initial_supply_fungible(1);
+            let component = Self {
+                badge: badge.resource_def(),
+                opened: 0,
+            }
+            .instantiate();
+
+            // The badge can only be returned once its bucket ref is dropped
+            let opened =
+                badge.authorize(|| component.call::<u32>("open", args!(BucketRef::presented())));
+            (opened, badge)
+        }
+
+        pub fn authorize_leaking() -> Bucket {
+            let badge = ResourceBuilder::new_fungible(DIVISIBILITY_NONE)
+                .metadata("name", "TestBadge")
+                .initial_supply_fungible(1);
+
+            let auth = badge.authorize(BucketRef::presented);
+            auth.drop();
+            badge
+        }
+    }
+}
@@ -1 +1,2 @@
 pub mod badge;
+pub mod gated;
@@ -0,0 +1,37 @@
+use sbor::*;
+use scrypto::prelude::*;
+
+#[derive(TypeId, Encode, Decode, Describe)]
+pub struct Config {
+    pub fee: Decimal,
+    pub admin: Address,
+}
+
+#[derive(TypeId, Encode, Decode, Describe)]
+pub struct Tip {
+    pub payment: Bucket,
+    pub memo: String,
+}
+
+#[derive(TypeId, Encode, Decode, Describe)]
+pub struct UnnamedTip(pub Bucket, pub String);
+
+blueprint! {
+    struct ConfigTest {}
+
+    impl ConfigTest {
+        pub fn fee_and_admin(config: Config) -> (Decimal, Address) {
+            (config.fee, config.admin)
+        }
+
+        pub fn tip(tip: Tip) -> Bucket {
+            info!("{}: {}", tip.memo, tip.payment.amount());
+            tip.payment
+        }
+
+        pub fn unnamed_tip(tip: UnnamedTip) -> Bucket {
+            info!("{}: {}", tip.1, tip.0.amount());
+            tip.0
+        }
+    }
+}
@@ -1 +1,3 @@
+pub mod config;
 pub mod moving;
+pub mod order;
@@ -0,0 +1,37 @@
+use sbor::*;
+use scrypto::prelude::*;
+
+#[derive(TypeId, Encode, Decode, Describe)]
+pub enum Order {
+    Market,
+    Limit(Decimal),
+}
+
+#[derive(TypeId, Encode, Decode, Describe)]
+pub enum Payment {
+    Free,
+    Paid(Bucket, String),
+}
+
+blueprint! {
+    struct OrderTest {}
+
+    impl OrderTest {
+        pub fn limit_price(order: Order) -> Option<Decimal> {
+            match order {
+                Order::Market => None,
+                Order::Limit(price) => Some(price),
+            }
+        }
+
+        pub fn pay(payment: Payment) -> Option<Bucket> {
+            match payment {
+                Payment::Free => None,
+                Payment::Paid(bucket, memo) => {
+                    info!("{}: {}", memo, bucket.amount());
+                    Some(bucket)
+                }
+            }
+        }
+    }
+}
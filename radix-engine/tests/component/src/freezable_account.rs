@@ -0,0 +1,53 @@
+use scrypto::prelude::*;
+
+blueprint! {
+    struct FreezableAccount {
+        vaults: LazyMap<Address, Vault>,
+        frozen: bool,
+    }
+
+    impl FreezableAccount {
+        pub fn new() -> Component {
+            Self {
+                vaults: LazyMap::new(),
+                frozen: false,
+            }
+            .instantiate()
+        }
+
+        pub fn with_owner(owner: Address) -> Component {
+            let component = Self::new();
+            component.set_owner(owner);
+            component
+        }
+
+        pub fn withdraw_all(&mut self, resource_address: Address) -> Bucket {
+            self.vaults.get(&resource_address).unwrap().take_all()
+        }
+
+        pub fn freeze(&mut self) {
+            self.frozen = true;
+        }
+
+        /// Deposits a batch of buckets, and rejects the deposit if this account is frozen.
+        pub fn deposit_batch(&mut self, buckets: Vec<Bucket>) {
+            for bucket in buckets {
+                let address = bucket.resource_address();
+                match self.vaults.get(&address) {
+                    Some(v) => v.put(bucket),
+                    None => self.vaults.insert(address, Vault::with_bucket(bucket)),
+                }
+            }
+
+            // checked after the vaults are updated, so that there is something to roll back
+            assert!(!self.frozen, "Account is frozen");
+        }
+
+        pub fn balance(&self, resource_address: Address) -> Decimal {
+            match self.vaults.get(&resource_address) {
+                Some(v) => v.amount(),
+                None => Decimal::zero(),
+            }
+        }
+    }
+}
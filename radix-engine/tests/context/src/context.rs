@@ -13,5 +13,19 @@ blueprint! {
                 Uuid::generate(),
             )
         }
+
+        pub fn random() -> Vec<u8> {
+            Context::random_bytes(48)
+        }
+
+        pub fn check_staged(resource_address: Address, amount: Decimal) -> bool {
+            if Context::worktop_amount(resource_address) >= amount {
+                info!("Enough staged");
+                true
+            } else {
+                info!("Not enough staged");
+                false
+            }
+        }
     }
 }
@@ -0,0 +1,79 @@
+use scrypto::prelude::*;
+
+#[derive(NftData)]
+pub struct Ticket {
+    pub seat: u32,
+}
+
+blueprint! {
+    struct DepositHookTest {
+        vault: Vault,
+        tickets: Option<Vault>,
+        received: u32,
+        last_deposit: Option<ResourceAmount>,
+        forward_to: Option<Component>,
+    }
+
+    impl DepositHookTest {
+        pub fn new() -> Component {
+            Self {
+                vault: Vault::new(RADIX_TOKEN),
+                tickets: None,
+                received: 0,
+                last_deposit: None,
+                forward_to: None,
+            }
+            .instantiate()
+        }
+
+        pub fn with_deposit(bucket: Bucket) -> Component {
+            Self {
+                vault: Vault::with_bucket(bucket),
+                tickets: None,
+                received: 0,
+                last_deposit: None,
+                forward_to: None,
+            }
+            .instantiate()
+        }
+
+        pub fn new_with_owner(owner: Address) -> Component {
+            let component = Self::new();
+            component.set_owner(owner);
+            component
+        }
+
+        pub fn deposit(&mut self, bucket: Bucket) {
+            self.vault.put(bucket);
+        }
+
+        pub fn set_forward_to(&mut self, component: Address) {
+            self.forward_to = Some(component.into());
+        }
+
+        pub fn mint_tickets(&mut self) {
+            let tickets = ResourceBuilder::new_non_fungible()
+                .metadata("name", "Tickets")
+                .initial_supply_non_fungible([(1, Ticket { seat: 1 }), (2, Ticket { seat: 2 })]);
+            self.tickets = Some(Vault::with_bucket(tickets));
+        }
+
+        pub fn get_received(&self) -> u32 {
+            self.received
+        }
+
+        pub fn get_last_deposit(&self) -> Option<ResourceAmount> {
+            self.last_deposit.clone()
+        }
+
+        pub fn on_deposit(&mut self, deposit: ResourceAmount) {
+            info!("Received {}", deposit);
+            self.received += 1;
+            self.last_deposit = Some(deposit);
+            if let Some(component) = self.forward_to.clone() {
+                let bucket = self.vault.take(1);
+                component.call::<()>("deposit", args!(bucket));
+            }
+        }
+    }
+}
@@ -0,0 +1 @@
+pub mod deposit_hook;
@@ -1,8 +1,20 @@
+use std::cell::RefCell;
 use std::fs;
 use std::process::Command;
+use std::rc::Rc;
+use std::time::Duration;
 
+use radix_engine::engine::{
+    AuthRequirement, Movement, MovementKind, ResourceCreation, RuntimeError, TraceEvent, Track,
+};
 use radix_engine::ledger::*;
+use radix_engine::model::{
+    BucketError, ComponentError, ComponentV1, NftError, ResourceDef, ResourceDefError, Supply,
+    Vault,
+};
 use radix_engine::transaction::*;
+use sbor::describe::{Describe, Type};
+use scrypto::abi;
 use scrypto::prelude::*;
 
 pub fn compile(name: &str) -> Vec<u8> {
@@ -37,7 +49,7 @@ fn test_package() {
         )
         .build(vec![key])
         .unwrap();
-    let receipt1 = executor.run(transaction1, true).unwrap();
+    let receipt1 = executor.sign_and_run(transaction1, true).unwrap();
     assert!(receipt1.success);
 }
 
@@ -53,7 +65,7 @@ fn test_context() {
         .call_function(package, "ContextTest", "query", vec![], Some(account))
         .build(vec![key])
         .unwrap();
-    let receipt1 = executor.run(transaction1, true).unwrap();
+    let receipt1 = executor.sign_and_run(transaction1, true).unwrap();
     assert!(receipt1.success);
 }
 
@@ -76,7 +88,7 @@ fn test_component() {
         )
         .build(vec![key])
         .unwrap();
-    let receipt1 = executor.run(transaction1, true).unwrap();
+    let receipt1 = executor.sign_and_run(transaction1, true).unwrap();
     assert!(receipt1.success);
 
     // Find the component address from receipt
@@ -97,7 +109,7 @@ fn test_component() {
         .deposit_all_buckets(account)
         .build(vec![key])
         .unwrap();
-    let receipt2 = executor.run(transaction2, true).unwrap();
+    let receipt2 = executor.sign_and_run(transaction2, true).unwrap();
     assert!(receipt2.success);
 }
 
@@ -119,7 +131,7 @@ fn test_lazy_map() {
         )
         .build(vec![key])
         .unwrap();
-    let receipt = executor.run(transaction, true).unwrap();
+    let receipt = executor.sign_and_run(transaction, true).unwrap();
     assert!(receipt.success);
 }
 
@@ -139,6 +151,27 @@ fn test_resource_def() {
             vec![],
             Some(account),
         )
+        .call_function(
+            package,
+            "ResourceTest",
+            "create_token_fixed_only",
+            vec![],
+            Some(account),
+        )
+        .call_function(
+            package,
+            "ResourceTest",
+            "create_token_mutable_only",
+            vec![],
+            Some(account),
+        )
+        .call_function(
+            package,
+            "ResourceTest",
+            "create_token_fixed_and_mutable",
+            vec![],
+            Some(account),
+        )
         .call_function(package, "ResourceTest", "query", vec![], Some(account))
         .call_function(package, "ResourceTest", "burn", vec![], Some(account))
         .call_function(
@@ -159,7 +192,7 @@ fn test_resource_def() {
         .deposit_all_buckets(account)
         .build(vec![key])
         .unwrap();
-    let receipt = executor.run(transaction, true).unwrap();
+    let receipt = executor.sign_and_run(transaction, true).unwrap();
     println!("{:?}", receipt);
     assert!(receipt.success);
 
@@ -175,7 +208,7 @@ fn test_resource_def() {
         .deposit_all_buckets(account)
         .build(vec![key])
         .unwrap();
-    let receipt = executor.run(transaction, true).unwrap();
+    let receipt = executor.sign_and_run(transaction, true).unwrap();
     println!("{:?}", receipt);
     assert!(!receipt.success);
 
@@ -191,11 +224,45 @@ fn test_resource_def() {
         .deposit_all_buckets(account)
         .build(vec![key])
         .unwrap();
-    let receipt = executor.run(transaction, true).unwrap();
+    let receipt = executor.sign_and_run(transaction, true).unwrap();
     println!("{:?}", receipt);
     assert!(!receipt.success);
 }
 
+#[test]
+fn test_resource_def_vaults() {
+    let mut ledger = InMemoryLedger::with_bootstrap();
+    let mut executor = TransactionExecutor::new(&mut ledger, 0, 0);
+    let key = executor.new_public_key();
+    let account = executor.new_account(key);
+    let package = executor.publish_package(&compile("resource_def"));
+
+    let transaction = TransactionBuilder::new(&executor)
+        .call_function(package, "ResourceTest", "new_vaults", vec![], Some(account))
+        .deposit_all_buckets(account)
+        .build(vec![key])
+        .unwrap();
+    let receipt = executor.sign_and_run(transaction, false).unwrap();
+    assert!(receipt.success);
+
+    let transaction = TransactionBuilder::new(&executor)
+        .call_function(
+            package,
+            "ResourceTest",
+            "new_vault_with_mismatched_bucket",
+            vec![],
+            Some(account),
+        )
+        .deposit_all_buckets(account)
+        .build(vec![key])
+        .unwrap();
+    let receipt = executor.sign_and_run(transaction, false).unwrap();
+    assert!(!receipt.success);
+    assert!(receipt.logs.iter().any(|(level, message)| {
+        *level == LogLevel::Error && message.starts_with("Panicked at 'Expect bucket of ")
+    }));
+}
+
 #[test]
 fn test_bucket() {
     let mut ledger = InMemoryLedger::with_bootstrap();
@@ -228,7 +295,7 @@ fn test_bucket() {
         .deposit_all_buckets(account)
         .build(vec![key])
         .unwrap();
-    let receipt = executor.run(transaction, true).unwrap();
+    let receipt = executor.sign_and_run(transaction, true).unwrap();
     assert!(receipt.success);
 }
 
@@ -249,7 +316,7 @@ fn test_badge() {
         .deposit_all_buckets(account)
         .build(vec![key])
         .unwrap();
-    let receipt = executor.run(transaction, true).unwrap();
+    let receipt = executor.sign_and_run(transaction, true).unwrap();
     assert!(receipt.success);
 }
 
@@ -274,7 +341,7 @@ fn test_call() {
         .deposit_all_buckets(account)
         .build(vec![key])
         .unwrap();
-    let receipt = executor.run(transaction, true).unwrap();
+    let receipt = executor.sign_and_run(transaction, true).unwrap();
     assert!(receipt.success);
 }
 
@@ -341,7 +408,4316 @@ fn test_nft() {
         .deposit_all_buckets(account)
         .build(vec![key])
         .unwrap();
-    let receipt = executor.run(transaction, true).unwrap();
+    let receipt = executor.sign_and_run(transaction, true).unwrap();
     println!("{:?}", receipt);
     assert!(receipt.success);
 }
+
+#[test]
+fn test_deposit_batch() {
+    let mut ledger = InMemoryLedger::with_bootstrap();
+    let mut executor = TransactionExecutor::new(&mut ledger, 0, 0);
+    let key = executor.new_public_key();
+    let account = executor.new_account(key);
+    let key1 = executor.new_public_key();
+    let account1 = executor.new_account(key1);
+    let key2 = executor.new_public_key();
+    let account2 = executor.new_account(key2);
+
+    let transaction = TransactionBuilder::new(&executor)
+        .withdraw_from_account(
+            &ResourceAmount::Fungible {
+                amount: 100.into(),
+                resource_address: RADIX_TOKEN,
+            },
+            account,
+        )
+        .declare_bucket(|builder, bid1| {
+            builder.declare_bucket(|builder, bid2| {
+                builder
+                    .take_from_context(40.into(), RADIX_TOKEN, bid1)
+                    .take_from_context(60.into(), RADIX_TOKEN, bid2)
+                    .deposit_batch(vec![(bid1, account1), (bid2, account2)])
+            })
+        })
+        .build(vec![key])
+        .unwrap();
+    let balance1 = executor.balance_of(account1, RADIX_TOKEN).unwrap();
+    let balance2 = executor.balance_of(account2, RADIX_TOKEN).unwrap();
+    let receipt = executor.sign_and_run(transaction, true).unwrap();
+    assert!(receipt.success);
+    assert_eq!(
+        executor.balance_of(account1, RADIX_TOKEN).unwrap(),
+        balance1 + 40
+    );
+    assert_eq!(
+        executor.balance_of(account2, RADIX_TOKEN).unwrap(),
+        balance2 + 60
+    );
+}
+
+#[test]
+fn test_max_instructions() {
+    let mut ledger = InMemoryLedger::with_bootstrap();
+    let mut executor = TransactionExecutor::new(&mut ledger, 0, 0);
+    let key = executor.new_public_key();
+    let account = executor.new_account(key);
+
+    // 1 reservation + 3 instructions + `End`
+    let transaction = TransactionBuilder::new(&executor)
+        .withdraw_from_account(
+            &ResourceAmount::Fungible {
+                amount: 100.into(),
+                resource_address: RADIX_TOKEN,
+            },
+            account,
+        )
+        .declare_bucket(|builder, bid| {
+            builder
+                .take_from_context(100.into(), RADIX_TOKEN, bid)
+                .deposit(bid, account)
+        })
+        .build(vec![key])
+        .unwrap();
+
+    executor.set_max_instructions(Some(4));
+    let result = executor.sign_and_run(transaction.clone(), true);
+    assert!(matches!(
+        result,
+        Err(TransactionExecutionError::ValidationError(
+            TransactionValidationError::TooManyInstructions { count: 5, limit: 4 }
+        ))
+    ));
+
+    executor.set_max_instructions(Some(5));
+    let receipt = executor.sign_and_run(transaction, true).unwrap();
+    assert!(receipt.success);
+}
+
+#[test]
+fn test_receipt_archive() {
+    let mut ledger = InMemoryLedger::with_bootstrap();
+    let mut executor = TransactionExecutor::new(&mut ledger, 0, 0);
+    let key = executor.new_public_key();
+    let account = executor.new_account(key);
+
+    let transaction = TransactionBuilder::new(&executor)
+        .withdraw_from_account(
+            &ResourceAmount::Fungible {
+                amount: 100.into(),
+                resource_address: RADIX_TOKEN,
+            },
+            account,
+        )
+        .new_badge_fixed(HashMap::new(), 1.into())
+        .deposit_all_buckets(account)
+        .build(vec![key])
+        .unwrap();
+    let receipt = executor.sign_and_run(transaction, true).unwrap();
+    assert!(receipt.success);
+
+    let archived = receipt.archive();
+    let decoded: VersionedReceipt = scrypto_decode(&scrypto_encode(&archived)).unwrap();
+    assert_eq!(decoded, archived);
+
+    let restored = decoded.into_latest();
+    assert_eq!(restored.transaction, receipt.transaction);
+    assert_eq!(restored.success, receipt.success);
+    assert_eq!(restored.results.len(), receipt.results.len());
+    assert_eq!(restored.logs, receipt.logs);
+    assert_eq!(restored.logs_truncated, receipt.logs_truncated);
+    assert!(!restored.resource_movements.is_empty());
+    assert_eq!(restored.resource_movements, receipt.resource_movements);
+    assert_eq!(restored.new_entities, receipt.new_entities);
+    assert_eq!(restored.execution_time, receipt.execution_time);
+    assert_eq!(restored.auth_requirements, receipt.auth_requirements);
+    assert_eq!(restored.resources_created.len(), 1);
+    assert_eq!(restored.resources_created, receipt.resources_created);
+}
+
+#[test]
+fn test_receipt_archive_v1() {
+    // a V1 archive, as encoded when the format was introduced
+    let hex = [
+        "1100130100000010120600000010120100000030110400000000140212030000008010000000000010632d5e",
+        "c76b0500000000000000821b0000000300000000000000000000000000000000000000000000000000048404",
+        "00000000020000161202000000840400000000020000821b00000002fa5fa5d1d4c0e6a43ec2b4a3e2b5d2ed",
+        "1c8c93e47a2b3c6a5b6e0812010000003082010000001b00000002fa5fa5d1d4c0e6a43ec2b4a3e2b5d2ed1c",
+        "8c93e47a2b3c6a5b6e0100302404000000002000002000010c14000000496e76616c69644275636b65744964",
+        "2835313229002000302301000000020000001102140c0500000068656c6c6f3082010000001b000000030000",
+        "00000000000000000000000000000000000000000000000420010b05000000000000000000000000000000",
+    ]
+    .concat();
+    let bytes: Vec<u8> = (0..hex.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&hex[i..i + 2], 16).unwrap())
+        .collect();
+
+    let account =
+        Address::from_str("02fa5fa5d1d4c0e6a43ec2b4a3e2b5d2ed1c8c93e47a2b3c6a5b6e").unwrap();
+    let decoded: VersionedReceipt = scrypto_decode(&bytes).unwrap();
+    assert!(matches!(decoded, VersionedReceipt::V1(_)));
+    assert_eq!(scrypto_encode(&decoded), bytes);
+
+    let restored = decoded.into_latest();
+    assert_eq!(
+        restored.transaction.instructions(),
+        &[
+            Instruction::DeclareTempBucket,
+            Instruction::TakeFromContext {
+                amount: 100.into(),
+                resource_address: RADIX_TOKEN,
+                to: Bid(512),
+            },
+            Instruction::Deposit {
+                bid: Bid(512),
+                account,
+            },
+            Instruction::End {
+                signers: vec![account],
+            },
+        ]
+    );
+    assert!(restored.transaction.signatures.is_empty());
+    assert!(!restored.success);
+    assert_eq!(
+        restored.results,
+        vec![
+            Ok(None),
+            Ok(None),
+            Err("InvalidBucketId(512)".to_owned()),
+            Ok(None)
+        ]
+    );
+    assert_eq!(restored.logs, vec![(LogLevel::Info, "hello".to_owned())]);
+    assert_eq!(restored.new_entities, vec![RADIX_TOKEN]);
+    assert_eq!(restored.execution_time, Some(5));
+}
+
+#[test]
+fn test_deposit_all_of() {
+    let mut ledger = InMemoryLedger::with_bootstrap();
+    let mut executor = TransactionExecutor::new(&mut ledger, 0, 0);
+    let key = executor.new_public_key();
+    let account = executor.new_account(key);
+    let key2 = executor.new_public_key();
+    let account2 = executor.new_account(key2);
+
+    let transaction = TransactionBuilder::new(&executor)
+        .new_badge_fixed(HashMap::new(), 1.into())
+        .deposit_all_buckets(account)
+        .build(vec![key])
+        .unwrap();
+    let receipt = executor.sign_and_run(transaction, true).unwrap();
+    assert!(receipt.success);
+    let badge = receipt.resource_def(0).unwrap();
+
+    let xrd_amount = ResourceAmount::Fungible {
+        amount: 100.into(),
+        resource_address: RADIX_TOKEN,
+    };
+    let badge_amount = ResourceAmount::Fungible {
+        amount: 1.into(),
+        resource_address: badge,
+    };
+
+    // Only the badge is moved, leaving XRD on the worktop
+    let transaction = TransactionBuilder::new(&executor)
+        .withdraw_from_account(&xrd_amount, account)
+        .withdraw_from_account(&badge_amount, account)
+        .deposit_all_of(badge, account2)
+        .build(vec![key])
+        .unwrap();
+    let receipt = executor.sign_and_run(transaction, true).unwrap();
+    assert!(!receipt.success);
+    assert!(receipt.results[2].is_ok());
+    assert!(matches!(
+        receipt.results[3],
+        Err(RuntimeError::ResourceCheckFailure)
+    ));
+
+    // The exact take proves XRD stayed on the worktop, and nothing else is left after it
+    let balance = executor.balance_of(account, RADIX_TOKEN).unwrap();
+    let transaction = TransactionBuilder::new(&executor)
+        .withdraw_from_account(&xrd_amount, account)
+        .withdraw_from_account(&badge_amount, account)
+        .deposit_all_of(badge, account2)
+        .declare_bucket(|builder, bid| {
+            builder
+                .take_exact_from_context(100.into(), RADIX_TOKEN, bid)
+                .assert_worktop_empty()
+                .deposit(bid, account)
+        })
+        .build(vec![key])
+        .unwrap();
+    let receipt = executor.sign_and_run(transaction, true).unwrap();
+    assert!(receipt.success);
+    assert_eq!(executor.balance_of(account2, badge).unwrap(), 1.into());
+    assert_eq!(executor.balance_of(account, badge).unwrap(), 0.into());
+    assert_eq!(executor.balance_of(account, RADIX_TOKEN).unwrap(), balance);
+}
+
+#[test]
+fn test_tracer() {
+    let events = Rc::new(RefCell::new(Vec::new()));
+    let events2 = events.clone();
+
+    let mut ledger = InMemoryLedger::with_bootstrap();
+    let mut executor =
+        TransactionExecutor::new(&mut ledger, 0, 0).with_tracer(Box::new(move |e: &TraceEvent| {
+            events2.borrow_mut().push(e.clone())
+        }));
+    let key = executor.new_public_key();
+    let account = executor.new_account(key);
+    events.borrow_mut().clear();
+
+    let transaction = TransactionBuilder::new(&executor)
+        .call_method(SYSTEM_COMPONENT, "free_xrd", vec!["100".to_owned()], None)
+        .deposit_all_buckets(account)
+        .build(vec![key])
+        .unwrap();
+    let receipt = executor.sign_and_run(transaction, false).unwrap();
+    assert!(receipt.success);
+
+    let xrd_moved = |depth| TraceEvent::BucketMoved {
+        depth,
+        bid: Bid(0),
+        resource_address: RADIX_TOKEN,
+        amount: 100.into(),
+    };
+    assert_eq!(
+        *events.borrow(),
+        vec![
+            TraceEvent::InstructionStarted { index: 0 },
+            TraceEvent::CallStarted {
+                depth: 1,
+                package_address: SYSTEM_PACKAGE,
+                export_name: "System_main".to_owned(),
+                function: "free_xrd".to_owned(),
+            },
+            xrd_moved(1),
+            TraceEvent::CallEnded {
+                depth: 1,
+                success: true
+            },
+            TraceEvent::InstructionEnded {
+                index: 0,
+                success: true
+            },
+            TraceEvent::InstructionStarted { index: 1 },
+            xrd_moved(0),
+            TraceEvent::CallStarted {
+                depth: 1,
+                package_address: ACCOUNT_PACKAGE,
+                export_name: "Account_main".to_owned(),
+                function: "deposit_batch".to_owned(),
+            },
+            TraceEvent::CallEnded {
+                depth: 1,
+                success: true
+            },
+            TraceEvent::InstructionEnded {
+                index: 1,
+                success: true
+            },
+            TraceEvent::InstructionStarted { index: 2 },
+            TraceEvent::InstructionEnded {
+                index: 2,
+                success: true
+            },
+        ]
+    );
+}
+
+struct Ed25519Signer {
+    keypair: ed25519_dalek::Keypair,
+}
+
+impl Ed25519Signer {
+    fn new(seed: u8) -> Self {
+        let secret = ed25519_dalek::SecretKey::from_bytes(&[seed; 32]).unwrap();
+        let public = ed25519_dalek::PublicKey::from(&secret);
+        Self {
+            keypair: ed25519_dalek::Keypair { secret, public },
+        }
+    }
+}
+
+impl Signer for Ed25519Signer {
+    fn public_key(&self) -> Address {
+        let mut raw = [0u8; 33];
+        raw[1..].copy_from_slice(self.keypair.public.as_bytes());
+        Address::PublicKey(raw)
+    }
+
+    fn sign(&self, payload: &[u8]) -> Signature {
+        Signature {
+            signer: self.public_key(),
+            bytes: ed25519_dalek::Signer::sign(&self.keypair, payload)
+                .to_bytes()
+                .to_vec(),
+        }
+    }
+}
+
+#[test]
+fn test_signed_transaction() {
+    let mut ledger = InMemoryLedger::with_bootstrap();
+    let mut executor = TransactionExecutor::new(&mut ledger, 0, 0);
+    executor.set_require_signatures(true);
+    let signer = Ed25519Signer::new(1);
+    let key = signer.public_key();
+    let account = executor.new_account(key);
+
+    let transaction = TransactionBuilder::new(&executor)
+        .withdraw_from_account(
+            &ResourceAmount::Fungible {
+                amount: 100.into(),
+                resource_address: RADIX_TOKEN,
+            },
+            account,
+        )
+        .deposit_all_buckets(account)
+        .build_signed(&[&signer])
+        .unwrap();
+
+    let mut instructions = transaction.instructions().to_vec();
+    instructions[0] = Instruction::CallMethod {
+        component_address: account,
+        method: "withdraw".to_owned(),
+        args: vec![
+            SmartValue::from(Decimal::from(1000)),
+            SmartValue::from(RADIX_TOKEN),
+        ],
+    };
+    let mut tampered = Transaction::from_instructions(instructions, Vec::new());
+    tampered.signatures = transaction.signatures.clone();
+    let result = executor.run(tampered.clone(), true);
+    assert!(matches!(
+        result,
+        Err(TransactionExecutionError::ValidationError(
+            TransactionValidationError::InvalidSignature(signer)
+        )) if signer == key
+    ));
+
+    // Stripping the signature from a tampered transaction doesn't get it accepted either
+    tampered.signatures.clear();
+    assert!(matches!(
+        executor.run(tampered, true),
+        Err(TransactionExecutionError::ValidationError(
+            TransactionValidationError::MissingSignature(signer)
+        )) if signer == key
+    ));
+
+    let receipt = executor.run(transaction, true).unwrap();
+    assert!(receipt.success);
+}
+
+#[test]
+fn test_missing_signature() {
+    let mut ledger = InMemoryLedger::with_bootstrap();
+    let mut executor = TransactionExecutor::new(&mut ledger, 0, 0);
+    executor.set_require_signatures(true);
+    let signer = Ed25519Signer::new(1);
+    let key = signer.public_key();
+    let other_key = Ed25519Signer::new(2).public_key();
+    let account = executor.new_account(key);
+
+    let mut builder = TransactionBuilder::new(&executor);
+    builder
+        .withdraw_from_account(
+            &ResourceAmount::Fungible {
+                amount: 100.into(),
+                resource_address: RADIX_TOKEN,
+            },
+            account,
+        )
+        .deposit_all_buckets(account);
+    let transaction = builder.build_signed(&[&signer]).unwrap();
+    let mut partially_signed = builder.build(vec![key, other_key]).unwrap();
+    partially_signed.signatures = vec![signer.sign(partially_signed.hash().as_ref())];
+
+    // Stripped signatures are rejected
+    let mut stripped = transaction.clone();
+    stripped.signatures.clear();
+    assert!(matches!(
+        executor.run(stripped, false),
+        Err(TransactionExecutionError::ValidationError(
+            TransactionValidationError::MissingSignature(signer)
+        )) if signer == key
+    ));
+
+    // A signer without signature is rejected as soon as any signature is attached, even
+    // when signatures aren't required
+    executor.set_require_signatures(false);
+    assert!(matches!(
+        executor.run(partially_signed, false),
+        Err(TransactionExecutionError::ValidationError(
+            TransactionValidationError::MissingSignature(signer)
+        )) if signer == other_key
+    ));
+
+    executor.set_require_signatures(true);
+    let receipt = executor.run(transaction, false).unwrap();
+    assert!(receipt.success);
+}
+
+#[test]
+fn test_sign_and_run() {
+    let mut ledger = InMemoryLedger::with_bootstrap();
+    let mut executor = TransactionExecutor::new(&mut ledger, 0, 0);
+    executor.set_require_signatures(true);
+    let key = executor.new_public_key();
+    let account = executor.new_account(key);
+
+    let transaction = TransactionBuilder::new(&executor)
+        .withdraw_from_account(
+            &ResourceAmount::Fungible {
+                amount: 100.into(),
+                resource_address: RADIX_TOKEN,
+            },
+            account,
+        )
+        .deposit_all_buckets(account)
+        .build(vec![key])
+        .unwrap();
+
+    // Keys generated by the executor aren't signed for implicitly
+    assert!(matches!(
+        executor.run(transaction.clone(), false),
+        Err(TransactionExecutionError::ValidationError(
+            TransactionValidationError::MissingSignature(signer)
+        )) if signer == key
+    ));
+
+    let receipt = executor.sign_and_run(transaction, false).unwrap();
+    assert!(receipt.success);
+}
+
+#[test]
+fn test_deposit_hook() {
+    let mut ledger = InMemoryLedger::with_bootstrap();
+    let mut executor = TransactionExecutor::new(&mut ledger, 0, 0);
+    let key = executor.new_public_key();
+    let account = executor.new_account(key);
+    let package = executor.publish_package(&compile("deposit_hook"));
+
+    let transaction = TransactionBuilder::new(&executor)
+        .call_function(package, "DepositHookTest", "new", vec![], Some(account))
+        .build(vec![key])
+        .unwrap();
+    let receipt = executor.sign_and_run(transaction, true).unwrap();
+    assert!(receipt.success);
+    let component = receipt.component(0).unwrap();
+
+    let transaction = TransactionBuilder::new(&executor)
+        .call_method(
+            component,
+            "deposit",
+            vec![format!("100,{}", RADIX_TOKEN)],
+            Some(account),
+        )
+        .call_method(component, "get_received", vec![], Some(account))
+        .build(vec![key])
+        .unwrap();
+    let receipt = executor.sign_and_run(transaction, true).unwrap();
+    assert!(receipt.success);
+    // The bucket argument is withdrawn, declared and taken before the `deposit` call
+    let get_received = 4;
+    assert_eq!(receipt.return_value::<u32>(get_received).unwrap(), 1);
+    let last_deposit = |executor: &mut TransactionExecutor<InMemoryLedger>| {
+        let transaction = TransactionBuilder::new(executor)
+            .call_method(component, "get_last_deposit", vec![], None)
+            .build(vec![])
+            .unwrap();
+        let receipt = executor.sign_and_run(transaction, false).unwrap();
+        receipt.return_value::<Option<ResourceAmount>>(0).unwrap()
+    };
+    assert_eq!(
+        last_deposit(&mut executor),
+        Some(ResourceAmount::Fungible {
+            amount: 100.into(),
+            resource_address: RADIX_TOKEN,
+        })
+    );
+
+    // NFTs are passed to the hook with their ids
+    let transaction = TransactionBuilder::new(&executor)
+        .call_method(component, "mint_tickets", vec![], None)
+        .build(vec![])
+        .unwrap();
+    let receipt = executor.sign_and_run(transaction, false).unwrap();
+    assert!(receipt.success);
+    assert_eq!(
+        last_deposit(&mut executor),
+        Some(ResourceAmount::NonFungible {
+            ids: [1, 2].into_iter().collect(),
+            resource_address: receipt.resource_def(0).unwrap(),
+        })
+    );
+
+    // The hook can't be called from a transaction
+    let transaction = TransactionBuilder::new(&executor)
+        .call_method(
+            component,
+            "on_deposit",
+            vec![format!("Fungible(100,{})", RADIX_TOKEN)],
+            Some(account),
+        )
+        .build(vec![key])
+        .unwrap();
+    let receipt = executor.sign_and_run(transaction, true).unwrap();
+    assert!(!receipt.success);
+    assert!(matches!(
+        receipt.results.last().unwrap(),
+        Err(RuntimeError::DepositHookNotCallable(_))
+    ));
+
+    // The hook is invoked for transfers between vaults too
+    let transaction = TransactionBuilder::new(&executor)
+        .call_function(
+            package,
+            "DepositHookTest",
+            "new_with_owner",
+            vec![key.to_string()],
+            Some(account),
+        )
+        .build(vec![key])
+        .unwrap();
+    let receipt = executor.sign_and_run(transaction, true).unwrap();
+    assert!(receipt.success);
+    let source = receipt.component(0).unwrap();
+
+    let transaction = TransactionBuilder::new(&executor)
+        .call_method(
+            source,
+            "deposit",
+            vec![format!("100,{}", RADIX_TOKEN)],
+            Some(account),
+        )
+        .call_method(SYSTEM_COMPONENT, "free_xrd", vec!["1".to_owned()], None)
+        .declare_bucket_ref(|builder, rid| {
+            builder.borrow_from_context(1.into(), RADIX_TOKEN, rid);
+            builder.transfer_between_vaults(source, component, RADIX_TOKEN, 100.into(), rid)
+        })
+        .deposit_all_buckets(account)
+        .build(vec![key])
+        .unwrap();
+    let receipt = executor.sign_and_run(transaction, true).unwrap();
+    assert!(receipt.success);
+    let transfer: Vec<(MovementKind, Option<Address>, Option<Address>)> = receipt
+        .resource_movements
+        .iter()
+        .filter(|m| m.amount == 100.into() && m.from == Some(source))
+        .map(|m| (m.kind, m.from, m.to))
+        .collect();
+    assert_eq!(
+        transfer,
+        vec![
+            (MovementKind::Withdrawn, Some(source), Some(component)),
+            (MovementKind::Deposited, Some(source), Some(component)),
+        ]
+    );
+
+    let transaction = TransactionBuilder::new(&executor)
+        .call_method(component, "get_received", vec![], None)
+        .call_method(source, "get_received", vec![], None)
+        .build(vec![key])
+        .unwrap();
+    let receipt = executor.sign_and_run(transaction, true).unwrap();
+    assert!(receipt.success);
+    assert_eq!(receipt.return_value::<u32>(0).unwrap(), 3);
+    assert_eq!(receipt.return_value::<u32>(1).unwrap(), 1);
+}
+
+#[test]
+fn test_deposit_hook_not_run_for_functions() {
+    let mut ledger = InMemoryLedger::with_bootstrap();
+    let mut executor = TransactionExecutor::new(&mut ledger, 0, 0);
+    let key = executor.new_public_key();
+    let account = executor.new_account(key);
+    let package = executor.publish_package(&compile("deposit_hook"));
+
+    // The vault of the new component is filled by a function
+    let transaction = TransactionBuilder::new(&executor)
+        .call_function(
+            package,
+            "DepositHookTest",
+            "with_deposit",
+            vec![format!("100,{}", RADIX_TOKEN)],
+            Some(account),
+        )
+        .build(vec![key])
+        .unwrap();
+    let receipt = executor.sign_and_run(transaction, true).unwrap();
+    assert!(receipt.success);
+    let component = receipt.component(0).unwrap();
+
+    let transaction = TransactionBuilder::new(&executor)
+        .call_method(component, "get_received", vec![], None)
+        .call_method(component, "get_last_deposit", vec![], None)
+        .build(vec![])
+        .unwrap();
+    let receipt = executor.sign_and_run(transaction, true).unwrap();
+    assert!(receipt.success);
+    assert_eq!(receipt.return_value::<u32>(0).unwrap(), 0);
+    assert_eq!(
+        receipt.return_value::<Option<ResourceAmount>>(1).unwrap(),
+        None
+    );
+}
+
+#[test]
+fn test_deposit_hook_reentrancy() {
+    let mut ledger = InMemoryLedger::with_bootstrap();
+    let mut executor = TransactionExecutor::new(&mut ledger, 0, 0);
+    let key = executor.new_public_key();
+    let account = executor.new_account(key);
+    let package = executor.publish_package(&compile("deposit_hook"));
+
+    let transaction = TransactionBuilder::new(&executor)
+        .call_function(package, "DepositHookTest", "new", vec![], Some(account))
+        .call_function(package, "DepositHookTest", "new", vec![], Some(account))
+        .build(vec![key])
+        .unwrap();
+    let receipt = executor.sign_and_run(transaction, true).unwrap();
+    assert!(receipt.success);
+    let component1 = receipt.component(0).unwrap();
+    let component2 = receipt.component(1).unwrap();
+
+    // A hook may deposit into another hooked component
+    let transaction = TransactionBuilder::new(&executor)
+        .call_method(
+            component1,
+            "set_forward_to",
+            vec![component2.to_string()],
+            None,
+        )
+        .call_method(
+            component1,
+            "deposit",
+            vec![format!("100,{}", RADIX_TOKEN)],
+            Some(account),
+        )
+        .call_method(component2, "get_received", vec![], None)
+        .build(vec![key])
+        .unwrap();
+    let receipt = executor.sign_and_run(transaction, true).unwrap();
+    assert!(receipt.success);
+    let get_received = receipt.results.len() - 1;
+    assert_eq!(receipt.return_value::<u32>(get_received).unwrap(), 1);
+
+    // But a hook receiving resources while it's running fails, rather than recursing
+    for component in [component1, component2] {
+        let transaction = TransactionBuilder::new(&executor)
+            .call_method(
+                component2,
+                "set_forward_to",
+                vec![component.to_string()],
+                None,
+            )
+            .call_method(
+                component1,
+                "deposit",
+                vec![format!("100,{}", RADIX_TOKEN)],
+                Some(account),
+            )
+            .build(vec![key])
+            .unwrap();
+        let receipt = executor.sign_and_run(transaction, true).unwrap();
+        assert!(!receipt.success);
+        assert!(matches!(
+            receipt.results.last().unwrap().as_ref().unwrap_err().root_cause(),
+            RuntimeError::DepositHookReentered(c) if *c == component
+        ));
+    }
+}
+
+#[test]
+fn test_ledger_diff() {
+    let mut ledger = InMemoryLedger::with_bootstrap();
+    let vaults_of = |diff: &StateDiff| -> HashSet<SubstateId> {
+        diff.added
+            .iter()
+            .filter(|id| matches!(id, SubstateId::Vault(_)))
+            .cloned()
+            .collect()
+    };
+
+    // Find the vaults owned by each account
+    let snapshot = ledger.snapshot();
+    let mut executor = TransactionExecutor::new(&mut ledger, 0, 0);
+    let key1 = executor.new_public_key();
+    let account1 = executor.new_account(key1);
+    let vaults1 = vaults_of(&ledger.diff(&snapshot));
+    let snapshot = ledger.snapshot();
+    let mut executor = TransactionExecutor::new(&mut ledger, 0, 10);
+    let key2 = executor.new_public_key();
+    let account2 = executor.new_account(key2);
+    let vaults2 = vaults_of(&ledger.diff(&snapshot));
+    assert_eq!(vaults1.len(), 1);
+    assert_eq!(vaults2.len(), 1);
+
+    // Transfer from account1 to account2
+    let snapshot = ledger.snapshot();
+    let mut executor = TransactionExecutor::new(&mut ledger, 0, 20);
+    let transaction = TransactionBuilder::new(&executor)
+        .withdraw_from_account(
+            &ResourceAmount::Fungible {
+                amount: 100.into(),
+                resource_address: RADIX_TOKEN,
+            },
+            account1,
+        )
+        .deposit_all_buckets(account2)
+        .build(vec![key1])
+        .unwrap();
+    let receipt = executor.run(transaction, false).unwrap();
+    assert!(receipt.success);
+
+    let diff = ledger.diff(&snapshot);
+    assert!(diff.added.is_empty());
+    assert!(diff.removed.is_empty());
+    assert_eq!(
+        diff.changed.into_iter().collect::<HashSet<SubstateId>>(),
+        vaults1.union(&vaults2).cloned().collect()
+    );
+
+    ledger.restore(&snapshot);
+    assert!(ledger.diff(&snapshot).is_empty());
+}
+
+#[test]
+fn test_label() {
+    let mut ledger = InMemoryLedger::with_bootstrap();
+    let mut executor = TransactionExecutor::new(&mut ledger, 0, 0);
+    let key = executor.new_public_key();
+    let account = executor.new_account(key);
+
+    let transaction = TransactionBuilder::new(&executor)
+        .label("Withdraw 100 XRD")
+        .withdraw_from_account(
+            &ResourceAmount::Fungible {
+                amount: 100.into(),
+                resource_address: RADIX_TOKEN,
+            },
+            account,
+        )
+        .label("Put it back")
+        .deposit_all_buckets(account)
+        .build(vec![key])
+        .unwrap();
+    let receipt = executor.sign_and_run(transaction, false).unwrap();
+    assert!(receipt.success);
+
+    let rendered = format!("{:?}", receipt);
+    assert!(rendered.contains("# Withdraw 100 XRD"));
+    assert!(rendered.contains("# Put it back"));
+}
+
+#[test]
+fn test_fixed_supply_granularity() {
+    let address = Address::ResourceDef([1u8; 26]);
+    let supply: Decimal = "1.5".parse().unwrap();
+
+    let result = ResourceDef::new_fixed(address, 0, HashMap::new(), supply);
+    assert!(matches!(result, Err(ResourceDefError::InvalidAmount(a)) if a == supply));
+
+    let (resource_def, bucket) =
+        ResourceDef::new_fixed(address, 1, HashMap::new(), supply).unwrap();
+    assert_eq!(resource_def.total_supply(), supply);
+    assert_eq!(bucket.amount(), supply);
+
+    // The same rule applies to resources created by transactions
+    let mut ledger = InMemoryLedger::with_bootstrap();
+    let mut executor = TransactionExecutor::new(&mut ledger, 0, 0);
+    let key = executor.new_public_key();
+    let account = executor.new_account(key);
+    let transaction = TransactionBuilder::new(&executor)
+        .new_badge_fixed(HashMap::new(), supply)
+        .deposit_all_buckets(account)
+        .build(vec![key])
+        .unwrap();
+    let receipt = executor.sign_and_run(transaction, false).unwrap();
+    assert!(!receipt.success);
+}
+
+#[test]
+fn test_publish_package_blob() {
+    let mut ledger = InMemoryLedger::with_bootstrap();
+    let mut executor = TransactionExecutor::new(&mut ledger, 0, 0);
+    let code = include_bytes!("../../assets/account.wasm");
+
+    let mut builder = TransactionBuilder::new(&executor);
+    let code_hash = builder.add_blob(code);
+    let transaction = builder
+        .publish_package_blob(code_hash)
+        .build(vec![])
+        .unwrap();
+    assert!(scrypto_encode(&transaction.instructions().to_vec()).len() < 1024);
+    let receipt = executor.sign_and_run(transaction, false).unwrap();
+    assert!(receipt.success);
+
+    let package = receipt.package(0).unwrap();
+    assert_eq!(ledger.get_package(package).unwrap().code(), code);
+
+    let mut ledger = InMemoryLedger::with_bootstrap();
+    let mut executor = TransactionExecutor::new(&mut ledger, 0, 0);
+    let transaction = TransactionBuilder::new(&executor)
+        .publish_package_blob(code_hash)
+        .build(vec![])
+        .unwrap();
+    let result = executor.sign_and_run(transaction, false);
+    assert!(matches!(
+        result,
+        Err(TransactionExecutionError::ValidationError(
+            TransactionValidationError::BlobNotFound(hash)
+        )) if hash == code_hash
+    ));
+}
+
+#[test]
+fn test_return_value() {
+    let mut ledger = InMemoryLedger::with_bootstrap();
+    let mut executor = TransactionExecutor::new(&mut ledger, 0, 0);
+    let key = executor.new_public_key();
+    let account = executor.new_account(key);
+
+    let transaction = TransactionBuilder::new(&executor)
+        .call_method(SYSTEM_COMPONENT, "free_xrd", vec!["100".to_owned()], None)
+        .drop_all_bucket_refs()
+        .deposit_all_buckets(account)
+        .build(vec![key])
+        .unwrap();
+    let receipt = executor.sign_and_run(transaction, false).unwrap();
+    assert!(receipt.success);
+    assert!(receipt.return_value::<Bid>(0).is_ok());
+    assert!(matches!(
+        receipt.return_value::<String>(0),
+        Err(ReturnError::DecodeError(_))
+    ));
+    assert!(matches!(
+        receipt.return_value::<Bid>(1),
+        Err(ReturnError::NoReturnValue(1))
+    ));
+    assert!(matches!(
+        receipt.return_value::<Bid>(100),
+        Err(ReturnError::IndexOutOfRange(100))
+    ));
+
+    let transaction = TransactionBuilder::new(&executor)
+        .withdraw_from_account(
+            &ResourceAmount::Fungible {
+                amount: 100_000_000.into(),
+                resource_address: RADIX_TOKEN,
+            },
+            account,
+        )
+        .deposit_all_buckets(account)
+        .build(vec![key])
+        .unwrap();
+    let receipt = executor.sign_and_run(transaction, false).unwrap();
+    assert!(!receipt.success);
+    assert!(matches!(
+        receipt.return_value::<Bid>(0),
+        Err(ReturnError::InstructionFailed(0))
+    ));
+}
+
+#[test]
+fn test_recall() {
+    let mut ledger = InMemoryLedger::with_bootstrap();
+    let mut executor = TransactionExecutor::new(&mut ledger, 0, 0);
+    let key = executor.new_public_key();
+    let account = executor.new_account(key);
+    let package = executor.publish_package(&compile("recall"));
+
+    let transaction = TransactionBuilder::new(&executor)
+        .call_function(package, "RecallTest", "create_token", vec![], Some(account))
+        .deposit_all_buckets(account)
+        .build(vec![key])
+        .unwrap();
+    let receipt = executor.sign_and_run(transaction, true).unwrap();
+    assert!(receipt.success);
+    let badge = receipt.resource_def(0).unwrap();
+    let token = receipt.resource_def(1).unwrap();
+
+    let transaction = TransactionBuilder::new(&executor)
+        .call_function(
+            package,
+            "RecallTest",
+            "new",
+            vec![format!("100,{}", token)],
+            Some(account),
+        )
+        .build(vec![key])
+        .unwrap();
+    let receipt = executor.sign_and_run(transaction, true).unwrap();
+    assert!(receipt.success);
+    let component = receipt.component(0).unwrap();
+
+    // Recall with the admin badge
+    let transaction = TransactionBuilder::new(&executor)
+        .call_function(
+            package,
+            "RecallTest",
+            "recall",
+            vec![
+                token.to_string(),
+                component.to_string(),
+                "30".to_owned(),
+                format!("1,{}", badge),
+            ],
+            Some(account),
+        )
+        .call_method(component, "amount", vec![], Some(account))
+        .drop_all_bucket_refs()
+        .deposit_all_buckets(account)
+        .build(vec![key])
+        .unwrap();
+    let receipt = executor.sign_and_run(transaction, true).unwrap();
+    println!("{:?}", receipt);
+    assert!(receipt.success);
+    let amount: Decimal = receipt.return_value(4).unwrap();
+    assert_eq!(amount, 70.into());
+    assert!(receipt
+        .resource_movements
+        .iter()
+        .any(|m| m.kind == MovementKind::Withdrawn
+            && m.resource == token
+            && m.amount == 30.into()
+            && m.from == Some(component)));
+
+    // Recall with an unrelated badge
+    let transaction = TransactionBuilder::new(&executor)
+        .call_function(
+            package,
+            "RecallTest",
+            "recall",
+            vec![
+                token.to_string(),
+                component.to_string(),
+                "30".to_owned(),
+                format!("1,{}", RADIX_TOKEN),
+            ],
+            Some(account),
+        )
+        .drop_all_bucket_refs()
+        .deposit_all_buckets(account)
+        .build(vec![key])
+        .unwrap();
+    let receipt = executor.sign_and_run(transaction, true).unwrap();
+    println!("{:?}", receipt);
+    assert!(!receipt.success);
+}
+
+#[test]
+fn test_replay() {
+    let mut ledger = InMemoryLedger::with_bootstrap();
+    let mut executor = TransactionExecutor::new(&mut ledger, 0, 0).with_recording();
+    let key1 = executor.new_public_key();
+    let account1 = executor.new_account(key1);
+    let key2 = executor.new_public_key();
+    let account2 = executor.new_account(key2);
+    executor.set_current_epoch(5);
+    let transaction = TransactionBuilder::new(&executor)
+        .withdraw_from_account(
+            &ResourceAmount::Fungible {
+                amount: 100.into(),
+                resource_address: RADIX_TOKEN,
+            },
+            account1,
+        )
+        .deposit_all_buckets(account2)
+        .build(vec![key1])
+        .unwrap();
+    let receipt = executor.sign_and_run(transaction, false).unwrap();
+    assert!(receipt.success);
+    let package = executor.publish_package(&compile("deposit_hook"));
+    let transaction = TransactionBuilder::new(&executor)
+        .call_function(package, "DepositHookTest", "new", vec![], Some(account1))
+        .build(vec![key1])
+        .unwrap();
+    let receipt = executor.sign_and_run(transaction, false).unwrap();
+    assert!(receipt.success);
+    assert_eq!(executor.recorded().len(), 5);
+
+    // Round trip the log through its serialized form
+    let mut log: Vec<AppliedTransaction> =
+        scrypto_decode(&scrypto_encode(&executor.recorded().to_vec())).unwrap();
+
+    let mut replayed = InMemoryLedger::with_bootstrap();
+    TransactionExecutor::replay(&mut replayed, &log).unwrap();
+    assert!(replayed.diff(&ledger.snapshot()).is_empty());
+    assert_eq!(replayed.state_root(), ledger.state_root());
+
+    // A replay which ends up in a different state is rejected
+    log[3].state_root = H256([0u8; 32]);
+    let mut replayed = InMemoryLedger::with_bootstrap();
+    assert!(matches!(
+        TransactionExecutor::replay(&mut replayed, &log),
+        Err(TransactionExecutionError::ReplayDiverged(nonce)) if nonce == log[3].nonce
+    ));
+}
+
+#[test]
+fn test_state_root() {
+    let mut ledger1 = InMemoryLedger::with_bootstrap();
+    let mut ledger2 = InMemoryLedger::with_bootstrap();
+    assert_eq!(ledger1.state_root(), ledger2.state_root());
+
+    // Generated keys are random, so both ledgers use the same fixed key
+    let key = Ed25519Signer::new(1).public_key();
+    let mut accounts = Vec::new();
+    for ledger in [&mut ledger1, &mut ledger2] {
+        let mut executor = TransactionExecutor::new(ledger, 0, 0);
+        accounts.push(executor.new_account(key));
+    }
+    assert_eq!(accounts[0], accounts[1]);
+    assert_eq!(ledger1.state_root(), ledger2.state_root());
+
+    // Diverge by claiming free XRD on one ledger only
+    let account = accounts[0];
+    let mut executor = TransactionExecutor::new(&mut ledger2, 0, 10);
+    let transaction = TransactionBuilder::new(&executor)
+        .call_method(SYSTEM_COMPONENT, "free_xrd", vec!["1".to_owned()], None)
+        .deposit_all_buckets(account)
+        .build(vec![])
+        .unwrap();
+    assert!(executor.run(transaction, false).unwrap().success);
+    assert_ne!(ledger1.state_root(), ledger2.state_root());
+}
+
+#[test]
+fn test_mock_oracle() {
+    let mut ledger = InMemoryLedger::with_bootstrap();
+    let mut executor = TransactionExecutor::new(&mut ledger, 0, 0);
+    let oracle = executor.new_mock_oracle();
+    assert_eq!(oracle.get_price(&mut executor, RADIX_TOKEN), None);
+
+    oracle.set_price(&mut executor, RADIX_TOKEN, Decimal::from(5));
+    assert_eq!(
+        oracle.get_price(&mut executor, RADIX_TOKEN),
+        Some(Decimal::from(5))
+    );
+
+    let package = executor.publish_package(&compile("oracle"));
+    let transaction = TransactionBuilder::new(&executor)
+        .call_function(
+            package,
+            "PriceConsumer",
+            "new",
+            vec![oracle.component_address().to_string()],
+            None,
+        )
+        .build(Vec::new())
+        .unwrap();
+    let receipt = executor.sign_and_run(transaction, false).unwrap();
+    assert!(receipt.success);
+    let consumer = receipt.component(0).unwrap();
+
+    let transaction = TransactionBuilder::new(&executor)
+        .call_method(consumer, "read_price", vec![RADIX_TOKEN.to_string()], None)
+        .build(Vec::new())
+        .unwrap();
+    let receipt = executor.sign_and_run(transaction, false).unwrap();
+    assert!(receipt.success);
+    assert_eq!(
+        receipt.return_value::<Option<Decimal>>(0).unwrap(),
+        Some(Decimal::from(5))
+    );
+}
+
+#[test]
+fn test_builder_reset() {
+    let mut ledger = InMemoryLedger::with_bootstrap();
+    let mut executor = TransactionExecutor::new(&mut ledger, 0, 0);
+    let key = executor.new_public_key();
+    let account = executor.new_account(key);
+
+    let mut builder = TransactionBuilder::new(&executor);
+    let mut bids = Vec::new();
+    let mut transactions = Vec::new();
+    for _ in 0..2 {
+        builder
+            .reset()
+            .declare_bucket(|builder, bid| {
+                bids.push(bid);
+                builder.take_from_context(100.into(), RADIX_TOKEN, bid)
+            })
+            .withdraw_from_account(
+                &ResourceAmount::Fungible {
+                    amount: 100.into(),
+                    resource_address: RADIX_TOKEN,
+                },
+                account,
+            );
+        transactions.push(builder.build(vec![key]).unwrap());
+    }
+    assert_eq!(bids[0], bids[1]);
+    assert_eq!(transactions[0], transactions[1]);
+
+    let fresh = TransactionBuilder::new(&executor)
+        .declare_bucket(|builder, bid| builder.take_from_context(100.into(), RADIX_TOKEN, bid))
+        .withdraw_from_account(
+            &ResourceAmount::Fungible {
+                amount: 100.into(),
+                resource_address: RADIX_TOKEN,
+            },
+            account,
+        )
+        .build(vec![key])
+        .unwrap();
+    assert_eq!(transactions[1], fresh);
+}
+
+#[test]
+fn test_sweep_all_resources() {
+    let mut ledger = InMemoryLedger::with_bootstrap();
+    let mut executor = TransactionExecutor::new(&mut ledger, 0, 0);
+    let key = executor.new_public_key();
+    let account = executor.new_account(key);
+
+    for n in 0..4 {
+        let mut builder = TransactionBuilder::new(&executor);
+        for _ in 0..n {
+            builder.call_method(SYSTEM_COMPONENT, "free_xrd", vec!["100".to_owned()], None);
+        }
+        let transaction = builder
+            .call_method_with_all_resources(account, "deposit_batch")
+            .build(vec![key])
+            .unwrap();
+        let receipt = executor.sign_and_run(transaction, false).unwrap();
+        assert!(receipt.success);
+    }
+
+    let transaction = TransactionBuilder::new(&executor)
+        .withdraw_from_account(
+            &ResourceAmount::Fungible {
+                amount: 1_000_600.into(),
+                resource_address: RADIX_TOKEN,
+            },
+            account,
+        )
+        .call_method_with_all_resources(account, "deposit_batch")
+        .build(vec![key])
+        .unwrap();
+    let receipt = executor.sign_and_run(transaction, false).unwrap();
+    assert!(receipt.success);
+}
+
+struct OverloadedAbiProvider;
+
+impl AbiProvider for OverloadedAbiProvider {
+    fn export_abi<S: AsRef<str>>(
+        &self,
+        package_address: Address,
+        _blueprint_name: S,
+        _trace: bool,
+    ) -> Result<abi::Blueprint, RuntimeError> {
+        Err(RuntimeError::PackageNotFound(package_address))
+    }
+
+    fn export_abi_component(
+        &self,
+        _component_address: Address,
+        _trace: bool,
+    ) -> Result<abi::Blueprint, RuntimeError> {
+        let method = |inputs: Vec<Type>| abi::Method {
+            name: "get".to_owned(),
+            mutability: abi::Mutability::Immutable,
+            inputs,
+            output: Type::Unit,
+            hook: None,
+        };
+        Ok(abi::Blueprint {
+            package: SYSTEM_PACKAGE.to_string(),
+            name: "Overloaded".to_owned(),
+            functions: vec![],
+            methods: vec![
+                method(vec![Type::U32]),
+                method(vec![Type::U32, Type::String]),
+                method(vec![Type::String, Type::U32]),
+            ],
+        })
+    }
+}
+
+#[test]
+fn test_overloaded_method() {
+    let provider = OverloadedAbiProvider;
+    let component = Address::Component([1u8; 26]);
+
+    let transaction = TransactionBuilder::new(&provider)
+        .call_method(component, "get", vec!["1".to_owned()], None)
+        .build(vec![])
+        .unwrap();
+    assert_eq!(
+        transaction.instructions()[0],
+        Instruction::CallMethod {
+            component_address: component,
+            method: "get".to_owned(),
+            args: vec![SmartValue::from(1u32)],
+        }
+    );
+
+    let result = TransactionBuilder::new(&provider)
+        .call_method(component, "get", vec!["1".to_owned(), "a".to_owned()], None)
+        .build(vec![]);
+    assert!(matches!(
+        result,
+        Err(BuildTransactionError::AmbiguousMethod(m, 2)) if m == "get"
+    ));
+
+    let result = TransactionBuilder::new(&provider)
+        .call_method(component, "get", vec![], None)
+        .build(vec![]);
+    assert!(matches!(
+        result,
+        Err(BuildTransactionError::NoMatchingOverload(m, 0)) if m == "get"
+    ));
+
+    let result = TransactionBuilder::new(&provider)
+        .call_method(component, "set", vec!["1".to_owned()], None)
+        .build(vec![]);
+    assert!(matches!(
+        result,
+        Err(BuildTransactionError::MethodNotFound(m)) if m == "set"
+    ));
+}
+
+#[test]
+fn test_transfer_between_vaults() {
+    let events = Rc::new(RefCell::new(Vec::new()));
+    let events2 = events.clone();
+
+    let mut ledger = InMemoryLedger::with_bootstrap();
+    let mut executor =
+        TransactionExecutor::new(&mut ledger, 0, 0).with_tracer(Box::new(move |e: &TraceEvent| {
+            events2.borrow_mut().push(e.clone())
+        }));
+    let key = executor.new_public_key();
+    let account = executor.new_account(key);
+    let package = executor.publish_package(&compile("recall"));
+
+    let transaction = TransactionBuilder::new(&executor)
+        .call_function(package, "RecallTest", "create_token", vec![], Some(account))
+        .deposit_all_buckets(account)
+        .build(vec![key])
+        .unwrap();
+    let receipt = executor.sign_and_run(transaction, false).unwrap();
+    assert!(receipt.success);
+    let badge = receipt.resource_def(0).unwrap();
+    let token = receipt.resource_def(1).unwrap();
+
+    let mut components = Vec::new();
+    for _ in 0..2 {
+        let transaction = TransactionBuilder::new(&executor)
+            .call_function(
+                package,
+                "RecallTest",
+                "new_with_owner",
+                vec![format!("50,{}", token), key.to_string()],
+                Some(account),
+            )
+            .build(vec![key])
+            .unwrap();
+        let receipt = executor.sign_and_run(transaction, false).unwrap();
+        assert!(receipt.success);
+        components.push(receipt.component(0).unwrap());
+    }
+    events.borrow_mut().clear();
+
+    let transaction = TransactionBuilder::new(&executor)
+        .withdraw_from_account(
+            &ResourceAmount::Fungible {
+                amount: 1.into(),
+                resource_address: badge,
+            },
+            account,
+        )
+        .declare_bucket_ref(|builder, rid| {
+            builder.borrow_from_context(1.into(), badge, rid);
+            builder.transfer_between_vaults(components[0], components[1], token, 20.into(), rid)
+        })
+        .call_method(components[0], "amount", vec![], None)
+        .call_method(components[1], "amount", vec![], None)
+        .deposit_all_buckets(account)
+        .build(vec![key])
+        .unwrap();
+    let receipt = executor.sign_and_run(transaction, false).unwrap();
+    assert!(receipt.success);
+    assert_eq!(receipt.return_value::<Decimal>(4).unwrap(), 30.into());
+    assert_eq!(receipt.return_value::<Decimal>(5).unwrap(), 70.into());
+    assert!(!events.borrow().iter().any(|e| matches!(
+        e,
+        TraceEvent::BucketMoved { resource_address, .. } if *resource_address == token
+    )));
+
+    // Not signed by the owner of the source component
+    let key2 = executor.new_public_key();
+    let account2 = executor.new_account(key2);
+    let transaction = TransactionBuilder::new(&executor)
+        .call_method(SYSTEM_COMPONENT, "free_xrd", vec!["1".to_owned()], None)
+        .declare_bucket_ref(|builder, rid| {
+            builder.borrow_from_context(1.into(), RADIX_TOKEN, rid);
+            builder.transfer_between_vaults(components[0], components[1], token, 20.into(), rid)
+        })
+        .deposit_all_buckets(account2)
+        .build(vec![key2])
+        .unwrap();
+    let receipt = executor.sign_and_run(transaction, false).unwrap();
+    assert!(!receipt.success);
+    assert!(matches!(
+        receipt.error(),
+        Some(RuntimeError::ComponentError(
+            ComponentError::UnauthorizedAccess
+        ))
+    ));
+}
+
+#[test]
+fn test_transfer_between_accounts() {
+    let mut ledger = InMemoryLedger::with_bootstrap();
+    let mut executor = TransactionExecutor::new(&mut ledger, 0, 0);
+    let key = executor.new_public_key();
+    let account = executor.new_account(key);
+    let key2 = executor.new_public_key();
+    let account2 = executor.new_account(key2);
+
+    let transfer = |executor: &TransactionExecutor<InMemoryLedger>, signer: Address| {
+        TransactionBuilder::new(executor)
+            .call_method(SYSTEM_COMPONENT, "free_xrd", vec!["1".to_owned()], None)
+            .declare_bucket_ref(|builder, rid| {
+                builder.borrow_from_context(1.into(), RADIX_TOKEN, rid);
+                builder.transfer_between_vaults(account, account2, RADIX_TOKEN, 100.into(), rid)
+            })
+            .deposit_all_buckets(account2)
+            .build(vec![signer])
+            .unwrap()
+    };
+
+    // Signed by the key of the source account
+    let receipt = executor
+        .sign_and_run(transfer(&executor, key), false)
+        .unwrap();
+    assert!(receipt.success);
+    assert_eq!(
+        executor.balance_of(account, RADIX_TOKEN).unwrap(),
+        999_900.into()
+    );
+    assert_eq!(
+        executor.balance_of(account2, RADIX_TOKEN).unwrap(),
+        1_000_101.into()
+    );
+
+    // Signed by another key
+    let receipt = executor
+        .sign_and_run(transfer(&executor, key2), false)
+        .unwrap();
+    assert!(matches!(
+        receipt.error(),
+        Some(RuntimeError::ComponentError(
+            ComponentError::UnauthorizedAccess
+        ))
+    ));
+}
+
+#[test]
+fn test_custom_clock() {
+    let time = Rc::new(RefCell::new(1_000u64));
+    let time2 = time.clone();
+
+    let mut ledger = InMemoryLedger::with_bootstrap();
+    let mut executor = TransactionExecutor::new(&mut ledger, 0, 0)
+        .with_custom_clock(Box::new(move || *time2.borrow()));
+    let key = executor.new_public_key();
+    let account = executor.new_account(key);
+    let package = executor.publish_package(&compile("vesting"));
+
+    let transaction = TransactionBuilder::new(&executor)
+        .call_function(
+            package,
+            "Vesting",
+            "new",
+            vec![format!("100,{}", RADIX_TOKEN), "2000".to_owned()],
+            Some(account),
+        )
+        .build(vec![key])
+        .unwrap();
+    let receipt = executor.sign_and_run(transaction, false).unwrap();
+    assert!(receipt.success);
+    let component = receipt.component(0).unwrap();
+
+    let release = |executor: &TransactionExecutor<InMemoryLedger>| {
+        TransactionBuilder::new(executor)
+            .call_method(component, "release", vec![], None)
+            .deposit_all_buckets(account)
+            .build(vec![key])
+            .unwrap()
+    };
+
+    let receipt = executor.sign_and_run(release(&executor), false).unwrap();
+    assert!(!receipt.success);
+
+    *time.borrow_mut() = 2_000;
+    let receipt = executor.sign_and_run(release(&executor), false).unwrap();
+    assert!(receipt.success);
+}
+
+#[test]
+fn test_deposit_all_buckets_or() {
+    let mut ledger = InMemoryLedger::with_bootstrap();
+    let mut executor = TransactionExecutor::new(&mut ledger, 0, 0);
+    let key = executor.new_public_key();
+    let account = executor.new_account(key);
+    let package = executor.publish_package(&compile("component"));
+
+    let transaction = TransactionBuilder::new(&executor)
+        .call_function(package, "FreezableAccount", "new", vec![], None)
+        .build(vec![])
+        .unwrap();
+    let receipt = executor.sign_and_run(transaction, false).unwrap();
+    assert!(receipt.success);
+    let frozen = receipt.component(0).unwrap();
+
+    let transaction = TransactionBuilder::new(&executor)
+        .call_method(frozen, "freeze", vec![], None)
+        .call_method(SYSTEM_COMPONENT, "free_xrd", vec!["100".to_owned()], None)
+        .deposit_all_buckets_or(frozen, account)
+        .build(vec![key])
+        .unwrap();
+    let receipt = executor.sign_and_run(transaction, false).unwrap();
+    assert!(receipt.success);
+
+    // The rejected deposit is rolled back
+    let transaction = TransactionBuilder::new(&executor)
+        .call_method(frozen, "balance", vec![RADIX_TOKEN.to_string()], None)
+        .build(vec![])
+        .unwrap();
+    let receipt = executor.sign_and_run(transaction, false).unwrap();
+    assert_eq!(receipt.return_value::<Decimal>(0).unwrap(), Decimal::zero());
+
+    // The fallback account received the deposit
+    let transaction = TransactionBuilder::new(&executor)
+        .withdraw_from_account(
+            &ResourceAmount::Fungible {
+                amount: 1_000_100.into(),
+                resource_address: RADIX_TOKEN,
+            },
+            account,
+        )
+        .deposit_all_buckets(account)
+        .build(vec![key])
+        .unwrap();
+    let receipt = executor.sign_and_run(transaction, false).unwrap();
+    assert!(receipt.success);
+
+    // A missing account is an error rather than a rejected deposit
+    let missing = Address::Component([7u8; 26]);
+    let transaction = TransactionBuilder::new(&executor)
+        .call_method(SYSTEM_COMPONENT, "free_xrd", vec!["100".to_owned()], None)
+        .deposit_all_buckets_or(missing, account)
+        .build(vec![])
+        .unwrap();
+    let receipt = executor.sign_and_run(transaction, false).unwrap();
+    assert!(!receipt.success);
+    assert!(receipt
+        .results
+        .iter()
+        .any(|r| matches!(r, Err(RuntimeError::ComponentNotFound(a)) if *a == missing)));
+}
+
+#[test]
+fn test_render_type() {
+    use radix_engine::utils::render_type;
+
+    assert_eq!(render_type(&Vec::<Decimal>::describe()), "Vec<Decimal>");
+    assert_eq!(
+        render_type(&HashMap::<String, u32>::describe()),
+        "Map<String, u32>"
+    );
+    assert_eq!(render_type(&Option::<Bucket>::describe()), "Option<Bucket>");
+    assert_eq!(
+        render_type(&<(Address, Vec<Option<u8>>)>::describe()),
+        "(Address, Vec<Option<u8>>)"
+    );
+    assert_eq!(
+        render_type(&Result::<BTreeSet<u128>, String>::describe()),
+        "Result<Set<u128>, String>"
+    );
+    assert_eq!(render_type(&<[Decimal; 2]>::describe()), "[Decimal; 2]");
+
+    let error = BuildTransactionError::FailedToBuildArgs(BuildArgsError::UnsupportedType(
+        1,
+        HashMap::<String, Vec<Decimal>>::describe(),
+    ));
+    assert_eq!(
+        error.to_string(),
+        "Unsupported type Map<String, Vec<Decimal>> of argument #1"
+    );
+}
+
+#[test]
+fn test_take_exact_from_context() {
+    let mut ledger = InMemoryLedger::with_bootstrap();
+    let mut executor = TransactionExecutor::new(&mut ledger, 0, 0);
+    let key = executor.new_public_key();
+    let account = executor.new_account(key);
+
+    let transaction = TransactionBuilder::new(&executor)
+        .call_method(SYSTEM_COMPONENT, "free_xrd", vec!["50".to_owned()], None)
+        .declare_bucket(|builder, bid| {
+            builder.take_exact_from_context(100.into(), RADIX_TOKEN, bid)
+        })
+        .deposit_all_buckets(account)
+        .build(vec![key])
+        .unwrap();
+    let receipt = executor.sign_and_run(transaction, false).unwrap();
+    assert!(!receipt.success);
+    assert!(matches!(
+        receipt.results.last().unwrap(),
+        Err(RuntimeError::InsufficientWorktopBalance(address, amount))
+            if *address == RADIX_TOKEN && *amount == 100.into()
+    ));
+
+    let transaction = TransactionBuilder::new(&executor)
+        .call_method(SYSTEM_COMPONENT, "free_xrd", vec!["50".to_owned()], None)
+        .declare_bucket(|builder, bid| {
+            builder
+                .take_exact_from_context(50.into(), RADIX_TOKEN, bid)
+                .deposit(bid, account)
+        })
+        .build(vec![key])
+        .unwrap();
+    let receipt = executor.sign_and_run(transaction, false).unwrap();
+    assert!(receipt.success);
+}
+
+#[test]
+fn test_list_methods() {
+    let mut ledger = InMemoryLedger::with_bootstrap();
+    let mut executor = TransactionExecutor::new(&mut ledger, 0, 0);
+    let key = executor.new_public_key();
+    let account = executor.new_account(key);
+
+    let builder = TransactionBuilder::new(&executor);
+    let methods = builder.list_methods(account);
+    assert!(methods.contains(&"deposit".to_owned()));
+    assert!(methods.contains(&"withdraw".to_owned()));
+    assert!(builder.component_has_method(account, "deposit_batch"));
+    assert!(!builder.component_has_method(account, "free_xrd"));
+    assert!(builder
+        .list_methods(Address::Component([9u8; 26]))
+        .is_empty());
+}
+
+#[test]
+fn test_log_limit() {
+    let mut ledger = InMemoryLedger::with_bootstrap();
+    let mut executor = TransactionExecutor::new(&mut ledger, 0, 0);
+    let package = executor.publish_package(&compile("logging"));
+
+    let log_many = |executor: &TransactionExecutor<InMemoryLedger>| {
+        TransactionBuilder::new(executor)
+            .call_function(package, "Logger", "log_many", vec!["1000".to_owned()], None)
+            .build(vec![])
+            .unwrap()
+    };
+
+    let receipt = executor.sign_and_run(log_many(&executor), false).unwrap();
+    assert!(receipt.success);
+    assert_eq!(receipt.logs.len(), 1000);
+    assert!(!receipt.logs_truncated);
+
+    executor.set_max_log_size(Some(1024));
+    let receipt = executor.sign_and_run(log_many(&executor), false).unwrap();
+    assert!(!receipt.success);
+    assert!(receipt.logs_truncated);
+    assert!(receipt.logs.iter().map(|(_, m)| m.len()).sum::<usize>() <= 1024);
+}
+
+#[test]
+fn test_fund_account() {
+    let mut ledger = InMemoryLedger::with_bootstrap();
+    let mut executor = TransactionExecutor::new(&mut ledger, 0, 0);
+    let key = executor.new_public_key();
+    let account = executor.new_account(key);
+    let mut metadata = HashMap::new();
+    metadata.insert("symbol".to_owned(), "USD".to_owned());
+    let usd = executor.fund_account(account, 500.into(), metadata);
+
+    let withdraw = |executor: &TransactionExecutor<InMemoryLedger>, amount: i32| {
+        TransactionBuilder::new(executor)
+            .withdraw_from_account(
+                &ResourceAmount::Fungible {
+                    amount: amount.into(),
+                    resource_address: usd,
+                },
+                account,
+            )
+            .deposit_all_buckets(account)
+            .build(vec![key])
+            .unwrap()
+    };
+
+    let receipt = executor
+        .sign_and_run(withdraw(&executor, 501), false)
+        .unwrap();
+    assert!(!receipt.success);
+    let receipt = executor
+        .sign_and_run(withdraw(&executor, 500), false)
+        .unwrap();
+    assert!(receipt.success);
+}
+
+#[test]
+fn test_membership_nft_data() {
+    let mut ledger = InMemoryLedger::with_bootstrap();
+    let mut executor = TransactionExecutor::new(&mut ledger, 0, 0);
+    let key = executor.new_public_key();
+    let account = executor.new_account(key);
+    let package = executor.publish_package(&compile("membership"));
+
+    let transaction = TransactionBuilder::new(&executor)
+        .call_function(package, "Club", "new", vec![], None)
+        .deposit_all_buckets(account)
+        .build(vec![key])
+        .unwrap();
+    let receipt = executor.sign_and_run(transaction, false).unwrap();
+    assert!(receipt.success);
+    let club = receipt.component(0).unwrap();
+    let membership = receipt.resource_def(0).unwrap();
+
+    for (id, room) in [(1, "Main hall"), (2, "VIP lounge")] {
+        let transaction = TransactionBuilder::new(&executor)
+            .call_method(
+                club,
+                "enter",
+                vec![format!("#{},{}", id, membership)],
+                Some(account),
+            )
+            .deposit_all_buckets(account)
+            .build(vec![key])
+            .unwrap();
+        let index = transaction
+            .instructions()
+            .iter()
+            .position(|i| matches!(i, Instruction::CallMethod { method, .. } if method == "enter"))
+            .unwrap();
+        let receipt = executor.sign_and_run(transaction, false).unwrap();
+        assert!(receipt.success);
+        assert_eq!(receipt.return_value::<String>(index).unwrap(), room);
+    }
+
+    // Only NFTs within the presented bucket can be read
+    let transaction = TransactionBuilder::new(&executor)
+        .call_method(
+            club,
+            "tier_of",
+            vec![format!("#1,{}", membership), "2".to_owned()],
+            Some(account),
+        )
+        .deposit_all_buckets(account)
+        .build(vec![key])
+        .unwrap();
+    let receipt = executor.sign_and_run(transaction, false).unwrap();
+    assert!(!receipt.success);
+    assert!(matches!(
+        receipt.error(),
+        Some(RuntimeError::BucketError(BucketError::NftNotFound))
+    ));
+}
+
+#[test]
+fn test_validate_instructions() {
+    let mut ledger = InMemoryLedger::with_bootstrap();
+    let mut executor = TransactionExecutor::new(&mut ledger, 0, 0);
+    let key = executor.new_public_key();
+    let account = executor.new_account(key);
+    let transaction = |instructions| Transaction::from_instructions(instructions, Vec::new());
+
+    // Undeclared bucket
+    let result = executor.sign_and_run(
+        transaction(vec![
+            Instruction::Deposit {
+                bid: Bid(5),
+                account,
+            },
+            Instruction::End {
+                signers: Vec::new(),
+            },
+        ]),
+        false,
+    );
+    assert!(matches!(
+        result,
+        Err(TransactionExecutionError::ValidationError(
+            TransactionValidationError::UndeclaredBucket(0, Bid(5))
+        ))
+    ));
+
+    // Missing end
+    let result = executor.sign_and_run(
+        transaction(vec![
+            Instruction::DeclareTempBucket,
+            Instruction::TakeFromContext {
+                amount: 1.into(),
+                resource_address: RADIX_TOKEN,
+                to: Bid(0),
+            },
+        ]),
+        false,
+    );
+    assert!(matches!(
+        result,
+        Err(TransactionExecutionError::ValidationError(
+            TransactionValidationError::MissingEndInstruction
+        ))
+    ));
+
+    // Reservation after use
+    let result = executor.sign_and_run(
+        transaction(vec![
+            Instruction::DepositAllBuckets { account },
+            Instruction::DeclareTempBucket,
+            Instruction::End {
+                signers: Vec::new(),
+            },
+        ]),
+        false,
+    );
+    assert!(matches!(
+        result,
+        Err(TransactionExecutionError::ValidationError(
+            TransactionValidationError::ReservationAfterUse(1)
+        ))
+    ));
+}
+
+#[test]
+fn test_fractional_non_fungible() {
+    let address = Address::ResourceDef([7u8; 26]);
+
+    // Parse time
+    let amount: ResourceAmount = format!("0.5,{}", address).parse().unwrap();
+    assert!(matches!(
+        amount.check_resource_type(ResourceType::NonFungible),
+        Err(ParseResourceAmountError::FractionalNonFungible)
+    ));
+    assert!(amount
+        .check_resource_type(ResourceType::Fungible { divisibility: 18 })
+        .is_ok());
+    let amount: ResourceAmount = format!("2,{}", address).parse().unwrap();
+    assert!(amount
+        .check_resource_type(ResourceType::NonFungible)
+        .is_ok());
+
+    // Engine time
+    let mut bucket = radix_engine::model::Bucket::new(
+        address,
+        ResourceType::NonFungible,
+        Supply::NonFungible {
+            ids: BTreeSet::from([1, 2]),
+        },
+    );
+    let half = Decimal::from_str("0.5").unwrap();
+    assert!(matches!(
+        bucket.take(half),
+        Err(BucketError::FractionalNonFungible(a)) if a == half
+    ));
+    assert_eq!(bucket.take(1.into()).unwrap().amount(), 1.into());
+}
+
+#[test]
+fn test_resource_conservation() {
+    let mut ledger = InMemoryLedger::with_bootstrap();
+    let mut executor = TransactionExecutor::new(&mut ledger, 0, 0);
+    let key = executor.new_public_key();
+    let account = executor.new_account(key);
+    executor.fund_account(account, 1000.into(), HashMap::new());
+    assert_eq!(executor.assert_conservation(), Ok(()));
+
+    // Resources appearing out of thin air, bypassing the resource definition
+    ledger.put_vault(
+        Vid(H256([9u8; 32]), 0),
+        Vault::new(
+            radix_engine::model::Bucket::new(
+                RADIX_TOKEN,
+                ResourceType::Fungible { divisibility: 18 },
+                Supply::Fungible { amount: 5.into() },
+            ),
+            SYSTEM_PACKAGE,
+        ),
+    );
+    let executor = TransactionExecutor::new(&mut ledger, 0, 0);
+    assert!(matches!(
+        executor.assert_conservation(),
+        Err(ConservationError::SupplyMismatch { resource_address, total_supply, held })
+            if resource_address == RADIX_TOKEN && held == total_supply + 5
+    ));
+}
+
+#[test]
+fn test_stage_to_worktop() {
+    let mut ledger = InMemoryLedger::with_bootstrap();
+    let mut executor = TransactionExecutor::new(&mut ledger, 0, 0);
+    let key = executor.new_public_key();
+
+    // The bucket argument is taken from the staged XRD, without an account
+    let transaction = TransactionBuilder::new(&executor)
+        .stage_to_worktop(100.into(), RADIX_TOKEN, None)
+        .call_function(
+            ACCOUNT_PACKAGE,
+            "Account",
+            "with_bucket",
+            vec![key.to_string(), format!("100,{}", RADIX_TOKEN)],
+            None,
+        )
+        .build(vec![key])
+        .unwrap();
+    let receipt = executor.sign_and_run(transaction, false).unwrap();
+    assert!(receipt.success);
+    assert!(receipt.component(0).is_some());
+
+    let result = TransactionBuilder::new(&executor)
+        .stage_to_worktop(100.into(), Address::ResourceDef([7u8; 26]), None)
+        .build(vec![key]);
+    assert!(matches!(
+        result,
+        Err(BuildTransactionError::MintBadgeNotProvided(_))
+    ));
+}
+
+#[test]
+fn test_transaction_manifest() {
+    let mut ledger = InMemoryLedger::with_bootstrap();
+    let mut executor = TransactionExecutor::new(&mut ledger, 0, 0);
+    let key = executor.new_public_key();
+    let account = executor.new_account(key);
+
+    let manifest = TransactionBuilder::new(&executor)
+        .call_method(SYSTEM_COMPONENT, "free_xrd", vec!["100".to_owned()], None)
+        .deposit_all_buckets(account)
+        .manifest()
+        .unwrap();
+
+    // Asserting an empty worktop before deposit fails the transaction
+    let mut early = manifest.clone();
+    early
+        .instructions
+        .insert(1, Instruction::AssertWorktopEmpty);
+    let receipt = executor
+        .sign_and_run(Transaction::from_manifest(early, vec![key]), false)
+        .unwrap();
+    assert!(!receipt.success);
+    assert!(matches!(
+        receipt.results[1],
+        Err(RuntimeError::WorktopNotEmpty(RADIX_TOKEN))
+    ));
+
+    let mut late = manifest;
+    late.instructions.push(Instruction::AssertWorktopEmpty);
+    let receipt = executor
+        .sign_and_run(Transaction::from_manifest(late, vec![key]), false)
+        .unwrap();
+    assert!(receipt.success);
+}
+
+struct Int256AbiProvider;
+
+impl AbiProvider for Int256AbiProvider {
+    fn export_abi<S: AsRef<str>>(
+        &self,
+        package_address: Address,
+        _blueprint_name: S,
+        _trace: bool,
+    ) -> Result<abi::Blueprint, RuntimeError> {
+        Err(RuntimeError::PackageNotFound(package_address))
+    }
+
+    fn export_abi_component(
+        &self,
+        _component_address: Address,
+        _trace: bool,
+    ) -> Result<abi::Blueprint, RuntimeError> {
+        Ok(abi::Blueprint {
+            package: SYSTEM_PACKAGE.to_string(),
+            name: "Pool".to_owned(),
+            functions: vec![],
+            methods: vec![abi::Method {
+                name: "set_reserves".to_owned(),
+                mutability: abi::Mutability::Mutable,
+                inputs: vec![U256::describe(), I256::describe()],
+                output: Type::Unit,
+                hook: None,
+            }],
+        })
+    }
+}
+
+#[test]
+fn test_int256_args() {
+    let provider = Int256AbiProvider;
+    let component = Address::Component([1u8; 26]);
+    let max = "115792089237316195423570985008687907853269984665640564039457584007913129639935";
+
+    let transaction = TransactionBuilder::new(&provider)
+        .call_method(
+            component,
+            "set_reserves",
+            vec![max.to_owned(), "-0xff".to_owned()],
+            None,
+        )
+        .build(vec![])
+        .unwrap();
+    assert_eq!(
+        transaction.instructions()[0],
+        Instruction::CallMethod {
+            component_address: component,
+            method: "set_reserves".to_owned(),
+            args: vec![
+                SmartValue::from(U256([0xffu8; 32])),
+                SmartValue::from(I256::from(-255i128)),
+            ],
+        }
+    );
+
+    let result = TransactionBuilder::new(&provider)
+        .call_method(
+            component,
+            "set_reserves",
+            vec![format!("{}0", max), "0".to_owned()],
+            None,
+        )
+        .build(vec![]);
+    assert!(matches!(
+        result,
+        Err(BuildTransactionError::FailedToBuildArgs(
+            BuildArgsError::FailedToParse(0, _, _)
+        ))
+    ));
+}
+
+#[test]
+fn test_not_authorized_names_badge() {
+    let mut ledger = InMemoryLedger::with_bootstrap();
+    let mut executor = TransactionExecutor::new(&mut ledger, 0, 0);
+    let key = executor.new_public_key();
+    let account = executor.new_account(key);
+
+    let transaction = TransactionBuilder::new(&executor)
+        .new_badge_fixed(HashMap::new(), 1.into())
+        .new_badge_fixed(HashMap::new(), 1.into())
+        .deposit_all_buckets(account)
+        .build(vec![key])
+        .unwrap();
+    let receipt = executor.sign_and_run(transaction, true).unwrap();
+    assert!(receipt.success);
+    let admin_badge = receipt.resource_def(0).unwrap();
+    let other_badge = receipt.resource_def(1).unwrap();
+
+    let transaction = TransactionBuilder::new(&executor)
+        .new_token_mutable(HashMap::new(), admin_badge)
+        .build(vec![key])
+        .unwrap();
+    let receipt = executor.sign_and_run(transaction, true).unwrap();
+    assert!(receipt.success);
+    let token = receipt.resource_def(0).unwrap();
+
+    // Minting with the wrong badge reports the badge that was required
+    let transaction = TransactionBuilder::new(&executor)
+        .withdraw_from_account(
+            &ResourceAmount::Fungible {
+                amount: 1.into(),
+                resource_address: other_badge,
+            },
+            account,
+        )
+        .mint(100.into(), token, other_badge)
+        .deposit_all_buckets(account)
+        .build(vec![key])
+        .unwrap();
+    let receipt = executor.sign_and_run(transaction, true).unwrap();
+    assert!(!receipt.success);
+    assert!(matches!(
+        receipt.error(),
+        Some(RuntimeError::NotAuthorized {
+            required_badge: Some(badge),
+            permission: MAY_MINT,
+        }) if *badge == admin_badge
+    ));
+}
+
+#[test]
+fn test_genesis_accounts() {
+    let (mut ledger, accounts) = InMemoryLedger::with_genesis_accounts(3, 1000.into());
+    assert_eq!(accounts.len(), 3);
+    let mut executor = TransactionExecutor::new(&mut ledger, 0, 0);
+    executor.set_require_signatures(true);
+
+    // Entities created afterwards don't clash with the genesis accounts
+    let key = executor.new_public_key();
+    let account = executor.new_account(key);
+    assert!(accounts
+        .iter()
+        .all(|a| a.address != account && a.key != key));
+
+    let withdraw = |executor: &TransactionExecutor<InMemoryLedger>,
+                    genesis: &GenesisAccount,
+                    amount: Decimal| {
+        TransactionBuilder::new(executor)
+            .withdraw_from_account(
+                &ResourceAmount::Fungible {
+                    amount,
+                    resource_address: RADIX_TOKEN,
+                },
+                genesis.address,
+            )
+            .deposit_all_buckets(account)
+            .build_signed(&[genesis])
+            .unwrap()
+    };
+
+    for genesis in &accounts {
+        assert_eq!(genesis.balance, 1000.into());
+        assert_eq!(
+            executor.balance_of(genesis.address, RADIX_TOKEN).unwrap(),
+            genesis.balance
+        );
+        let receipt = executor
+            .run(withdraw(&executor, genesis, 1001.into()), false)
+            .unwrap();
+        assert!(!receipt.success);
+        let receipt = executor
+            .run(withdraw(&executor, genesis, genesis.balance), false)
+            .unwrap();
+        assert!(receipt.success);
+    }
+    executor.assert_conservation().unwrap();
+}
+
+#[test]
+fn test_required_withdrawals() {
+    let mut ledger = InMemoryLedger::with_bootstrap();
+    let mut executor = TransactionExecutor::new(&mut ledger, 0, 0);
+    let key = executor.new_public_key();
+    let account = executor.new_account(key);
+    let key2 = executor.new_public_key();
+    let account2 = executor.new_account(key2);
+    let badge = executor.fund_account(account, 1.into(), HashMap::new());
+
+    // The bucket argument is withdrawn from the account, then taken from context
+    let transaction = TransactionBuilder::new(&executor)
+        .call_method(
+            account2,
+            "deposit",
+            vec![format!("100,{}", RADIX_TOKEN)],
+            Some(account),
+        )
+        .withdraw_from_account(
+            &ResourceAmount::Fungible {
+                amount: 1.into(),
+                resource_address: badge,
+            },
+            account,
+        )
+        .withdraw_from_account(
+            &ResourceAmount::Fungible {
+                amount: 50.into(),
+                resource_address: RADIX_TOKEN,
+            },
+            account,
+        )
+        .deposit_all_buckets(account2)
+        .build(vec![key])
+        .unwrap();
+
+    assert_eq!(
+        transaction.required_withdrawals(|a| executor.is_account(a)),
+        vec![
+            (
+                account,
+                ResourceAmount::Fungible {
+                    amount: 150.into(),
+                    resource_address: RADIX_TOKEN,
+                }
+            ),
+            (
+                account,
+                ResourceAmount::Fungible {
+                    amount: 1.into(),
+                    resource_address: badge,
+                }
+            ),
+        ]
+    );
+
+    // A withdraw method on a component other than an account isn't counted
+    let mut instructions = transaction.instructions().to_vec();
+    instructions.insert(
+        0,
+        Instruction::CallMethod {
+            component_address: SYSTEM_COMPONENT,
+            method: "withdraw".to_owned(),
+            args: vec![
+                SmartValue::from(Decimal::from(10)),
+                SmartValue::from(RADIX_TOKEN),
+            ],
+        },
+    );
+    let with_payout = Transaction::from_instructions(instructions, Vec::new());
+    assert_eq!(
+        with_payout.required_withdrawals(|a| executor.is_account(a)),
+        transaction.required_withdrawals(|a| executor.is_account(a))
+    );
+
+    let receipt = executor.sign_and_run(transaction, false).unwrap();
+    assert!(receipt.success);
+}
+
+#[test]
+fn test_resource_type_query() {
+    let mut ledger = InMemoryLedger::with_bootstrap();
+    let nft = Address::ResourceDef([7u8; 26]);
+    ledger.put_resource_def(
+        nft,
+        ResourceDef::new(
+            ResourceType::NonFungible,
+            HashMap::new(),
+            0,
+            0,
+            HashMap::new(),
+            &None,
+        )
+        .unwrap(),
+    );
+    let mut executor = TransactionExecutor::new(&mut ledger, 0, 0);
+    let key = executor.new_public_key();
+    let account = executor.new_account(key);
+    let token = executor.fund_account(account, 1000.into(), HashMap::new());
+
+    let token_type = executor.resource_type(token).unwrap();
+    assert!(token_type.is_fungible() && !token_type.is_non_fungible());
+    let nft_type = executor.resource_type(nft).unwrap();
+    assert!(nft_type.is_non_fungible() && !nft_type.is_fungible());
+    assert_eq!(
+        executor.resource_type(Address::ResourceDef([8u8; 26])),
+        None
+    );
+
+    // NFT ids only apply to NFT resources, and NFTs can't be split
+    let result = TransactionBuilder::new(&executor)
+        .call_method(
+            account,
+            "deposit",
+            vec![format!("#1,{}", token)],
+            Some(account),
+        )
+        .build(vec![key]);
+    assert!(matches!(
+        result,
+        Err(BuildTransactionError::FailedToBuildArgs(
+            BuildArgsError::ResourceTypeMismatch(0, _)
+        ))
+    ));
+    let result = TransactionBuilder::new(&executor)
+        .call_method(
+            account,
+            "deposit",
+            vec![format!("1.5,{}", nft)],
+            Some(account),
+        )
+        .build(vec![key]);
+    assert!(matches!(
+        result,
+        Err(BuildTransactionError::FailedToBuildArgs(
+            BuildArgsError::FractionalNonFungible(0, _)
+        ))
+    ));
+    assert!(TransactionBuilder::new(&executor)
+        .call_method(
+            account,
+            "deposit",
+            vec![format!("#1,{}", nft)],
+            Some(account)
+        )
+        .build(vec![key])
+        .is_ok());
+}
+
+#[test]
+fn test_deposit_worktop_remainder() {
+    let mut ledger = InMemoryLedger::with_bootstrap();
+    let mut executor = TransactionExecutor::new(&mut ledger, 0, 0);
+    let key = executor.new_public_key();
+    let account = executor.new_account(key);
+    let key2 = executor.new_public_key();
+    let account2 = executor.new_account(key2);
+
+    // Pays 60 out of the 100 XRD withdrawn, leaving 40 XRD of change on the worktop
+    let (withdrawn, paid) = (Decimal::from(100), Decimal::from(60));
+    let balance = executor.balance_of(account, RADIX_TOKEN).unwrap();
+    let transaction = TransactionBuilder::new(&executor)
+        .withdraw_from_account(
+            &ResourceAmount::Fungible {
+                amount: withdrawn,
+                resource_address: RADIX_TOKEN,
+            },
+            account,
+        )
+        .call_method(
+            account2,
+            "deposit",
+            vec![format!("{},{}", paid, RADIX_TOKEN)],
+            None,
+        )
+        .deposit_worktop_remainder(account)
+        .build(vec![key])
+        .unwrap();
+    let index = transaction
+        .instructions()
+        .iter()
+        .position(|i| matches!(i, Instruction::DepositWorktopRemainder { .. }))
+        .unwrap();
+    let receipt = executor.sign_and_run(transaction, false).unwrap();
+    assert!(receipt.success);
+    assert_eq!(
+        receipt
+            .return_value::<Vec<(Address, Decimal)>>(index)
+            .unwrap(),
+        vec![(RADIX_TOKEN, withdrawn - paid)]
+    );
+
+    // The change is back in the account
+    assert_eq!(
+        executor.balance_of(account, RADIX_TOKEN).unwrap(),
+        balance - paid
+    );
+}
+
+struct AmountAbiProvider;
+
+impl AbiProvider for AmountAbiProvider {
+    fn export_abi<S: AsRef<str>>(
+        &self,
+        package_address: Address,
+        _blueprint_name: S,
+        _trace: bool,
+    ) -> Result<abi::Blueprint, RuntimeError> {
+        Err(RuntimeError::PackageNotFound(package_address))
+    }
+
+    fn export_abi_component(
+        &self,
+        _component_address: Address,
+        _trace: bool,
+    ) -> Result<abi::Blueprint, RuntimeError> {
+        // The second amount is misdeclared as an integer
+        Ok(abi::Blueprint {
+            package: SYSTEM_PACKAGE.to_string(),
+            name: "Vendor".to_owned(),
+            functions: vec![],
+            methods: vec![abi::Method {
+                name: "set_prices".to_owned(),
+                mutability: abi::Mutability::Mutable,
+                inputs: vec![Decimal::describe(), Type::U128],
+                output: Type::Unit,
+                hook: None,
+            }],
+        })
+    }
+}
+
+#[test]
+fn test_parse_error_consistency() {
+    let provider = AmountAbiProvider;
+    let component = Address::Component([1u8; 26]);
+    let error = |args: [&str; 2]| match TransactionBuilder::new(&provider)
+        .call_method(
+            component,
+            "set_prices",
+            args.iter().map(|a| a.to_string()).collect(),
+            None,
+        )
+        .build(vec![])
+    {
+        Err(e @ BuildTransactionError::FailedToBuildArgs(BuildArgsError::FailedToParse(..))) => {
+            e.to_string()
+        }
+        _ => panic!("Expected a parse failure"),
+    };
+
+    assert_eq!(
+        error(["1.5.5", "1"]),
+        "Failed to parse argument #0 as Decimal: 1.5.5"
+    );
+    assert_eq!(
+        error(["1.5", "1.5"]),
+        "Failed to parse argument #1 as u128: 1.5"
+    );
+}
+
+#[test]
+fn test_liquidity_pool() {
+    let mut ledger = InMemoryLedger::with_bootstrap();
+    let mut executor = TransactionExecutor::new(&mut ledger, 0, 0);
+    let key = executor.new_public_key();
+    let account = executor.new_account(key);
+    let usd = executor.fund_account(account, 1000.into(), HashMap::new());
+    let package = executor.publish_package(&compile("pool"));
+
+    let transaction = TransactionBuilder::new(&executor)
+        .create_liquidity_pool(
+            package,
+            "Pool",
+            account,
+            ResourceAmount::Fungible {
+                amount: 1000.into(),
+                resource_address: usd,
+            },
+            ResourceAmount::Fungible {
+                amount: 10.into(),
+                resource_address: RADIX_TOKEN,
+            },
+        )
+        .build(vec![key])
+        .unwrap();
+    let receipt = executor.sign_and_run(transaction, false).unwrap();
+    assert!(receipt.success);
+    let pool = receipt.component(0).unwrap();
+    let lp_token = receipt.resource_def(0).unwrap();
+
+    // The pool holds both tokens, and the LP tokens are in the account
+    let transaction = TransactionBuilder::new(&executor)
+        .call_method(pool, "reserves", vec![], None)
+        .withdraw_from_account(
+            &ResourceAmount::Fungible {
+                amount: 100.into(),
+                resource_address: lp_token,
+            },
+            account,
+        )
+        .deposit_all_buckets(account)
+        .build(vec![key])
+        .unwrap();
+    let receipt = executor.sign_and_run(transaction, false).unwrap();
+    assert!(receipt.success);
+    assert_eq!(
+        receipt.return_value::<(Decimal, Decimal)>(0).unwrap(),
+        (Decimal::from(1000), Decimal::from(10))
+    );
+}
+
+#[test]
+fn test_export_resource_behavior() {
+    let mut ledger = InMemoryLedger::with_bootstrap();
+    let mut executor = TransactionExecutor::new(&mut ledger, 0, 0);
+    let key = executor.new_public_key();
+    let account = executor.new_account(key);
+    let badge = executor.fund_account(account, 1.into(), HashMap::new());
+
+    let transaction = TransactionBuilder::new(&executor)
+        .new_token_mutable(HashMap::new(), badge)
+        .build(vec![key])
+        .unwrap();
+    let receipt = executor.sign_and_run(transaction, false).unwrap();
+    assert!(receipt.success);
+    let token = receipt.resource_def(0).unwrap();
+
+    let behavior = executor.export_resource_behavior(token).unwrap();
+    assert!(behavior.mintable);
+    assert!(behavior.burnable);
+    assert!(!behavior.freely_burnable);
+    assert!(!behavior.restricted_transfer);
+    assert!(!behavior.shared_metadata_mutable);
+
+    assert_eq!(
+        BasicAbiProvider::new()
+            .export_resource_behavior(RADIX_TOKEN)
+            .unwrap(),
+        ResourceBehavior::from_flags(0)
+    );
+    assert!(matches!(
+        executor.export_resource_behavior(Address::ResourceDef([7u8; 26])),
+        Err(RuntimeError::ResourceDefNotFound(_))
+    ));
+}
+
+#[test]
+fn test_run_unsigned() {
+    let mut ledger = InMemoryLedger::with_bootstrap();
+    let mut executor = TransactionExecutor::new(&mut ledger, 0, 0);
+    let key = executor.new_public_key();
+    let account = executor.new_account(key);
+
+    let transaction = TransactionBuilder::new(&executor)
+        .withdraw_from_account(
+            &ResourceAmount::Fungible {
+                amount: 100.into(),
+                resource_address: RADIX_TOKEN,
+            },
+            account,
+        )
+        .deposit_all_buckets(account)
+        .build(vec![key])
+        .unwrap();
+
+    let receipt = executor.run_unsigned(transaction.clone(), false).unwrap();
+    assert!(!receipt.success);
+    assert!(receipt.results[0].is_err());
+    // The account rejects the withdrawal for the missing signature, not for any other reason
+    assert!(matches!(
+        receipt.error(),
+        Some(RuntimeError::InvokeError(_))
+    ));
+    assert!(receipt
+        .logs
+        .iter()
+        .any(|(level, message)| *level == LogLevel::Error
+            && message.starts_with("Panicked at 'Not authorized!")));
+    assert_eq!(
+        receipt.transaction.instructions().last(),
+        Some(&Instruction::End {
+            signers: Vec::new()
+        })
+    );
+
+    let receipt = executor.sign_and_run(transaction, false).unwrap();
+    assert!(receipt.success);
+}
+
+#[test]
+fn test_delete_component() {
+    let mut ledger = InMemoryLedger::with_bootstrap();
+    let mut executor = TransactionExecutor::new(&mut ledger, 0, 0);
+    let key = executor.new_public_key();
+    let account = executor.new_account(key);
+    let package = executor.publish_package(&compile("component"));
+
+    // The blueprint makes the signer the owner of the new component
+    let transaction = TransactionBuilder::new(&executor)
+        .call_function(
+            package,
+            "FreezableAccount",
+            "with_owner",
+            vec![key.to_string()],
+            None,
+        )
+        .build(vec![])
+        .unwrap();
+    let receipt = executor.sign_and_run(transaction, false).unwrap();
+    assert!(receipt.success);
+    let component = receipt.component(0).unwrap();
+
+    let xrd = ResourceAmount::Fungible {
+        amount: 100.into(),
+        resource_address: RADIX_TOKEN,
+    };
+    let transaction = TransactionBuilder::new(&executor)
+        .withdraw_from_account(&xrd, account)
+        .deposit_all_buckets(component)
+        .build(vec![key])
+        .unwrap();
+    assert!(executor.sign_and_run(transaction, false).unwrap().success);
+
+    let transaction = TransactionBuilder::new(&executor)
+        .delete_component(component)
+        .build(vec![key])
+        .unwrap();
+    let receipt = executor.sign_and_run(transaction, false).unwrap();
+    assert!(matches!(
+        receipt.results[0],
+        Err(RuntimeError::ComponentNotEmpty(c)) if c == component
+    ));
+
+    let transaction = TransactionBuilder::new(&executor)
+        .call_method(
+            component,
+            "withdraw_all",
+            vec![RADIX_TOKEN.to_string()],
+            None,
+        )
+        .deposit_all_buckets(account)
+        .build(vec![])
+        .unwrap();
+    assert!(executor.sign_and_run(transaction, false).unwrap().success);
+
+    // Only the owner can delete the component
+    let mut transaction = TransactionBuilder::new(&executor)
+        .delete_component(component)
+        .build(vec![key])
+        .unwrap();
+    executor.sign(&mut transaction);
+    let receipt = executor.run_unsigned(transaction.clone(), false).unwrap();
+    assert!(matches!(
+        receipt.results[0],
+        Err(RuntimeError::ComponentError(
+            ComponentError::UnauthorizedAccess
+        ))
+    ));
+
+    // The component is deleted with its lazy map and vault
+    let snapshot = ledger.snapshot();
+    let mut executor = TransactionExecutor::new(&mut ledger, 0, 10);
+    let receipt = executor.run(transaction, false).unwrap();
+    assert!(receipt.success);
+    let diff = ledger.diff(&snapshot);
+    assert!(diff.added.is_empty());
+    assert_eq!(diff.removed.len(), 3);
+    assert!(diff.removed.contains(&SubstateId::Component(component)));
+    assert!(diff
+        .removed
+        .iter()
+        .any(|id| matches!(id, SubstateId::LazyMap(_))));
+    assert!(diff
+        .removed
+        .iter()
+        .any(|id| matches!(id, SubstateId::Vault(_))));
+
+    // A component without owner can't be deleted
+    let mut executor = TransactionExecutor::new(&mut ledger, 0, 20);
+    let transaction = TransactionBuilder::new(&executor)
+        .delete_component(account)
+        .build(vec![key])
+        .unwrap();
+    let receipt = executor.run(transaction, false).unwrap();
+    assert!(matches!(
+        receipt.results[0],
+        Err(RuntimeError::ComponentError(
+            ComponentError::UnauthorizedAccess
+        ))
+    ));
+}
+
+#[test]
+fn test_decode_component_without_owner() {
+    let legacy = ComponentV1 {
+        package_address: SYSTEM_PACKAGE,
+        blueprint_name: "System".to_owned(),
+        state: Vec::new(),
+        authority: SYSTEM_PACKAGE,
+    };
+    let bytes = scrypto_encode(&legacy);
+    assert!(scrypto_decode::<radix_engine::model::Component>(&bytes).is_err());
+    let component = radix_engine::model::Component::decode_stored(&bytes).unwrap();
+    assert_eq!(component.package_address(), SYSTEM_PACKAGE);
+    assert_eq!(component.owner(), None);
+
+    let latest = radix_engine::model::Component::decode_stored(&scrypto_encode(&component));
+    assert_eq!(latest.unwrap(), component);
+}
+
+#[test]
+fn test_withdraw_nfts_range() {
+    let mut ledger = InMemoryLedger::with_bootstrap();
+    let mut executor = TransactionExecutor::new(&mut ledger, 0, 0);
+    let key = executor.new_public_key();
+    let account = executor.new_account(key);
+    let key2 = executor.new_public_key();
+    let account2 = executor.new_account(key2);
+    let package = executor.publish_package(&compile("nft"));
+
+    // Mints #1..#10 into the account
+    let transaction = TransactionBuilder::new(&executor)
+        .call_function(
+            package,
+            "NftTest",
+            "create_nft_batch",
+            vec!["10".to_owned()],
+            Some(account),
+        )
+        .deposit_all_buckets(account)
+        .build(vec![key])
+        .unwrap();
+    let receipt = executor.sign_and_run(transaction, false).unwrap();
+    assert!(receipt.success);
+    let nft = receipt.resource_def(0).unwrap();
+
+    // Moves #3..#7 to the second account
+    let transaction = TransactionBuilder::new(&executor)
+        .withdraw_nfts_range_from_account(3..8, nft, account)
+        .deposit_worktop_remainder(account2)
+        .build(vec![key])
+        .unwrap();
+    let receipt = executor.sign_and_run(transaction, false).unwrap();
+    assert!(receipt.success);
+    assert_eq!(
+        receipt.return_value::<Vec<(Address, Decimal)>>(1).unwrap(),
+        vec![(nft, Decimal::from(5))]
+    );
+
+    let withdraw = |executor: &mut TransactionExecutor<InMemoryLedger>,
+                    ids: Vec<u128>,
+                    account: Address,
+                    key: Address| {
+        let transaction = TransactionBuilder::new(executor)
+            .withdraw_from_account(
+                &ResourceAmount::NonFungible {
+                    ids: ids.into_iter().collect(),
+                    resource_address: nft,
+                },
+                account,
+            )
+            .deposit_all_buckets(account)
+            .build(vec![key])
+            .unwrap();
+        executor.sign_and_run(transaction, false).unwrap().success
+    };
+    assert!(withdraw(&mut executor, vec![3, 4, 5, 6, 7], account2, key2));
+    assert!(withdraw(&mut executor, vec![1, 2, 8, 9, 10], account, key));
+    assert!(!withdraw(&mut executor, vec![3], account, key));
+
+    // An account without a vault of the resource gives an empty bucket, which leaves nothing
+    // to deposit
+    let key3 = executor.new_public_key();
+    let account3 = executor.new_account(key3);
+    let transaction = TransactionBuilder::new(&executor)
+        .withdraw_nfts_range_from_account(1..11, nft, account3)
+        .deposit_worktop_remainder(account3)
+        .build(vec![key3])
+        .unwrap();
+    let receipt = executor.sign_and_run(transaction, false).unwrap();
+    assert!(receipt.success);
+    assert_eq!(
+        receipt.return_value::<Vec<(Address, Decimal)>>(1).unwrap(),
+        vec![]
+    );
+}
+
+#[test]
+fn test_resource_movements() {
+    let mut ledger = InMemoryLedger::with_bootstrap();
+    let mut executor = TransactionExecutor::new(&mut ledger, 0, 0);
+    let key = executor.new_public_key();
+    let account = executor.new_account(key);
+    let key2 = executor.new_public_key();
+    let account2 = executor.new_account(key2);
+
+    let transaction = TransactionBuilder::new(&executor)
+        .withdraw_from_account(
+            &ResourceAmount::Fungible {
+                amount: 100.into(),
+                resource_address: RADIX_TOKEN,
+            },
+            account,
+        )
+        .call_method(
+            account2,
+            "deposit",
+            vec![format!("60,{}", RADIX_TOKEN)],
+            None,
+        )
+        .deposit_all_buckets(account)
+        .build(vec![key])
+        .unwrap();
+    let position =
+        |f: fn(&Instruction) -> bool| transaction.instructions().iter().position(f).unwrap();
+    let withdraw =
+        position(|i| matches!(i, Instruction::CallMethod { method, .. } if method == "withdraw"));
+    let take = position(|i| matches!(i, Instruction::TakeFromContext { .. }));
+    let deposit =
+        position(|i| matches!(i, Instruction::CallMethod { method, .. } if method == "deposit"));
+    let deposit_all = position(|i| matches!(i, Instruction::DepositAllBuckets { .. }));
+
+    let receipt = executor.sign_and_run(transaction, false).unwrap();
+    assert!(receipt.success);
+    let movement = |instruction_index, kind, amount: i32, from, to| Movement {
+        instruction_index,
+        kind,
+        resource: RADIX_TOKEN,
+        amount: amount.into(),
+        from,
+        to,
+    };
+    assert_eq!(
+        receipt.resource_movements,
+        vec![
+            movement(
+                withdraw,
+                MovementKind::Withdrawn,
+                60,
+                Some(account),
+                Some(account2)
+            ),
+            movement(
+                withdraw,
+                MovementKind::Withdrawn,
+                40,
+                Some(account),
+                Some(account)
+            ),
+            movement(take, MovementKind::TakenFromContext, 60, None, None),
+            movement(
+                deposit,
+                MovementKind::Deposited,
+                60,
+                Some(account),
+                Some(account2)
+            ),
+            movement(
+                deposit_all,
+                MovementKind::Deposited,
+                40,
+                Some(account),
+                Some(account)
+            ),
+        ]
+    );
+}
+
+#[test]
+fn test_immutable_nft_field_update() {
+    let mut ledger = InMemoryLedger::with_bootstrap();
+    let mut executor = TransactionExecutor::new(&mut ledger, 0, 0);
+    let key = executor.new_public_key();
+    let account = executor.new_account(key);
+    let package = executor.publish_package(&compile("nft"));
+
+    // `available` is mutable and can be updated
+    let transaction = TransactionBuilder::new(&executor)
+        .call_function(
+            package,
+            "NftTest",
+            "update_and_get_nft",
+            vec![],
+            Some(account),
+        )
+        .deposit_all_buckets(account)
+        .build(vec![key])
+        .unwrap();
+    assert!(executor.sign_and_run(transaction, false).unwrap().success);
+
+    // `name` is immutable and can't be
+    let transaction = TransactionBuilder::new(&executor)
+        .call_function(
+            package,
+            "NftTest",
+            "update_immutable_nft_data",
+            vec![],
+            Some(account),
+        )
+        .deposit_all_buckets(account)
+        .build(vec![key])
+        .unwrap();
+    let receipt = executor.sign_and_run(transaction, false).unwrap();
+    assert!(matches!(
+        receipt.error(),
+        Some(RuntimeError::NftError(NftError::ImmutableDataChanged))
+    ));
+}
+
+#[test]
+fn test_run_and_commit_if() {
+    let mut ledger = InMemoryLedger::with_bootstrap();
+    let mut executor = TransactionExecutor::new(&mut ledger, 0, 0);
+    let key = executor.new_public_key();
+    let account = executor.new_account(key);
+    let key2 = executor.new_public_key();
+    let account2 = executor.new_account(key2);
+
+    let transfer = |executor: &TransactionExecutor<InMemoryLedger>| {
+        let mut transaction = TransactionBuilder::new(executor)
+            .withdraw_from_account(
+                &ResourceAmount::Fungible {
+                    amount: 100.into(),
+                    resource_address: RADIX_TOKEN,
+                },
+                account,
+            )
+            .deposit_worktop_remainder(account2)
+            .build(vec![key])
+            .unwrap();
+        executor.sign(&mut transaction);
+        transaction
+    };
+    let at_least = |min: i32| {
+        move |receipt: &Receipt| {
+            let deposited = receipt.return_value::<Vec<(Address, Decimal)>>(1).unwrap();
+            deposited[0].1 >= Decimal::from(min)
+        }
+    };
+
+    // Discarded when the predicate fails
+    let nonce = executor.nonce();
+    let state_root = executor.ledger().state_root();
+    let (receipt, committed) = executor
+        .run_and_commit_if(transfer(&executor), false, at_least(200))
+        .unwrap();
+    assert!(receipt.success);
+    assert!(!committed);
+    assert_eq!(executor.nonce(), nonce);
+    assert_eq!(executor.ledger().state_root(), state_root);
+
+    // Committed when it passes
+    let (receipt, committed) = executor
+        .run_and_commit_if(transfer(&executor), false, at_least(100))
+        .unwrap();
+    assert!(receipt.success);
+    assert!(committed);
+    assert_eq!(executor.nonce(), nonce + 1);
+    assert_ne!(executor.ledger().state_root(), state_root);
+}
+
+#[test]
+fn test_try_call_method() {
+    let mut ledger = InMemoryLedger::with_bootstrap();
+    let mut executor = TransactionExecutor::new(&mut ledger, 0, 0);
+    let key = executor.new_public_key();
+    let account = executor.new_account(key);
+
+    let build = |method: &str| -> Result<Transaction, BuildTransactionError> {
+        TransactionBuilder::new(&executor)
+            .try_call_method(
+                account,
+                method,
+                vec!["10".to_owned(), RADIX_TOKEN.to_string()],
+                None,
+            )?
+            .deposit_all_buckets(account)
+            .build(vec![key])
+    };
+    let transaction = build("withdraw").unwrap();
+    assert!(matches!(
+        build("no_such_method"),
+        Err(BuildTransactionError::MethodNotFound(m)) if m == "no_such_method"
+    ));
+    assert!(executor.sign_and_run(transaction, false).unwrap().success);
+}
+
+#[test]
+fn test_try_call_method_rolls_back_failed_args() {
+    let mut ledger = InMemoryLedger::with_bootstrap();
+    let mut executor = TransactionExecutor::new(&mut ledger, 0, 0);
+    let key = executor.new_public_key();
+    let account = executor.new_account(key);
+    let package = executor.publish_package(&compile("spin"));
+    let transaction = TransactionBuilder::new(&executor)
+        .call_function(package, "BudgetTest", "new", vec![], None)
+        .build(vec![])
+        .unwrap();
+    let receipt = executor.sign_and_run(transaction, true).unwrap();
+    assert!(receipt.success);
+    let sink = receipt.component(0).unwrap();
+
+    // the bucket argument is prepared before the bad rounds argument fails
+    let args = |rounds: &str| vec![format!("10,{}", RADIX_TOKEN), rounds.to_owned()];
+    let mut builder = TransactionBuilder::new(&executor);
+    assert!(matches!(
+        builder.try_call_method(sink, "take_and_spin", args("many"), Some(account)),
+        Err(BuildTransactionError::FailedToBuildArgs(
+            BuildArgsError::FailedToParse(1, ..)
+        ))
+    ));
+    let transaction = builder
+        .try_call_method(sink, "take_and_spin", args("1"), Some(account))
+        .unwrap()
+        .build(vec![key])
+        .unwrap();
+
+    // neither the withdrawal nor the bucket of the failed call is left behind
+    let expected = TransactionBuilder::new(&executor)
+        .try_call_method(sink, "take_and_spin", args("1"), Some(account))
+        .unwrap()
+        .build(vec![key])
+        .unwrap();
+    assert_eq!(transaction.hash(), expected.hash());
+}
+
+#[test]
+fn test_worktop_resource_limit() {
+    let mut ledger = InMemoryLedger::with_bootstrap();
+    let mut executor = TransactionExecutor::new(&mut ledger, 0, 0);
+    let key = executor.new_public_key();
+    let account = executor.new_account(key);
+    assert_eq!(
+        executor.max_worktop_resources(),
+        Some(DEFAULT_MAX_WORKTOP_RESOURCES)
+    );
+    executor.set_max_worktop_resources(Some(2));
+
+    let new_tokens = |executor: &TransactionExecutor<InMemoryLedger>, n: usize| {
+        let mut builder = TransactionBuilder::new(executor);
+        for _ in 0..n {
+            builder.new_token_fixed(HashMap::new(), 100.into());
+        }
+        builder
+            .deposit_all_buckets(account)
+            .build(vec![key])
+            .unwrap()
+    };
+
+    let receipt = executor
+        .sign_and_run(new_tokens(&executor, 2), false)
+        .unwrap();
+    assert!(receipt.success);
+
+    let receipt = executor
+        .sign_and_run(new_tokens(&executor, 3), false)
+        .unwrap();
+    assert!(!receipt.success);
+    assert!(matches!(
+        receipt.results[2],
+        Err(RuntimeError::WorktopResourceLimitExceeded(2))
+    ));
+}
+
+#[test]
+fn test_create_badge() {
+    let mut ledger = InMemoryLedger::with_bootstrap();
+    let mut executor = TransactionExecutor::new(&mut ledger, 0, 0);
+    let key = executor.new_public_key();
+    let account = executor.new_account(key);
+    let admin_badge = executor.create_badge(account, 1.into());
+
+    let transaction = TransactionBuilder::new(&executor)
+        .new_token_mutable(HashMap::new(), admin_badge)
+        .build(vec![key])
+        .unwrap();
+    let receipt = executor.sign_and_run(transaction, false).unwrap();
+    assert!(receipt.success);
+    let token = receipt.resource_def(0).unwrap();
+
+    // The badge is in the account and authorizes minting
+    let transaction = TransactionBuilder::new(&executor)
+        .withdraw_from_account(
+            &ResourceAmount::Fungible {
+                amount: 1.into(),
+                resource_address: admin_badge,
+            },
+            account,
+        )
+        .mint(100.into(), token, admin_badge)
+        .deposit_all_buckets(account)
+        .build(vec![key])
+        .unwrap();
+    assert!(executor.sign_and_run(transaction, false).unwrap().success);
+}
+
+#[test]
+fn test_transaction_instructions() {
+    let mut ledger = InMemoryLedger::with_bootstrap();
+    let mut executor = TransactionExecutor::new(&mut ledger, 0, 0);
+    let key = executor.new_public_key();
+    let account = executor.new_account(key);
+
+    let transaction = TransactionBuilder::new(&executor)
+        .withdraw_from_account(
+            &ResourceAmount::Fungible {
+                amount: 1.into(),
+                resource_address: RADIX_TOKEN,
+            },
+            account,
+        )
+        .deposit_all_buckets(account)
+        .build(vec![key])
+        .unwrap();
+    assert!(!transaction.is_empty());
+    assert_eq!(transaction.len(), 3);
+    assert_eq!(transaction.instructions().len(), transaction.len());
+    assert_eq!(
+        transaction.instructions().last(),
+        Some(&Instruction::End { signers: vec![key] })
+    );
+}
+
+#[test]
+fn test_deposit_merges_nfts() {
+    let mut ledger = InMemoryLedger::with_bootstrap();
+    let mut executor = TransactionExecutor::new(&mut ledger, 0, 0);
+    let key = executor.new_public_key();
+    let account = executor.new_account(key);
+    let key2 = executor.new_public_key();
+    let account2 = executor.new_account(key2);
+    let package = executor.publish_package(&compile("nft"));
+
+    // Mints #1..#3 into the second account
+    let transaction = TransactionBuilder::new(&executor)
+        .call_function(
+            package,
+            "NftTest",
+            "create_nft_batch",
+            vec!["3".to_owned()],
+            None,
+        )
+        .deposit_all_buckets(account2)
+        .build(vec![key2])
+        .unwrap();
+    let receipt = executor.sign_and_run(transaction, false).unwrap();
+    assert!(receipt.success);
+    let nft = receipt.resource_def(0).unwrap();
+
+    let transfer = |executor: &mut TransactionExecutor<InMemoryLedger>, ids: Vec<u128>| {
+        let transaction = TransactionBuilder::new(executor)
+            .withdraw_from_account(
+                &ResourceAmount::NonFungible {
+                    ids: ids.into_iter().collect(),
+                    resource_address: nft,
+                },
+                account2,
+            )
+            .deposit_all_buckets(account)
+            .build(vec![key2])
+            .unwrap();
+        assert!(executor.sign_and_run(transaction, false).unwrap().success);
+    };
+    transfer(&mut executor, vec![3]);
+    assert_eq!(executor.nft_ids_of(account, nft).unwrap(), vec![3]);
+    transfer(&mut executor, vec![1, 2]);
+
+    let mut ids = executor.nft_ids_of(account, nft).unwrap();
+    ids.sort_unstable();
+    assert_eq!(ids, vec![1, 2, 3]);
+    assert!(executor.nft_ids_of(account2, nft).unwrap().is_empty());
+}
+
+#[test]
+fn test_validate_args() {
+    let mut ledger = InMemoryLedger::with_bootstrap();
+    let mut executor = TransactionExecutor::new(&mut ledger, 0, 0);
+    let key = executor.new_public_key();
+    let account = executor.new_account(key);
+
+    let builder = TransactionBuilder::new(&executor);
+    let invalid = |args: [&str; 2]| -> Vec<usize> {
+        let args: Vec<String> = args.iter().map(|a| a.to_string()).collect();
+        match builder.validate_method_args(account, "withdraw", &args) {
+            Ok(()) => vec![],
+            Err(BuildTransactionError::InvalidArgs(errors)) => {
+                errors.into_iter().map(|(i, _)| i).collect()
+            }
+            Err(e) => panic!("Unexpected error: {}", e),
+        }
+    };
+    let xrd = RADIX_TOKEN.to_string();
+    assert_eq!(invalid(["10", &xrd]), Vec::<usize>::new());
+    assert_eq!(invalid(["ten", &xrd]), vec![0]);
+    assert_eq!(invalid(["10", "xrd"]), vec![1]);
+    assert_eq!(invalid(["ten", "xrd"]), vec![0, 1]);
+
+    assert!(matches!(
+        builder.validate_function_args(ACCOUNT_PACKAGE, "Account", "new", &["key".to_owned()]),
+        Err(BuildTransactionError::InvalidArgs(errors)) if errors.len() == 1 && errors[0].0 == 0
+    ));
+    assert!(matches!(
+        builder.validate_method_args(account, "no_such_method", &[]),
+        Err(BuildTransactionError::MethodNotFound(_))
+    ));
+}
+
+#[test]
+fn test_context_worktop_amount() {
+    let mut ledger = InMemoryLedger::with_bootstrap();
+    let mut executor = TransactionExecutor::new(&mut ledger, 0, 0);
+    let key = executor.new_public_key();
+    let account = executor.new_account(key);
+    let package = executor.publish_package(&compile("context"));
+
+    for (staged, expected) in [(100, true), (10, false)] {
+        let transaction = TransactionBuilder::new(&executor)
+            .withdraw_from_account(
+                &ResourceAmount::Fungible {
+                    amount: staged.into(),
+                    resource_address: RADIX_TOKEN,
+                },
+                account,
+            )
+            .call_function(
+                package,
+                "ContextTest",
+                "check_staged",
+                vec![RADIX_TOKEN.to_string(), "50".to_owned()],
+                Some(account),
+            )
+            .call_method_with_all_resources(account, "deposit_batch")
+            .build(vec![key])
+            .unwrap();
+        let receipt = executor.sign_and_run(transaction, true).unwrap();
+        assert!(receipt.success);
+        assert_eq!(receipt.return_value::<bool>(1).unwrap(), expected);
+    }
+}
+
+#[test]
+fn test_metadata_encoding_is_deterministic() {
+    let mut ledger = InMemoryLedger::with_bootstrap();
+    let mut executor = TransactionExecutor::new(&mut ledger, 0, 0);
+    let key = executor.new_public_key();
+    let account = executor.new_account(key);
+
+    let build = |executor: &TransactionExecutor<InMemoryLedger>, keys: Vec<u32>| {
+        let metadata: HashMap<String, String> = keys
+            .into_iter()
+            .map(|i| (format!("key{}", i), format!("value{}", i)))
+            .collect();
+        TransactionBuilder::new(executor)
+            .new_token_fixed(metadata, 100.into())
+            .call_method_with_all_resources(account, "deposit_batch")
+            .build(vec![])
+            .unwrap()
+    };
+    let transaction1 = build(&executor, (0..32).collect());
+    let transaction2 = build(&executor, (0..32).rev().collect());
+    assert_eq!(
+        scrypto_encode(&transaction1.instructions().to_vec()),
+        scrypto_encode(&transaction2.instructions().to_vec())
+    );
+
+    let receipt = executor.sign_and_run(transaction1, false).unwrap();
+    assert!(receipt.success);
+    let resource_def = executor
+        .ledger()
+        .get_resource_def(receipt.resource_def(0).unwrap())
+        .unwrap();
+    assert_eq!(resource_def.metadata().len(), 32);
+    assert_eq!(resource_def.metadata()["key7"], "value7");
+}
+
+#[test]
+fn test_read_only_executor() {
+    let mut ledger = InMemoryLedger::with_bootstrap();
+    let account = {
+        let mut executor = TransactionExecutor::new(&mut ledger, 0, 0);
+        let key = executor.new_public_key();
+        executor.new_account(key)
+    };
+
+    // Several read-only executors may share the ledger
+    let mut executor1 = TransactionExecutor::new_read_only(&ledger, 0, 1);
+    let executor2 = TransactionExecutor::new_read_only(&ledger, 0, 1);
+    assert!(executor1.is_read_only());
+    assert_eq!(
+        executor1.balance_of(account, RADIX_TOKEN).unwrap(),
+        Decimal::from(1_000_000)
+    );
+    assert_eq!(
+        executor2.balance_of(account, RADIX_TOKEN).unwrap(),
+        Decimal::from(1_000_000)
+    );
+    assert!(matches!(
+        executor2.balance_of(account, Address::ResourceDef([7u8; 26])),
+        Err(RuntimeError::ComponentVaultNotFound(..))
+    ));
+
+    let transaction = TransactionBuilder::new(&executor1)
+        .withdraw_from_account(
+            &ResourceAmount::Fungible {
+                amount: 100.into(),
+                resource_address: RADIX_TOKEN,
+            },
+            account,
+        )
+        .call_method_with_all_resources(account, "deposit_batch")
+        .build(vec![])
+        .unwrap();
+    assert!(matches!(
+        executor1.sign_and_run(transaction, false),
+        Err(TransactionExecutionError::ReadOnlyLedger)
+    ));
+}
+
+#[test]
+fn test_create_proof_from_account() {
+    let mut ledger = InMemoryLedger::with_bootstrap();
+    let mut executor = TransactionExecutor::new(&mut ledger, 0, 0);
+    let key = executor.new_public_key();
+    let account = executor.new_account(key);
+    let package = executor.publish_package(&compile("membership"));
+
+    let transaction = TransactionBuilder::new(&executor)
+        .call_function(package, "Club", "new", vec![], None)
+        .deposit_all_buckets(account)
+        .build(vec![key])
+        .unwrap();
+    let receipt = executor.sign_and_run(transaction, false).unwrap();
+    assert!(receipt.success);
+    let club = receipt.component(0).unwrap();
+    let membership = receipt.resource_def(0).unwrap();
+
+    // The membership is presented straight from the account vault
+    let transaction = TransactionBuilder::new(&executor)
+        .declare_bucket_ref(|builder, rid| {
+            builder
+                .create_proof_from_account(1.into(), membership, account, rid)
+                .add_instruction(Instruction::CallMethod {
+                    component_address: club,
+                    method: "enter".to_owned(),
+                    args: vec![SmartValue::from(rid)],
+                })
+        })
+        .build(vec![key])
+        .unwrap();
+    let receipt = executor.sign_and_run(transaction, false).unwrap();
+    assert!(receipt.success);
+    assert_eq!(
+        executor.nft_ids_of(account, membership).unwrap(),
+        vec![1, 2]
+    );
+
+    // The resource backing the proof can't be withdrawn while the proof is alive
+    let transaction = TransactionBuilder::new(&executor)
+        .declare_bucket_ref(|builder, rid| {
+            builder
+                .create_proof_from_account(2.into(), membership, account, rid)
+                .withdraw_from_account(
+                    &ResourceAmount::NonFungible {
+                        ids: BTreeSet::from([1]),
+                        resource_address: membership,
+                    },
+                    account,
+                )
+                .add_instruction(Instruction::CallMethod {
+                    component_address: club,
+                    method: "enter".to_owned(),
+                    args: vec![SmartValue::from(rid)],
+                })
+        })
+        .deposit_all_buckets(account)
+        .build(vec![key])
+        .unwrap();
+    let receipt = executor.sign_and_run(transaction, false).unwrap();
+    assert!(!receipt.success);
+
+    // Only the owner can create a proof
+    let other_key = executor.new_public_key();
+    let transaction = TransactionBuilder::new(&executor)
+        .declare_bucket_ref(|builder, rid| {
+            builder
+                .create_proof_from_account(1.into(), membership, account, rid)
+                .add_instruction(Instruction::CallMethod {
+                    component_address: club,
+                    method: "enter".to_owned(),
+                    args: vec![SmartValue::from(rid)],
+                })
+        })
+        .build(vec![other_key])
+        .unwrap();
+    let receipt = executor.sign_and_run(transaction, false).unwrap();
+    assert!(!receipt.success);
+}
+
+#[test]
+fn test_max_execution_time() {
+    let mut ledger = InMemoryLedger::with_bootstrap();
+    let mut executor = TransactionExecutor::new(&mut ledger, 0, 0);
+    let key = executor.new_public_key();
+    let account = executor.new_account(key);
+    let transaction = || {
+        TransactionBuilder::new(&executor)
+            .withdraw_from_account(
+                &ResourceAmount::Fungible {
+                    amount: 100.into(),
+                    resource_address: RADIX_TOKEN,
+                },
+                account,
+            )
+            .call_method_with_all_resources(account, "deposit_batch")
+            .build(vec![key])
+            .unwrap()
+    };
+    let (transaction1, transaction2) = (transaction(), transaction());
+
+    // Instrumented blueprints run as usual within the limit
+    executor.set_max_execution_time(Some(Duration::from_secs(60)));
+    let receipt = executor.sign_and_run(transaction1, false).unwrap();
+    assert!(receipt.success);
+
+    // and are aborted at the first loop iteration past it
+    executor.set_max_execution_time(Some(Duration::ZERO));
+    let receipt = executor.sign_and_run(transaction2, false).unwrap();
+    assert!(!receipt.success);
+    assert!(matches!(receipt.error(), Some(RuntimeError::Timeout)));
+}
+
+#[test]
+fn test_timeout_spinning_blueprint() {
+    let mut ledger = InMemoryLedger::with_bootstrap();
+    let mut executor = TransactionExecutor::new(&mut ledger, 0, 0);
+    let package = executor.publish_package(&compile("spin"));
+
+    executor.set_max_execution_time(Some(Duration::from_millis(200)));
+    let transaction = TransactionBuilder::new(&executor)
+        .call_function(package, "Spinner", "spin", vec![], None)
+        .build(vec![])
+        .unwrap();
+    let receipt = executor.sign_and_run(transaction, false).unwrap();
+    assert!(!receipt.success);
+    assert!(matches!(receipt.error(), Some(RuntimeError::Timeout)));
+}
+
+#[test]
+fn test_call_with_budget() {
+    let mut ledger = InMemoryLedger::with_bootstrap();
+    let mut executor = TransactionExecutor::new(&mut ledger, 0, 0);
+    let package = executor.publish_package(&compile("spin"));
+
+    let transaction1 = TransactionBuilder::new(&executor)
+        .call_function(package, "BudgetTest", "new", vec![], None)
+        .build(vec![])
+        .unwrap();
+    let receipt1 = executor.sign_and_run(transaction1, true).unwrap();
+    assert!(receipt1.success);
+    let component = receipt1.component(1).unwrap();
+
+    // a callee within its budget keeps the bucket
+    let transaction2 = TransactionBuilder::new(&executor)
+        .call_method(
+            component,
+            "send_with_budget",
+            vec!["10".to_owned(), "1000".to_owned()],
+            None,
+        )
+        .call_method(component, "amounts", vec![], None)
+        .build(vec![])
+        .unwrap();
+    let receipt2 = executor.sign_and_run(transaction2, true).unwrap();
+    assert!(receipt2.success);
+    assert_eq!(receipt2.return_value::<Option<CallError>>(0).unwrap(), None);
+    assert_eq!(
+        receipt2.return_value::<(Decimal, Decimal)>(1).unwrap(),
+        (999.into(), 1.into())
+    );
+
+    // a callee spinning past its budget fails, with its deposit rolled back and the bucket
+    // returned, while the caller carries on
+    let transaction3 = TransactionBuilder::new(&executor)
+        .call_method(
+            component,
+            "send_with_budget",
+            vec!["1000000".to_owned(), "1000".to_owned()],
+            None,
+        )
+        .call_method(component, "amounts", vec![], None)
+        .build(vec![])
+        .unwrap();
+    let receipt3 = executor.sign_and_run(transaction3, true).unwrap();
+    assert!(receipt3.success);
+    assert_eq!(
+        receipt3.return_value::<Option<CallError>>(0).unwrap(),
+        Some(CallError::BudgetExceeded)
+    );
+    assert_eq!(
+        receipt3.return_value::<(Decimal, Decimal)>(1).unwrap(),
+        (999.into(), 1.into())
+    );
+
+    // only the failed call is rolled back when both happen in one transaction
+    let transaction4 = TransactionBuilder::new(&executor)
+        .call_method(
+            component,
+            "send_with_budget",
+            vec!["10".to_owned(), "1000".to_owned()],
+            None,
+        )
+        .call_method(
+            component,
+            "send_with_budget",
+            vec!["1000000".to_owned(), "1000".to_owned()],
+            None,
+        )
+        .call_method(component, "amounts", vec![], None)
+        .build(vec![])
+        .unwrap();
+    let receipt4 = executor.sign_and_run(transaction4, true).unwrap();
+    assert!(receipt4.success);
+    assert_eq!(
+        receipt4.return_value::<(Decimal, Decimal)>(2).unwrap(),
+        (998.into(), 2.into())
+    );
+}
+
+#[test]
+fn test_nft_collection() {
+    let mut ledger = InMemoryLedger::with_bootstrap();
+    let mut executor = TransactionExecutor::new(&mut ledger, 0, 0);
+    let key = executor.new_public_key();
+    let account = executor.new_account(key);
+    let package = executor.publish_package(&compile("nft"));
+
+    let transaction = TransactionBuilder::new(&executor)
+        .call_function(package, "NftTest", "create_nft_collection", vec![], None)
+        .deposit_all_buckets(account)
+        .build(vec![key])
+        .unwrap();
+    let receipt = executor.sign_and_run(transaction, false).unwrap();
+    assert!(receipt.success);
+    let (_, name) = receipt.return_value::<(Bid, String)>(0).unwrap();
+    assert_eq!(name, "Two");
+    let collection = receipt.resource_def(0).unwrap();
+    assert_eq!(
+        executor.nft_ids_of(account, collection).unwrap(),
+        vec![1, 2, 3]
+    );
+}
+
+#[test]
+fn test_preview_auth() {
+    let mut ledger = InMemoryLedger::with_bootstrap();
+    let mut executor = TransactionExecutor::new(&mut ledger, 0, 0);
+    let key = executor.new_public_key();
+    let account = executor.new_account(key);
+    let admin_badge = executor.create_badge(account, 1.into());
+    let other_badge = executor.create_badge(account, 1.into());
+    let package = executor.publish_package(&compile("badge"));
+
+    let transaction = TransactionBuilder::new(&executor)
+        .call_function(
+            package,
+            "GatedTest",
+            "new",
+            vec![admin_badge.to_string()],
+            Some(account),
+        )
+        .build(vec![key])
+        .unwrap();
+    let receipt = executor.sign_and_run(transaction, false).unwrap();
+    assert!(receipt.success);
+    let component = receipt.component(0).unwrap();
+
+    let open = |executor: &TransactionExecutor<InMemoryLedger>, badge: Address| {
+        let mut transaction = TransactionBuilder::new(executor)
+            .call_method(
+                component,
+                "open",
+                vec![format!("1,{}", badge)],
+                Some(account),
+            )
+            .deposit_all_buckets(account)
+            .build(vec![key])
+            .unwrap();
+        executor.sign(&mut transaction);
+        transaction
+    };
+    let transaction = open(&executor, other_badge);
+    let open_index = transaction
+        .instructions()
+        .iter()
+        .position(|i| matches!(i, Instruction::CallMethod { method, .. } if method == "open"))
+        .unwrap();
+    assert_eq!(
+        executor.preview_auth(transaction.clone()).unwrap(),
+        vec![AuthRequirement::Method {
+            instruction_index: open_index,
+            component_address: component,
+            method: "open".to_owned(),
+            allowed_badges: vec![admin_badge],
+        }]
+    );
+
+    // The preview is not enforced when running normally
+    let receipt = executor.sign_and_run(transaction, false).unwrap();
+    assert!(!receipt.success);
+    assert!(receipt.auth_requirements.is_empty());
+
+    // Nothing done by the preview was kept
+    let transaction = open(&executor, admin_badge);
+    assert!(executor
+        .preview_auth(transaction.clone())
+        .unwrap()
+        .is_empty());
+    let receipt = executor.sign_and_run(transaction, false).unwrap();
+    assert!(receipt.success);
+    assert_eq!(receipt.return_value::<u32>(open_index).unwrap(), 1);
+
+    // A missing signature is reported rather than rejected
+    let mut transaction = open(&executor, admin_badge);
+    transaction.signatures.clear();
+    assert_eq!(
+        executor.preview_auth(transaction.clone()).unwrap(),
+        vec![AuthRequirement::Signature { signer: key }]
+    );
+
+    // A transaction failing for another reason is an error
+    let failing = TransactionBuilder::new(&executor)
+        .withdraw_from_account(
+            &ResourceAmount::Fungible {
+                amount: 1000.into(),
+                resource_address: admin_badge,
+            },
+            account,
+        )
+        .deposit_all_buckets(account)
+        .build(vec![key])
+        .unwrap();
+    assert!(matches!(
+        executor.preview_auth(failing),
+        Err(TransactionExecutionError::PreviewFailed(receipt)) if !receipt.success
+    ));
+
+    // Previewing only reads the ledger
+    let executor = TransactionExecutor::new_read_only(&ledger, 0, 0);
+    assert_eq!(
+        executor.preview_auth(transaction).unwrap(),
+        vec![AuthRequirement::Signature { signer: key }]
+    );
+}
+
+#[test]
+fn test_call_enum_arg() {
+    let mut ledger = InMemoryLedger::with_bootstrap();
+    let mut executor = TransactionExecutor::new(&mut ledger, 0, 0);
+    let key = executor.new_public_key();
+    let account = executor.new_account(key);
+    let package = executor.publish_package(&compile("call"));
+
+    let transaction = TransactionBuilder::new(&executor)
+        .call_function(
+            package,
+            "OrderTest",
+            "limit_price",
+            vec!["Market".to_owned()],
+            Some(account),
+        )
+        .call_function(
+            package,
+            "OrderTest",
+            "limit_price",
+            vec!["Limit(10)".to_owned()],
+            Some(account),
+        )
+        .build(vec![key])
+        .unwrap();
+    let receipt = executor.sign_and_run(transaction, false).unwrap();
+    assert!(receipt.success);
+    assert_eq!(receipt.return_value::<Option<Decimal>>(0).unwrap(), None);
+    assert_eq!(
+        receipt.return_value::<Option<Decimal>>(1).unwrap(),
+        Some(10.into())
+    );
+
+    // A bucket field is withdrawn from the account
+    let transaction = TransactionBuilder::new(&executor)
+        .call_function(
+            package,
+            "OrderTest",
+            "pay",
+            vec![format!("Paid(100,{},tip)", RADIX_TOKEN)],
+            Some(account),
+        )
+        .deposit_all_buckets(account)
+        .build(vec![key])
+        .unwrap();
+    let receipt = executor.sign_and_run(transaction, false).unwrap();
+    assert!(receipt.success);
+    assert!(receipt
+        .logs
+        .contains(&(LogLevel::Info, "tip: 100".to_owned())));
+
+    for arg in [
+        format!("Paid(100,{})", RADIX_TOKEN),
+        format!("Paid(100,tip,{})", RADIX_TOKEN),
+        "Paid(100,tip)".to_owned(),
+    ] {
+        let result = TransactionBuilder::new(&executor)
+            .call_function(package, "OrderTest", "pay", vec![arg], Some(account))
+            .build(vec![key]);
+        assert!(matches!(
+            result,
+            Err(BuildTransactionError::FailedToBuildArgs(
+                BuildArgsError::FailedToParse(0, _, _)
+            ))
+        ));
+    }
+
+    for arg in ["Stop(1)", "Market(1)", "Limit", "Limit(1,2)", "Limit(x)"] {
+        let result = TransactionBuilder::new(&executor)
+            .call_function(
+                package,
+                "OrderTest",
+                "limit_price",
+                vec![arg.to_owned()],
+                Some(account),
+            )
+            .build(vec![key]);
+        assert!(matches!(
+            result,
+            Err(BuildTransactionError::FailedToBuildArgs(
+                BuildArgsError::FailedToParse(0, _, _)
+            ))
+        ));
+    }
+}
+
+#[test]
+fn test_call_struct_arg() {
+    let mut ledger = InMemoryLedger::with_bootstrap();
+    let mut executor = TransactionExecutor::new(&mut ledger, 0, 0);
+    let key = executor.new_public_key();
+    let account = executor.new_account(key);
+    let package = executor.publish_package(&compile("call"));
+
+    let transaction = TransactionBuilder::new(&executor)
+        .call_function(
+            package,
+            "ConfigTest",
+            "fee_and_admin",
+            vec![format!("{{fee=0.25,admin={}}}", account)],
+            Some(account),
+        )
+        .call_function(
+            package,
+            "ConfigTest",
+            "fee_and_admin",
+            vec![format!("{{ admin = {}, fee = 0.5 }}", account)],
+            Some(account),
+        )
+        .build(vec![key])
+        .unwrap();
+    let receipt = executor.sign_and_run(transaction, false).unwrap();
+    assert!(receipt.success);
+    assert_eq!(
+        receipt.return_value::<(Decimal, Address)>(0).unwrap(),
+        (Decimal::from_str("0.25").unwrap(), account)
+    );
+    assert_eq!(
+        receipt.return_value::<(Decimal, Address)>(1).unwrap(),
+        (Decimal::from_str("0.5").unwrap(), account)
+    );
+
+    for arg in [
+        "fee=1".to_owned(),
+        "{fee=1}".to_owned(),
+        format!("{{fee=1,fee=2,admin={}}}", account),
+        format!("{{fee=1,admin={},owner={}}}", account, account),
+        format!("{{fee=x,admin={}}}", account),
+        format!("{{1,{}}}", account),
+    ] {
+        let result = TransactionBuilder::new(&executor)
+            .call_function(
+                package,
+                "ConfigTest",
+                "fee_and_admin",
+                vec![arg],
+                Some(account),
+            )
+            .build(vec![key]);
+        assert!(matches!(
+            result,
+            Err(BuildTransactionError::FailedToBuildArgs(
+                BuildArgsError::FailedToParse(0, _, _)
+            ))
+        ));
+    }
+
+    // A bucket field is written like a standalone argument
+    let transaction = TransactionBuilder::new(&executor)
+        .call_function(
+            package,
+            "ConfigTest",
+            "tip",
+            vec![format!("{{payment=100,{},memo=thanks}}", RADIX_TOKEN)],
+            Some(account),
+        )
+        .call_function(
+            package,
+            "ConfigTest",
+            "tip",
+            vec![format!("{{ memo = cheers, payment = 5,{} }}", RADIX_TOKEN)],
+            Some(account),
+        )
+        .call_function(
+            package,
+            "ConfigTest",
+            "unnamed_tip",
+            vec![format!("{{7,{},again}}", RADIX_TOKEN)],
+            Some(account),
+        )
+        .deposit_all_buckets(account)
+        .build(vec![key])
+        .unwrap();
+    let receipt = executor.sign_and_run(transaction, false).unwrap();
+    assert!(receipt.success);
+    for log in ["thanks: 100", "cheers: 5", "again: 7"] {
+        assert!(receipt.logs.contains(&(LogLevel::Info, log.to_owned())));
+    }
+
+    for (function, arg) in [
+        (
+            "tip",
+            format!("{{payment=100,memo=thanks,{}}}", RADIX_TOKEN),
+        ),
+        (
+            "tip",
+            format!("{{memo=thanks,{},payment=100}}", RADIX_TOKEN),
+        ),
+        ("unnamed_tip", format!("{{7,again,{}}}", RADIX_TOKEN)),
+    ] {
+        let result = TransactionBuilder::new(&executor)
+            .call_function(package, "ConfigTest", function, vec![arg], Some(account))
+            .build(vec![key]);
+        assert!(matches!(
+            result,
+            Err(BuildTransactionError::FailedToBuildArgs(
+                BuildArgsError::FailedToParse(0, _, _)
+            ))
+        ));
+    }
+}
+
+#[test]
+fn test_shared_ledger() {
+    let (ledger, accounts) = InMemoryLedger::with_genesis_accounts(2, 1000.into());
+    let ledger = SharedLedger::new(ledger);
+    let (from, to) = (&accounts[0], &accounts[1]);
+
+    let readers: Vec<_> = (0..4)
+        .map(|_| {
+            let ledger = ledger.clone();
+            let (from, to) = (from.address, to.address);
+            std::thread::spawn(move || {
+                for _ in 0..50 {
+                    let reader = ledger.read();
+                    let executor = TransactionExecutor::new_read_only(&reader, 0, 0);
+                    let sent = executor.balance_of(from, RADIX_TOKEN).unwrap();
+                    let received = executor.balance_of(to, RADIX_TOKEN).unwrap();
+                    // Readers never observe a half-applied transfer
+                    assert!(sent == 1000.into() || sent == 900.into());
+                    assert_eq!(sent + received, 2000.into());
+                }
+            })
+        })
+        .collect();
+
+    {
+        let mut guard = ledger.write();
+        let mut executor = TransactionExecutor::new(&mut *guard, 0, 0);
+        executor.set_require_signatures(true);
+        let transaction = TransactionBuilder::new(&executor)
+            .withdraw_from_account(
+                &ResourceAmount::Fungible {
+                    amount: 100.into(),
+                    resource_address: RADIX_TOKEN,
+                },
+                from.address,
+            )
+            .deposit_all_buckets(to.address)
+            .build_signed(&[from])
+            .unwrap();
+        assert!(executor.run(transaction, false).unwrap().success);
+    }
+
+    for reader in readers {
+        reader.join().unwrap();
+    }
+    let reader = ledger.read();
+    let executor = TransactionExecutor::new_read_only(&reader, 0, 0);
+    assert_eq!(
+        executor.balance_of(from.address, RADIX_TOKEN).unwrap(),
+        900.into()
+    );
+    assert_eq!(
+        executor.balance_of(to.address, RADIX_TOKEN).unwrap(),
+        1100.into()
+    );
+
+    // Readers can preview transactions too
+    let transaction = TransactionBuilder::new(&executor)
+        .withdraw_from_account(
+            &ResourceAmount::Fungible {
+                amount: 100.into(),
+                resource_address: RADIX_TOKEN,
+            },
+            to.address,
+        )
+        .deposit_all_buckets(from.address)
+        .build(vec![to.key])
+        .unwrap();
+    assert_eq!(
+        executor.preview_auth(transaction).unwrap(),
+        vec![AuthRequirement::Signature { signer: to.key }]
+    );
+}
+
+#[test]
+fn test_pool_price() {
+    let mut ledger = InMemoryLedger::with_bootstrap();
+    let mut executor = TransactionExecutor::new(&mut ledger, 0, 0);
+    let key = executor.new_public_key();
+    let account = executor.new_account(key);
+    let other_account = executor.new_account(key);
+    let usd = executor.fund_account(account, 1000.into(), HashMap::new());
+    let gold = executor.fund_account(account, 10.into(), HashMap::new());
+
+    // Any component holding both resources can be priced, e.g. an account
+    assert_eq!(
+        executor.pool_price(account, usd, gold),
+        Some(Decimal::from_str("0.01").unwrap())
+    );
+    assert_eq!(executor.pool_price(account, gold, usd), Some(100.into()));
+
+    // Missing or empty reserves have no price
+    assert_eq!(executor.pool_price(other_account, usd, gold), None);
+    let transaction = TransactionBuilder::new(&executor)
+        .withdraw_from_account(
+            &ResourceAmount::Fungible {
+                amount: 10.into(),
+                resource_address: gold,
+            },
+            account,
+        )
+        .deposit_all_buckets(other_account)
+        .build(vec![key])
+        .unwrap();
+    assert!(executor.sign_and_run(transaction, false).unwrap().success);
+    assert_eq!(executor.pool_price(account, usd, gold), None);
+    assert_eq!(executor.pool_price(account, gold, usd), None);
+}
+
+#[test]
+fn test_liquidity_pool_price() {
+    let mut ledger = InMemoryLedger::with_bootstrap();
+    let mut executor = TransactionExecutor::new(&mut ledger, 0, 0);
+    let key = executor.new_public_key();
+    let account = executor.new_account(key);
+    let usd = executor.fund_account(account, 1000.into(), HashMap::new());
+    let package = executor.publish_package(&compile("pool"));
+
+    let transaction = TransactionBuilder::new(&executor)
+        .create_liquidity_pool(
+            package,
+            "Pool",
+            account,
+            ResourceAmount::Fungible {
+                amount: 1000.into(),
+                resource_address: usd,
+            },
+            ResourceAmount::Fungible {
+                amount: 10.into(),
+                resource_address: RADIX_TOKEN,
+            },
+        )
+        .build(vec![key])
+        .unwrap();
+    let receipt = executor.sign_and_run(transaction, false).unwrap();
+    assert!(receipt.success);
+    let pool = receipt.component(0).unwrap();
+
+    assert_eq!(
+        executor.pool_price(pool, usd, RADIX_TOKEN),
+        Some(Decimal::from_str("0.01").unwrap())
+    );
+}
+
+#[test]
+fn test_resources_created() {
+    let mut ledger = InMemoryLedger::with_bootstrap();
+    let mut executor = TransactionExecutor::new(&mut ledger, 0, 0);
+    let key = executor.new_public_key();
+    let account = executor.new_account(key);
+    let mint_badge = executor.create_badge(account, 1.into());
+
+    let transaction = TransactionBuilder::new(&executor)
+        .new_token_fixed(HashMap::new(), 1000.into())
+        .new_badge_fixed(HashMap::new(), 1.into())
+        .new_token_mutable(HashMap::new(), mint_badge)
+        .deposit_all_buckets(account)
+        .build(vec![key])
+        .unwrap();
+    let receipt = executor.sign_and_run(transaction, false).unwrap();
+    assert!(receipt.success);
+    assert_eq!(
+        receipt.resources_created,
+        vec![
+            ResourceCreation {
+                instruction_index: 0,
+                resource_address: receipt.resource_def(0).unwrap(),
+                resource_type: ResourceType::Fungible { divisibility: 18 },
+                initial_supply: Some(Supply::Fungible {
+                    amount: 1000.into()
+                }),
+            },
+            ResourceCreation {
+                instruction_index: 1,
+                resource_address: receipt.resource_def(1).unwrap(),
+                resource_type: ResourceType::Fungible { divisibility: 0 },
+                initial_supply: Some(Supply::Fungible { amount: 1.into() }),
+            },
+            ResourceCreation {
+                instruction_index: 2,
+                resource_address: receipt.resource_def(2).unwrap(),
+                resource_type: ResourceType::Fungible { divisibility: 18 },
+                initial_supply: None,
+            },
+        ]
+    );
+    let minted: Vec<(usize, Address, Decimal)> = receipt
+        .resource_movements
+        .iter()
+        .filter(|m| m.kind == MovementKind::Minted)
+        .map(|m| (m.instruction_index, m.resource, m.amount))
+        .collect();
+    assert_eq!(
+        minted,
+        vec![
+            (0, receipt.resource_def(0).unwrap(), 1000.into()),
+            (1, receipt.resource_def(1).unwrap(), 1.into()),
+        ]
+    );
+
+    // Nothing is reported as created by a failed transaction
+    let transaction = TransactionBuilder::new(&executor)
+        .new_token_fixed(HashMap::new(), 1000.into())
+        .assert_worktop_empty()
+        .build(vec![key])
+        .unwrap();
+    let receipt = executor.sign_and_run(transaction, false).unwrap();
+    assert!(!receipt.success);
+    assert!(receipt.resources_created.is_empty());
+}
+
+#[test]
+fn test_assert_worktop_contains_nfts() {
+    let mut ledger = InMemoryLedger::with_bootstrap();
+    let mut executor = TransactionExecutor::new(&mut ledger, 0, 0);
+    let key = executor.new_public_key();
+    let account = executor.new_account(key);
+    let package = executor.publish_package(&compile("nft"));
+
+    // Mints #1 and #2 into the account
+    let transaction = TransactionBuilder::new(&executor)
+        .call_function(
+            package,
+            "NftTest",
+            "create_nft_batch",
+            vec!["2".to_owned()],
+            Some(account),
+        )
+        .deposit_all_buckets(account)
+        .build(vec![key])
+        .unwrap();
+    let receipt = executor.sign_and_run(transaction, false).unwrap();
+    assert!(receipt.success);
+    let nft = receipt.resource_def(0).unwrap();
+
+    let withdraw_and_assert = |executor: &mut TransactionExecutor<InMemoryLedger>, ids| {
+        let transaction = TransactionBuilder::new(executor)
+            .withdraw_from_account(
+                &ResourceAmount::NonFungible {
+                    ids: BTreeSet::from([1]),
+                    resource_address: nft,
+                },
+                account,
+            )
+            .assert_worktop_contains_nfts(ids, nft)
+            .deposit_all_buckets(account)
+            .build(vec![key])
+            .unwrap();
+        executor.sign_and_run(transaction, false).unwrap()
+    };
+
+    let receipt = withdraw_and_assert(&mut executor, BTreeSet::from([1, 2]));
+    assert!(!receipt.success);
+    assert!(matches!(
+        receipt.error(),
+        Some(RuntimeError::WorktopAssertionFailed {
+            resource_address,
+            missing_ids,
+        }) if *resource_address == nft && *missing_ids == BTreeSet::from([2])
+    ));
+
+    let receipt = withdraw_and_assert(&mut executor, BTreeSet::from([1]));
+    assert!(receipt.success);
+}
+
+#[test]
+fn test_random_bytes() {
+    let mut ledger = InMemoryLedger::with_bootstrap();
+    let hash = sha256("transaction");
+    let mut random_bytes = |hash: H256, instruction_index: usize, lens: &[usize]| {
+        let mut track = Track::new(&mut ledger, 0, hash, vec![]);
+        track.set_instruction_index(instruction_index);
+        lens.iter()
+            .map(|len| track.random_bytes(*len))
+            .collect::<Vec<Vec<u8>>>()
+    };
+
+    let first = random_bytes(hash, 1, &[40, 40]);
+    assert_eq!(first[0].len(), 40);
+    assert_ne!(first[0], first[1]);
+    assert_eq!(random_bytes(hash, 1, &[40, 40]), first);
+    assert_ne!(random_bytes(hash, 2, &[40])[0], first[0]);
+    assert_ne!(random_bytes(sha256("other"), 1, &[40])[0], first[0]);
+
+    // Bytes generated by rolled back calls don't shift later output
+    let mut track = Track::new(&mut ledger, 0, hash, vec![]);
+    track.set_instruction_index(1);
+    let checkpoint = track.checkpoint();
+    track.random_bytes(40);
+    track.rollback(checkpoint);
+    assert_eq!(track.random_bytes(40), first[0]);
+}
+
+#[test]
+fn test_context_random_bytes() {
+    let run = |label: &str, epoch: u64| {
+        let mut ledger = InMemoryLedger::with_bootstrap();
+        let mut executor = TransactionExecutor::new(&mut ledger, epoch, 0);
+        let key = executor.new_public_key();
+        let account = executor.new_account(key);
+        let package = executor.publish_package(&compile("context"));
+        let transaction = TransactionBuilder::new(&executor)
+            .call_function(package, "ContextTest", "random", vec![], Some(account))
+            .call_function(package, "ContextTest", "random", vec![], Some(account))
+            .label(label)
+            .build(vec![key])
+            .unwrap();
+        let receipt = executor.sign_and_run(transaction, false).unwrap();
+        assert!(receipt.success);
+        (
+            receipt.return_value::<Vec<u8>>(0).unwrap(),
+            receipt.return_value::<Vec<u8>>(1).unwrap(),
+        )
+    };
+
+    // The same transaction gets the same bytes, which differ between instructions
+    let (first, second) = run("a", 0);
+    assert_eq!(first.len(), 48);
+    assert_ne!(first, second);
+    assert_eq!(run("a", 0), (first.clone(), second));
+
+    // A different transaction at the same nonce gets different bytes
+    assert_ne!(run("b", 0).0, first);
+
+    // The same transaction gets the same bytes on another ledger, e.g. in another epoch
+    assert_eq!(run("a", 1).0, first);
+}
+
+struct CorruptAbiProvider;
+
+impl AbiProvider for CorruptAbiProvider {
+    fn export_abi<S: AsRef<str>>(
+        &self,
+        _package_address: Address,
+        _blueprint_name: S,
+        _trace: bool,
+    ) -> Result<abi::Blueprint, RuntimeError> {
+        Err(RuntimeError::InvalidData(sbor::DecodeError::InvalidIndex(
+            9,
+        )))
+    }
+
+    fn export_abi_component(
+        &self,
+        _component_address: Address,
+        _trace: bool,
+    ) -> Result<abi::Blueprint, RuntimeError> {
+        Err(RuntimeError::InvalidData(sbor::DecodeError::InvalidIndex(
+            9,
+        )))
+    }
+}
+
+#[test]
+fn test_abi_export_errors() {
+    let package = Address::Package([7u8; 26]);
+    let component = Address::Component([7u8; 26]);
+    let mut provider = BasicAbiProvider::new();
+    provider.with_component(component, package, "Test".to_owned(), vec![]);
+
+    // A component which doesn't exist
+    let bogus = Address::Component([9u8; 26]);
+    assert!(matches!(
+        TransactionBuilder::new(&provider)
+            .call_method(bogus, "get", vec![], None)
+            .build(vec![]),
+        Err(BuildTransactionError::ComponentNotFound(address)) if address == bogus
+    ));
+
+    // A package which doesn't exist, directly or behind a component
+    assert!(matches!(
+        TransactionBuilder::new(&provider)
+            .call_method(component, "get", vec![], None)
+            .build(vec![]),
+        Err(BuildTransactionError::AbiReadFailed(address, _)) if address == component
+    ));
+    assert!(matches!(
+        TransactionBuilder::new(&provider)
+            .call_function(package, "Test", "new", vec![], None)
+            .build(vec![]),
+        Err(BuildTransactionError::AbiReadFailed(address, _)) if address == package
+    ));
+
+    // An ABI which can't be decoded
+    assert!(matches!(
+        TransactionBuilder::new(&CorruptAbiProvider)
+            .call_method(component, "get", vec![], None)
+            .build(vec![]),
+        Err(BuildTransactionError::AbiParseFailed(address, _)) if address == component
+    ));
+}
+
+#[test]
+fn test_badge_authorize() {
+    let mut ledger = InMemoryLedger::with_bootstrap();
+    let mut executor = TransactionExecutor::new(&mut ledger, 0, 0);
+    let key = executor.new_public_key();
+    let account = executor.new_account(key);
+    let package = executor.publish_package(&compile("badge"));
+
+    let transaction = TransactionBuilder::new(&executor)
+        .call_function(package, "GatedTest", "authorize", vec![], Some(account))
+        .deposit_all_buckets(account)
+        .build(vec![key])
+        .unwrap();
+    let receipt = executor.sign_and_run(transaction, true).unwrap();
+    assert!(receipt.success);
+    assert_eq!(receipt.return_value::<(u32, Bid)>(0).unwrap().0, 1);
+
+    // A clone of the presented bucket ref can't outlive the closure
+    let transaction = TransactionBuilder::new(&executor)
+        .call_function(
+            package,
+            "GatedTest",
+            "authorize_leaking",
+            vec![],
+            Some(account),
+        )
+        .deposit_all_buckets(account)
+        .build(vec![key])
+        .unwrap();
+    let receipt = executor.sign_and_run(transaction, false).unwrap();
+    assert!(!receipt.success);
+    assert!(matches!(
+        receipt.error(),
+        Some(RuntimeError::PresentedBucketRefLeaked(_))
+    ));
+}
+
+#[test]
+fn test_withdraw_units_from_account() {
+    let mut ledger = InMemoryLedger::with_bootstrap();
+    let mut executor = TransactionExecutor::new(&mut ledger, 0, 0);
+    let key = executor.new_public_key();
+    let account = executor.new_account(key);
+    let other_account = executor.new_account(key);
+    let badge = executor.create_badge(account, 5.into());
+
+    let transaction = TransactionBuilder::new(&executor)
+        .withdraw_units_from_account(3, badge, account)
+        .deposit_all_buckets(other_account)
+        .build(vec![key])
+        .unwrap();
+    assert!(executor.sign_and_run(transaction, false).unwrap().success);
+    assert_eq!(executor.balance_of(account, badge).unwrap(), 2.into());
+    assert_eq!(executor.balance_of(other_account, badge).unwrap(), 3.into());
+
+    // Divisible tokens can't be counted in units
+    let result = TransactionBuilder::new(&executor)
+        .withdraw_units_from_account(3, RADIX_TOKEN, account)
+        .deposit_all_buckets(other_account)
+        .build(vec![key]);
+    assert!(matches!(
+        result,
+        Err(BuildTransactionError::NotWholeUnitResource(RADIX_TOKEN))
+    ));
+
+    // Resources of unknown type can't be checked
+    let unknown = Address::ResourceDef([1u8; 26]);
+    let result = TransactionBuilder::new(&executor)
+        .withdraw_units_from_account(3, unknown, account)
+        .deposit_all_buckets(other_account)
+        .build(vec![key]);
+    assert!(matches!(
+        result,
+        Err(BuildTransactionError::UnknownResourceType(address)) if address == unknown
+    ));
+}
+
+#[test]
+fn test_receipt_expectation() {
+    let mut ledger = InMemoryLedger::with_bootstrap();
+    let mut executor = TransactionExecutor::new(&mut ledger, 0, 0);
+    let key = executor.new_public_key();
+    let account = executor.new_account(key);
+    let other_account = executor.new_account(key);
+
+    let transaction = TransactionBuilder::new(&executor)
+        .withdraw_from_account(
+            &ResourceAmount::Fungible {
+                amount: 100.into(),
+                resource_address: RADIX_TOKEN,
+            },
+            account,
+        )
+        .deposit_all_buckets(other_account)
+        .build(vec![key])
+        .unwrap();
+    let receipt = executor.sign_and_run(transaction, false).unwrap();
+    let expectation = ReceiptExpectation::new()
+        .success(true)
+        .new_entities(0)
+        .return_value(1, ());
+    receipt.assert_matches(&expectation);
+
+    // Every mismatch is reported
+    let expectation = ReceiptExpectation::new()
+        .success(false)
+        .new_entities(1)
+        .log_containing("No such log")
+        .return_value(1, 1u32);
+    assert_eq!(expectation.mismatches(&receipt).len(), 4);
+    let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+        receipt.assert_matches(&expectation)
+    }));
+    assert!(result.is_err());
+
+    // Logs emitted by a deposit hook are matched
+    let package = executor.publish_package(&compile("deposit_hook"));
+    let transaction = TransactionBuilder::new(&executor)
+        .call_function(package, "DepositHookTest", "new", vec![], Some(account))
+        .build(vec![key])
+        .unwrap();
+    let component = executor
+        .sign_and_run(transaction, false)
+        .unwrap()
+        .component(0)
+        .unwrap();
+    let transaction = TransactionBuilder::new(&executor)
+        .call_method(
+            component,
+            "deposit",
+            vec![format!("100,{}", RADIX_TOKEN)],
+            Some(account),
+        )
+        .build(vec![key])
+        .unwrap();
+    let receipt = executor.sign_and_run(transaction, false).unwrap();
+    let expectation = ReceiptExpectation::new()
+        .success(true)
+        .log_containing(&format!("Received 100,{}", RADIX_TOKEN));
+    receipt.assert_matches(&expectation);
+}
+
+#[test]
+fn test_not_before() {
+    let mut ledger = InMemoryLedger::with_bootstrap();
+    let mut executor = TransactionExecutor::new(&mut ledger, 3, 0);
+    let key = executor.new_public_key();
+    let account = executor.new_account(key);
+
+    let transaction = TransactionBuilder::new(&executor)
+        .not_before(5)
+        .withdraw_from_account(
+            &ResourceAmount::Fungible {
+                amount: 100.into(),
+                resource_address: RADIX_TOKEN,
+            },
+            account,
+        )
+        .deposit_all_buckets(account)
+        .build(vec![key])
+        .unwrap();
+    assert!(matches!(
+        executor.sign_and_run(transaction.clone(), false),
+        Err(TransactionExecutionError::ValidationError(
+            TransactionValidationError::NotYetValid {
+                not_before: 5,
+                current: 3
+            }
+        ))
+    ));
+
+    executor.set_current_epoch(5);
+    let receipt = executor.sign_and_run(transaction, false).unwrap();
+    assert!(receipt.success);
+}
@@ -0,0 +1,13 @@
+use scrypto::prelude::*;
+
+blueprint! {
+    struct Logger {}
+
+    impl Logger {
+        pub fn log_many(count: u32) {
+            for i in 0..count {
+                info!("Log message #{}", i);
+            }
+        }
+    }
+}
@@ -0,0 +1,48 @@
+use scrypto::prelude::*;
+
+#[derive(NftData)]
+pub struct Membership {
+    pub tier: u8,
+}
+
+blueprint! {
+    struct Club {
+        membership_def: ResourceDef,
+    }
+
+    impl Club {
+        pub fn new() -> (Component, Bucket) {
+            let memberships = ResourceBuilder::new_non_fungible()
+                .metadata("name", "Club Membership")
+                .initial_supply_non_fungible([
+                    (1, Membership { tier: 1 }),
+                    (2, Membership { tier: 2 }),
+                ]);
+
+            let component = Self {
+                membership_def: memberships.resource_def(),
+            }
+            .instantiate();
+
+            (component, memberships)
+        }
+
+        pub fn enter(&self, member: BucketRef) -> String {
+            assert!(member.contains(self.membership_def.clone()), "Not a member");
+            let membership: Membership = member.get_nft_data(member.get_nft_id());
+            member.drop();
+
+            if membership.tier >= 2 {
+                "VIP lounge".to_owned()
+            } else {
+                "Main hall".to_owned()
+            }
+        }
+
+        pub fn tier_of(&self, member: BucketRef, id: u128) -> u8 {
+            let membership: Membership = member.get_nft_data(id);
+            member.drop();
+            membership.tier
+        }
+    }
+}
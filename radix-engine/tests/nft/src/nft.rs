@@ -71,6 +71,43 @@ blueprint! {
                 ])
         }
 
+        pub fn create_nft_batch(count: u128) -> Bucket {
+            ResourceBuilder::new_non_fungible()
+                .metadata("name", "Katz's Sandwiches")
+                .initial_supply_non_fungible((1..=count).map(|id| {
+                    (
+                        id,
+                        Sandwich {
+                            name: id.to_string(),
+                            available: true,
+                        },
+                    )
+                }))
+        }
+
+        pub fn create_nft_collection() -> (Bucket, String) {
+            let (resource_def, bucket) = ResourceBuilder::new_non_fungible()
+                .metadata("name", "Katz's Sandwiches")
+                .new_nft_fixed_with_def(
+                    (1..)
+                        .zip(["One", "Two", "Three"])
+                        .map(|(id, name)| {
+                            (
+                                id,
+                                Sandwich {
+                                    name: name.to_owned(),
+                                    available: true,
+                                },
+                            )
+                        })
+                        .collect(),
+                );
+            assert_eq!(resource_def.total_supply(), 3.into());
+
+            let data: Sandwich = resource_def.get_nft_data(2);
+            (bucket, data.name)
+        }
+
         pub fn update_and_get_nft() -> (Bucket, Bucket) {
             let (mint_badge, resource_def, bucket) = Self::create_nft_mutable();
             let mut data: Sandwich = resource_def.get_nft_data(0);
@@ -84,6 +121,15 @@ blueprint! {
             (mint_badge, bucket)
         }
 
+        pub fn update_immutable_nft_data() -> (Bucket, Bucket) {
+            let (mint_badge, resource_def, bucket) = Self::create_nft_mutable();
+            let mut data: Sandwich = resource_def.get_nft_data(0);
+
+            data.name = "Changed".to_owned();
+            resource_def.update_nft_data(0, data, mint_badge.present());
+            (mint_badge, bucket)
+        }
+
         pub fn take_and_put_bucket() -> Bucket {
             let bucket = Self::create_nft_fixed();
             assert_eq!(bucket.amount(), 3.into());
@@ -126,13 +172,13 @@ blueprint! {
         pub fn nft_and_vault() -> (Bucket, Bucket) {
             let (mint_badge, resource_def, bucket) = Self::create_nft_mutable();
 
-            let nft = mint_badge.authorize(|auth| {
+            let nft = mint_badge.authorize(|| {
                 resource_def.mint_nft(
                     Uuid::generate(),
                     VaultContainer {
                         vault: Vault::new(RADIX_TOKEN),
                     },
-                    auth,
+                    BucketRef::presented(),
                 )
             });
             bucket.put(nft);
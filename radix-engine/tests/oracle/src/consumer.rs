@@ -0,0 +1,20 @@
+use scrypto::prelude::*;
+
+blueprint! {
+    struct PriceConsumer {
+        oracle: Component,
+    }
+
+    impl PriceConsumer {
+        pub fn new(oracle: Address) -> Component {
+            Self {
+                oracle: oracle.into(),
+            }
+            .instantiate()
+        }
+
+        pub fn read_price(&self, resource_address: Address) -> Option<Decimal> {
+            self.oracle.call("get_price", args!(resource_address))
+        }
+    }
+}
@@ -0,0 +1,35 @@
+use scrypto::prelude::*;
+
+blueprint! {
+    struct Pool {
+        a_pool: Vault,
+        b_pool: Vault,
+        lp_resource_def: ResourceDef,
+    }
+
+    impl Pool {
+        pub fn new(a_tokens: Bucket, b_tokens: Bucket) -> (Component, Bucket) {
+            assert!(
+                !a_tokens.is_empty() && !b_tokens.is_empty(),
+                "You must pass in an initial supply of each token"
+            );
+
+            let lp_tokens = ResourceBuilder::new_fungible(DIVISIBILITY_MAXIMUM)
+                .metadata("symbol", "LP")
+                .initial_supply_fungible(100);
+
+            let component = Self {
+                a_pool: Vault::with_bucket(a_tokens),
+                b_pool: Vault::with_bucket(b_tokens),
+                lp_resource_def: lp_tokens.resource_def(),
+            }
+            .instantiate();
+
+            (component, lp_tokens)
+        }
+
+        pub fn reserves(&self) -> (Decimal, Decimal) {
+            (self.a_pool.amount(), self.b_pool.amount())
+        }
+    }
+}
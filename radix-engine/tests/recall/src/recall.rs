@@ -0,0 +1,45 @@
+use scrypto::prelude::*;
+
+blueprint! {
+    struct RecallTest {
+        vault: Vault,
+    }
+
+    impl RecallTest {
+        pub fn create_token() -> (Bucket, Bucket) {
+            let badge = ResourceBuilder::new_fungible(DIVISIBILITY_NONE).initial_supply_fungible(1);
+            let tokens = ResourceBuilder::new_fungible(DIVISIBILITY_MAXIMUM)
+                .metadata("name", "TestToken")
+                .flags(RECALLABLE)
+                .badge(badge.resource_address(), MAY_RECALL)
+                .initial_supply_fungible(100);
+            (badge, tokens)
+        }
+
+        pub fn new(tokens: Bucket) -> Component {
+            Self {
+                vault: Vault::with_bucket(tokens),
+            }
+            .instantiate()
+        }
+
+        pub fn new_with_owner(tokens: Bucket, owner: Address) -> Component {
+            let component = Self::new(tokens);
+            component.set_owner(owner);
+            component
+        }
+
+        pub fn recall(
+            resource_address: Address,
+            component_address: Address,
+            amount: Decimal,
+            auth: BucketRef,
+        ) -> Bucket {
+            ResourceDef::from(resource_address).recall(component_address, amount, auth)
+        }
+
+        pub fn amount(&self) -> Decimal {
+            self.vault.amount()
+        }
+    }
+}
@@ -19,6 +19,49 @@ blueprint! {
             (bucket.take(Decimal::from_str("0.1").unwrap()), bucket)
         }
 
+        pub fn create_token_fixed_only() -> Bucket {
+            let (resource_def, bucket) = ResourceBuilder::new_fungible(DIVISIBILITY_MAXIMUM)
+                .metadata("name", "TestToken")
+                .with_initial_supply(1000)
+                .build_token();
+            assert_eq!(resource_def.flags(), 0);
+            assert_eq!(resource_def.total_supply(), 1000.into());
+            assert_eq!(bucket.amount(), 1000.into());
+            bucket
+        }
+
+        pub fn create_token_mutable_only() -> (Bucket, Bucket) {
+            let badge = ResourceBuilder::new_fungible(DIVISIBILITY_NONE).initial_supply_fungible(1);
+            let (resource_def, bucket) = ResourceBuilder::new_fungible(DIVISIBILITY_MAXIMUM)
+                .metadata("name", "TestToken")
+                .mintable_by(badge.resource_def())
+                .burnable_by(badge.resource_def())
+                .build_token();
+            assert_eq!(resource_def.flags(), MINTABLE | BURNABLE);
+            assert_eq!(resource_def.total_supply(), 0.into());
+            assert!(bucket.is_empty());
+
+            let minted = resource_def.mint(5, badge.present());
+            assert_eq!(resource_def.total_supply(), 5.into());
+            minted.put(bucket);
+            (badge, minted)
+        }
+
+        pub fn create_token_fixed_and_mutable() -> (Bucket, Bucket) {
+            let badge = ResourceBuilder::new_fungible(DIVISIBILITY_NONE).initial_supply_fungible(1);
+            let (resource_def, bucket) = ResourceBuilder::new_fungible(DIVISIBILITY_MAXIMUM)
+                .metadata("name", "TestToken")
+                .mintable_by(badge.resource_def())
+                .burnable_by(badge.resource_def())
+                .with_initial_supply(1000)
+                .build_token();
+            assert_eq!(resource_def.total_supply(), 1000.into());
+
+            resource_def.burn_with_auth(bucket.take(400), badge.present());
+            assert_eq!(resource_def.total_supply(), 600.into());
+            (badge, bucket)
+        }
+
         pub fn query() -> (Bucket, HashMap<String, String>, u16, u16, Decimal) {
             let (badge, resource_def) = Self::create_fungible();
             (
@@ -92,5 +135,29 @@ blueprint! {
 
             badge
         }
+
+        pub fn new_vaults() -> Bucket {
+            let (resource_def, bucket) = ResourceBuilder::new_fungible(DIVISIBILITY_MAXIMUM)
+                .with_initial_supply(100)
+                .build_token();
+
+            let empty_vault = resource_def.new_empty_vault();
+            assert!(empty_vault.is_empty());
+            empty_vault.put(bucket.take(40));
+            assert_eq!(empty_vault.amount(), 40.into());
+
+            let filled_vault = resource_def.new_vault_with(bucket.take(50));
+            assert_eq!(filled_vault.amount(), 50.into());
+            bucket
+        }
+
+        pub fn new_vault_with_mismatched_bucket() -> Bucket {
+            let resource_def = ResourceBuilder::new_fungible(DIVISIBILITY_MAXIMUM)
+                .no_initial_supply();
+            let bucket = ResourceBuilder::new_fungible(DIVISIBILITY_MAXIMUM)
+                .initial_supply_fungible(100);
+            let vault = resource_def.new_vault_with(bucket);
+            vault.take_all()
+        }
     }
 }
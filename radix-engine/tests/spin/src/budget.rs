@@ -0,0 +1,42 @@
+use scrypto::prelude::*;
+
+use crate::sink::Sink;
+
+blueprint! {
+    struct BudgetTest {
+        sink: Component,
+        vault: Vault,
+    }
+
+    impl BudgetTest {
+        pub fn new() -> Component {
+            let bucket = ResourceBuilder::new_fungible(DIVISIBILITY_MAXIMUM)
+                .metadata("name", "TestToken")
+                .initial_supply_fungible(1000);
+            BudgetTest {
+                sink: Sink::new(bucket.resource_def()),
+                vault: Vault::with_bucket(bucket),
+            }
+            .instantiate()
+        }
+
+        pub fn send_with_budget(&mut self, rounds: u64, max_cost: u64) -> Option<CallError> {
+            let bucket = self.vault.take(1);
+            match self
+                .sink
+                .call_with_budget::<()>("take_and_spin", args!(bucket, rounds), max_cost)
+            {
+                Ok(()) => None,
+                Err(e) => {
+                    // the bucket is returned on failure
+                    self.vault.put(bucket);
+                    Some(e)
+                }
+            }
+        }
+
+        pub fn amounts(&self) -> (Decimal, Decimal) {
+            (self.vault.amount(), self.sink.call("amount", vec![]))
+        }
+    }
+}
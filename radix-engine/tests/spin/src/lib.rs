@@ -0,0 +1,3 @@
+pub mod budget;
+pub mod sink;
+pub mod spin;
@@ -0,0 +1,28 @@
+use scrypto::prelude::*;
+
+blueprint! {
+    struct Sink {
+        vault: Vault,
+    }
+
+    impl Sink {
+        pub fn new(resource_def: ResourceDef) -> Component {
+            Sink {
+                vault: Vault::new(resource_def),
+            }
+            .instantiate()
+        }
+
+        pub fn take_and_spin(&mut self, bucket: Bucket, rounds: u64) {
+            self.vault.put(bucket);
+            let mut count = 0u64;
+            for _ in 0..rounds {
+                count = core::hint::black_box(count + 1);
+            }
+        }
+
+        pub fn amount(&self) -> Decimal {
+            self.vault.amount()
+        }
+    }
+}
@@ -0,0 +1,14 @@
+use scrypto::prelude::*;
+
+blueprint! {
+    struct Spinner;
+
+    impl Spinner {
+        pub fn spin() {
+            let mut count = 0u64;
+            loop {
+                count = core::hint::black_box(count + 1);
+            }
+        }
+    }
+}
@@ -0,0 +1,26 @@
+use scrypto::prelude::*;
+
+blueprint! {
+    struct Vesting {
+        vault: Vault,
+        release_time: u64,
+    }
+
+    impl Vesting {
+        pub fn new(tokens: Bucket, release_time: u64) -> Component {
+            Self {
+                vault: Vault::with_bucket(tokens),
+                release_time,
+            }
+            .instantiate()
+        }
+
+        pub fn release(&mut self) -> Bucket {
+            assert!(
+                Context::current_time() >= self.release_time,
+                "Tokens are not vested yet"
+            );
+            self.vault.take_all()
+        }
+    }
+}
@@ -1,4 +1,5 @@
 use crate::decode::*;
+use crate::describe::{self, Type};
 use crate::encode::*;
 use crate::rust::borrow::Borrow;
 use crate::rust::boxed::Box;
@@ -38,7 +39,13 @@ pub enum Value {
     HashSet(u8, Vec<Value>),
     HashMap(u8, u8, Vec<(Value, Value)>),
 
-    Custom(u8, Vec<u8>),
+    /// A custom type, with the name of the type it represents if the decoder knows it,
+    /// e.g. `Decimal`.
+    Custom {
+        ty: u8,
+        name: Option<String>,
+        bytes: Vec<u8>,
+    },
 }
 
 /// Represents the fields of a struct or enum variant.
@@ -193,12 +200,12 @@ pub fn encode_any(ty_ctx: Option<u8>, value: &Value, enc: &mut Encoder) {
             }
         }
         // custom types
-        Value::Custom(ty, data) => {
+        Value::Custom { ty, bytes, .. } => {
             if ty_ctx.is_none() {
                 enc.write_type(*ty);
             }
-            enc.write_len(data.len());
-            enc.write_slice(data);
+            enc.write_len(bytes.len());
+            enc.write_slice(bytes);
         }
     }
 }
@@ -233,14 +240,33 @@ fn encode_basic<T: Encode>(ty_ctx: Option<u8>, t: u8, v: &T, enc: &mut Encoder)
 }
 
 /// Decode any SBOR data.
+///
+/// Custom values are left unnamed; use `decode_any_with_names` to name them.
 pub fn decode_any(data: &[u8]) -> Result<Value, DecodeError> {
     let mut decoder = Decoder::with_type(data);
-    let result = decode_next(None, &mut decoder);
+    let result = decode_next(None, &mut decoder, None)?;
     decoder.check_end()?;
-    result
+    Ok(result)
 }
 
-fn decode_next(ty_ctx: Option<u8>, dec: &mut Decoder) -> Result<Value, DecodeError> {
+/// Decode any SBOR data, naming custom values with the given function.
+///
+/// Custom types which the function doesn't recognize are rejected.
+pub fn decode_any_with_names(data: &[u8], custom_name: CustomNames) -> Result<Value, DecodeError> {
+    let mut decoder = Decoder::with_type(data);
+    let result = decode_next(None, &mut decoder, Some(custom_name))?;
+    decoder.check_end()?;
+    Ok(result)
+}
+
+/// Returns the name of a custom type, if the type id is known.
+pub type CustomNames = fn(u8) -> Option<&'static str>;
+
+fn decode_next(
+    ty_ctx: Option<u8>,
+    dec: &mut Decoder,
+    names: Option<CustomNames>,
+) -> Result<Value, DecodeError> {
     let ty = match ty_ctx {
         Some(t) => t,
         None => dec.read_type()?,
@@ -264,14 +290,14 @@ fn decode_next(ty_ctx: Option<u8>, dec: &mut Decoder) -> Result<Value, DecodeErr
         // struct & enum
         TYPE_STRUCT => {
             // fields
-            let fields = decode_fields(dec)?;
+            let fields = decode_fields(dec, names)?;
             Ok(Value::Struct(fields))
         }
         TYPE_ENUM => {
             // index
             let index = dec.read_u8()?;
             // fields
-            let fields = decode_fields(dec)?;
+            let fields = decode_fields(dec, names)?;
             Ok(Value::Enum(index, fields))
         }
         // composite types
@@ -281,11 +307,13 @@ fn decode_next(ty_ctx: Option<u8>, dec: &mut Decoder) -> Result<Value, DecodeErr
             // optional value
             match index {
                 0 => Ok(Value::Option(Box::new(None))),
-                1 => Ok(Value::Option(Box::new(Some(decode_next(None, dec)?)))),
+                1 => Ok(Value::Option(Box::new(Some(decode_next(
+                    None, dec, names,
+                )?)))),
                 _ => Err(DecodeError::InvalidIndex(index)),
             }
         }
-        TYPE_BOX => Ok(Value::Box(Box::new(decode_next(None, dec)?))),
+        TYPE_BOX => Ok(Value::Box(Box::new(decode_next(None, dec, names)?))),
         TYPE_ARRAY => {
             // element type
             let ele_ty = dec.read_type()?;
@@ -294,7 +322,7 @@ fn decode_next(ty_ctx: Option<u8>, dec: &mut Decoder) -> Result<Value, DecodeErr
             // values
             let mut elements = Vec::new();
             for _ in 0..len {
-                elements.push(decode_next(Some(ele_ty), dec)?);
+                elements.push(decode_next(Some(ele_ty), dec, names)?);
             }
             Ok(Value::Array(ele_ty, elements))
         }
@@ -304,7 +332,7 @@ fn decode_next(ty_ctx: Option<u8>, dec: &mut Decoder) -> Result<Value, DecodeErr
             // values
             let mut elements = Vec::new();
             for _ in 0..len {
-                elements.push(decode_next(None, dec)?);
+                elements.push(decode_next(None, dec, names)?);
             }
             Ok(Value::Tuple(elements))
         }
@@ -313,8 +341,8 @@ fn decode_next(ty_ctx: Option<u8>, dec: &mut Decoder) -> Result<Value, DecodeErr
             let index = dec.read_u8()?;
             // result value
             match index {
-                0 => Ok(Value::Result(Box::new(Ok(decode_next(None, dec)?)))),
-                1 => Ok(Value::Result(Box::new(Err(decode_next(None, dec)?)))),
+                0 => Ok(Value::Result(Box::new(Ok(decode_next(None, dec, names)?)))),
+                1 => Ok(Value::Result(Box::new(Err(decode_next(None, dec, names)?)))),
                 _ => Err(DecodeError::InvalidIndex(index)),
             }
         }
@@ -327,7 +355,7 @@ fn decode_next(ty_ctx: Option<u8>, dec: &mut Decoder) -> Result<Value, DecodeErr
             // values
             let mut elements = Vec::new();
             for _ in 0..len {
-                elements.push(decode_next(Some(ele_ty), dec)?);
+                elements.push(decode_next(Some(ele_ty), dec, names)?);
             }
             Ok(Value::Vec(ele_ty, elements))
         }
@@ -339,7 +367,7 @@ fn decode_next(ty_ctx: Option<u8>, dec: &mut Decoder) -> Result<Value, DecodeErr
             // values
             let mut elements = Vec::new();
             for _ in 0..len {
-                elements.push(decode_next(Some(ele_ty), dec)?);
+                elements.push(decode_next(Some(ele_ty), dec, names)?);
             }
             if ty == TYPE_TREE_SET {
                 Ok(Value::TreeSet(ele_ty, elements))
@@ -358,8 +386,8 @@ fn decode_next(ty_ctx: Option<u8>, dec: &mut Decoder) -> Result<Value, DecodeErr
             let mut elements = Vec::new();
             for _ in 0..len {
                 elements.push((
-                    decode_next(Some(key_ty), dec)?,
-                    decode_next(Some(value_ty), dec)?,
+                    decode_next(Some(key_ty), dec, names)?,
+                    decode_next(Some(value_ty), dec, names)?,
                 ));
             }
             if ty == TYPE_TREE_MAP {
@@ -370,10 +398,22 @@ fn decode_next(ty_ctx: Option<u8>, dec: &mut Decoder) -> Result<Value, DecodeErr
         }
         _ => {
             if ty >= TYPE_CUSTOM_START {
+                // name
+                let name = match names {
+                    Some(f) => Some(String::from(f(ty).ok_or(DecodeError::InvalidType {
+                        expected: None,
+                        actual: ty,
+                    })?)),
+                    None => None,
+                };
                 // length
                 let len = dec.read_len()?;
                 let slice = dec.read_bytes(len)?;
-                Ok(Value::Custom(ty, slice.to_vec()))
+                Ok(Value::Custom {
+                    ty,
+                    name,
+                    bytes: slice.to_vec(),
+                })
             } else {
                 Err(DecodeError::InvalidType {
                     expected: None,
@@ -384,7 +424,7 @@ fn decode_next(ty_ctx: Option<u8>, dec: &mut Decoder) -> Result<Value, DecodeErr
     }
 }
 
-fn decode_fields(dec: &mut Decoder) -> Result<Fields, DecodeError> {
+fn decode_fields(dec: &mut Decoder, names: Option<CustomNames>) -> Result<Fields, DecodeError> {
     let ty = dec.read_type()?;
     match ty {
         TYPE_FIELDS_NAMED => {
@@ -393,7 +433,7 @@ fn decode_fields(dec: &mut Decoder) -> Result<Fields, DecodeError> {
             // named fields
             let mut named = Vec::new();
             for _ in 0..len {
-                named.push(decode_next(None, dec)?);
+                named.push(decode_next(None, dec, names)?);
             }
             Ok(Fields::Named(named))
         }
@@ -403,7 +443,7 @@ fn decode_fields(dec: &mut Decoder) -> Result<Fields, DecodeError> {
             // named fields
             let mut unnamed = Vec::new();
             for _ in 0..len {
-                unnamed.push(decode_next(None, dec)?);
+                unnamed.push(decode_next(None, dec, names)?);
             }
             Ok(Fields::Unnamed(unnamed))
         }
@@ -415,6 +455,159 @@ fn decode_fields(dec: &mut Decoder) -> Result<Fields, DecodeError> {
     }
 }
 
+/// Represents a SBOR value, labeled with the struct, field and variant names from its schema.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LabeledValue {
+    /// A value with no inner structure, i.e. a primitive or custom type.
+    Plain(Value),
+
+    Struct {
+        name: String,
+        fields: LabeledFields,
+    },
+    Enum {
+        name: String,
+        variant: String,
+        fields: LabeledFields,
+    },
+
+    Option(Box<Option<LabeledValue>>),
+    Box(Box<LabeledValue>),
+    Result(Box<Result<LabeledValue, LabeledValue>>),
+
+    /// An array, tuple, vec or set.
+    Sequence(Vec<LabeledValue>),
+
+    /// A tree map or hash map.
+    Map(Vec<(LabeledValue, LabeledValue)>),
+}
+
+/// Represents the labeled fields of a struct or enum variant.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LabeledFields {
+    Named(Vec<(String, LabeledValue)>),
+
+    Unnamed(Vec<LabeledValue>),
+
+    Unit,
+}
+
+/// Decodes any SBOR value, labeling it with the names from the given schema.
+///
+/// This allows rendering data whose Rust type is unknown, e.g. component state described
+/// by an ABI.
+pub fn decode_with_schema(data: &[u8], schema: &Type) -> Result<LabeledValue, DecodeError> {
+    label(decode_any(data)?, schema)
+}
+
+fn label(value: Value, schema: &Type) -> Result<LabeledValue, DecodeError> {
+    match (value, schema) {
+        // primitive and custom types
+        (v @ Value::Unit, Type::Unit)
+        | (v @ Value::Bool(_), Type::Bool)
+        | (v @ Value::I8(_), Type::I8)
+        | (v @ Value::I16(_), Type::I16)
+        | (v @ Value::I32(_), Type::I32)
+        | (v @ Value::I64(_), Type::I64)
+        | (v @ Value::I128(_), Type::I128)
+        | (v @ Value::U8(_), Type::U8)
+        | (v @ Value::U16(_), Type::U16)
+        | (v @ Value::U32(_), Type::U32)
+        | (v @ Value::U64(_), Type::U64)
+        | (v @ Value::U128(_), Type::U128)
+        | (v @ Value::String(_), Type::String) => Ok(LabeledValue::Plain(v)),
+        (Value::Custom { ty, bytes, .. }, Type::Custom { name, .. }) => {
+            Ok(LabeledValue::Plain(Value::Custom {
+                ty,
+                name: Some(name.clone()),
+                bytes,
+            }))
+        }
+        // struct & enum
+        (Value::Struct(fields), Type::Struct { name, fields: ty }) => Ok(LabeledValue::Struct {
+            name: name.clone(),
+            fields: label_fields(fields, ty)?,
+        }),
+        (Value::Enum(index, fields), Type::Enum { name, variants }) => {
+            let variant = variants
+                .get(index as usize)
+                .ok_or(DecodeError::InvalidIndex(index))?;
+            Ok(LabeledValue::Enum {
+                name: name.clone(),
+                variant: variant.name.clone(),
+                fields: label_fields(fields, &variant.fields)?,
+            })
+        }
+        // composite types
+        (Value::Option(v), Type::Option { value: ty }) => {
+            Ok(LabeledValue::Option(Box::new(match *v {
+                Some(v) => Some(label(v, ty)?),
+                None => None,
+            })))
+        }
+        (Value::Box(v), Type::Box { value: ty }) => Ok(LabeledValue::Box(Box::new(label(*v, ty)?))),
+        (Value::Result(v), Type::Result { okay, error }) => {
+            Ok(LabeledValue::Result(Box::new(match *v {
+                Ok(v) => Ok(label(v, okay)?),
+                Err(e) => Err(label(e, error)?),
+            })))
+        }
+        (Value::Tuple(elements), Type::Tuple { elements: types })
+            if elements.len() == types.len() =>
+        {
+            elements
+                .into_iter()
+                .zip(types)
+                .map(|(v, ty)| label(v, ty))
+                .collect::<Result<_, _>>()
+                .map(LabeledValue::Sequence)
+        }
+        (Value::Array(_, elements), Type::Array { element, .. })
+        | (Value::Vec(_, elements), Type::Vec { element })
+        | (Value::TreeSet(_, elements), Type::TreeSet { element })
+        | (Value::HashSet(_, elements), Type::HashSet { element }) => elements
+            .into_iter()
+            .map(|v| label(v, element))
+            .collect::<Result<_, _>>()
+            .map(LabeledValue::Sequence),
+        // collections
+        (Value::TreeMap(_, _, entries), Type::TreeMap { key, value })
+        | (Value::HashMap(_, _, entries), Type::HashMap { key, value }) => entries
+            .into_iter()
+            .map(|(k, v)| Ok((label(k, key)?, label(v, value)?)))
+            .collect::<Result<_, _>>()
+            .map(LabeledValue::Map),
+        _ => Err(DecodeError::SchemaMismatch),
+    }
+}
+
+fn label_fields(fields: Fields, schema: &describe::Fields) -> Result<LabeledFields, DecodeError> {
+    match (fields, schema) {
+        (Fields::Named(values), describe::Fields::Named { named })
+            if values.len() == named.len() =>
+        {
+            values
+                .into_iter()
+                .zip(named)
+                .map(|(v, (name, ty))| Ok((name.clone(), label(v, ty)?)))
+                .collect::<Result<_, _>>()
+                .map(LabeledFields::Named)
+        }
+        (Fields::Unnamed(values), describe::Fields::Unnamed { unnamed })
+            if values.len() == unnamed.len() =>
+        {
+            values
+                .into_iter()
+                .zip(unnamed)
+                .map(|(v, ty)| label(v, ty))
+                .collect::<Result<_, _>>()
+                .map(LabeledFields::Unnamed)
+        }
+        (Fields::Unit, describe::Fields::Unit) => Ok(LabeledFields::Unit),
+        _ => Err(DecodeError::SchemaMismatch),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use crate::rust::boxed::Box;
@@ -431,7 +624,7 @@ mod tests {
         x: u32,
     }
 
-    #[derive(TypeId, Encode)]
+    #[derive(TypeId, Encode, Describe)]
     enum TestEnum {
         A { x: u32 },
         B(u32),
@@ -548,11 +741,93 @@ mod tests {
         assert_eq!(bytes2, bytes);
     }
 
+    #[derive(TypeId, Encode, Describe)]
+    struct User {
+        deposit_balance: u64,
+        borrow_balance: u64,
+        loan: Option<u64>,
+    }
+
+    #[test]
+    pub fn test_decode_with_schema() {
+        let bytes = encode_with_type(
+            Vec::new(),
+            &User {
+                deposit_balance: 100,
+                borrow_balance: 20,
+                loan: Some(7),
+            },
+        );
+        let value = decode_with_schema(&bytes, &User::describe()).unwrap();
+        assert_eq!(
+            LabeledValue::Struct {
+                name: String::from("User"),
+                fields: LabeledFields::Named(vec![
+                    (
+                        String::from("deposit_balance"),
+                        LabeledValue::Plain(Value::U64(100))
+                    ),
+                    (
+                        String::from("borrow_balance"),
+                        LabeledValue::Plain(Value::U64(20))
+                    ),
+                    (
+                        String::from("loan"),
+                        LabeledValue::Option(Box::new(Some(LabeledValue::Plain(Value::U64(7)))))
+                    ),
+                ])
+            },
+            value
+        );
+
+        let bytes = encode_with_type(Vec::new(), &TestEnum::B(2));
+        let value = decode_with_schema(&bytes, &TestEnum::describe()).unwrap();
+        assert_eq!(
+            LabeledValue::Enum {
+                name: String::from("TestEnum"),
+                variant: String::from("B"),
+                fields: LabeledFields::Unnamed(vec![LabeledValue::Plain(Value::U32(2))])
+            },
+            value
+        );
+
+        // data of a different shape is rejected
+        let bytes = encode_with_type(Vec::new(), &7u64);
+        assert!(matches!(
+            decode_with_schema(&bytes, &User::describe()),
+            Err(DecodeError::SchemaMismatch)
+        ));
+    }
+
     #[test]
     pub fn test_parse_custom() {
         let bytes: Vec<u8> = vec![0x80, 0x02, 0x00, 0x00, 0x00, 0x01, 0x02];
         let value = decode_any(&bytes).unwrap();
+        assert_eq!(
+            Value::Custom {
+                ty: 0x80,
+                name: None,
+                bytes: vec![1, 2]
+            },
+            value
+        );
 
-        assert_eq!(Value::Custom(0x80, vec![1, 2]), value);
+        let names: CustomNames = |ty| if ty == 0x80 { Some("Test") } else { None };
+        let value = decode_any_with_names(&bytes, names).unwrap();
+        assert_eq!(
+            Value::Custom {
+                ty: 0x80,
+                name: Some(String::from("Test")),
+                bytes: vec![1, 2]
+            },
+            value
+        );
+        assert!(matches!(
+            decode_any_with_names(&bytes, |_| None),
+            Err(DecodeError::InvalidType {
+                expected: None,
+                actual: 0x80
+            })
+        ));
     }
 }
@@ -29,6 +29,8 @@ pub enum DecodeError {
     InvalidCustomData(u8),
 
     DuplicateEntry,
+
+    SchemaMismatch,
 }
 
 /// A data structure that can be decoded from a byte array using SBOR.
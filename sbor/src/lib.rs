@@ -18,7 +18,7 @@ pub mod rust;
 /// SBOR type ids.
 pub mod type_id;
 
-pub use any::{decode_any, encode_any};
+pub use any::{decode_any, decode_any_with_names, decode_with_schema, encode_any};
 pub use decode::{Decode, DecodeError, Decoder};
 pub use describe::Describe;
 pub use encode::{Encode, Encoder};
@@ -16,7 +16,7 @@ use sbor::{Decode, Encode, TypeId};
     any(feature = "serde_std", feature = "serde_alloc"),
     derive(Serialize, Deserialize)
 )]
-#[derive(Debug, Clone, TypeId, Encode, Decode)]
+#[derive(Debug, Clone, PartialEq, Eq, TypeId, Encode, Decode)]
 pub struct Blueprint {
     pub package: String,
     pub name: String,
@@ -24,12 +24,113 @@ pub struct Blueprint {
     pub methods: Vec<Method>,
 }
 
+#[cfg(any(feature = "serde_std", feature = "serde_alloc"))]
+impl Blueprint {
+    /// Serializes this blueprint ABI into JSON, for consumption by external tooling.
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).unwrap()
+    }
+
+    /// Deserializes a blueprint ABI from JSON.
+    pub fn from_json(json: &str) -> Result<Self, serde_json::Error> {
+        serde_json::from_str(json)
+    }
+}
+
+impl Blueprint {
+    /// Checks that this blueprint can replace an older version, i.e. that every function and
+    /// method of the older version still exists with the same inputs and output.
+    ///
+    /// Overloaded methods are told apart by the number of inputs. Additions are always allowed.
+    pub fn is_compatible_with(&self, older: &Blueprint) -> Result<(), Vec<Incompatibility>> {
+        let mut incompatibilities = Vec::new();
+
+        for old in &older.functions {
+            let new = self.functions.iter().find(|f| f.name == old.name);
+            check_signature(
+                &old.name,
+                (&old.inputs, &old.output),
+                new.map(|f| (&f.inputs, &f.output)),
+                Incompatibility::FunctionRemoved,
+                &mut incompatibilities,
+            );
+        }
+
+        for old in &older.methods {
+            let same_name: Vec<&Method> =
+                self.methods.iter().filter(|m| m.name == old.name).collect();
+            let new = same_name
+                .iter()
+                .find(|m| m.inputs.len() == old.inputs.len())
+                .or_else(|| same_name.first());
+            check_signature(
+                &old.name,
+                (&old.inputs, &old.output),
+                new.map(|m| (&m.inputs, &m.output)),
+                Incompatibility::MethodRemoved,
+                &mut incompatibilities,
+            );
+        }
+
+        if incompatibilities.is_empty() {
+            Ok(())
+        } else {
+            Err(incompatibilities)
+        }
+    }
+}
+
+/// Represents a breaking change between two versions of a blueprint.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Incompatibility {
+    FunctionRemoved(String),
+    MethodRemoved(String),
+    InputsChanged {
+        name: String,
+        old: Vec<Type>,
+        new: Vec<Type>,
+    },
+    OutputChanged {
+        name: String,
+        old: Type,
+        new: Type,
+    },
+}
+
+fn check_signature(
+    name: &str,
+    old: (&Vec<Type>, &Type),
+    new: Option<(&Vec<Type>, &Type)>,
+    removed: fn(String) -> Incompatibility,
+    incompatibilities: &mut Vec<Incompatibility>,
+) {
+    match new {
+        None => incompatibilities.push(removed(name.into())),
+        Some((inputs, output)) => {
+            if inputs != old.0 {
+                incompatibilities.push(Incompatibility::InputsChanged {
+                    name: name.into(),
+                    old: old.0.clone(),
+                    new: inputs.clone(),
+                });
+            }
+            if output != old.1 {
+                incompatibilities.push(Incompatibility::OutputChanged {
+                    name: name.into(),
+                    old: old.1.clone(),
+                    new: output.clone(),
+                });
+            }
+        }
+    }
+}
+
 /// Represents a function.
 #[cfg_attr(
     any(feature = "serde_std", feature = "serde_alloc"),
     derive(Serialize, Deserialize)
 )]
-#[derive(Debug, Clone, TypeId, Encode, Decode)]
+#[derive(Debug, Clone, PartialEq, Eq, TypeId, Encode, Decode)]
 pub struct Function {
     pub name: String,
     pub inputs: Vec<Type>,
@@ -41,12 +142,24 @@ pub struct Function {
     any(feature = "serde_std", feature = "serde_alloc"),
     derive(Serialize, Deserialize)
 )]
-#[derive(Debug, Clone, TypeId, Encode, Decode)]
+#[derive(Debug, Clone, PartialEq, Eq, TypeId, Encode, Decode)]
 pub struct Method {
     pub name: String,
     pub mutability: Mutability,
     pub inputs: Vec<Type>,
     pub output: Type,
+    #[cfg_attr(
+        any(feature = "serde_std", feature = "serde_alloc"),
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    pub hook: Option<Hook>,
+}
+
+impl Method {
+    /// Returns whether this method may change the component state.
+    pub fn mutates_state(&self) -> bool {
+        self.mutability == Mutability::Mutable
+    }
 }
 
 /// Whether a method is going to change the component state.
@@ -54,7 +167,7 @@ pub struct Method {
     any(feature = "serde_std", feature = "serde_alloc"),
     derive(Serialize, Deserialize)
 )]
-#[derive(Debug, Clone, TypeId, Encode, Decode)]
+#[derive(Debug, Clone, PartialEq, Eq, TypeId, Encode, Decode)]
 pub enum Mutability {
     /// An immutable method requires an immutable reference to component state.
     Immutable,
@@ -62,3 +175,71 @@ pub enum Mutability {
     /// A mutable method requires a mutable reference to component state.
     Mutable,
 }
+
+/// An engine hook which a method implements.
+#[cfg_attr(
+    any(feature = "serde_std", feature = "serde_alloc"),
+    derive(Serialize, Deserialize)
+)]
+#[derive(Debug, Clone, PartialEq, Eq, TypeId, Encode, Decode)]
+pub enum Hook {
+    /// Invoked by the engine after resources are deposited into the component.
+    Deposit,
+}
+
+#[cfg(test)]
+mod tests {
+    use sbor::describe::*;
+    use sbor::rust::borrow::ToOwned;
+    use sbor::rust::vec;
+    use sbor::rust::vec::Vec;
+
+    use super::*;
+
+    fn method(name: &str, inputs: Vec<Type>, output: Type) -> Method {
+        Method {
+            name: name.to_owned(),
+            mutability: Mutability::Immutable,
+            inputs,
+            output,
+            hook: None,
+        }
+    }
+
+    #[test]
+    fn test_is_compatible_with() {
+        let v1 = Blueprint {
+            package: "01".to_owned(),
+            name: "Counter".to_owned(),
+            functions: vec![Function {
+                name: "new".to_owned(),
+                inputs: vec![],
+                output: Type::U32,
+            }],
+            methods: vec![
+                method("get", vec![], Type::U32),
+                method("set", vec![Type::U32], Type::Unit),
+            ],
+        };
+
+        // Adding a method is compatible
+        let mut v2 = v1.clone();
+        v2.methods.push(method("reset", vec![], Type::Unit));
+        assert_eq!(v2.is_compatible_with(&v1), Ok(()));
+
+        // Removing a method or changing a signature is not
+        let mut v3 = v1.clone();
+        v3.methods = vec![method("get", vec![], Type::U64)];
+        assert_eq!(
+            v3.is_compatible_with(&v1),
+            Err(vec![
+                Incompatibility::OutputChanged {
+                    name: "get".to_owned(),
+                    old: Type::U32,
+                    new: Type::U64,
+                },
+                Incompatibility::MethodRemoved("set".to_owned()),
+            ])
+        );
+    }
+}
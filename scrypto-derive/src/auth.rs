@@ -64,7 +64,7 @@ pub fn handle_auth(attr: TokenStream, item: TokenStream) -> Result<TokenStream>
     let output = quote! {
         #(#f_attrs)*
         #f_vis fn #f_ident (#(#f_inputs),*) #f_output {
-            if !(#(auth.contains(self.#allowed_badges.clone()))||*) {
+            if !auth.check_method_auth(&[#(::scrypto::resource::ResourceDef::from(self.#allowed_badges.clone())),*]) {
                 panic!("Auth check failure")
             }
 
@@ -103,7 +103,10 @@ mod tests {
             quote! {
                 #[other]
                 pub fn x(&self, auth: ::scrypto::resource::BucketRef) -> u32 {
-                    if !(auth.contains(self.foo.clone()) || auth.contains(self.bar.clone())) {
+                    if !auth.check_method_auth(&[
+                        ::scrypto::resource::ResourceDef::from(self.foo.clone()),
+                        ::scrypto::resource::ResourceDef::from(self.bar.clone())
+                    ]) {
                         panic!("Auth check failure")
                     }
                     let output = (|| {
@@ -126,7 +129,7 @@ mod tests {
             output,
             quote! {
                 pub fn x(&self, auth: ::scrypto::resource::BucketRef) -> u32 {
-                    if !(auth.contains(self.foo.clone())) {
+                    if !auth.check_method_auth(&[::scrypto::resource::ResourceDef::from(self.foo.clone())]) {
                         panic!("Auth check failure")
                     }
                     let output = (|| {
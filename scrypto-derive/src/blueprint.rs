@@ -42,6 +42,8 @@ pub fn handle_blueprint(input: TokenStream) -> Result<TokenStream> {
         ));
     }
 
+    let output_hooks = generate_hooks(bp_ident, bp_items)?;
+
     let output_mod = quote! {
         mod blueprint {
             use super::*;
@@ -53,6 +55,8 @@ pub fn handle_blueprint(input: TokenStream) -> Result<TokenStream> {
                 #(#bp_items)*
             }
 
+            #output_hooks
+
             impl ::scrypto::core::ComponentState for #bp_ident {
                 fn blueprint_name() -> &'static str {
                     #bp_name
@@ -264,6 +268,66 @@ fn generate_dispatcher(bp_ident: &Ident, items: &[ImplItem]) -> Result<(Vec<Expr
     Ok((arm_guards, arm_bodies))
 }
 
+// Returns whether a method is the deposit hook.
+fn is_deposit_hook(m: &ImplItemMethod) -> bool {
+    matches!(m.vis, Visibility::Public(_)) && m.sig.ident == "on_deposit"
+}
+
+// Checks that the deposit hook takes `&mut self` and a `ResourceAmount`, and returns nothing.
+fn check_deposit_hook(m: &ImplItemMethod) -> Result<()> {
+    let mut inputs = m.sig.inputs.iter();
+    let receiver_ok = matches!(
+        inputs.next(),
+        Some(FnArg::Receiver(r)) if r.reference.is_some() && r.mutability.is_some()
+    );
+    let deposit_ok = match inputs.next() {
+        Some(FnArg::Typed(t)) => match &*t.ty {
+            Type::Path(p) => p
+                .path
+                .segments
+                .last()
+                .filter(|s| s.ident == "ResourceAmount" && s.arguments.is_empty())
+                .is_some(),
+            _ => false,
+        },
+        _ => false,
+    };
+    let output_ok = matches!(m.sig.output, ReturnType::Default);
+
+    if !receiver_ok || !deposit_ok || inputs.next().is_some() || !output_ok {
+        return Err(Error::new(
+            m.sig.span(),
+            "The deposit hook must be declared as `pub fn on_deposit(&mut self, deposit: ResourceAmount)`",
+        ));
+    }
+    Ok(())
+}
+
+// Implements the hook traits for the recognized hook methods.
+fn generate_hooks(bp_ident: &Ident, items: &[ImplItem]) -> Result<TokenStream> {
+    let mut has_deposit_hook = false;
+    for item in items {
+        if let ImplItem::Method(ref m) = item {
+            if is_deposit_hook(m) {
+                check_deposit_hook(m)?;
+                has_deposit_hook = true;
+            }
+        }
+    }
+
+    if has_deposit_hook {
+        Ok(quote! {
+            impl ::scrypto::core::DepositHook for #bp_ident {
+                fn on_deposit(&mut self, deposit: ::scrypto::resource::ResourceAmount) {
+                    #bp_ident::on_deposit(self, deposit)
+                }
+            }
+        })
+    } else {
+        Ok(quote! {})
+    }
+}
+
 // Parses function items of an `Impl` and returns ABI of functions.
 fn generate_abi(bp_ident: &Ident, items: &[ImplItem]) -> Result<(Vec<Expr>, Vec<Expr>)> {
     let mut functions = Vec::<Expr>::new();
@@ -335,12 +399,18 @@ fn generate_abi(bp_ident: &Ident, items: &[ImplItem]) -> Result<(Vec<Expr>, Vec<
                             }
                         });
                     } else {
+                        let hook = if is_deposit_hook(m) {
+                            quote! { Some(::scrypto::abi::Hook::Deposit) }
+                        } else {
+                            quote! { None }
+                        };
                         methods.push(parse_quote! {
                             ::scrypto::abi::Method {
                                 name: #name.to_owned(),
                                 mutability: #mutability,
                                 inputs: vec![#(#inputs),*],
                                 output: #output,
+                                hook: #hook,
                             }
                         });
                     }
@@ -528,6 +598,26 @@ mod tests {
         handle_blueprint(input).unwrap();
     }
 
+    #[test]
+    fn test_deposit_hook() {
+        let input = TokenStream::from_str(
+            "struct Test {} impl Test { pub fn on_deposit(&mut self, deposit: ResourceAmount) {} }",
+        )
+        .unwrap();
+        let output = handle_blueprint(input).unwrap().to_string();
+        assert!(output.contains("impl :: scrypto :: core :: DepositHook for Test"));
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_deposit_hook_with_wrong_signature_should_fail() {
+        let input = TokenStream::from_str(
+            "struct Test {} impl Test { pub fn on_deposit(&mut self, resource_address: Address, amount: Decimal) {} }",
+        )
+        .unwrap();
+        handle_blueprint(input).unwrap();
+    }
+
     #[test]
     fn test_blueprint() {
         let input = TokenStream::from_str(
@@ -606,6 +696,7 @@ mod tests {
                             <::scrypto::resource::BucketRef>::describe()
                         ],
                         output: <u32>::describe(),
+                        hook: None,
                     }];
                     let output = (functions, methods);
                     let output_bytes = ::scrypto::buffer::scrypto_encode_for_kernel(&output);
@@ -193,3 +193,30 @@ fn test_simple_abi() {
         ]),
     );
 }
+
+#[test]
+fn test_abi_json_round_trip() {
+    let ptr = Simple_abi();
+    let (functions, methods): (Vec<abi::Function>, Vec<abi::Method>) =
+        unsafe { scrypto_consume(ptr, |slice| scrypto_decode(slice).unwrap()) };
+    let blueprint = abi::Blueprint {
+        package: "01ca59a8d6ea4f7efa1765cef702d14e47570c079aedd44992dd09".to_owned(),
+        name: "Simple".to_owned(),
+        functions,
+        methods,
+    };
+
+    let json = blueprint.to_json();
+    assert_eq!(abi::Blueprint::from_json(&json).unwrap(), blueprint);
+}
+
+#[test]
+fn test_method_mutability() {
+    let ptr = Simple_abi();
+    let (_, methods): (Vec<abi::Function>, Vec<abi::Method>) =
+        unsafe { scrypto_consume(ptr, |slice| scrypto_decode(slice).unwrap()) };
+    let find = |name: &str| methods.iter().find(|m| m.name == name).unwrap();
+
+    assert!(!find("get_state").mutates_state());
+    assert!(find("set_state").mutates_state());
+}
@@ -1,12 +1,22 @@
+use sbor::any::*;
 use sbor::*;
 
+use crate::buffer::*;
+use crate::rust::convert::TryFrom;
+use crate::rust::mem;
 use crate::rust::vec::Vec;
+use crate::types::*;
 
 /// Encodes a data structure into byte array.
 pub fn scrypto_encode<T: Encode + ?Sized>(v: &T) -> Vec<u8> {
     sbor::encode_with_type(Vec::with_capacity(512), v)
 }
 
+/// Encodes a data structure and appends it to the given buffer.
+pub fn scrypto_encode_to<T: Encode + ?Sized>(v: &T, buf: &mut Vec<u8>) {
+    *buf = sbor::encode_with_type(mem::take(buf), v);
+}
+
 /// Encodes a data structure into byte array for kernel.
 pub fn scrypto_encode_for_kernel<T: Encode + ?Sized>(v: &T) -> Vec<u8> {
     // create a buffer and pre-append with length (0).
@@ -28,16 +38,103 @@ pub fn scrypto_decode<T: Decode>(buf: &[u8]) -> Result<T, DecodeError> {
     sbor::decode_with_type(buf)
 }
 
+/// Decodes a dynamic value from a slice, checking that all custom values are valid Scrypto types.
+///
+/// Custom values are named after the Scrypto type they represent, e.g. `Decimal`.
+pub fn scrypto_decode_any(buf: &[u8]) -> Result<Value, DecodeError> {
+    let value = sbor::decode_any_with_names(buf, scrypto_type_name)?;
+    validate_custom(&value)?;
+    Ok(value)
+}
+
+fn scrypto_type_name(ty: u8) -> Option<&'static str> {
+    match ty {
+        SCRYPTO_TYPE_DECIMAL => Some(SCRYPTO_NAME_DECIMAL),
+        SCRYPTO_TYPE_BIG_DECIMAL => Some(SCRYPTO_NAME_BIG_DECIMAL),
+        SCRYPTO_TYPE_ADDRESS => Some(SCRYPTO_NAME_ADDRESS),
+        SCRYPTO_TYPE_H256 => Some(SCRYPTO_NAME_H256),
+        SCRYPTO_TYPE_BID => Some(SCRYPTO_NAME_BID),
+        SCRYPTO_TYPE_RID => Some(SCRYPTO_NAME_RID),
+        SCRYPTO_TYPE_MID => Some(SCRYPTO_NAME_MID),
+        SCRYPTO_TYPE_VID => Some(SCRYPTO_NAME_VID),
+        SCRYPTO_TYPE_U256 => Some(SCRYPTO_NAME_U256),
+        SCRYPTO_TYPE_I256 => Some(SCRYPTO_NAME_I256),
+        SCRYPTO_TYPE_FIXED_POINT => Some(SCRYPTO_NAME_FIXED_POINT),
+        _ => None,
+    }
+}
+
+fn validate_custom(value: &Value) -> Result<(), DecodeError> {
+    match value {
+        Value::Struct(fields) | Value::Enum(_, fields) => match fields {
+            Fields::Named(values) | Fields::Unnamed(values) => validate_all(values.iter()),
+            Fields::Unit => Ok(()),
+        },
+        Value::Option(v) => match v.as_ref() {
+            Some(v) => validate_custom(v),
+            None => Ok(()),
+        },
+        Value::Box(v) => validate_custom(v),
+        Value::Result(v) => match v.as_ref() {
+            Ok(v) | Err(v) => validate_custom(v),
+        },
+        Value::Array(_, values)
+        | Value::Tuple(values)
+        | Value::Vec(_, values)
+        | Value::TreeSet(_, values)
+        | Value::HashSet(_, values) => validate_all(values.iter()),
+        Value::TreeMap(_, _, entries) | Value::HashMap(_, _, entries) => {
+            validate_all(entries.iter().flat_map(|(k, v)| [k, v]))
+        }
+        Value::Custom { ty, bytes, .. } => {
+            let valid = match *ty {
+                SCRYPTO_TYPE_DECIMAL => Decimal::try_from(bytes.as_slice()).is_ok(),
+                SCRYPTO_TYPE_BIG_DECIMAL => BigDecimal::try_from(bytes.as_slice()).is_ok(),
+                SCRYPTO_TYPE_ADDRESS => Address::try_from(bytes.as_slice()).is_ok(),
+                SCRYPTO_TYPE_H256 => H256::try_from(bytes.as_slice()).is_ok(),
+                SCRYPTO_TYPE_BID => Bid::try_from(bytes.as_slice()).is_ok(),
+                SCRYPTO_TYPE_RID => Rid::try_from(bytes.as_slice()).is_ok(),
+                SCRYPTO_TYPE_MID => Mid::try_from(bytes.as_slice()).is_ok(),
+                SCRYPTO_TYPE_VID => Vid::try_from(bytes.as_slice()).is_ok(),
+                SCRYPTO_TYPE_U256 => U256::try_from(bytes.as_slice()).is_ok(),
+                SCRYPTO_TYPE_I256 => I256::try_from(bytes.as_slice()).is_ok(),
+                SCRYPTO_TYPE_FIXED_POINT => format_fixed_point(bytes).is_ok(),
+                _ => {
+                    return Err(DecodeError::InvalidType {
+                        expected: None,
+                        actual: *ty,
+                    })
+                }
+            };
+            if valid {
+                Ok(())
+            } else {
+                Err(DecodeError::InvalidCustomData(*ty))
+            }
+        }
+        _ => Ok(()),
+    }
+}
+
+fn validate_all<'a, I: Iterator<Item = &'a Value>>(mut values: I) -> Result<(), DecodeError> {
+    values.try_for_each(validate_custom)
+}
+
 #[cfg(test)]
 mod tests {
+    use sbor::any::*;
     use sbor::*;
 
     use crate::buffer::*;
     use crate::kernel::*;
     use crate::resource::*;
     use crate::rust::borrow::ToOwned;
+    use crate::rust::convert::TryFrom;
+    use crate::rust::str::FromStr;
     use crate::rust::string::String;
+    use crate::rust::string::ToString;
     use crate::rust::vec;
+    use crate::rust::vec::Vec;
     use crate::types::*;
 
     #[test]
@@ -58,6 +155,85 @@ mod tests {
         assert_eq!(vec![8, 0, 0, 0, 12, 3, 0, 0, 0, 97, 98, 99], encoded);
     }
 
+    #[test]
+    fn test_encode_to() {
+        let mut buf = Vec::new();
+        let mut offsets = vec![0];
+        scrypto_encode_to(&1u32, &mut buf);
+        offsets.push(buf.len());
+        scrypto_encode_to("abc", &mut buf);
+        offsets.push(buf.len());
+        scrypto_encode_to(&Decimal::from(5), &mut buf);
+        offsets.push(buf.len());
+
+        assert_eq!(
+            scrypto_decode::<u32>(&buf[offsets[0]..offsets[1]]).unwrap(),
+            1u32
+        );
+        assert_eq!(
+            scrypto_decode::<String>(&buf[offsets[1]..offsets[2]]).unwrap(),
+            "abc"
+        );
+        assert_eq!(
+            scrypto_decode::<Decimal>(&buf[offsets[2]..offsets[3]]).unwrap(),
+            Decimal::from(5)
+        );
+    }
+
+    #[test]
+    fn test_decode_any_custom() {
+        let obj = DecimalAndAddress {
+            amount: Decimal::from_str("100.5").unwrap(),
+            address: Address::Component([1u8; 26]),
+        };
+        let value = scrypto_decode_any(&scrypto_encode(&obj)).unwrap();
+
+        let fields = match value {
+            Value::Struct(Fields::Named(fields)) => fields,
+            _ => panic!("Expected named struct"),
+        };
+        match &fields[0] {
+            Value::Custom { name, bytes, .. } => {
+                assert_eq!(name.as_deref(), Some(SCRYPTO_NAME_DECIMAL));
+                let amount = Decimal::try_from(bytes.as_slice()).unwrap();
+                assert_eq!(amount.to_string(), "100.5");
+            }
+            _ => panic!("Expected custom value"),
+        }
+        match &fields[1] {
+            Value::Custom { name, bytes, .. } => {
+                assert_eq!(name.as_deref(), Some(SCRYPTO_NAME_ADDRESS));
+                let address = Address::try_from(bytes.as_slice()).unwrap();
+                assert_eq!(address, Address::Component([1u8; 26]));
+            }
+            _ => panic!("Expected custom value"),
+        }
+    }
+
+    #[test]
+    fn test_decode_any_invalid_custom() {
+        let mut enc = Encoder::with_type(Vec::new());
+        encode_any(
+            None,
+            &Value::Custom {
+                ty: SCRYPTO_TYPE_DECIMAL,
+                name: Some(SCRYPTO_NAME_DECIMAL.to_owned()),
+                bytes: vec![1, 2],
+            },
+            &mut enc,
+        );
+        assert!(matches!(
+            scrypto_decode_any(&Vec::<u8>::from(enc)),
+            Err(DecodeError::InvalidCustomData(SCRYPTO_TYPE_DECIMAL))
+        ));
+    }
+
+    #[derive(TypeId, Encode, Decode)]
+    struct DecimalAndAddress {
+        amount: Decimal,
+        address: Address,
+    }
+
     #[derive(TypeId, Encode, Decode)]
     struct ComponentTest {
         resource_address: Address,
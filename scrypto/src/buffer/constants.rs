@@ -6,6 +6,9 @@ pub const SCRYPTO_TYPE_BID: u8 = 0x84;
 pub const SCRYPTO_TYPE_RID: u8 = 0x85;
 pub const SCRYPTO_TYPE_MID: u8 = 0x86;
 pub const SCRYPTO_TYPE_VID: u8 = 0x87;
+pub const SCRYPTO_TYPE_U256: u8 = 0x88;
+pub const SCRYPTO_TYPE_I256: u8 = 0x89;
+pub const SCRYPTO_TYPE_FIXED_POINT: u8 = 0x8a;
 
 pub const SCRYPTO_NAME_DECIMAL: &str = "scrypto::types::Decimal";
 pub const SCRYPTO_NAME_BIG_DECIMAL: &str = "scrypto::types::BigDecimal";
@@ -15,6 +18,9 @@ pub const SCRYPTO_NAME_BID: &str = "scrypto::types::Bid";
 pub const SCRYPTO_NAME_RID: &str = "scrypto::types::Rid";
 pub const SCRYPTO_NAME_MID: &str = "scrypto::types::Mid";
 pub const SCRYPTO_NAME_VID: &str = "scrypto::types::Vid";
+pub const SCRYPTO_NAME_U256: &str = "scrypto::types::U256";
+pub const SCRYPTO_NAME_I256: &str = "scrypto::types::I256";
+pub const SCRYPTO_NAME_FIXED_POINT: &str = "scrypto::types::FixedPoint";
 
 pub const SCRYPTO_NAME_ACCOUNT: &str = "scrypto::core::Account";
 pub const SCRYPTO_NAME_PACKAGE: &str = "scrypto::core::Package";
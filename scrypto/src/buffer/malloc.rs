@@ -19,7 +19,9 @@ pub unsafe extern "C" fn scrypto_alloc(len: u32) -> *mut u8 {
 /// Wraps a byte array into a pointer.
 ///
 /// This function assumes the input byte array has the layout specified by `scrypto_alloc`.
-pub fn scrypto_wrap(mut buf: Vec<u8>) -> *mut u8 {
+/// Spare capacity is released, as `scrypto_consume` frees exactly the length prefix plus data.
+pub fn scrypto_wrap(buf: Vec<u8>) -> *mut u8 {
+    let mut buf = buf.into_boxed_slice();
     let ptr = buf.as_mut_ptr();
     forget(buf);
     ptr
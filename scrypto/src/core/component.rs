@@ -3,6 +3,7 @@ use sbor::{describe::Type, *};
 use crate::buffer::*;
 use crate::core::*;
 use crate::kernel::*;
+use crate::resource::*;
 use crate::rust::borrow::ToOwned;
 use crate::rust::vec;
 use crate::rust::vec::Vec;
@@ -18,6 +19,21 @@ pub trait ComponentState: sbor::Encode + sbor::Decode {
     fn instantiate(self) -> Component;
 }
 
+/// The name of the method invoked by the engine after resources are deposited into a component.
+///
+/// Only the engine may invoke it; calls from transactions or other components are rejected.
+pub const DEPOSIT_HOOK: &str = "on_deposit";
+
+/// A hook which is invoked by the engine after resources are put into the vaults of a component.
+///
+/// The `blueprint!` macro implements this trait for blueprints defining an `on_deposit` method,
+/// and marks the method with `Hook::Deposit` in the blueprint ABI.
+pub trait DepositHook {
+    /// Called once for each deposit made into the component, with the deposited amount, or
+    /// the ids of the deposited NFTs.
+    fn on_deposit(&mut self, deposit: ResourceAmount);
+}
+
 /// An instance of a blueprint, which lives in the ledger state.
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Component {
@@ -59,6 +75,28 @@ impl Component {
         scrypto_unwrap(scrypto_decode(&output))
     }
 
+    /// Invokes a method on this component, with at most `max_cost` units of cost to spend.
+    ///
+    /// Every loop iteration and kernel call of the callee costs one unit. If the callee fails,
+    /// including by running out of its budget, its state changes are rolled back and the
+    /// error is returned, instead of aborting the caller.
+    pub fn call_with_budget<T: Decode>(
+        &self,
+        method: &str,
+        args: Vec<Vec<u8>>,
+        max_cost: u64,
+    ) -> Result<T, CallError> {
+        let input = CallMethodWithBudgetInput {
+            component_address: self.address,
+            method: method.to_owned(),
+            args,
+            max_cost,
+        };
+        let output: CallMethodWithBudgetOutput = call_kernel(CALL_METHOD_WITH_BUDGET, input);
+
+        output.rtn.map(|rtn| scrypto_unwrap(scrypto_decode(&rtn)))
+    }
+
     /// Returns the state of this component.
     pub fn get_state<T: ComponentState>(&self) -> T {
         let input = GetComponentStateInput {
@@ -78,6 +116,19 @@ impl Component {
         let _: PutComponentStateOutput = call_kernel(PUT_COMPONENT_STATE, input);
     }
 
+    /// Sets the owner of this component, who can delete it by signing a transaction with the
+    /// `DeleteComponent` instruction.
+    ///
+    /// Only the blueprint of this component can set the owner; components without an owner
+    /// can't be deleted.
+    pub fn set_owner(&self, owner: Address) {
+        let input = SetComponentOwnerInput {
+            component_address: self.address,
+            owner,
+        };
+        let _: SetComponentOwnerOutput = call_kernel(SET_COMPONENT_OWNER, input);
+    }
+
     /// Returns the blueprint that this component is instantiated from.
     pub fn blueprint(&self) -> Blueprint {
         let input = GetComponentInfoInput {
@@ -28,6 +28,38 @@ impl Context {
         output.current_epoch
     }
 
+    /// Returns the current time, as a unix timestamp in seconds.
+    ///
+    /// Unlike the epoch, this is not agreed by consensus.
+    pub fn current_time() -> u64 {
+        let input = GetCurrentTimeInput {};
+        let output: GetCurrentTimeOutput = call_kernel(GET_CURRENT_TIME, input);
+        output.current_time
+    }
+
+    /// Returns the amount of a resource the transaction has staged in its context, as of
+    /// the latest call made by the transaction.
+    pub fn worktop_amount(resource_address: Address) -> Decimal {
+        let input = GetWorktopAmountInput { resource_address };
+        let output: GetWorktopAmountOutput = call_kernel(GET_WORKTOP_AMOUNT, input);
+        output.amount
+    }
+
+    /// Returns `len` random bytes, derived from the transaction hash, the index of the running
+    /// instruction and the number of bytes generated so far.
+    ///
+    /// The bytes are the same every time the transaction is run, e.g. when it's replayed, so
+    /// they are safe for consensus. They are NOT secret: anyone who knows the transaction
+    /// before it's executed, including its submitter, can compute them, and a submitter may
+    /// alter the transaction until it gets a favorable outcome. Don't rely on them where
+    /// predicting the outcome is profitable, e.g. a lottery paying out in the same
+    /// transaction.
+    pub fn random_bytes(len: u32) -> Vec<u8> {
+        let input = GenerateRandomBytesInput { len };
+        let output: GenerateRandomBytesOutput = call_kernel(GENERATE_RANDOM_BYTES, input);
+        output.bytes
+    }
+
     /// Returns the signers of this transaction.
     pub fn transaction_signers() -> Vec<Address> {
         let input = GetTransactionSignersInput {};
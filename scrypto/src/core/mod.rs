@@ -11,7 +11,7 @@ mod uuid;
 pub use account::Account;
 pub use blueprint::Blueprint;
 pub use call::{call_function, call_method};
-pub use component::{Component, ComponentState};
+pub use component::{Component, ComponentState, DepositHook, DEPOSIT_HOOK};
 pub use context::Context;
 pub use lazy_map::LazyMap;
 pub use logger::Logger;
@@ -18,6 +18,8 @@ pub const PUBLISH_PACKAGE: u32 = 0x00;
 pub const CALL_FUNCTION: u32 = 0x01;
 /// Call a method
 pub const CALL_METHOD: u32 = 0x02;
+/// Call a method with a cost budget
+pub const CALL_METHOD_WITH_BUDGET: u32 = 0x03;
 
 /// Create a component
 pub const CREATE_COMPONENT: u32 = 0x10;
@@ -27,6 +29,8 @@ pub const GET_COMPONENT_INFO: u32 = 0x11;
 pub const GET_COMPONENT_STATE: u32 = 0x12;
 /// Update component state
 pub const PUT_COMPONENT_STATE: u32 = 0x13;
+/// Set the owner of a component
+pub const SET_COMPONENT_OWNER: u32 = 0x14;
 
 /// Create a lazy map
 pub const CREATE_LAZY_MAP: u32 = 0x20;
@@ -61,6 +65,8 @@ pub const GET_NFT_DATA: u32 = 0x3a;
 pub const UPDATE_NFT_MUTABLE_DATA: u32 = 0x3b;
 /// Update resource metadata
 pub const UPDATE_RESOURCE_METADATA: u32 = 0x3c;
+/// Recall resource from a component
+pub const RECALL_RESOURCE: u32 = 0x3d;
 
 /// Create an empty vault
 pub const CREATE_EMPTY_VAULT: u32 = 0x40;
@@ -76,6 +82,8 @@ pub const GET_VAULT_RESOURCE_ADDRESS: u32 = 0x44;
 pub const TAKE_NFT_FROM_VAULT: u32 = 0x45;
 /// Get the IDs of all NFTs in this vault
 pub const GET_NFT_IDS_IN_VAULT: u32 = 0x46;
+/// Create a bucket ref backed by resources in a vault
+pub const CREATE_BUCKET_REF_FROM_VAULT: u32 = 0x47;
 
 /// Create an empty bucket
 pub const CREATE_EMPTY_BUCKET: u32 = 0x50;
@@ -104,6 +112,16 @@ pub const GET_BUCKET_REF_RESOURCE_DEF: u32 = 0x63;
 pub const GET_NFT_IDS_IN_BUCKET_REF: u32 = 0x64;
 /// Clone bucket ref
 pub const CLONE_BUCKET_REF: u32 = 0x65;
+/// Check a bucket ref against the badges a method is guarded with
+pub const CHECK_METHOD_AUTH: u32 = 0x66;
+/// Get the data of an NFT in the bucket referenced
+pub const GET_NFT_DATA_IN_BUCKET_REF: u32 = 0x67;
+/// Present a bucket, until the presented bucket ref is dropped
+pub const PRESENT_BUCKET: u32 = 0x68;
+/// Clone the bucket ref presented last
+pub const CLONE_PRESENTED_BUCKET_REF: u32 = 0x69;
+/// Drop the bucket ref presented last
+pub const DROP_PRESENTED_BUCKET_REF: u32 = 0x6a;
 
 /// Log a message
 pub const EMIT_LOG: u32 = 0xf0;
@@ -119,6 +137,12 @@ pub const GET_TRANSACTION_HASH: u32 = 0xf4;
 pub const GET_TRANSACTION_SIGNERS: u32 = 0xf5;
 /// Generate an UUID
 pub const GENERATE_UUID: u32 = 0xf6;
+/// Retrieve current time
+pub const GET_CURRENT_TIME: u32 = 0xf7;
+/// Retrieve the amount of a resource staged in transaction context
+pub const GET_WORKTOP_AMOUNT: u32 = 0xf8;
+/// Generate deterministic random bytes
+pub const GENERATE_RANDOM_BYTES: u32 = 0xf9;
 
 //==========
 // blueprint
@@ -159,6 +183,19 @@ pub struct CallMethodOutput {
     pub rtn: Vec<u8>,
 }
 
+#[derive(Debug, Clone, TypeId, Encode, Decode)]
+pub struct CallMethodWithBudgetInput {
+    pub component_address: Address,
+    pub method: String,
+    pub args: Vec<Vec<u8>>,
+    pub max_cost: u64,
+}
+
+#[derive(Debug, Clone, TypeId, Encode, Decode)]
+pub struct CallMethodWithBudgetOutput {
+    pub rtn: Result<Vec<u8>, CallError>,
+}
+
 //==========
 // component
 //==========
@@ -204,6 +241,15 @@ pub struct PutComponentStateInput {
 #[derive(Debug, Clone, TypeId, Encode, Decode)]
 pub struct PutComponentStateOutput {}
 
+#[derive(Debug, Clone, TypeId, Encode, Decode)]
+pub struct SetComponentOwnerInput {
+    pub component_address: Address,
+    pub owner: Address,
+}
+
+#[derive(Debug, Clone, TypeId, Encode, Decode)]
+pub struct SetComponentOwnerOutput {}
+
 //==========
 // LazyMap
 //==========
@@ -324,6 +370,7 @@ pub struct GetNftDataOutput {
 pub struct UpdateNftMutableDataInput {
     pub resource_address: Address,
     pub id: u128,
+    pub immutable_data: Vec<u8>,
     pub new_mutable_data: Vec<u8>,
     pub auth: Rid,
 }
@@ -381,6 +428,19 @@ pub struct UpdateResourceMetadataInput {
 #[derive(Debug, Clone, TypeId, Encode, Decode)]
 pub struct UpdateResourceMetadataOutput {}
 
+#[derive(Debug, Clone, TypeId, Encode, Decode)]
+pub struct RecallResourceInput {
+    pub resource_address: Address,
+    pub component_address: Address,
+    pub amount: Decimal,
+    pub auth: Rid,
+}
+
+#[derive(Debug, Clone, TypeId, Encode, Decode)]
+pub struct RecallResourceOutput {
+    pub bid: Bid,
+}
+
 //==========
 // vault
 //==========
@@ -458,6 +518,17 @@ pub struct GetNftIdsInVaultOutput {
     pub ids: Vec<u128>,
 }
 
+#[derive(Debug, Clone, TypeId, Encode, Decode)]
+pub struct CreateBucketRefFromVaultInput {
+    pub vid: Vid,
+    pub amount: Decimal,
+}
+
+#[derive(Debug, Clone, TypeId, Encode, Decode)]
+pub struct CreateBucketRefFromVaultOutput {
+    pub rid: Rid,
+}
+
 //==========
 // bucket
 //==========
@@ -585,6 +656,18 @@ pub struct GetNftIdsInBucketRefOutput {
     pub ids: Vec<u128>,
 }
 
+#[derive(Debug, Clone, TypeId, Encode, Decode)]
+pub struct GetNftDataInBucketRefInput {
+    pub rid: Rid,
+    pub id: u128,
+}
+
+#[derive(Debug, Clone, TypeId, Encode, Decode)]
+pub struct GetNftDataInBucketRefOutput {
+    pub immutable_data: Vec<u8>,
+    pub mutable_data: Vec<u8>,
+}
+
 #[derive(Debug, Clone, TypeId, Encode, Decode)]
 pub struct CloneBucketRefInput {
     pub rid: Rid,
@@ -595,6 +678,39 @@ pub struct CloneBucketRefOutput {
     pub rid: Rid,
 }
 
+#[derive(Debug, Clone, TypeId, Encode, Decode)]
+pub struct PresentBucketInput {
+    pub bid: Bid,
+}
+
+#[derive(Debug, Clone, TypeId, Encode, Decode)]
+pub struct PresentBucketOutput {}
+
+#[derive(Debug, Clone, TypeId, Encode, Decode)]
+pub struct ClonePresentedBucketRefInput {}
+
+#[derive(Debug, Clone, TypeId, Encode, Decode)]
+pub struct ClonePresentedBucketRefOutput {
+    pub rid: Rid,
+}
+
+#[derive(Debug, Clone, TypeId, Encode, Decode)]
+pub struct DropPresentedBucketRefInput {}
+
+#[derive(Debug, Clone, TypeId, Encode, Decode)]
+pub struct DropPresentedBucketRefOutput {}
+
+#[derive(Debug, Clone, TypeId, Encode, Decode)]
+pub struct CheckMethodAuthInput {
+    pub rid: Rid,
+    pub allowed_badges: Vec<Address>,
+}
+
+#[derive(Debug, Clone, TypeId, Encode, Decode)]
+pub struct CheckMethodAuthOutput {
+    pub authorized: bool,
+}
+
 //=======
 // others
 //=======
@@ -633,6 +749,24 @@ pub struct GetCurrentEpochOutput {
     pub current_epoch: u64,
 }
 
+#[derive(Debug, Clone, TypeId, Encode, Decode)]
+pub struct GetCurrentTimeInput {}
+
+#[derive(Debug, Clone, TypeId, Encode, Decode)]
+pub struct GetCurrentTimeOutput {
+    pub current_time: u64,
+}
+
+#[derive(Debug, Clone, TypeId, Encode, Decode)]
+pub struct GetWorktopAmountInput {
+    pub resource_address: Address,
+}
+
+#[derive(Debug, Clone, TypeId, Encode, Decode)]
+pub struct GetWorktopAmountOutput {
+    pub amount: Decimal,
+}
+
 #[derive(Debug, Clone, TypeId, Encode, Decode)]
 pub struct GetTransactionHashInput {}
 
@@ -649,6 +783,16 @@ pub struct GetTransactionSignersOutput {
     pub transaction_signers: Vec<Address>,
 }
 
+#[derive(Debug, Clone, TypeId, Encode, Decode)]
+pub struct GenerateRandomBytesInput {
+    pub len: u32,
+}
+
+#[derive(Debug, Clone, TypeId, Encode, Decode)]
+pub struct GenerateRandomBytesOutput {
+    pub bytes: Vec<u8>,
+}
+
 #[derive(Debug, Clone, TypeId, Encode, Decode)]
 pub struct GenerateUuidInput {}
 
@@ -15,6 +15,16 @@ pub enum LogLevel {
     Trace,
 }
 
+/// Represents the failure of a call made with a cost budget.
+#[derive(Debug, Clone, Copy, TypeId, Encode, Decode, Describe, Eq, PartialEq)]
+pub enum CallError {
+    /// The callee ran out of its cost budget.
+    BudgetExceeded,
+
+    /// The callee failed for another reason.
+    Failed,
+}
+
 /// Represents the type of a resource.
 #[derive(Debug, Clone, Copy, TypeId, Encode, Decode, Describe, Eq, PartialEq)]
 pub enum ResourceType {
@@ -32,6 +42,14 @@ impl ResourceType {
             ResourceType::NonFungible => 0,
         }
     }
+
+    pub fn is_fungible(&self) -> bool {
+        matches!(self, ResourceType::Fungible { .. })
+    }
+
+    pub fn is_non_fungible(&self) -> bool {
+        matches!(self, ResourceType::NonFungible)
+    }
 }
 
 /// Represents some supply of resource.
@@ -1,6 +1,6 @@
 pub use crate::buffer::{scrypto_decode, scrypto_encode};
 pub use crate::core::*;
-pub use crate::kernel::{call_kernel, LogLevel, NewSupply, ResourceType};
+pub use crate::kernel::{call_kernel, CallError, LogLevel, NewSupply, ResourceType};
 pub use crate::resource::*;
 pub use crate::types::*;
 pub use crate::utils::*;
@@ -102,9 +102,20 @@ impl Bucket {
         self.amount() == 0.into()
     }
 
-    /// Uses resources in this bucket as authorization for an operation.
-    pub fn authorize<F: FnOnce(BucketRef) -> O, O>(&self, f: F) -> O {
-        f(self.present())
+    /// Presents this bucket as authorization for the duration of a closure.
+    ///
+    /// The bucket ref is held by the engine and dropped once the closure returns; within the
+    /// closure, `BucketRef::presented` gives a clone of it to hand over to an operation. A
+    /// clone that is still held when the closure returns fails the transaction.
+    pub fn authorize<F: FnOnce() -> R, R>(&self, f: F) -> R {
+        let input = PresentBucketInput { bid: self.bid };
+        let _: PresentBucketOutput = call_kernel(PRESENT_BUCKET, input);
+
+        let output = f();
+
+        let input = DropPresentedBucketRefInput {};
+        let _: DropPresentedBucketRefOutput = call_kernel(DROP_PRESENTED_BUCKET_REF, input);
+        output
     }
 
     /// Takes an NFT from this bucket, by id.
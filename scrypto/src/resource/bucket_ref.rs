@@ -7,6 +7,7 @@ use crate::rust::borrow::ToOwned;
 use crate::rust::vec;
 use crate::rust::vec::Vec;
 use crate::types::*;
+use crate::utils::*;
 
 /// Represents a reference to a bucket.
 #[derive(Debug)]
@@ -36,6 +37,17 @@ impl Clone for BucketRef {
 }
 
 impl BucketRef {
+    /// Returns a clone of the bucket ref presented by the innermost `Bucket::authorize`.
+    ///
+    /// # Panics
+    /// Panics if called outside of `Bucket::authorize`.
+    pub fn presented() -> BucketRef {
+        let input = ClonePresentedBucketRefInput {};
+        let output: ClonePresentedBucketRefOutput = call_kernel(CLONE_PRESENTED_BUCKET_REF, input);
+
+        output.rid.into()
+    }
+
     /// Checks if the referenced bucket contains the given resource, and aborts if not so.
     pub fn check<A: Into<ResourceDef>>(self, resource_def: A) {
         if self.contains(resource_def) {
@@ -45,6 +57,20 @@ impl BucketRef {
         }
     }
 
+    /// Checks if the referenced bucket contains any of the badges a method is guarded with.
+    ///
+    /// Used by `#[auth]`. When previewing auth, the engine records a missing badge and
+    /// reports it as present.
+    pub fn check_method_auth(&self, allowed_badges: &[ResourceDef]) -> bool {
+        let input = CheckMethodAuthInput {
+            rid: self.rid,
+            allowed_badges: allowed_badges.iter().map(ResourceDef::address).collect(),
+        };
+        let output: CheckMethodAuthOutput = call_kernel(CHECK_METHOD_AUTH, input);
+
+        output.authorized
+    }
+
     /// Checks if the referenced bucket contains the given resource.
     pub fn contains<A: Into<ResourceDef>>(&self, resource_def: A) -> bool {
         let resource_def: ResourceDef = resource_def.into();
@@ -88,6 +114,17 @@ impl BucketRef {
         ids[0]
     }
 
+    /// Returns the data of an NFT in the referenced bucket.
+    ///
+    /// # Panics
+    /// Panics if the referenced bucket doesn't contain the NFT.
+    pub fn get_nft_data<T: NftData>(&self, id: u128) -> T {
+        let input = GetNftDataInBucketRefInput { rid: self.rid, id };
+        let output: GetNftDataInBucketRefOutput = call_kernel(GET_NFT_DATA_IN_BUCKET_REF, input);
+
+        scrypto_unwrap(T::decode(&output.immutable_data, &output.mutable_data))
+    }
+
     /// Destroys this reference.
     pub fn drop(self) {
         let input = DropBucketRefInput { rid: self.rid };
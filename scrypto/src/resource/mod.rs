@@ -2,6 +2,7 @@ mod bucket;
 mod bucket_ref;
 mod nft;
 mod nft_data;
+mod resource_amount;
 mod resource_builder;
 mod resource_def;
 mod vault;
@@ -16,7 +17,10 @@ pub use bucket::Bucket;
 pub use bucket_ref::BucketRef;
 pub use nft::Nft;
 pub use nft_data::NftData;
-pub use resource_builder::{ResourceBuilder, DIVISIBILITY_MAXIMUM, DIVISIBILITY_NONE};
+pub use resource_amount::{MergeError, ParseResourceAmountError, ResourceAmount};
+pub use resource_builder::{
+    ResourceBuilder, ResourceBuilderError, DIVISIBILITY_MAXIMUM, DIVISIBILITY_NONE,
+};
 pub use resource_def::ResourceDef;
 pub use resource_flags::*;
 pub use resource_permissions::*;
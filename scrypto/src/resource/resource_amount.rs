@@ -0,0 +1,217 @@
+use core::ops::{Add, Mul};
+use sbor::*;
+
+use crate::kernel::*;
+use crate::rust::collections::BTreeSet;
+use crate::rust::fmt;
+use crate::rust::str::FromStr;
+use crate::rust::vec::Vec;
+use crate::types::*;
+
+/// Represents some amount of resource.
+#[derive(Debug, Clone, PartialEq, Eq, TypeId, Encode, Decode, Describe)]
+pub enum ResourceAmount {
+    Fungible {
+        amount: Decimal,
+        resource_address: Address,
+    },
+    NonFungible {
+        ids: BTreeSet<u128>,
+        resource_address: Address,
+    },
+}
+
+/// Represents an error when parsing `ResourceAmount` from string.
+#[derive(Debug, Clone)]
+pub enum ParseResourceAmountError {
+    InvalidAmount,
+    InvalidNftId,
+    InvalidResourceAddress,
+    MissingResourceAddress,
+    FractionalNonFungible,
+}
+
+/// Represents an error when merging two `ResourceAmount`s.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MergeError {
+    ResourceAddressMismatch,
+    ResourceTypeMismatch,
+}
+
+impl FromStr for ResourceAmount {
+    type Err = ParseResourceAmountError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let tokens: Vec<&str> = s.trim().split(',').collect();
+
+        if tokens.len() >= 2 {
+            let resource_address = tokens
+                .last()
+                .unwrap()
+                .parse::<Address>()
+                .map_err(|_| ParseResourceAmountError::InvalidResourceAddress)?;
+            if tokens[0].starts_with('#') {
+                let mut ids = BTreeSet::<u128>::new();
+                for id in &tokens[..tokens.len() - 1] {
+                    if let Some(id) = id.strip_prefix('#') {
+                        ids.insert(
+                            id.parse()
+                                .map_err(|_| ParseResourceAmountError::InvalidNftId)?,
+                        );
+                    } else {
+                        return Err(ParseResourceAmountError::InvalidNftId);
+                    }
+                }
+                Ok(ResourceAmount::NonFungible {
+                    ids,
+                    resource_address,
+                })
+            } else {
+                if tokens.len() == 2 {
+                    Ok(ResourceAmount::Fungible {
+                        amount: tokens[0]
+                            .parse()
+                            .map_err(|_| ParseResourceAmountError::InvalidAmount)?,
+                        resource_address,
+                    })
+                } else {
+                    Err(ParseResourceAmountError::InvalidAmount)
+                }
+            }
+        } else {
+            Err(ParseResourceAmountError::MissingResourceAddress)
+        }
+    }
+}
+
+impl fmt::Display for ResourceAmount {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ResourceAmount::Fungible {
+                amount,
+                resource_address,
+            } => write!(f, "{},{}", amount, resource_address),
+            ResourceAmount::NonFungible {
+                ids,
+                resource_address,
+            } => {
+                for id in ids {
+                    write!(f, "#{},", id)?;
+                }
+                write!(f, "{}", resource_address)
+            }
+        }
+    }
+}
+
+impl ResourceAmount {
+    pub fn amount(&self) -> Decimal {
+        match self {
+            ResourceAmount::Fungible { amount, .. } => *amount,
+            ResourceAmount::NonFungible { ids, .. } => ids.len().into(),
+        }
+    }
+    pub fn resource_address(&self) -> Address {
+        match self {
+            ResourceAmount::Fungible {
+                resource_address, ..
+            }
+            | ResourceAmount::NonFungible {
+                resource_address, ..
+            } => *resource_address,
+        }
+    }
+
+    /// Checks that the amount is valid for the given resource type, e.g. that it doesn't
+    /// ask for a fraction of an NFT.
+    pub fn check_resource_type(
+        &self,
+        resource_type: ResourceType,
+    ) -> Result<(), ParseResourceAmountError> {
+        match (self, resource_type) {
+            (ResourceAmount::Fungible { amount, .. }, ResourceType::NonFungible)
+                if amount.0 % Decimal::PRECISION != 0 =>
+            {
+                Err(ParseResourceAmountError::FractionalNonFungible)
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Combines two amounts of the same resource, by adding fungible amounts
+    /// or taking the union of NFT ids.
+    pub fn try_merge(&self, other: &ResourceAmount) -> Result<ResourceAmount, MergeError> {
+        if self.resource_address() != other.resource_address() {
+            return Err(MergeError::ResourceAddressMismatch);
+        }
+
+        match (self, other) {
+            (
+                ResourceAmount::Fungible {
+                    amount,
+                    resource_address,
+                },
+                ResourceAmount::Fungible {
+                    amount: other_amount,
+                    ..
+                },
+            ) => Ok(ResourceAmount::Fungible {
+                amount: *amount + *other_amount,
+                resource_address: *resource_address,
+            }),
+            (
+                ResourceAmount::NonFungible {
+                    ids,
+                    resource_address,
+                },
+                ResourceAmount::NonFungible { ids: other_ids, .. },
+            ) => Ok(ResourceAmount::NonFungible {
+                ids: ids.union(other_ids).cloned().collect(),
+                resource_address: *resource_address,
+            }),
+            _ => Err(MergeError::ResourceTypeMismatch),
+        }
+    }
+
+    /// Scales a fungible amount by the given factor.
+    ///
+    /// NFTs can't be scaled, as they're identified by ids.
+    pub fn try_scale(&self, factor: Decimal) -> Result<ResourceAmount, MergeError> {
+        match self {
+            ResourceAmount::Fungible {
+                amount,
+                resource_address,
+            } => Ok(ResourceAmount::Fungible {
+                amount: *amount * factor,
+                resource_address: *resource_address,
+            }),
+            ResourceAmount::NonFungible { .. } => Err(MergeError::ResourceTypeMismatch),
+        }
+    }
+}
+
+/// Adds two amounts of the same resource, see `try_merge`.
+///
+/// # Panics
+/// If the amounts are of different resources, or of different types.
+impl Add for ResourceAmount {
+    type Output = ResourceAmount;
+
+    fn add(self, other: ResourceAmount) -> ResourceAmount {
+        self.try_merge(&other)
+            .unwrap_or_else(|e| panic!("Failed to add resource amounts: {:?}", e))
+    }
+}
+
+/// Scales a fungible amount, see `try_scale`.
+///
+/// # Panics
+/// If the amount is of NFTs.
+impl<T: Into<Decimal>> Mul<T> for ResourceAmount {
+    type Output = ResourceAmount;
+
+    fn mul(self, factor: T) -> ResourceAmount {
+        self.try_scale(factor.into())
+            .unwrap_or_else(|e| panic!("Failed to scale resource amount: {:?}", e))
+    }
+}
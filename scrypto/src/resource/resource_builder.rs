@@ -1,8 +1,13 @@
 use crate::kernel::*;
+use crate::resource::resource_flags::*;
+use crate::resource::resource_permissions::*;
 use crate::resource::*;
 use crate::rust::borrow::ToOwned;
+use crate::rust::collections::BTreeMap;
+use crate::rust::collections::BTreeSet;
 use crate::rust::collections::HashMap;
 use crate::rust::string::String;
+use crate::rust::vec::Vec;
 use crate::types::*;
 
 /// Not divisible.
@@ -10,6 +15,14 @@ pub const DIVISIBILITY_NONE: u8 = 0;
 /// The maximum divisibility supported.
 pub const DIVISIBILITY_MAXIMUM: u8 = 18;
 
+/// Represents an error when validating a `ResourceBuilder`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ResourceBuilderError {
+    MissingMetadata(String),
+    DuplicateNftId(u128),
+    ZeroNftId,
+}
+
 /// Utility for creating resources.
 pub struct ResourceBuilder {
     resource_type: ResourceType,
@@ -17,6 +30,8 @@ pub struct ResourceBuilder {
     flags: u16,
     mutable_flags: u16,
     authorities: HashMap<Address, u16>,
+    supply: Option<Decimal>,
+    required_metadata: Vec<String>,
 }
 
 impl ResourceBuilder {
@@ -28,6 +43,8 @@ impl ResourceBuilder {
             flags: 0,
             mutable_flags: 0,
             authorities: HashMap::new(),
+            supply: None,
+            required_metadata: Vec::new(),
         }
     }
 
@@ -53,6 +70,23 @@ impl ResourceBuilder {
         self
     }
 
+    /// Requires the given metadata keys to be set, which is checked when the resource is created.
+    pub fn require_metadata(&mut self, keys: &[&str]) -> &mut Self {
+        self.required_metadata
+            .extend(keys.iter().map(|k| (*k).to_owned()));
+        self
+    }
+
+    /// Checks that all required metadata are set.
+    pub fn validate(&self) -> Result<(), ResourceBuilderError> {
+        for key in &self.required_metadata {
+            if !self.metadata.contains_key(key) {
+                return Err(ResourceBuilderError::MissingMetadata(key.clone()));
+            }
+        }
+        Ok(())
+    }
+
     /// Sets the feature flags.
     pub fn flags(&mut self, flags: u16) -> &mut Self {
         self.flags = flags;
@@ -72,6 +106,51 @@ impl ResourceBuilder {
         self
     }
 
+    /// Makes the resource mintable by the holder of the given badge.
+    pub fn mintable_by<A: Into<ResourceDef>>(&mut self, badge_address: A) -> &mut Self {
+        self.flags |= MINTABLE;
+        *self
+            .authorities
+            .entry(badge_address.into().address())
+            .or_insert(0) |= MAY_MINT;
+        self
+    }
+
+    /// Makes the resource burnable by the holder of the given badge.
+    pub fn burnable_by<A: Into<ResourceDef>>(&mut self, badge_address: A) -> &mut Self {
+        self.flags |= BURNABLE;
+        *self
+            .authorities
+            .entry(badge_address.into().address())
+            .or_insert(0) |= MAY_BURN;
+        self
+    }
+
+    /// Sets the initial fungible supply, to be used by `build_token`.
+    pub fn with_initial_supply<T: Into<Decimal>>(&mut self, amount: T) -> &mut Self {
+        self.supply = Some(amount.into());
+        self
+    }
+
+    /// Creates a fungible resource, returning the resource definition and a bucket of the initial supply.
+    ///
+    /// If no initial supply is set, the bucket will be empty.
+    ///
+    /// # Example
+    /// ```ignore
+    /// let (resource_def, bucket) = ResourceBuilder::new_fungible(DIVISIBILITY_MAXIMUM)
+    ///     .metadata("name", "TestToken")
+    ///     .mintable_by(badge)
+    ///     .burnable_by(badge)
+    ///     .with_initial_supply(1000)
+    ///     .build_token();
+    /// ```
+    pub fn build_token(&self) -> (ResourceDef, Bucket) {
+        let amount = self.supply.unwrap_or_else(Decimal::zero);
+        let (resource_def, bucket) = self.build(Some(NewSupply::fungible(amount)));
+        (resource_def, bucket.unwrap())
+    }
+
     /// Creates resource with the given initial supply.
     pub fn initial_supply(&self, supply: NewSupply) -> Bucket {
         self.build(Some(supply)).1.unwrap()
@@ -110,12 +189,64 @@ impl ResourceBuilder {
             .unwrap()
     }
 
+    /// Creates a non-fungible resource, returning the resource definition and a bucket of the
+    /// initial supply.
+    ///
+    /// # Example
+    /// ```ignore
+    /// let (resource_def, bucket) = ResourceBuilder::new_non_fungible()
+    ///     .metadata("name", "TestNft")
+    ///     .build_nft([(1, Ticket { row: 1 }), (2, Ticket { row: 2 })]);
+    /// ```
+    ///
+    /// # Panics
+    /// Panics if an id is zero or given more than once.
+    pub fn build_nft<T, V>(&self, entries: T) -> (ResourceDef, Bucket)
+    where
+        T: IntoIterator<Item = (u128, V)>,
+        V: NftData,
+    {
+        let entries: Vec<(u128, V)> = entries.into_iter().collect();
+        if let Err(e) = check_nft_ids(entries.iter().map(|(id, _)| *id)) {
+            panic!("Invalid resource: {:?}", e);
+        }
+
+        let (resource_def, bucket) = self.build(Some(NewSupply::non_fungible(entries)));
+        (resource_def, bucket.unwrap())
+    }
+
+    /// Creates a non-fungible resource, assigning ids `1, 2, 3, ...` to the initial supply in
+    /// order, and returns the resource definition and a bucket of the initial supply.
+    pub fn build_nft_with_auto_ids<T, V>(&self, entries: T) -> (ResourceDef, Bucket)
+    where
+        T: IntoIterator<Item = V>,
+        V: NftData,
+    {
+        self.build_nft((1..).zip(entries))
+    }
+
+    /// Creates a non-fungible resource with the given initial supply, returning the resource
+    /// definition and a bucket of the initial supply.
+    ///
+    /// # Panics
+    /// Panics if an id is zero.
+    pub fn new_nft_fixed_with_def<V: NftData>(
+        &self,
+        supply: BTreeMap<u128, V>,
+    ) -> (ResourceDef, Bucket) {
+        self.build_nft(supply)
+    }
+
     /// Creates resource with no initial supply.
     pub fn no_initial_supply(&self) -> ResourceDef {
         self.build(None).0
     }
 
     fn build(&self, supply: Option<NewSupply>) -> (ResourceDef, Option<Bucket>) {
+        if let Err(e) = self.validate() {
+            panic!("Invalid resource: {:?}", e);
+        }
+
         ResourceDef::new(
             self.resource_type,
             self.metadata.clone(),
@@ -126,3 +257,50 @@ impl ResourceBuilder {
         )
     }
 }
+
+/// Checks that no NFT id is zero or given more than once.
+fn check_nft_ids<T: IntoIterator<Item = u128>>(ids: T) -> Result<(), ResourceBuilderError> {
+    let mut seen = BTreeSet::new();
+    for id in ids {
+        if id == 0 {
+            return Err(ResourceBuilderError::ZeroNftId);
+        }
+        if !seen.insert(id) {
+            return Err(ResourceBuilderError::DuplicateNftId(id));
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_require_metadata() {
+        let mut builder = ResourceBuilder::new_fungible(DIVISIBILITY_MAXIMUM);
+        builder
+            .require_metadata(&["name", "symbol"])
+            .metadata("name", "TestToken");
+        assert_eq!(
+            builder.validate(),
+            Err(ResourceBuilderError::MissingMetadata("symbol".to_owned()))
+        );
+
+        builder.metadata("symbol", "TT");
+        assert_eq!(builder.validate(), Ok(()));
+    }
+
+    #[test]
+    fn test_duplicate_nft_ids() {
+        assert_eq!(check_nft_ids([1, 2, 3]), Ok(()));
+        assert_eq!(
+            check_nft_ids([1, 2, 1]),
+            Err(ResourceBuilderError::DuplicateNftId(1))
+        );
+        assert_eq!(
+            check_nft_ids([1, 0, 2]),
+            Err(ResourceBuilderError::ZeroNftId)
+        );
+    }
+}
@@ -107,6 +107,26 @@ impl ResourceDef {
         let _output: BurnResourceOutput = call_kernel(BURN_RESOURCE, input);
     }
 
+    /// Creates an empty vault to permanently hold resource of this definition.
+    pub fn new_empty_vault(&self) -> Vault {
+        Vault::new(self.address)
+    }
+
+    /// Creates a vault of this resource and fills it with an initial bucket, which must
+    /// hold resource of this definition.
+    pub fn new_vault_with(&self, bucket: Bucket) -> Vault {
+        let resource_address = bucket.resource_address();
+        assert!(
+            resource_address == self.address,
+            "Expect bucket of {}, but found {}",
+            self.address,
+            resource_address
+        );
+        let vault = self.new_empty_vault();
+        vault.put(bucket);
+        vault
+    }
+
     /// Returns the resource type.
     pub fn resource_type(&self) -> ResourceType {
         let input = GetResourceTypeInput {
@@ -117,6 +137,16 @@ impl ResourceDef {
         output.resource_type
     }
 
+    /// Returns whether this resource is fungible.
+    pub fn is_fungible(&self) -> bool {
+        self.resource_type().is_fungible()
+    }
+
+    /// Returns whether this resource is non-fungible, i.e. made of NFTs.
+    pub fn is_non_fungible(&self) -> bool {
+        self.resource_type().is_non_fungible()
+    }
+
     /// Returns the metadata associated with this resource.
     pub fn metadata(&self) -> HashMap<String, String> {
         let input = GetResourceMetadataInput {
@@ -179,11 +209,13 @@ impl ResourceDef {
     /// Updates the mutable part of an NFT unit.
     ///
     /// # Panics
-    /// Panics if this is not an NFT resource or the specified NFT is not found.
+    /// Panics if this is not an NFT resource, the specified NFT is not found, or any immutable
+    /// field of the new data differs from the stored one.
     pub fn update_nft_data<T: NftData>(&self, id: u128, new_data: T, auth: BucketRef) {
         let input = UpdateNftMutableDataInput {
             resource_address: self.address,
             id,
+            immutable_data: new_data.immutable_data(),
             new_mutable_data: new_data.mutable_data(),
             auth: auth.into(),
         };
@@ -229,6 +261,26 @@ impl ResourceDef {
         };
         let _output: UpdateResourceMetadataOutput = call_kernel(UPDATE_RESOURCE_METADATA, input);
     }
+
+    /// Forcibly withdraws resources of this kind from a component's vault.
+    ///
+    /// Only applicable to `RECALLABLE` resources, and requires a badge with `MAY_RECALL` permission.
+    pub fn recall<T: Into<Decimal>, A: Into<Address>>(
+        &self,
+        component: A,
+        amount: T,
+        auth: BucketRef,
+    ) -> Bucket {
+        let input = RecallResourceInput {
+            resource_address: self.address,
+            component_address: component.into(),
+            amount: amount.into(),
+            auth: auth.into(),
+        };
+        let output: RecallResourceOutput = call_kernel(RECALL_RESOURCE, input);
+
+        output.bid.into()
+    }
 }
 
 //========
@@ -159,6 +159,21 @@ impl Vault {
         output
     }
 
+    /// Creates an immutable reference to some amount of resources in this vault.
+    ///
+    /// The resources are locked until the reference and all its clones are dropped, and
+    /// can't be taken out of the vault meanwhile.
+    pub fn create_bucket_ref<A: Into<Decimal>>(&self, amount: A) -> BucketRef {
+        let input = CreateBucketRefFromVaultInput {
+            vid: self.vid,
+            amount: amount.into(),
+        };
+        let output: CreateBucketRefFromVaultOutput =
+            call_kernel(CREATE_BUCKET_REF_FROM_VAULT, input);
+
+        output.rid.into()
+    }
+
     /// Returns the amount of resources within this vault.
     pub fn amount(&self) -> Decimal {
         let input = GetVaultDecimalInput { vid: self.vid };
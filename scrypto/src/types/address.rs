@@ -29,6 +29,14 @@ pub const RADIX_TOKEN: Address = Address::ResourceDef([
     0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 4,
 ]);
 
+/// The well-known addresses, by constant name.
+const WELL_KNOWN_ADDRESSES: [(&str, Address); 4] = [
+    ("SYSTEM_PACKAGE", SYSTEM_PACKAGE),
+    ("SYSTEM_COMPONENT", SYSTEM_COMPONENT),
+    ("ACCOUNT_PACKAGE", ACCOUNT_PACKAGE),
+    ("RADIX_TOKEN", RADIX_TOKEN),
+];
+
 /// Represents an address.
 #[derive(Clone, Copy, PartialEq, Eq, Hash)]
 pub enum Address {
@@ -53,6 +61,16 @@ pub enum ParseAddressError {
     InvalidType(u8),
 }
 
+impl fmt::Display for ParseAddressError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::InvalidHex(e) => write!(f, "Invalid address hex: {}", e),
+            Self::InvalidLength(len) => write!(f, "Invalid address length: {} bytes", len),
+            Self::InvalidType(ty) => write!(f, "Invalid address type: {}", ty),
+        }
+    }
+}
+
 impl Address {
     pub fn to_vec(&self) -> Vec<u8> {
         match self {
@@ -78,14 +96,36 @@ impl Address {
     pub fn is_public_key(&self) -> bool {
         matches!(self, Address::PublicKey(_))
     }
+
+    /// Looks up a well-known address by its constant name, e.g. `RADIX_TOKEN`.
+    pub fn well_known(name: &str) -> Option<Address> {
+        WELL_KNOWN_ADDRESSES
+            .iter()
+            .find(|(n, _)| *n == name)
+            .map(|(_, address)| *address)
+    }
+
+    /// Returns whether this is one of the well-known addresses created at bootstrap.
+    pub fn is_well_known(&self) -> bool {
+        WELL_KNOWN_ADDRESSES
+            .iter()
+            .any(|(_, address)| address == self)
+    }
+}
+
+impl Address {
+    /// Parses an address from its hex representation.
+    pub fn try_from_str(s: &str) -> Result<Self, ParseAddressError> {
+        let bytes = hex::decode(s).map_err(ParseAddressError::InvalidHex)?;
+        Self::try_from(bytes.as_slice())
+    }
 }
 
 impl FromStr for Address {
     type Err = ParseAddressError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let bytes = hex::decode(s).map_err(ParseAddressError::InvalidHex)?;
-        Self::try_from(bytes.as_slice())
+        Self::try_from_str(s)
     }
 }
 
@@ -158,6 +198,36 @@ mod tests {
     use super::*;
     use crate::rust::string::ToString;
 
+    #[test]
+    fn test_try_from_str_errors() {
+        let s = "037ac8066e51cd0d6b320c338d5abbcdbcca25572b6b3e11ee944a";
+        assert_eq!(
+            Address::try_from_str(s).unwrap(),
+            Address::from_str(s).unwrap()
+        );
+        let message = |s: &str| Address::try_from_str(s).unwrap_err().to_string();
+        assert!(message("zz").starts_with("Invalid address hex: "));
+        assert_eq!(message("0301"), "Invalid address length: 2 bytes");
+        assert_eq!(
+            message("097ac8066e51cd0d6b320c338d5abbcdbcca25572b6b3e11ee944a"),
+            "Invalid address type: 9"
+        );
+    }
+
+    #[test]
+    fn test_well_known() {
+        assert_eq!(Address::well_known("RADIX_TOKEN"), Some(RADIX_TOKEN));
+        assert_eq!(
+            Address::well_known("ACCOUNT_PACKAGE"),
+            Some(ACCOUNT_PACKAGE)
+        );
+        assert_eq!(Address::well_known("radix_token"), None);
+        assert!(SYSTEM_COMPONENT.is_well_known());
+        assert_eq!(Address::well_known("AIRDROP_PACKAGE"), None);
+        let s = "01bda8686d6c2fa45dce04fac71a09b54efbc8028c23aac74bc00e";
+        assert!(!Address::from_str(s).unwrap().is_well_known());
+    }
+
     #[test]
     fn test_from_to_string() {
         let s = "037ac8066e51cd0d6b320c338d5abbcdbcca25572b6b3e11ee944a";
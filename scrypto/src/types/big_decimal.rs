@@ -30,6 +30,18 @@ pub enum ParseBigDecimalError {
     InvalidLength,
 }
 
+impl fmt::Display for ParseBigDecimalError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::InvalidBigDecimal(s) => write!(f, "Invalid big decimal: {:?}", s),
+            Self::InvalidSign(b) => write!(f, "Invalid big decimal sign: {}", b),
+            Self::InvalidChar(c) => write!(f, "Invalid character in big decimal: {:?}", c),
+            Self::UnsupportedDecimalPlace => write!(f, "Too many decimal places"),
+            Self::InvalidLength => write!(f, "Invalid big decimal length"),
+        }
+    }
+}
+
 impl BigDecimal {
     /// Return a `BigDecimal` of 0.
     pub fn zero() -> Self {
@@ -342,15 +354,17 @@ fn read_dot(c: char) -> Result<(), ParseBigDecimalError> {
     }
 }
 
-impl FromStr for BigDecimal {
-    type Err = ParseBigDecimalError;
-
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
+impl BigDecimal {
+    /// Parses a big decimal from its string representation, e.g. `"-1.5"`.
+    pub fn try_from_str(s: &str) -> Result<Self, ParseBigDecimalError> {
         let mut sign = 1i128;
         let mut value = BigInt::zero();
 
         let chars: Vec<char> = s.chars().collect();
         let mut p = 0;
+        if chars.is_empty() || chars == ['-'] {
+            return Err(ParseBigDecimalError::InvalidBigDecimal(s.to_owned()));
+        }
 
         // read sign
         if chars[p] == '-' {
@@ -388,6 +402,14 @@ impl FromStr for BigDecimal {
     }
 }
 
+impl FromStr for BigDecimal {
+    type Err = ParseBigDecimalError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::try_from_str(s)
+    }
+}
+
 impl TryFrom<&[u8]> for BigDecimal {
     type Error = ParseBigDecimalError;
 
@@ -493,6 +515,24 @@ mod tests {
     use super::*;
     use crate::rust::string::ToString;
 
+    #[test]
+    fn test_try_from_str_errors() {
+        assert_eq!(
+            BigDecimal::try_from_str("1.5").unwrap(),
+            BigDecimal::from_str("1.5").unwrap()
+        );
+        let message = |s: &str| BigDecimal::try_from_str(s).unwrap_err().to_string();
+        assert_eq!(message(""), "Invalid big decimal: \"\"");
+        assert_eq!(message("1,5"), "Invalid character in big decimal: ','");
+        assert_eq!(message("0.0000000000000000001"), "Too many decimal places");
+        assert_eq!(
+            BigDecimal::try_from([7u8].as_ref())
+                .unwrap_err()
+                .to_string(),
+            "Invalid big decimal sign: 7"
+        );
+    }
+
     #[test]
     fn test_format() {
         assert_eq!(BigDecimal(1i128.into()).to_string(), "0.000000000000000001");
@@ -1,7 +1,8 @@
+use core::cmp::Ordering;
 use core::ops::*;
 
 use num_bigint::BigInt;
-use num_traits::Signed;
+use num_traits::{Signed, Zero};
 use sbor::{describe::Type, *};
 
 use crate::buffer::*;
@@ -10,6 +11,7 @@ use crate::rust::convert::TryFrom;
 use crate::rust::fmt;
 use crate::rust::str::FromStr;
 use crate::rust::string::String;
+use crate::rust::string::ToString;
 use crate::rust::vec;
 use crate::rust::vec::Vec;
 use crate::types::copy_u8_array;
@@ -17,14 +19,21 @@ use crate::types::copy_u8_array;
 /// The universal precision used by `Decimal`.
 pub const PRECISION: i128 = 10i128.pow(18);
 
-/// Represents a **signed**, **bounded** fixed-point decimal, where the precision is 10^-18.
+/// The max number of decimal places supported by `FixedPoint`.
+pub const MAX_DECIMALS: u8 = 38;
+
+/// Represents a **signed**, **bounded** fixed-point number with `DECIMALS` decimal places,
+/// i.e. the precision is 10^-DECIMALS.
 ///
 /// Panic when there is an overflow.
 ///
 /// FIXME prevent RE from panicking caused by arithmetic overflow.
 ///
 #[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
-pub struct Decimal(pub i128);
+pub struct FixedPoint<const DECIMALS: u8>(pub i128);
+
+/// Represents a **signed**, **bounded** fixed-point decimal, where the precision is 10^-18.
+pub type Decimal = FixedPoint<18>;
 
 /// Represents an error when parsing Decimal.
 #[derive(Debug, Clone)]
@@ -33,21 +42,60 @@ pub enum ParseDecimalError {
     InvalidChar(char),
     UnsupportedDecimalPlace,
     InvalidLength,
+    Overflow,
+}
+
+/// Defines how to round a number which can't be represented exactly.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RoundingMode {
+    /// Rounds towards zero, i.e. truncates.
+    TowardsZero,
+    /// Rounds away from zero.
+    AwayFromZero,
+    /// Rounds towards negative infinity, i.e. floor.
+    TowardsNegativeInfinity,
+    /// Rounds towards positive infinity, i.e. ceiling.
+    TowardsPositiveInfinity,
+    /// Rounds to the nearest, with midpoints rounded away from zero.
+    HalfAwayFromZero,
+    /// Rounds to the nearest, with midpoints rounded to the even neighbour.
+    HalfEven,
+}
+
+impl fmt::Display for ParseDecimalError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::InvalidDecimal(s) => write!(f, "Invalid decimal: {:?}", s),
+            Self::InvalidChar(c) => write!(f, "Invalid character in decimal: {:?}", c),
+            Self::UnsupportedDecimalPlace => write!(f, "Too many decimal places"),
+            Self::InvalidLength => write!(f, "Invalid decimal length"),
+            Self::Overflow => write!(f, "Decimal out of range"),
+        }
+    }
 }
 
-impl Decimal {
-    /// The min value of `Decimal`.
+impl<const D: u8> FixedPoint<D> {
+    /// The number of units of 10^-D in one.
+    pub const PRECISION: i128 = {
+        assert!(
+            D <= MAX_DECIMALS,
+            "FixedPoint supports at most MAX_DECIMALS decimal places"
+        );
+        10i128.pow(D as u32)
+    };
+
+    /// The min value.
     pub const MIN: Self = Self(i128::MIN);
 
-    /// The max value of `Decimal`.
+    /// The max value.
     pub const MAX: Self = Self(i128::MAX);
 
-    /// Returns `Decimal` of 0.
+    /// Returns 0.
     pub fn zero() -> Self {
         0.into()
     }
 
-    /// Returns `Decimal` of 1.
+    /// Returns 1.
     pub fn one() -> Self {
         1.into()
     }
@@ -57,6 +105,30 @@ impl Decimal {
         self.0.to_le_bytes().to_vec()
     }
 
+    /// Creates a value from its raw representation, i.e. the little-endian bytes of
+    /// the number of 10^-D units.
+    ///
+    /// This is unchecked: the bytes are taken as-is and are not validated in any way.
+    pub const fn from_raw(raw: [u8; 16]) -> Self {
+        Self(i128::from_le_bytes(raw))
+    }
+
+    /// Returns the raw representation, i.e. the little-endian bytes of the number of 10^-D units.
+    pub const fn to_raw(&self) -> [u8; 16] {
+        self.0.to_le_bytes()
+    }
+
+    /// Creates a value from a number of its smallest units, i.e. 10^-D, as external systems
+    /// commonly represent raw amounts.
+    pub const fn from_smallest_units(units: i128) -> Self {
+        Self(units)
+    }
+
+    /// Returns the number of smallest units, i.e. 10^-D, in this value.
+    pub const fn to_smallest_units(&self) -> i128 {
+        self.0
+    }
+
     /// Whether this decimal is zero.
     pub fn is_zero(&self) -> bool {
         self.0 == 0
@@ -73,16 +145,144 @@ impl Decimal {
     }
 
     /// Returns the absolute value.
-    pub fn abs(&self) -> Decimal {
-        Decimal(self.0.abs())
+    pub fn abs(&self) -> Self {
+        Self(self.0.abs())
+    }
+
+    /// Returns `1` if positive, `-1` if negative and `0` if zero.
+    pub fn signum(&self) -> i8 {
+        self.0.signum() as i8
+    }
+
+    /// Returns the smaller of two decimals.
+    pub fn min(a: Self, b: Self) -> Self {
+        if a <= b {
+            a
+        } else {
+            b
+        }
+    }
+
+    /// Returns the larger of two decimals.
+    pub fn max(a: Self, b: Self) -> Self {
+        if a >= b {
+            a
+        } else {
+            b
+        }
+    }
+
+    /// Creates a value from `numerator / denominator`, at full precision.
+    ///
+    /// Returns `None` if the denominator is zero or the result overflows.
+    pub fn from_fraction(numerator: i128, denominator: i128) -> Option<Self> {
+        if denominator == 0 {
+            return None;
+        }
+        let a = BigInt::from(numerator);
+        let b = BigInt::from(denominator);
+        big_int_to_fixed_point_checked(a * Self::PRECISION / b)
+    }
+
+    /// Divides by another decimal, returning `None` if the divisor is zero or the result overflows.
+    pub fn checked_div<T: Into<Self>>(self, other: T) -> Option<Self> {
+        let other = other.into();
+        if other.is_zero() {
+            return None;
+        }
+        let a = BigInt::from(self.0);
+        let b = BigInt::from(other.0);
+        big_int_to_fixed_point_checked(a * Self::PRECISION / b)
+    }
+
+    /// Rounds to a multiple of the given granularity, e.g. `0.01`.
+    ///
+    /// Panics if the granularity is not positive.
+    pub fn round_to_granularity(&self, granularity: Self, mode: RoundingMode) -> Self {
+        assert!(granularity.is_positive(), "Granularity must be positive");
+
+        let g = granularity.0;
+        let quotient = self.0 / g;
+        let remainder = self.0 % g;
+        if remainder == 0 {
+            return *self;
+        }
+
+        // The remainder has the sign of self, so the result is either `quotient` or `away`.
+        let away = quotient + self.0.signum();
+        let half = remainder.abs().cmp(&(g - remainder.abs()));
+        let rounded = match mode {
+            RoundingMode::TowardsZero => quotient,
+            RoundingMode::AwayFromZero => away,
+            RoundingMode::TowardsNegativeInfinity => quotient.min(away),
+            RoundingMode::TowardsPositiveInfinity => quotient.max(away),
+            RoundingMode::HalfAwayFromZero => match half {
+                Ordering::Less => quotient,
+                _ => away,
+            },
+            RoundingMode::HalfEven => match half {
+                Ordering::Less => quotient,
+                Ordering::Equal if quotient % 2 == 0 => quotient,
+                _ => away,
+            },
+        };
+        Self(rounded * g)
+    }
+
+    /// Multiplies by another decimal, rounding the full-precision product with the given mode
+    /// instead of truncating it.
+    ///
+    /// Panics if the result overflows.
+    pub fn mul_with_rounding<T: Into<Self>>(&self, other: T, mode: RoundingMode) -> Self {
+        let product = BigInt::from(self.0) * BigInt::from(other.into().0);
+        big_int_to_fixed_point(round_div(product, &BigInt::from(Self::PRECISION), mode))
+    }
+
+    /// Converts to `u128`, returning `None` if this is negative or has a fractional part.
+    pub fn to_u128(&self) -> Option<u128> {
+        if self.0 % Self::PRECISION == 0 {
+            u128::try_from(self.0 / Self::PRECISION).ok()
+        } else {
+            None
+        }
+    }
+
+    /// Converts to `u64`, returning `None` if this is out of range or has a fractional part.
+    pub fn to_u64(&self) -> Option<u64> {
+        self.to_u128().and_then(|n| u64::try_from(n).ok())
+    }
+
+    /// Rounds down to the nearest integer and converts it to `u128`, returning `None` if
+    /// the result is negative.
+    pub fn try_floor_to_u128(&self) -> Option<u128> {
+        u128::try_from(self.0.div_euclid(Self::PRECISION)).ok()
+    }
+
+    /// Converts to another precision, rounding half away from zero when decimal places are dropped.
+    ///
+    /// Panics if the result overflows.
+    pub fn rescale<const E: u8>(self) -> FixedPoint<E> {
+        if E >= D {
+            let factor = BigInt::from(10i128.pow((E - D) as u32));
+            big_int_to_fixed_point(BigInt::from(self.0) * factor)
+        } else {
+            let factor = 10i128.pow((D - E) as u32);
+            let quotient = self.0 / factor;
+            let remainder = self.0 % factor;
+            if remainder.abs() >= factor / 2 {
+                FixedPoint(quotient + self.0.signum())
+            } else {
+                FixedPoint(quotient)
+            }
+        }
     }
 }
 
 macro_rules! from_int {
     ($type:ident) => {
-        impl From<$type> for Decimal {
+        impl<const D: u8> From<$type> for FixedPoint<D> {
             fn from(val: $type) -> Self {
-                Self((val as i128) * PRECISION)
+                Self((val as i128) * Self::PRECISION)
             }
         }
     };
@@ -103,11 +303,11 @@ from_int!(isize);
 // ADD
 //=====
 
-impl<T: Into<Decimal>> Add<T> for Decimal {
-    type Output = Decimal;
+impl<const D: u8, T: Into<FixedPoint<D>>> Add<T> for FixedPoint<D> {
+    type Output = Self;
 
     fn add(self, other: T) -> Self::Output {
-        Decimal(self.0 + other.into().0)
+        Self(self.0 + other.into().0)
     }
 }
 
@@ -115,11 +315,11 @@ impl<T: Into<Decimal>> Add<T> for Decimal {
 // Sub
 //=====
 
-impl<T: Into<Decimal>> Sub<T> for Decimal {
-    type Output = Decimal;
+impl<const D: u8, T: Into<FixedPoint<D>>> Sub<T> for FixedPoint<D> {
+    type Output = Self;
 
     fn sub(self, other: T) -> Self::Output {
-        Decimal(self.0 - other.into().0)
+        Self(self.0 - other.into().0)
     }
 }
 
@@ -127,10 +327,10 @@ impl<T: Into<Decimal>> Sub<T> for Decimal {
 // Mul
 //=====
 
-fn big_int_to_decimal(v: BigInt) -> Decimal {
+fn big_int_to_fixed_point_checked<const D: u8>(v: BigInt) -> Option<FixedPoint<D>> {
     let bytes = v.to_signed_bytes_le();
     if bytes.len() > 16 {
-        panic!("Overflow");
+        None
     } else {
         let mut buf = if v.is_negative() {
             [255u8; 16]
@@ -138,18 +338,50 @@ fn big_int_to_decimal(v: BigInt) -> Decimal {
             [0u8; 16]
         };
         buf[..bytes.len()].copy_from_slice(&bytes);
-        Decimal(i128::from_le_bytes(buf))
+        Some(FixedPoint(i128::from_le_bytes(buf)))
+    }
+}
+
+fn big_int_to_fixed_point<const D: u8>(v: BigInt) -> FixedPoint<D> {
+    big_int_to_fixed_point_checked(v).expect("Overflow")
+}
+
+/// Divides by a positive divisor, rounding the quotient with the given mode.
+fn round_div(n: BigInt, d: &BigInt, mode: RoundingMode) -> BigInt {
+    let quotient = &n / d;
+    let remainder = &n % d;
+    if remainder.is_zero() {
+        return quotient;
+    }
+
+    // The remainder has the sign of n, so the result is either `quotient` or `away`.
+    let away = &quotient + n.signum();
+    let half = remainder.abs().cmp(&(d - remainder.abs()));
+    match mode {
+        RoundingMode::TowardsZero => quotient,
+        RoundingMode::AwayFromZero => away,
+        RoundingMode::TowardsNegativeInfinity => quotient.min(away),
+        RoundingMode::TowardsPositiveInfinity => quotient.max(away),
+        RoundingMode::HalfAwayFromZero => match half {
+            Ordering::Less => quotient,
+            _ => away,
+        },
+        RoundingMode::HalfEven => match half {
+            Ordering::Less => quotient,
+            Ordering::Equal if (&quotient % BigInt::from(2)).is_zero() => quotient,
+            _ => away,
+        },
     }
 }
 
-impl<T: Into<Decimal>> Mul<T> for Decimal {
-    type Output = Decimal;
+impl<const D: u8, T: Into<FixedPoint<D>>> Mul<T> for FixedPoint<D> {
+    type Output = Self;
 
     fn mul(self, other: T) -> Self::Output {
         let a = BigInt::from(self.0);
         let b = BigInt::from(other.into().0);
-        let c = a * b / PRECISION;
-        big_int_to_decimal(c)
+        let c = a * b / Self::PRECISION;
+        big_int_to_fixed_point(c)
     }
 }
 
@@ -157,14 +389,14 @@ impl<T: Into<Decimal>> Mul<T> for Decimal {
 // Div
 //=====
 
-impl<T: Into<Decimal>> Div<T> for Decimal {
-    type Output = Decimal;
+impl<const D: u8, T: Into<FixedPoint<D>>> Div<T> for FixedPoint<D> {
+    type Output = Self;
 
     fn div(self, other: T) -> Self::Output {
         let a = BigInt::from(self.0);
         let b = BigInt::from(other.into().0);
-        let c = a * PRECISION / b;
-        big_int_to_decimal(c)
+        let c = a * Self::PRECISION / b;
+        big_int_to_fixed_point(c)
     }
 }
 
@@ -172,11 +404,11 @@ impl<T: Into<Decimal>> Div<T> for Decimal {
 // Neg
 //=======
 
-impl Neg for Decimal {
-    type Output = Decimal;
+impl<const D: u8> Neg for FixedPoint<D> {
+    type Output = Self;
 
     fn neg(self) -> Self::Output {
-        Decimal(-self.0)
+        Self(-self.0)
     }
 }
 
@@ -184,7 +416,7 @@ impl Neg for Decimal {
 // AddAssign
 //===========
 
-impl<T: Into<Decimal>> AddAssign<T> for Decimal {
+impl<const D: u8, T: Into<FixedPoint<D>>> AddAssign<T> for FixedPoint<D> {
     fn add_assign(&mut self, other: T) {
         self.0 += other.into().0;
     }
@@ -194,7 +426,7 @@ impl<T: Into<Decimal>> AddAssign<T> for Decimal {
 // SubAssign
 //===========
 
-impl<T: Into<Decimal>> SubAssign<T> for Decimal {
+impl<const D: u8, T: Into<FixedPoint<D>>> SubAssign<T> for FixedPoint<D> {
     fn sub_assign(&mut self, other: T) {
         self.0 -= other.into().0;
     }
@@ -204,7 +436,7 @@ impl<T: Into<Decimal>> SubAssign<T> for Decimal {
 // MulAssign
 //===========
 
-impl<T: Into<Decimal>> MulAssign<T> for Decimal {
+impl<const D: u8, T: Into<FixedPoint<D>>> MulAssign<T> for FixedPoint<D> {
     fn mul_assign(&mut self, other: T) {
         self.0 = (self.clone() * other.into()).0;
     }
@@ -214,7 +446,7 @@ impl<T: Into<Decimal>> MulAssign<T> for Decimal {
 // DivAssign
 //===========
 
-impl<T: Into<Decimal>> DivAssign<T> for Decimal {
+impl<const D: u8, T: Into<FixedPoint<D>>> DivAssign<T> for FixedPoint<D> {
     fn div_assign(&mut self, other: T) {
         self.0 = (self.clone() / other.into()).0;
     }
@@ -229,6 +461,14 @@ fn read_digit(c: char) -> Result<i128, ParseDecimalError> {
     }
 }
 
+/// Appends a signed digit to the value read so far, failing on overflow.
+fn push_digit(value: i128, digit: i128) -> Result<i128, ParseDecimalError> {
+    value
+        .checked_mul(10)
+        .and_then(|v| v.checked_add(digit))
+        .ok_or(ParseDecimalError::Overflow)
+}
+
 fn read_dot(c: char) -> Result<(), ParseDecimalError> {
     if c == '.' {
         Ok(())
@@ -237,15 +477,17 @@ fn read_dot(c: char) -> Result<(), ParseDecimalError> {
     }
 }
 
-impl FromStr for Decimal {
-    type Err = ParseDecimalError;
-
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
+impl<const D: u8> FixedPoint<D> {
+    /// Parses a decimal from its string representation, e.g. `"-1.5"`.
+    pub fn try_from_str(s: &str) -> Result<Self, ParseDecimalError> {
         let mut sign = 1i128;
         let mut value = 0i128;
 
         let chars: Vec<char> = s.chars().collect();
         let mut p = 0;
+        if chars.is_empty() || chars == ['-'] {
+            return Err(ParseDecimalError::InvalidDecimal(s.to_owned()));
+        }
 
         // read sign
         if chars[p] == '-' {
@@ -255,7 +497,7 @@ impl FromStr for Decimal {
 
         // read integral
         while p < chars.len() && chars[p] != '.' {
-            value = value * 10 + read_digit(chars[p])? * sign;
+            value = push_digit(value, read_digit(chars[p])? * sign)?;
             p += 1;
         }
 
@@ -266,12 +508,12 @@ impl FromStr for Decimal {
         }
 
         // read fraction
-        for _ in 0..18 {
+        for _ in 0..D {
             if p < chars.len() {
-                value = value * 10 + read_digit(chars[p])? * sign;
+                value = push_digit(value, read_digit(chars[p])? * sign)?;
                 p += 1;
             } else {
-                value *= 10;
+                value = push_digit(value, 0)?;
             }
         }
 
@@ -283,7 +525,15 @@ impl FromStr for Decimal {
     }
 }
 
-impl TryFrom<&[u8]> for Decimal {
+impl<const D: u8> FromStr for FixedPoint<D> {
+    type Err = ParseDecimalError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::try_from_str(s)
+    }
+}
+
+impl<const D: u8> TryFrom<&[u8]> for FixedPoint<D> {
     type Error = ParseDecimalError;
 
     fn try_from(slice: &[u8]) -> Result<Self, Self::Error> {
@@ -295,77 +545,129 @@ impl TryFrom<&[u8]> for Decimal {
     }
 }
 
-impl fmt::Debug for Decimal {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        let mut a = self.0;
-        let mut buf = String::new();
+fn write_fixed_point(f: &mut fmt::Formatter<'_>, value: i128, decimals: u8) -> fmt::Result {
+    let mut a = value;
+    let mut buf = String::new();
 
-        let mut trailing_zeros = true;
-        for _ in 0..18 {
-            let m = a % 10;
-            if m != 0 || !trailing_zeros {
-                trailing_zeros = false;
-                buf.push(char::from_digit(m.abs() as u32, 10).unwrap())
-            }
-            a /= 10;
+    let mut trailing_zeros = true;
+    for _ in 0..decimals {
+        let m = a % 10;
+        if m != 0 || !trailing_zeros {
+            trailing_zeros = false;
+            buf.push(char::from_digit(m.abs() as u32, 10).unwrap())
         }
+        a /= 10;
+    }
 
-        if !buf.is_empty() {
-            buf.push('.');
-        }
+    if !buf.is_empty() {
+        buf.push('.');
+    }
 
-        if a == 0 {
-            buf.push('0')
-        } else {
-            while a != 0 {
-                let m = a % 10;
-                buf.push(char::from_digit(m.abs() as u32, 10).unwrap());
-                a /= 10
-            }
+    if a == 0 {
+        buf.push('0')
+    } else {
+        while a != 0 {
+            let m = a % 10;
+            buf.push(char::from_digit(m.abs() as u32, 10).unwrap());
+            a /= 10
         }
+    }
+
+    write!(
+        f,
+        "{}{}",
+        if value < 0 { "-" } else { "" },
+        buf.chars().rev().collect::<String>()
+    )
+}
 
-        write!(
-            f,
-            "{}{}",
-            if self.is_negative() { "-" } else { "" },
-            buf.chars().rev().collect::<String>()
-        )
+impl<const D: u8> fmt::Debug for FixedPoint<D> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write_fixed_point(f, self.0, D)
     }
 }
 
-impl fmt::Display for Decimal {
+impl<const D: u8> fmt::Display for FixedPoint<D> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(f, "{:?}", self)
     }
 }
 
-impl TypeId for Decimal {
+//======
+// SBOR
+//======
+
+/// Parses the SBOR payload of a `FixedPoint`, i.e. the number of decimal places followed by
+/// the little-endian raw value, and formats it as a decimal string.
+pub fn format_fixed_point(bytes: &[u8]) -> Result<String, ParseDecimalError> {
+    struct Raw(i128, u8);
+
+    impl fmt::Display for Raw {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            write_fixed_point(f, self.0, self.1)
+        }
+    }
+
+    match bytes.split_first() {
+        Some((&decimals, raw)) if decimals <= MAX_DECIMALS && raw.len() == 16 => {
+            Ok(Raw(i128::from_le_bytes(copy_u8_array(raw)), decimals).to_string())
+        }
+        Some(_) => Err(ParseDecimalError::UnsupportedDecimalPlace),
+        None => Err(ParseDecimalError::InvalidLength),
+    }
+}
+
+impl<const D: u8> FixedPoint<D> {
+    /// Whether this is `Decimal`, which keeps its own SBOR type without a decimals byte.
+    const IS_DECIMAL: bool = Self::PRECISION == PRECISION;
+}
+
+impl<const D: u8> TypeId for FixedPoint<D> {
     #[inline]
     fn type_id() -> u8 {
-        SCRYPTO_TYPE_DECIMAL
+        if Self::IS_DECIMAL {
+            SCRYPTO_TYPE_DECIMAL
+        } else {
+            SCRYPTO_TYPE_FIXED_POINT
+        }
     }
 }
 
-impl Encode for Decimal {
+impl<const D: u8> Encode for FixedPoint<D> {
     fn encode_value(&self, encoder: &mut Encoder) {
-        let bytes = self.to_vec();
+        let mut bytes = if Self::IS_DECIMAL { vec![] } else { vec![D] };
+        bytes.extend(self.to_vec());
         encoder.write_len(bytes.len());
         encoder.write_slice(&bytes);
     }
 }
 
-impl Decode for Decimal {
+impl<const D: u8> Decode for FixedPoint<D> {
     fn decode_value(decoder: &mut Decoder) -> Result<Self, DecodeError> {
         let len = decoder.read_len()?;
         let slice = decoder.read_bytes(len)?;
-        Self::try_from(slice).map_err(|_| DecodeError::InvalidCustomData(SCRYPTO_TYPE_DECIMAL))
+        let raw = if Self::IS_DECIMAL {
+            Some(slice)
+        } else {
+            match slice.split_first() {
+                Some((&decimals, raw)) if decimals == D => Some(raw),
+                _ => None,
+            }
+        };
+        raw.and_then(|raw| Self::try_from(raw).ok())
+            .ok_or_else(|| DecodeError::InvalidCustomData(Self::type_id()))
     }
 }
 
-impl Describe for Decimal {
+impl<const D: u8> Describe for FixedPoint<D> {
     fn describe() -> Type {
+        let name = if Self::IS_DECIMAL {
+            SCRYPTO_NAME_DECIMAL
+        } else {
+            SCRYPTO_NAME_FIXED_POINT
+        };
         Type::Custom {
-            name: SCRYPTO_NAME_DECIMAL.to_owned(),
+            name: name.to_owned(),
             generics: vec![],
         }
     }
@@ -374,19 +676,140 @@ impl Describe for Decimal {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::rust::string::ToString;
+
+    #[test]
+    fn test_round_to_granularity() {
+        let a = Decimal::from_str("100.555").unwrap();
+        let g = Decimal::from_str("0.01").unwrap();
+        let round = |a: Decimal, mode| a.round_to_granularity(g, mode).to_string();
+        assert_eq!(round(a, RoundingMode::TowardsZero), "100.55");
+        assert_eq!(round(a, RoundingMode::AwayFromZero), "100.56");
+        assert_eq!(round(a, RoundingMode::TowardsNegativeInfinity), "100.55");
+        assert_eq!(round(a, RoundingMode::TowardsPositiveInfinity), "100.56");
+        assert_eq!(round(a, RoundingMode::HalfAwayFromZero), "100.56");
+        assert_eq!(round(a, RoundingMode::HalfEven), "100.56");
+        assert_eq!(round(-a, RoundingMode::TowardsNegativeInfinity), "-100.56");
+        assert_eq!(round(-a, RoundingMode::TowardsPositiveInfinity), "-100.55");
+        assert_eq!(round(-a, RoundingMode::HalfAwayFromZero), "-100.56");
+
+        let b = Decimal::from_str("100.545").unwrap();
+        assert_eq!(round(b, RoundingMode::HalfEven), "100.54");
+        let c = Decimal::from_str("100.5549").unwrap();
+        assert_eq!(round(c, RoundingMode::HalfAwayFromZero), "100.55");
+        let d = Decimal::from_str("100.55").unwrap();
+        assert_eq!(round(d, RoundingMode::AwayFromZero), "100.55");
+    }
+
+    #[test]
+    fn test_mul_with_rounding() {
+        let a = Decimal::from_str("0.000000000000000005").unwrap();
+        let b = Decimal::from_str("0.5").unwrap();
+        let mul = |a: Decimal, mode| a.mul_with_rounding(b, mode).to_string();
+        assert_eq!(a * b, a.mul_with_rounding(b, RoundingMode::TowardsZero));
+        assert_eq!(mul(a, RoundingMode::TowardsZero), "0.000000000000000002");
+        assert_eq!(
+            mul(a, RoundingMode::HalfAwayFromZero),
+            "0.000000000000000003"
+        );
+        assert_eq!(mul(a, RoundingMode::HalfEven), "0.000000000000000002");
+        assert_eq!(
+            mul(-a, RoundingMode::TowardsNegativeInfinity),
+            "-0.000000000000000003"
+        );
+        assert_eq!(
+            mul(-a, RoundingMode::TowardsPositiveInfinity),
+            "-0.000000000000000002"
+        );
+
+        let c = Decimal::from_str("0.020000000000000001").unwrap();
+        let d = Decimal::from_str("1.499999999999999999").unwrap();
+        assert_eq!(c * d, Decimal::from_str("0.030000000000000001").unwrap());
+        assert_eq!(
+            c.mul_with_rounding(d, RoundingMode::HalfAwayFromZero),
+            Decimal::from_str("0.030000000000000001").unwrap()
+        );
+        assert_eq!(
+            c.mul_with_rounding(d, RoundingMode::AwayFromZero),
+            Decimal::from_str("0.030000000000000002").unwrap()
+        );
+    }
+
+    #[test]
+    fn test_to_integer() {
+        assert_eq!(Decimal::from(42).to_u128(), Some(42));
+        assert_eq!(Decimal::from(42).to_u64(), Some(42));
+        assert_eq!(Decimal::zero().to_u128(), Some(0));
+        assert_eq!(Decimal::from_str("1.5").unwrap().to_u128(), None);
+        assert_eq!(Decimal::from(-1).to_u128(), None);
+        assert_eq!(Decimal::MAX.to_u128(), None);
+        assert_eq!(Decimal::from(u64::MAX).to_u64(), Some(u64::MAX));
+        assert_eq!((Decimal::from(u64::MAX) + 1).to_u64(), None);
+        assert_eq!(
+            (Decimal::from(u64::MAX) + 1).to_u128(),
+            Some(u64::MAX as u128 + 1)
+        );
+
+        assert_eq!(
+            Decimal::from_str("1.5").unwrap().try_floor_to_u128(),
+            Some(1)
+        );
+        assert_eq!(
+            Decimal::from_str("0.1").unwrap().try_floor_to_u128(),
+            Some(0)
+        );
+        assert_eq!(Decimal::from(7).try_floor_to_u128(), Some(7));
+        assert_eq!(Decimal::from_str("-0.1").unwrap().try_floor_to_u128(), None);
+        assert_eq!(
+            Decimal::MAX.try_floor_to_u128(),
+            Some((i128::MAX / Decimal::PRECISION) as u128)
+        );
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_round_to_zero_granularity() {
+        Decimal::one().round_to_granularity(Decimal::zero(), RoundingMode::TowardsZero);
+    }
+
+    #[test]
+    fn test_try_from_str_errors() {
+        assert_eq!(
+            Decimal::try_from_str("1.5").unwrap(),
+            Decimal::from_str("1.5").unwrap()
+        );
+        let message = |s: &str| Decimal::try_from_str(s).unwrap_err().to_string();
+        assert_eq!(message(""), "Invalid decimal: \"\"");
+        assert_eq!(message("-"), "Invalid decimal: \"-\"");
+        assert_eq!(message("1x"), "Invalid character in decimal: 'x'");
+        assert_eq!(message("0.0000000000000000001"), "Too many decimal places");
+        assert_eq!(message("1000000000000000000000"), "Decimal out of range");
+        assert_eq!(message("-1000000000000000000000"), "Decimal out of range");
+        assert_eq!(
+            message("170141183460469231731.687303715884105728"),
+            "Decimal out of range"
+        );
+    }
 
     #[test]
     fn test_format() {
-        assert_eq!(Decimal(1i128.into()).to_string(), "0.000000000000000001");
         assert_eq!(
-            Decimal(123456789123456789i128.into()).to_string(),
+            Decimal::from_smallest_units(1).to_string(),
+            "0.000000000000000001"
+        );
+        assert_eq!(
+            Decimal::from_smallest_units(123456789123456789).to_string(),
             "0.123456789123456789"
         );
-        assert_eq!(Decimal(1000000000000000000i128.into()).to_string(), "1");
-        assert_eq!(Decimal(123000000000000000000i128.into()).to_string(), "123");
         assert_eq!(
-            Decimal(123456789123456789000000000000000000i128.into()).to_string(),
+            Decimal::from_smallest_units(1000000000000000000).to_string(),
+            "1"
+        );
+        assert_eq!(
+            Decimal::from_smallest_units(123000000000000000000).to_string(),
+            "123"
+        );
+        assert_eq!(
+            Decimal::from_smallest_units(123456789123456789000000000000000000).to_string(),
             "123456789123456789"
         );
         assert_eq!(
@@ -403,19 +826,19 @@ mod tests {
     fn test_parse() {
         assert_eq!(
             Decimal::from_str("0.000000000000000001").unwrap(),
-            Decimal(1i128.into()),
+            Decimal::from_smallest_units(1),
         );
         assert_eq!(
             Decimal::from_str("0.123456789123456789").unwrap(),
-            Decimal(123456789123456789i128.into()),
+            Decimal::from_smallest_units(123456789123456789),
         );
         assert_eq!(
             Decimal::from_str("1").unwrap(),
-            Decimal(1000000000000000000i128.into()),
+            Decimal::from_smallest_units(1000000000000000000),
         );
         assert_eq!(
             Decimal::from_str("123456789123456789").unwrap(),
-            Decimal(123456789123456789000000000000000000i128.into()),
+            Decimal::from_smallest_units(123456789123456789000000000000000000),
         );
         assert_eq!(
             Decimal::from_str("170141183460469231731.687303715884105727").unwrap(),
@@ -475,9 +898,210 @@ mod tests {
         assert_eq!((a / b).to_string(), "-21");
     }
 
+    #[test]
+    fn test_from_fraction() {
+        assert_eq!(
+            Decimal::from_fraction(1, 3).unwrap().to_string(),
+            "0.333333333333333333"
+        );
+        assert_eq!(Decimal::from_fraction(7, 8).unwrap().to_string(), "0.875");
+        assert_eq!(Decimal::from_fraction(-7, 8).unwrap().to_string(), "-0.875");
+        assert_eq!(Decimal::from_fraction(1, 0), None);
+    }
+
+    #[test]
+    fn test_checked_div() {
+        let a: Decimal = "1.5".parse().unwrap();
+        assert_eq!(a.checked_div(3).unwrap().to_string(), "0.5");
+        assert_eq!(a.checked_div(Decimal::zero()), None);
+        assert_eq!(
+            Decimal::MAX.checked_div("0.5".parse::<Decimal>().unwrap()),
+            None
+        );
+    }
+
+    #[test]
+    fn test_sign() {
+        let negative: Decimal = "-1.5".parse().unwrap();
+        let positive: Decimal = "2.5".parse().unwrap();
+        let zero = Decimal::zero();
+
+        assert_eq!(negative.abs(), "1.5".parse().unwrap());
+        assert_eq!(positive.abs(), positive);
+        assert_eq!(zero.abs(), zero);
+
+        assert_eq!(negative.signum(), -1);
+        assert_eq!(positive.signum(), 1);
+        assert_eq!(zero.signum(), 0);
+
+        assert!(negative.is_negative() && !negative.is_positive() && !negative.is_zero());
+        assert!(positive.is_positive() && !positive.is_negative() && !positive.is_zero());
+        assert!(zero.is_zero() && !zero.is_positive() && !zero.is_negative());
+    }
+
+    #[test]
+    fn test_min_max() {
+        let a: Decimal = "-1.5".parse().unwrap();
+        let b: Decimal = "2.5".parse().unwrap();
+        assert_eq!(Decimal::min(a, b), a);
+        assert_eq!(Decimal::min(b, a), a);
+        assert_eq!(Decimal::max(a, b), b);
+        assert_eq!(Decimal::max(b, a), b);
+        assert_eq!(Decimal::max(a, a), a);
+    }
+
+    #[test]
+    fn test_fixed_point_6() {
+        type Micro = FixedPoint<6>;
+
+        let a: Micro = "1.5".parse().unwrap();
+        let b = Micro::from(2);
+        assert_eq!(a.0, 1_500_000);
+        assert_eq!((a + b).to_string(), "3.5");
+        assert_eq!((a - b).to_string(), "-0.5");
+        assert_eq!((a * b).to_string(), "3");
+        assert_eq!((b / 3).to_string(), "0.666666");
+        assert_eq!(Micro::from_fraction(1, 3).unwrap().to_string(), "0.333333");
+        assert!(matches!(
+            Micro::from_str("0.0000001"),
+            Err(ParseDecimalError::UnsupportedDecimalPlace)
+        ));
+    }
+
+    #[test]
+    fn test_rescale() {
+        type Micro = FixedPoint<6>;
+
+        let a: Micro = "1.5".parse().unwrap();
+        let d: Decimal = a.rescale();
+        assert_eq!(d, "1.5".parse().unwrap());
+        assert_eq!(d.rescale::<6>(), a);
+
+        let round_down: Decimal = "0.1234564999".parse().unwrap();
+        let round_up: Decimal = "0.1234565".parse().unwrap();
+        assert_eq!(round_down.rescale::<6>().to_string(), "0.123456");
+        assert_eq!(round_up.rescale::<6>().to_string(), "0.123457");
+        assert_eq!((-round_up).rescale::<6>().to_string(), "-0.123457");
+        assert_eq!((-round_down).rescale::<6>().to_string(), "-0.123456");
+    }
+
     #[test]
     fn test_one_and_zero() {
         assert_eq!(Decimal::one().to_string(), "1");
         assert_eq!(Decimal::zero().to_string(), "0");
     }
+
+    #[test]
+    fn test_smallest_units() {
+        let one = Decimal::from_smallest_units(1_000_000_000_000_000_000);
+        assert_eq!(one, Decimal::one());
+        assert_eq!(one.to_smallest_units(), 1_000_000_000_000_000_000);
+        assert_eq!(
+            Decimal::from_smallest_units(1).to_string(),
+            "0.000000000000000001"
+        );
+        assert_eq!(
+            Decimal::from_str("-2.5").unwrap().to_smallest_units(),
+            -2_500_000_000_000_000_000
+        );
+        assert_eq!(
+            FixedPoint::<6>::from_smallest_units(1_500_000).to_string(),
+            "1.5"
+        );
+    }
+
+    #[test]
+    fn test_fixed_point_sbor() {
+        type Micro = FixedPoint<6>;
+
+        let a: Micro = "-1.5".parse().unwrap();
+        let bytes = scrypto_encode(&a);
+        assert_eq!(bytes[0], SCRYPTO_TYPE_FIXED_POINT);
+        assert_eq!(scrypto_decode::<Micro>(&bytes).unwrap(), a);
+        assert!(scrypto_decode::<FixedPoint<8>>(&bytes).is_err());
+        assert_eq!(format_fixed_point(&bytes[5..]).unwrap(), "-1.5");
+
+        let d: Decimal = "-1.5".parse().unwrap();
+        let bytes = scrypto_encode(&d);
+        assert_eq!(bytes[0], SCRYPTO_TYPE_DECIMAL);
+        assert_eq!(&bytes[5..], &d.to_vec()[..]);
+        assert_eq!(scrypto_decode::<Decimal>(&bytes).unwrap(), d);
+    }
+}
+
+#[cfg(test)]
+mod property_tests {
+    use super::*;
+
+    /// A xorshift generator, so that runs are reproducible.
+    struct Rng(u64);
+
+    impl Rng {
+        fn next(&mut self) -> u64 {
+            self.0 ^= self.0 << 13;
+            self.0 ^= self.0 >> 7;
+            self.0 ^= self.0 << 17;
+            self.0
+        }
+
+        /// Returns a decimal whose magnitude is less than `bound` units of 10^-18.
+        fn decimal(&mut self, bound: i128) -> Decimal {
+            let raw = ((self.next() as i128) << 64 | self.next() as i128) % bound;
+            if self.next() & 1 == 0 {
+                Decimal::from_smallest_units(raw)
+            } else {
+                Decimal::from_smallest_units(-raw)
+            }
+        }
+    }
+
+    const ROUNDS: usize = 10_000;
+
+    #[test]
+    fn test_raw_roundtrip() {
+        let mut rng = Rng(0x2545f4914f6cdd1d);
+        for _ in 0..ROUNDS {
+            let a = rng.decimal(i128::MAX);
+            assert_eq!(Decimal::from_raw(a.to_raw()), a);
+            assert_eq!(a.to_raw().to_vec(), a.to_vec());
+        }
+    }
+
+    #[test]
+    fn test_add_sub_inverse() {
+        let mut rng = Rng(0x9e3779b97f4a7c15);
+        for _ in 0..ROUNDS {
+            let a = rng.decimal(i128::MAX / 2);
+            let b = rng.decimal(i128::MAX / 2);
+            assert_eq!(a + b - b, a);
+            assert_eq!(a - a, Decimal::zero());
+            assert_eq!(a + b, b + a);
+        }
+    }
+
+    #[test]
+    fn test_mul_identity() {
+        let mut rng = Rng(0xd1b54a32d192ed03);
+        for _ in 0..ROUNDS {
+            let a = rng.decimal(i128::MAX);
+            assert_eq!(a * 1, a);
+            assert_eq!(a / 1, a);
+        }
+    }
+
+    #[test]
+    fn test_mul_associative_within_precision() {
+        let mut rng = Rng(0xbf58476d1ce4e5b9);
+        // Operands below 10^6 keep every intermediate product in range, and bound the
+        // truncation error of either grouping to (10^6 + 1) units of 10^-18.
+        let bound = 1_000_000 * Decimal::PRECISION;
+        let tolerance = Decimal::from_smallest_units(2 * (1_000_000 + 1));
+        for _ in 0..ROUNDS {
+            let a = rng.decimal(bound);
+            let b = rng.decimal(bound);
+            let c = rng.decimal(bound);
+            assert!((a * b * c - a * (b * c)).abs() <= tolerance);
+            assert_eq!(a * b, b * a);
+        }
+    }
 }
@@ -15,10 +15,24 @@ pub struct H256(pub [u8; 32]);
 /// Represents an error when parsing H256.
 #[derive(Debug, Clone)]
 pub enum ParseH256Error {
-    InvalidHex(hex::FromHexError),
+    /// The string is not hex, after the optional `0x` prefix.
+    BadHex(hex::FromHexError),
+    /// The string doesn't have 64 hex digits, after the optional `0x` prefix.
+    BadLength(usize),
+    /// The byte slice isn't 32 bytes long.
     InvalidLength(usize),
 }
 
+impl fmt::Display for ParseH256Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::BadHex(e) => write!(f, "Invalid hash hex: {}", e),
+            Self::BadLength(len) => write!(f, "Invalid hash hex length: {} digits", len),
+            Self::InvalidLength(len) => write!(f, "Invalid hash length: {} bytes", len),
+        }
+    }
+}
+
 impl H256 {
     /// Returns the lower 26 bytes.
     pub fn lower_26_bytes(&self) -> [u8; 26] {
@@ -32,12 +46,24 @@ impl H256 {
     }
 }
 
+impl H256 {
+    /// Parses a hash from its hex representation, with an optional `0x` prefix.
+    pub fn try_from_str(s: &str) -> Result<Self, ParseH256Error> {
+        // The `0x` prefix is optional
+        let s = s.strip_prefix("0x").unwrap_or(s);
+        if s.len() != 64 {
+            return Err(ParseH256Error::BadLength(s.len()));
+        }
+        let bytes = hex::decode(s).map_err(ParseH256Error::BadHex)?;
+        Self::try_from(bytes.as_slice())
+    }
+}
+
 impl FromStr for H256 {
     type Err = ParseH256Error;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let bytes = hex::decode(s).map_err(ParseH256Error::InvalidHex)?;
-        Self::try_from(bytes.as_slice())
+        Self::try_from_str(s)
     }
 }
 
@@ -114,10 +140,42 @@ mod tests {
     use super::*;
     use crate::rust::string::ToString;
 
+    #[test]
+    fn test_try_from_str_errors() {
+        let message = |s: &str| H256::try_from_str(s).unwrap_err().to_string();
+        let s = "z177968c9c68877dc8d33e25759183c556379daa45a4d78a2b91c70133c873ca";
+        assert!(message(s).starts_with("Invalid hash hex: "));
+        assert_eq!(message("0x0102"), "Invalid hash hex length: 4 digits");
+        assert_eq!(
+            H256::try_from([1u8, 2].as_ref()).unwrap_err().to_string(),
+            "Invalid hash length: 2 bytes"
+        );
+    }
+
     #[test]
     fn test_from_to_string() {
         let s = "b177968c9c68877dc8d33e25759183c556379daa45a4d78a2b91c70133c873ca";
         let h = H256::from_str(s).unwrap();
         assert_eq!(h.to_string(), s);
     }
+
+    #[test]
+    fn test_from_str_encodings() {
+        let s = "b177968c9c68877dc8d33e25759183c556379daa45a4d78a2b91c70133c873ca";
+        let h = H256::from_str(s).unwrap();
+        assert_eq!(H256::from_str(&("0x".to_owned() + s)).unwrap(), h);
+
+        assert!(matches!(
+            H256::from_str("b177968c"),
+            Err(ParseH256Error::BadLength(8))
+        ));
+        assert!(matches!(
+            H256::from_str("0xb177968c"),
+            Err(ParseH256Error::BadLength(8))
+        ));
+        assert!(matches!(
+            H256::from_str("x177968c9c68877dc8d33e25759183c556379daa45a4d78a2b91c70133c873ca"),
+            Err(ParseH256Error::BadHex(_))
+        ));
+    }
 }
@@ -0,0 +1,302 @@
+use num_bigint::{BigInt, BigUint, Sign};
+use num_traits::Num;
+use sbor::{describe::Type, *};
+
+use crate::buffer::*;
+use crate::rust::borrow::ToOwned;
+use crate::rust::convert::TryFrom;
+use crate::rust::fmt;
+use crate::rust::str::FromStr;
+use crate::rust::string::String;
+use crate::rust::vec;
+use crate::rust::vec::Vec;
+use crate::types::copy_u8_array;
+
+/// Represents an **unsigned** 256-bit integer, stored in little-endian byte order.
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+pub struct U256(pub [u8; 32]);
+
+/// Represents a **signed** 256-bit integer, stored in little-endian two's complement.
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+pub struct I256(pub [u8; 32]);
+
+/// Represents an error when parsing U256 or I256.
+#[derive(Debug, Clone)]
+pub enum ParseInt256Error {
+    InvalidDigits(String),
+    Overflow,
+    InvalidLength(usize),
+}
+
+impl fmt::Display for ParseInt256Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::InvalidDigits(s) => write!(f, "Invalid integer: {:?}", s),
+            Self::Overflow => write!(f, "Integer does not fit in 256 bits"),
+            Self::InvalidLength(len) => write!(f, "Invalid integer length: {} bytes", len),
+        }
+    }
+}
+
+/// Parses the magnitude of an integer, in decimal or `0x`-prefixed hex.
+fn parse_magnitude(s: &str) -> Result<BigUint, ParseInt256Error> {
+    let (digits, radix) = match s.strip_prefix("0x") {
+        Some(hex) => (hex, 16),
+        None => (s, 10),
+    };
+    if digits.is_empty() || digits.starts_with('+') {
+        return Err(ParseInt256Error::InvalidDigits(s.to_owned()));
+    }
+    BigUint::from_str_radix(digits, radix)
+        .map_err(|_| ParseInt256Error::InvalidDigits(s.to_owned()))
+}
+
+impl U256 {
+    /// Parses an integer in decimal, or in hex with the `0x` prefix.
+    pub fn try_from_str(s: &str) -> Result<Self, ParseInt256Error> {
+        Self::try_from(parse_magnitude(s)?)
+    }
+
+    /// Converts into a big integer.
+    pub fn to_big_uint(&self) -> BigUint {
+        BigUint::from_bytes_le(&self.0)
+    }
+
+    pub fn to_vec(&self) -> Vec<u8> {
+        self.0.to_vec()
+    }
+}
+
+impl I256 {
+    /// Parses an integer in decimal, or in hex with the `0x` prefix, after an optional `-`.
+    pub fn try_from_str(s: &str) -> Result<Self, ParseInt256Error> {
+        let value = match s.strip_prefix('-') {
+            Some(magnitude) => -BigInt::from(parse_magnitude(magnitude)?),
+            None => BigInt::from(parse_magnitude(s)?),
+        };
+        Self::try_from(value)
+    }
+
+    /// Converts into a big integer.
+    pub fn to_big_int(&self) -> BigInt {
+        BigInt::from_signed_bytes_le(&self.0)
+    }
+
+    pub fn to_vec(&self) -> Vec<u8> {
+        self.0.to_vec()
+    }
+}
+
+impl From<u128> for U256 {
+    fn from(val: u128) -> Self {
+        let mut bytes = [0u8; 32];
+        bytes[..16].copy_from_slice(&val.to_le_bytes());
+        Self(bytes)
+    }
+}
+
+impl From<i128> for I256 {
+    fn from(val: i128) -> Self {
+        let mut bytes = if val < 0 { [0xffu8; 32] } else { [0u8; 32] };
+        bytes[..16].copy_from_slice(&val.to_le_bytes());
+        Self(bytes)
+    }
+}
+
+impl TryFrom<BigUint> for U256 {
+    type Error = ParseInt256Error;
+
+    fn try_from(value: BigUint) -> Result<Self, Self::Error> {
+        let le = value.to_bytes_le();
+        if le.len() > 32 {
+            return Err(ParseInt256Error::Overflow);
+        }
+        let mut bytes = [0u8; 32];
+        bytes[..le.len()].copy_from_slice(&le);
+        Ok(Self(bytes))
+    }
+}
+
+impl TryFrom<BigInt> for I256 {
+    type Error = ParseInt256Error;
+
+    fn try_from(value: BigInt) -> Result<Self, Self::Error> {
+        let le = value.to_signed_bytes_le();
+        if le.len() > 32 {
+            return Err(ParseInt256Error::Overflow);
+        }
+        let mut bytes = if value.sign() == Sign::Minus {
+            [0xffu8; 32]
+        } else {
+            [0u8; 32]
+        };
+        bytes[..le.len()].copy_from_slice(&le);
+        Ok(Self(bytes))
+    }
+}
+
+impl FromStr for U256 {
+    type Err = ParseInt256Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::try_from_str(s)
+    }
+}
+
+impl FromStr for I256 {
+    type Err = ParseInt256Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::try_from_str(s)
+    }
+}
+
+impl TryFrom<&[u8]> for U256 {
+    type Error = ParseInt256Error;
+
+    fn try_from(slice: &[u8]) -> Result<Self, Self::Error> {
+        if slice.len() != 32 {
+            Err(ParseInt256Error::InvalidLength(slice.len()))
+        } else {
+            Ok(Self(copy_u8_array(slice)))
+        }
+    }
+}
+
+impl TryFrom<&[u8]> for I256 {
+    type Error = ParseInt256Error;
+
+    fn try_from(slice: &[u8]) -> Result<Self, Self::Error> {
+        if slice.len() != 32 {
+            Err(ParseInt256Error::InvalidLength(slice.len()))
+        } else {
+            Ok(Self(copy_u8_array(slice)))
+        }
+    }
+}
+
+impl fmt::Debug for U256 {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.to_big_uint().to_str_radix(10))
+    }
+}
+
+impl fmt::Display for U256 {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:?}", self)
+    }
+}
+
+impl fmt::Debug for I256 {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.to_big_int().to_str_radix(10))
+    }
+}
+
+impl fmt::Display for I256 {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:?}", self)
+    }
+}
+
+//========
+// SBOR
+//========
+
+macro_rules! sbor_int256 {
+    ($type:ident, $type_id:ident, $name:ident) => {
+        impl TypeId for $type {
+            #[inline]
+            fn type_id() -> u8 {
+                $type_id
+            }
+        }
+
+        impl Encode for $type {
+            fn encode_value(&self, encoder: &mut Encoder) {
+                encoder.write_len(self.0.len());
+                encoder.write_slice(&self.0);
+            }
+        }
+
+        impl Decode for $type {
+            fn decode_value(decoder: &mut Decoder) -> Result<Self, DecodeError> {
+                let len = decoder.read_len()?;
+                let slice = decoder.read_bytes(len)?;
+                Self::try_from(slice).map_err(|_| DecodeError::InvalidCustomData($type_id))
+            }
+        }
+
+        impl Describe for $type {
+            fn describe() -> Type {
+                Type::Custom {
+                    name: $name.to_owned(),
+                    generics: vec![],
+                }
+            }
+        }
+    };
+}
+
+sbor_int256!(U256, SCRYPTO_TYPE_U256, SCRYPTO_NAME_U256);
+sbor_int256!(I256, SCRYPTO_TYPE_I256, SCRYPTO_NAME_I256);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rust::format;
+    use crate::rust::string::ToString;
+
+    #[test]
+    fn test_u256_from_str() {
+        let max = "115792089237316195423570985008687907853269984665640564039457584007913129639935";
+        assert_eq!(max.len(), 78);
+        assert_eq!(U256::from_str(max).unwrap(), U256([0xffu8; 32]));
+        assert_eq!(U256::from_str(max).unwrap().to_string(), max);
+        assert_eq!(U256::from_str("0x0100").unwrap(), U256::from(256u128));
+        assert_eq!(
+            U256::from_str(&format!("0x{}", "ff".repeat(32))).unwrap(),
+            U256([0xffu8; 32])
+        );
+        assert!(matches!(
+            U256::from_str(
+                "115792089237316195423570985008687907853269984665640564039457584007913129639936"
+            ),
+            Err(ParseInt256Error::Overflow)
+        ));
+        assert!(matches!(
+            U256::from_str("-1"),
+            Err(ParseInt256Error::InvalidDigits(_))
+        ));
+    }
+
+    #[test]
+    fn test_i256_from_str() {
+        assert_eq!(I256::from_str("-1").unwrap(), I256([0xffu8; 32]));
+        assert_eq!(I256::from_str("-1").unwrap().to_string(), "-1");
+        assert_eq!(I256::from_str("-0x10").unwrap(), I256::from(-16i128));
+        assert_eq!(I256::from_str("0").unwrap().to_string(), "0");
+        let min = "-57896044618658097711785492504343953926634992332820282019728792003956564819968";
+        assert_eq!(I256::from_str(min).unwrap().to_string(), min);
+        assert!(matches!(
+            I256::from_str(
+                "57896044618658097711785492504343953926634992332820282019728792003956564819968"
+            ),
+            Err(ParseInt256Error::Overflow)
+        ));
+    }
+
+    #[test]
+    fn test_sbor() {
+        let value = U256::from(12345u128);
+        let bytes = scrypto_encode(&value);
+        assert_eq!(bytes[0], SCRYPTO_TYPE_U256);
+        assert_eq!(scrypto_decode::<U256>(&bytes).unwrap(), value);
+
+        let value = I256::from(-12345i128);
+        assert_eq!(
+            scrypto_decode::<I256>(&scrypto_encode(&value)).unwrap(),
+            value
+        );
+    }
+}
@@ -3,6 +3,7 @@ mod bid;
 mod big_decimal;
 mod decimal;
 mod h256;
+mod int256;
 mod mid;
 mod rid;
 mod vid;
@@ -12,8 +13,11 @@ pub use address::{
 };
 pub use bid::{Bid, ParseBidError};
 pub use big_decimal::{BigDecimal, ParseBigDecimalError};
-pub use decimal::{Decimal, ParseDecimalError};
+pub use decimal::{
+    format_fixed_point, Decimal, FixedPoint, ParseDecimalError, RoundingMode, MAX_DECIMALS,
+};
 pub use h256::{ParseH256Error, H256};
+pub use int256::{ParseInt256Error, I256, U256};
 pub use mid::{Mid, ParseMidError};
 pub use rid::{ParseRidError, Rid};
 pub use vid::{ParseVidError, Vid};
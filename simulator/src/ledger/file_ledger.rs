@@ -71,6 +71,47 @@ impl FileBasedLedger {
         results
     }
 
+    fn list_substates(&self) -> Vec<(SubstateId, Vec<u8>)> {
+        let mut results = Vec::new();
+        for kind in [PACKAGES, COMPONENTS, LAZY_MAPS, RESOURCE_DEFS, VAULTS, NFTS] {
+            let mut path = self.root.clone();
+            path.push(kind);
+            for entry in fs::read_dir(path).unwrap() {
+                let path = entry.unwrap().path();
+                if path.is_file() {
+                    let name = path.file_stem().unwrap().to_str().unwrap();
+                    let id = Self::substate_id(kind, name)
+                        .unwrap_or_else(|| panic!("Invalid substate file: {:?}", path));
+                    results.push((id, Self::read(&path).unwrap()));
+                }
+            }
+        }
+        results
+    }
+
+    /// Parses the id of a substate from the kind and name of its file.
+    fn substate_id(kind: &str, name: &str) -> Option<SubstateId> {
+        let id = match kind {
+            PACKAGES => SubstateId::Package(Address::from_str(name).ok()?),
+            COMPONENTS => SubstateId::Component(Address::from_str(name).ok()?),
+            RESOURCE_DEFS => SubstateId::ResourceDef(Address::from_str(name).ok()?),
+            LAZY_MAPS => {
+                let (hash, index) = name.rsplit_once('_')?;
+                SubstateId::LazyMap(Mid(H256::from_str(hash).ok()?, index.parse().ok()?))
+            }
+            VAULTS => {
+                let (hash, index) = name.rsplit_once('_')?;
+                SubstateId::Vault(Vid(H256::from_str(hash).ok()?, index.parse().ok()?))
+            }
+            NFTS => {
+                let (address, id) = name.rsplit_once('_')?;
+                SubstateId::Nft(Address::from_str(address).ok()?, id.parse().ok()?)
+            }
+            _ => return None,
+        };
+        Some(id)
+    }
+
     fn get_path<T: AsRef<str>>(&self, kind: &str, name: T, ext: &str) -> PathBuf {
         let mut path = self.root.clone();
         path.push(kind);
@@ -98,6 +139,14 @@ impl FileBasedLedger {
         }
     }
 
+    fn remove<P: AsRef<Path>>(path: P) {
+        let p = path.as_ref();
+
+        if p.exists() {
+            fs::remove_file(p).unwrap_or_else(|_| panic!("Failed to remove file: {:?}", p));
+        }
+    }
+
     pub fn encode<T: sbor::Encode>(v: &T) -> Vec<u8> {
         sbor::encode_with_type(Vec::with_capacity(512), v)
     }
@@ -131,7 +180,8 @@ impl Ledger for FileBasedLedger {
     }
 
     fn get_component(&self, address: Address) -> Option<Component> {
-        Self::read(self.get_path(COMPONENTS, address.to_string(), FILE_EXT)).map(Self::decode)
+        Self::read(self.get_path(COMPONENTS, address.to_string(), FILE_EXT))
+            .map(|bytes| Component::decode_stored(&bytes).unwrap())
     }
 
     fn put_component(&mut self, address: Address, component: Component) {
@@ -141,6 +191,10 @@ impl Ledger for FileBasedLedger {
         )
     }
 
+    fn delete_component(&mut self, address: Address) {
+        Self::remove(self.get_path(COMPONENTS, address.to_string(), FILE_EXT))
+    }
+
     fn get_lazy_map(&self, mid: Mid) -> Option<LazyMap> {
         Self::read(self.get_path(LAZY_MAPS, format!("{}_{}", mid.0, mid.1), FILE_EXT))
             .map(Self::decode)
@@ -153,6 +207,10 @@ impl Ledger for FileBasedLedger {
         )
     }
 
+    fn delete_lazy_map(&mut self, mid: Mid) {
+        Self::remove(self.get_path(LAZY_MAPS, format!("{}_{}", mid.0, mid.1), FILE_EXT))
+    }
+
     fn get_vault(&self, vid: Vid) -> Option<Vault> {
         Self::read(self.get_path(VAULTS, format!("{}_{}", vid.0, vid.1), FILE_EXT))
             .map(Self::decode)
@@ -165,6 +223,10 @@ impl Ledger for FileBasedLedger {
         )
     }
 
+    fn delete_vault(&mut self, vid: Vid) {
+        Self::remove(self.get_path(VAULTS, format!("{}_{}", vid.0, vid.1), FILE_EXT))
+    }
+
     fn get_nft(&self, resource_address: Address, id: u128) -> Option<Nft> {
         Self::read(self.get_path(NFTS, format!("{}_{}", resource_address, id), FILE_EXT))
             .map(Self::decode)
@@ -176,4 +238,8 @@ impl Ledger for FileBasedLedger {
             Self::encode(&nft),
         )
     }
+
+    fn state_root(&self) -> H256 {
+        compute_state_root(self.list_substates())
+    }
 }